@@ -5,6 +5,7 @@ const MAX_LIGHTS = [MAX_LIGHTS];
 struct CameraUniform
 {
     view_pos: vec4<f32>,
+    view: mat4x4<f32>,
     view_proj: mat4x4<f32>,
 };
 @group(1) @binding(0)
@@ -0,0 +1,109 @@
+// ****************************** inputs ******************************
+
+struct DofUniform
+{
+    texel_size: vec2<f32>,
+    near: f32,
+    far: f32,
+    focus_distance: f32,
+    aperture: f32,
+    max_blur: f32,
+    _padding: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> dof: DofUniform;
+
+@group(0) @binding(1)
+var t_color: texture_2d<f32>;
+@group(0) @binding(2)
+var s_color: sampler;
+
+@group(0) @binding(3)
+var t_depth: texture_depth_2d;
+
+struct VertexOutput
+{
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+const SAMPLES: i32 = 48;
+const GOLDEN_ANGLE: f32 = 2.39996323;
+
+// ****************************** vertex ******************************
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput
+{
+    // fullscreen triangle
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VertexOutput;
+    out.uv = uv;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+
+    return out;
+}
+
+// ****************************** fragment ******************************
+
+fn linear_depth(uv: vec2<f32>) -> f32
+{
+    let size = vec2<i32>(textureDimensions(t_depth));
+    let pixel = clamp(vec2<i32>(uv * vec2<f32>(size)), vec2<i32>(0, 0), size - vec2<i32>(1, 1));
+    let depth = textureLoad(t_depth, pixel, 0);
+
+    return dof.near * dof.far / (dof.far - depth * (dof.far - dof.near));
+}
+
+// circle of confusion (blur radius in pixels)
+fn circle_of_confusion(depth: f32) -> f32
+{
+    let coc = abs(depth - dof.focus_distance) / max(depth, 0.0001) * dof.aperture;
+
+    return clamp(coc, 0.0, 1.0) * dof.max_blur;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let center_color = textureSampleLevel(t_color, s_color, in.uv, 0.0);
+    let center_depth = linear_depth(in.uv);
+    let center_coc = circle_of_confusion(center_depth);
+
+    if (center_coc < 0.5)
+    {
+        return center_color;
+    }
+
+    var color = center_color;
+    var weight_sum = 1.0;
+
+    // gather samples on a golden angle spiral within the blur radius
+    for (var i = 1; i < SAMPLES; i += 1)
+    {
+        let radius = center_coc * sqrt(f32(i) / f32(SAMPLES));
+        let angle = f32(i) * GOLDEN_ANGLE;
+        let offset = vec2<f32>(cos(angle), sin(angle)) * radius;
+
+        let uv = in.uv + offset * dof.texel_size;
+
+        let sample_depth = linear_depth(uv);
+        var sample_coc = circle_of_confusion(sample_depth);
+
+        // sharp background should not bleed into a blurred foreground
+        if (sample_depth > center_depth)
+        {
+            sample_coc = min(sample_coc, center_coc);
+        }
+
+        // the sample contributes if its own blur reaches the center pixel
+        let weight = smoothstep(radius - 1.0, radius + 1.0, sample_coc);
+
+        color += textureSampleLevel(t_color, s_color, uv, 0.0) * weight;
+        weight_sum += weight;
+    }
+
+    return color / weight_sum;
+}
@@ -0,0 +1,122 @@
+// ****************************** inputs ******************************
+
+struct CameraUniform
+{
+    view_pos: vec4<f32>,
+    view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+};
+
+struct GridUniform
+{
+    color: vec4<f32>,
+    spacing: f32,
+    sub_spacing: f32,
+    fade_distance: f32,
+    axis_highlight: u32,
+
+    up: vec4<f32>,
+    tangent: vec4<f32>,
+    bitangent: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> grid: GridUniform;
+
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexOutput
+{
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec3<f32>,
+};
+
+// ****************************** vertex ******************************
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput
+{
+    // quad on the ground plane (through the origin - orthogonal to the up axis) which is following the camera
+    var corners = array<vec2<f32>, 6>
+    (
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>( 1.0, -1.0),
+        vec2<f32>( 1.0,  1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>( 1.0,  1.0),
+        vec2<f32>(-1.0,  1.0),
+    );
+
+    let corner = corners[index] * grid.fade_distance;
+    let cam_on_plane = camera.view_pos.xyz - grid.up.xyz * dot(camera.view_pos.xyz, grid.up.xyz);
+    let position = cam_on_plane + grid.tangent.xyz * corner.x + grid.bitangent.xyz * corner.y;
+
+    var out: VertexOutput;
+    out.position = position;
+    out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
+
+    return out;
+}
+
+// ****************************** fragment ******************************
+
+// anti aliased lines based on the screen space derivative
+fn grid_lines(coord: vec2<f32>, spacing: f32) -> f32
+{
+    let scaled = coord / spacing;
+    let derivative = fwidth(scaled);
+    let lines = abs(fract(scaled - 0.5) - 0.5) / derivative;
+
+    return 1.0 - min(min(lines.x, lines.y), 1.0);
+}
+
+// position on the plane (tangent/bitangent coordinates)
+fn plane_coord(position: vec3<f32>) -> vec2<f32>
+{
+    return vec2<f32>(dot(position, grid.tangent.xyz), dot(position, grid.bitangent.xyz));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let coord = plane_coord(in.position);
+
+    let major = grid_lines(coord, grid.spacing);
+    let minor = grid_lines(coord, grid.sub_spacing) * 0.5;
+
+    var color = grid.color.rgb;
+    var alpha = max(major, minor);
+
+    // axis highlight (colored by the world axis: x: red / y: green / z: blue)
+    let axis = abs(coord) / (fwidth(coord) * 1.5);
+    if (grid.axis_highlight != 0u)
+    {
+        let tangent_axis = 1.0 - min(axis.y, 1.0);
+        let bitangent_axis = 1.0 - min(axis.x, 1.0);
+
+        if (tangent_axis > 0.0)
+        {
+            color = mix(color, max(abs(grid.tangent.xyz), vec3<f32>(0.2)), tangent_axis);
+            alpha = max(alpha, tangent_axis);
+        }
+
+        if (bitangent_axis > 0.0)
+        {
+            color = mix(color, max(abs(grid.bitangent.xyz), vec3<f32>(0.2)), bitangent_axis);
+            alpha = max(alpha, bitangent_axis);
+        }
+    }
+
+    // fade out with distance
+    let cam_distance = distance(coord, plane_coord(camera.view_pos.xyz));
+    alpha *= 1.0 - smoothstep(grid.fade_distance * 0.3, grid.fade_distance, cam_distance);
+    alpha *= grid.color.a;
+
+    if (alpha <= 0.001)
+    {
+        discard;
+    }
+
+    return vec4<f32>(color, alpha);
+}
@@ -0,0 +1,64 @@
+// ****************************** inputs ******************************
+
+struct CameraUniform
+{
+    view_pos: vec4<f32>,
+    view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+};
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(0) @binding(0)
+var t_font: texture_2d<f32>;
+@group(0) @binding(1)
+var s_font: sampler;
+
+struct VertexInput
+{
+    @location(0) position: vec3<f32>,
+    @location(1) offset: vec2<f32>,
+    @location(2) tex_coords: vec2<f32>,
+    @location(3) color: vec3<f32>,
+};
+
+struct VertexOutput
+{
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+// ****************************** vertex ******************************
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput
+{
+    // camera right and up axis (rows of the view matrix)
+    let right = normalize(vec3<f32>(camera.view[0].x, camera.view[1].x, camera.view[2].x));
+    let up = normalize(vec3<f32>(camera.view[0].y, camera.view[1].y, camera.view[2].y));
+
+    let world_position = model.position + right * model.offset.x + up * model.offset.y;
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(world_position, 1.0);
+    out.tex_coords = model.tex_coords;
+    out.color = model.color;
+
+    return out;
+}
+
+// ****************************** fragment ******************************
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let alpha = textureSample(t_font, s_font, in.tex_coords).a;
+
+    if (alpha < 0.5)
+    {
+        discard;
+    }
+
+    return vec4<f32>(in.color, alpha);
+}
@@ -0,0 +1,101 @@
+// ****************************** inputs ******************************
+
+struct CameraUniform
+{
+    view_pos: vec4<f32>,
+    view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+};
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct NormalLinesUniform
+{
+    length: f32,
+};
+@group(0) @binding(0)
+var<uniform> normal_lines: NormalLinesUniform;
+
+struct VertexInput
+{
+    @location(0) position: vec3<f32>,
+    @location(1) direction: vec3<f32>,
+    @location(2) offset: f32,
+    @location(3) kind: f32,
+};
+
+struct InstanceInput
+{
+    @location(5) model_matrix_0: vec4<f32>,
+    @location(6) model_matrix_1: vec4<f32>,
+    @location(7) model_matrix_2: vec4<f32>,
+    @location(8) model_matrix_3: vec4<f32>,
+
+    @location(9) alpha: f32,
+    @location(10) highlight: f32,
+};
+
+struct VertexOutput
+{
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+    @location(1) highlight: f32,
+};
+
+// ****************************** vertex ******************************
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput
+{
+    let model_matrix = mat4x4<f32>
+    (
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+
+    let world_position = model_matrix * vec4<f32>(model.position, 1.0);
+
+    // same as in phong.wgsl
+    let scale_squared = vec3<f32>
+    (
+        dot(model_matrix[0].xyz, model_matrix[0].xyz),
+        dot(model_matrix[1].xyz, model_matrix[1].xyz),
+        dot(model_matrix[2].xyz, model_matrix[2].xyz)
+    );
+
+    let direction = normalize((model_matrix * vec4<f32>(model.direction / scale_squared, 0.0)).xyz);
+
+    // normal = blue, tangent = red, bitangent = green
+    var color = vec3<f32>(0.0, 0.0, 1.0);
+    if (model.kind > 1.5)
+    {
+        color = vec3<f32>(0.0, 1.0, 0.0);
+    }
+    else if (model.kind > 0.5)
+    {
+        color = vec3<f32>(1.0, 0.0, 0.0);
+    }
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(world_position.xyz / world_position.w + direction * model.offset * normal_lines.length, 1.0);
+    out.color = color;
+    out.highlight = instance.highlight;
+
+    return out;
+}
+
+// ****************************** fragment ******************************
+
+// only highlighted (selected) instances
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    if (in.highlight < 0.0001)
+    {
+        discard;
+    }
+
+    return vec4<f32>(in.color, 1.0);
+}
@@ -0,0 +1,97 @@
+// ****************************** inputs ******************************
+
+struct OutlineUniform
+{
+    color: vec4<f32>,
+    thickness: f32,
+    _padding_1: f32,
+    _padding_2: f32,
+    _padding_3: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> outline: OutlineUniform;
+
+@group(0) @binding(1)
+var t_mask: texture_2d<f32>;
+
+struct VertexOutput
+{
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+const DIRECTIONS: i32 = 16;
+const PI: f32 = 3.14159265;
+
+// ****************************** vertex ******************************
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput
+{
+    // fullscreen triangle
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+
+    return out;
+}
+
+// ****************************** fragment ******************************
+
+fn mask(pixel: vec2<i32>) -> f32
+{
+    let size = vec2<i32>(textureDimensions(t_mask));
+    if (pixel.x < 0 || pixel.y < 0 || pixel.x >= size.x || pixel.y >= size.y)
+    {
+        return 0.0;
+    }
+
+    return textureLoad(t_mask, pixel, 0).r;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    let pixel = vec2<i32>(in.clip_position.xy);
+
+    // inside of the selection (the object itself is tinted by the highlight color)
+    if (mask(pixel) > 0.5)
+    {
+        discard;
+    }
+
+    // search for the nearest masked pixel within the outline thickness
+    let steps = i32(ceil(outline.thickness));
+    var nearest = outline.thickness + 1.0;
+
+    for (var step = 1; step <= steps; step += 1)
+    {
+        for (var i = 0; i < DIRECTIONS; i += 1)
+        {
+            let angle = f32(i) / f32(DIRECTIONS) * 2.0 * PI;
+            let offset = vec2<f32>(cos(angle), sin(angle)) * f32(step);
+
+            if (mask(pixel + vec2<i32>(round(offset))) > 0.5)
+            {
+                nearest = f32(step);
+                break;
+            }
+        }
+
+        if (nearest <= outline.thickness)
+        {
+            break;
+        }
+    }
+
+    if (nearest > outline.thickness)
+    {
+        discard;
+    }
+
+    // anti aliased outer edge
+    let alpha = 1.0 - smoothstep(outline.thickness - 1.0, outline.thickness, nearest - 0.5);
+
+    return vec4<f32>(outline.color.rgb, outline.color.a * alpha);
+}
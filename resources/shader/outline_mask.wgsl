@@ -0,0 +1,67 @@
+// ****************************** inputs ******************************
+
+struct CameraUniform
+{
+    view_pos: vec4<f32>,
+    view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+};
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct VertexInput
+{
+    @location(0) position: vec3<f32>,
+};
+
+struct InstanceInput
+{
+    @location(5) model_matrix_0: vec4<f32>,
+    @location(6) model_matrix_1: vec4<f32>,
+    @location(7) model_matrix_2: vec4<f32>,
+    @location(8) model_matrix_3: vec4<f32>,
+
+    @location(9) alpha: f32,
+    @location(10) highlight: f32,
+};
+
+struct VertexOutput
+{
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) highlight: f32,
+};
+
+// ****************************** vertex ******************************
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput
+{
+    let model_matrix = mat4x4<f32>
+    (
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+
+    var out: VertexOutput;
+
+    out.highlight = instance.highlight;
+    out.clip_position = camera.view_proj * model_matrix * vec4<f32>(model.position, 1.0);
+
+    return out;
+}
+
+// ****************************** fragment ******************************
+
+// only highlighted (selected) instances are written into the mask
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    if (in.highlight < 0.0001)
+    {
+        discard;
+    }
+
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
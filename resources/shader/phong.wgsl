@@ -29,7 +29,9 @@ struct LightUniform
 struct SceneUniform
 {
     gamma: f32,
-    exposure: f32
+    exposure: f32,
+    environment_intensity: f32,
+    debug_channel: u32
 };
 
 @group(1) @binding(0)
@@ -187,6 +189,13 @@ struct MaterialUniform
     unlit_shading: u32,
 
     textures_used: u32,
+
+    clearcoat: f32,
+    clearcoat_roughness: f32,
+
+    transmission: f32,
+
+    texture_channels: u32,
 };
 
 @group(0) @binding(0)
@@ -222,8 +231,32 @@ var<uniform> material: MaterialUniform;
 @group(0) @binding(27) var t_custom3: texture_2d<f32>;
 @group(0) @binding(28) var s_custom3: sampler;
 
-@group(0) @binding(29) var t_depth: texture_2d<f32>;
-@group(0) @binding(30) var s_depth: sampler;
+@group(0) @binding(29) var t_clearcoat: texture_2d<f32>;
+@group(0) @binding(30) var s_clearcoat: sampler;
+@group(0) @binding(31) var t_clearcoat_roughness: texture_2d<f32>;
+@group(0) @binding(32) var s_clearcoat_roughness: sampler;
+@group(0) @binding(33) var t_clearcoat_normal: texture_2d<f32>;
+@group(0) @binding(34) var s_clearcoat_normal: sampler;
+
+@group(0) @binding(35) var t_irradiance: texture_2d<f32>;
+@group(0) @binding(36) var s_irradiance: sampler;
+@group(0) @binding(37) var t_brdf_lut: texture_2d<f32>;
+@group(0) @binding(38) var s_brdf_lut: sampler;
+
+@group(0) @binding(39) var t_depth: texture_2d<f32>;
+@group(0) @binding(40) var s_depth: sampler;
+
+@group(0) @binding(41) var t_environment_cube: texture_cube<f32>;
+@group(0) @binding(42) var s_environment_cube: sampler;
+
+// opaque scene (only bound while rendering transmissive materials)
+@group(2) @binding(0) var t_background: texture_2d<f32>;
+@group(2) @binding(1) var s_background: sampler;
+
+const TRANSMISSION_SAMPLES: i32 = 16;
+const TRANSMISSION_MAX_BLUR: f32 = 16.0; // blur radius in pixels at roughness 1
+const REFRACTION_SCALE: f32 = 0.1; // screen space distance of the refracted background (there is no thickness)
+const GOLDEN_ANGLE: f32 = 2.39996323;
 
 
 fn has_ambient_texture() -> bool            { return (material.textures_used & (1u << 1u)) != 0u; }
@@ -236,6 +269,11 @@ fn has_ambient_occlusion_texture() -> bool  { return (material.textures_used & (
 fn has_reflectivity_texture() -> bool       { return (material.textures_used & (1u << 8u)) != 0u; }
 fn has_shininess_texture() -> bool          { return (material.textures_used & (1u << 9u)) != 0u; }
 fn has_environment_texture() -> bool        { return (material.textures_used & (1u << 10u)) != 0u; }
+fn has_clearcoat_texture() -> bool          { return (material.textures_used & (1u << 18u)) != 0u; }
+fn has_clearcoat_roughness_texture() -> bool{ return (material.textures_used & (1u << 19u)) != 0u; }
+fn has_clearcoat_normal_texture() -> bool   { return (material.textures_used & (1u << 20u)) != 0u; }
+fn has_environment_cubemap() -> bool        { return (material.textures_used & (1u << 21u)) != 0u; }
+fn has_ibl_textures() -> bool               { return (material.textures_used & (1u << 16u)) != 0u && (material.textures_used & (1u << 17u)) != 0u; }
 
 fn has_custom0_texture() -> bool            { return (material.textures_used & (1u << 11u)) != 0u; }
 fn has_custom1_texture() -> bool            { return (material.textures_used & (1u << 12u)) != 0u; }
@@ -244,6 +282,21 @@ fn has_custom3_texture() -> bool            { return (material.textures_used & (
 
 fn has_depth_texture() -> bool              { return (material.textures_used & (1u << 15u)) != 0u; }
 
+// selected channel of single channel textures (packed textures like occlusion/roughness/metallic)
+const CHANNEL_ROUGHNESS: u32 = 0u;
+const CHANNEL_AMBIENT_OCCLUSION: u32 = 1u;
+const CHANNEL_REFLECTIVITY: u32 = 2u;
+const CHANNEL_CLEARCOAT: u32 = 3u;
+const CHANNEL_CLEARCOAT_ROUGHNESS: u32 = 4u;
+
+fn texture_channel(value: vec4<f32>, texture_slot: u32) -> f32
+{
+    let channel = (material.texture_channels >> (texture_slot * 2u)) & 3u;
+
+    var channels = value;
+    return channels[channel];
+}
+
 
 /*
 fn sphericalCoords(direction: vec3<f32>) -> vec2<f32>
@@ -267,9 +320,129 @@ fn sphericalCoords(direction: vec3<f32>) -> vec2<f32>
     return uv;
 }
 
+// equirectangular environment map or cubemap
+fn environment_levels() -> u32
+{
+    if (has_environment_cubemap())
+    {
+        return textureNumLevels(t_environment_cube) - 1u;
+    }
+
+    return textureNumLevels(t_environment) - 1u;
+}
+
+fn sample_environment(direction: vec3<f32>, level: f32) -> vec4<f32>
+{
+    if (has_environment_cubemap())
+    {
+        return textureSampleLevel(t_environment_cube, s_environment_cube, direction, level);
+    }
+
+    return textureSampleLevel(t_environment, s_environment, sphericalCoords(direction), level);
+}
+
+// reflectance of the clearcoat layer (ior 1.5)
+fn fresnel_schlick(cos_theta: f32) -> f32
+{
+    return 0.04 + 0.96 * pow(1.0 - cos_theta, 5.0);
+}
+
+// background behind the surface - shifted by the refracted view direction and blurred by the roughness
+fn transmission_color(frag_coord: vec2<f32>, normal: vec3<f32>, view_dir: vec3<f32>, roughness: f32) -> vec3<f32>
+{
+    let size = vec2<f32>(textureDimensions(t_background));
+
+    let refracted = refract(-view_dir, normal, 1.0 / max(material.refraction_index, 1.0));
+    let offset = (camera.view * vec4<f32>(refracted + view_dir, 0.0)).xy * vec2<f32>(1.0, -1.0) * REFRACTION_SCALE;
+
+    let uv = frag_coord / size + offset;
+    let radius = clamp(roughness, 0.0, 1.0) * TRANSMISSION_MAX_BLUR;
+
+    var color = textureSampleLevel(t_background, s_background, uv, 0.0).rgb;
+
+    if (radius < 0.5)
+    {
+        return color;
+    }
+
+    // samples on a golden angle spiral within the blur radius
+    for (var i = 1; i < TRANSMISSION_SAMPLES; i += 1)
+    {
+        let sample_radius = radius * sqrt(f32(i) / f32(TRANSMISSION_SAMPLES));
+        let angle = f32(i) * GOLDEN_ANGLE;
+        let sample_offset = vec2<f32>(cos(angle), sin(angle)) * sample_radius / size;
+
+        color += textureSampleLevel(t_background, s_background, uv + sample_offset, 0.0).rgb;
+    }
+
+    return color / f32(TRANSMISSION_SAMPLES);
+}
+
+// material debug output (see DebugChannel)
+const DEBUG_CHANNEL_SHADED: u32 = 0u;
+const DEBUG_CHANNEL_OVERDRAW: u32 = 7u;
+const OVERDRAW_COLOR: vec3<f32> = vec3<f32>(0.1, 0.04, 0.01); // added per fragment (additive blending)
+
+fn debug_color(uvs: vec2<f32>, object_color: vec4<f32>, normal: vec3<f32>) -> vec4<f32>
+{
+    var color = vec3<f32>(0.0, 0.0, 0.0);
+
+    switch scene.debug_channel
+    {
+        case 1u //DEBUG_CHANNEL_ALBEDO
+        {
+            color = object_color.rgb;
+        }
+        case 2u //DEBUG_CHANNEL_WORLD_NORMAL
+        {
+            color = normal * 0.5 + 0.5;
+        }
+        case 3u //DEBUG_CHANNEL_TANGENT_NORMAL
+        {
+            color = vec3<f32>(0.5, 0.5, 1.0);
+            if (has_normal_texture())
+            {
+                color = textureSample(t_normal, s_normal, uvs).xyz;
+            }
+        }
+        case 4u //DEBUG_CHANNEL_ROUGHNESS
+        {
+            var roughness = material.roughness;
+            if (has_roughness_texture())
+            {
+                roughness *= texture_channel(textureSample(t_roughness, s_roughness, uvs), CHANNEL_ROUGHNESS);
+            }
+
+            color = vec3<f32>(roughness);
+        }
+        case 5u //DEBUG_CHANNEL_METALLIC
+        {
+            var reflectivity = material.reflectivity;
+            if (has_reflectivity_texture())
+            {
+                reflectivity *= texture_channel(textureSample(t_reflectivity, s_reflectivity, uvs), CHANNEL_REFLECTIVITY);
+            }
+
+            color = vec3<f32>(reflectivity);
+        }
+        case 6u //DEBUG_CHANNEL_UV
+        {
+            color = vec3<f32>(fract(uvs), 0.0);
+        }
+        default {}
+    }
+
+    return vec4<f32>(color, 1.0);
+}
+
 @fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+fn fs_main(in: VertexOutput, @builtin(front_facing) front_facing: bool) -> @location(0) vec4<f32>
 {
+    if (scene.debug_channel == DEBUG_CHANNEL_OVERDRAW)
+    {
+        return vec4<f32>(OVERDRAW_COLOR, 1.0);
+    }
+
     var uvs = in.tex_coords;
 
     // base color
@@ -316,6 +489,42 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
         normal = normalize(normal);
     }
 
+    // clearcoat: second specular layer on top of the base material (with its own normal)
+    var clearcoat = material.clearcoat;
+    var clearcoat_roughness = material.clearcoat_roughness;
+    var clearcoat_normal = normalize(in.normal);
+
+    if (has_clearcoat_texture())
+    {
+        clearcoat *= texture_channel(textureSample(t_clearcoat, s_clearcoat, uvs), CHANNEL_CLEARCOAT);
+    }
+
+    if (has_clearcoat_roughness_texture())
+    {
+        clearcoat_roughness *= texture_channel(textureSample(t_clearcoat_roughness, s_clearcoat_roughness, uvs), CHANNEL_CLEARCOAT_ROUGHNESS);
+    }
+
+    if (has_clearcoat_normal_texture())
+    {
+        let clearcoat_normal_map = textureSample(t_clearcoat_normal, s_clearcoat_normal, uvs).xyz * 2.0 - 1.0;
+        clearcoat_normal = normalize(normalize(tangent) * clearcoat_normal_map.x + normalize(bitangent) * clearcoat_normal_map.y + clearcoat_normal * clearcoat_normal_map.z);
+    }
+
+    // back side of double sided materials (single sided ones are culled)
+    if (!front_facing)
+    {
+        normal = -normal;
+        clearcoat_normal = -clearcoat_normal;
+    }
+
+    if (scene.debug_channel != DEBUG_CHANNEL_SHADED)
+    {
+        return debug_color(uvs, object_color, normal);
+    }
+
+    let clearcoat_alpha = max(clearcoat_roughness * clearcoat_roughness, 0.01);
+    let clearcoat_shininess = 2.0 / (clearcoat_alpha * clearcoat_alpha) - 2.0;
+
     var color = vec3<f32>(0.0, 0.0, 0.0);
 
     if (material.unlit_shading != 0u || light_amount == 0)
@@ -396,16 +605,34 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
 
             let specular_color = (lights[i].color * material.specular_color * specular_strength).rgb;
 
-            color += (diffuse_color + specular_color) * intensity;
+            var light_color_sum = (diffuse_color + specular_color) * intensity;
+
+            // clearcoat lobe (the base layer is attenuated by the light reflected on the coat)
+            if (clearcoat > 0.001)
+            {
+                let clearcoat_strength = pow(max(dot(clearcoat_normal, half_dir), 0.0), clearcoat_shininess);
+                let coat = clearcoat * fresnel_schlick(max(dot(half_dir, view_dir), 0.0));
+
+                light_color_sum = light_color_sum * (1.0 - coat) + light_color * clearcoat_strength * coat * intensity;
+            }
+
+            color += light_color_sum;
+        }
+
+        // diffuse image based lighting
+        if (has_ibl_textures())
+        {
+            let irradiance = textureSampleLevel(t_irradiance, s_irradiance, sphericalCoords(normal), 0.0).rgb * scene.environment_intensity;
+            color += irradiance * object_color.rgb * (1.0 - clamp(material.reflectivity, 0.0, 1.0));
         }
 
         // ambient occlusion
         if (has_ambient_occlusion_texture())
         {
-            let ambient_occlusion = textureSample(t_ambient_occlusion, s_ambient_occlusion, uvs);
-            color.x *= ambient_occlusion.x;
-            color.y *= ambient_occlusion.x;
-            color.z *= ambient_occlusion.x;
+            let ambient_occlusion = texture_channel(textureSample(t_ambient_occlusion, s_ambient_occlusion, uvs), CHANNEL_AMBIENT_OCCLUSION);
+            color.x *= ambient_occlusion;
+            color.y *= ambient_occlusion;
+            color.z *= ambient_occlusion;
         }
 
         // reflection with env map
@@ -415,27 +642,46 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
             if (has_reflectivity_texture())
             {
                 let reflectivity_value = textureSample(t_reflectivity, s_reflectivity, uvs);
-                reflectivity *= reflectivity_value.x;
+                reflectivity *= texture_channel(reflectivity_value, CHANNEL_REFLECTIVITY);
             }
 
             var roughness = material.roughness;
             if (has_roughness_texture())
             {
                 let roughness_value = textureSample(t_roughness, s_roughness, uvs);
-                roughness *= roughness_value.x;
+                roughness *= texture_channel(roughness_value, CHANNEL_ROUGHNESS);
             }
 
             let reflection = reflect(-view_dir, normal);
-            let sphere_coords = sphericalCoords(reflection);
 
-            let environment_map_levels = textureNumLevels(t_environment) - 1u;
-            let mipmap_level = roughness * f32(environment_map_levels);
+            let mipmap_level = roughness * f32(environment_levels());
+
+            // split sum: the env map mipmaps are prefiltered - scale and bias for the reflectivity are coming from the brdf lut
+            if (has_ibl_textures())
+            {
+                let n_dot_v = max(dot(normal, view_dir), 0.0);
+                let brdf = textureSampleLevel(t_brdf_lut, s_brdf_lut, vec2<f32>(n_dot_v, roughness), 0.0).rg;
+                reflectivity = reflectivity * brdf.x + brdf.y;
+            }
 
-            let reflection_color = textureSampleLevel(t_environment, s_environment, sphere_coords, mipmap_level);
+            let reflection_color = sample_environment(reflection, mipmap_level) * scene.environment_intensity;
             color.x += reflection_color.x * reflectivity;
             color.y += reflection_color.y * reflectivity;
             color.z += reflection_color.z * reflectivity;
         }
+
+        // clearcoat reflection with env map
+        if (has_environment_texture() && clearcoat > 0.001)
+        {
+            let reflection = reflect(-view_dir, clearcoat_normal);
+
+            let mipmap_level = clearcoat_roughness * f32(environment_levels());
+
+            let reflection_color = sample_environment(reflection, mipmap_level).rgb * scene.environment_intensity;
+            let coat = clearcoat * fresnel_schlick(max(dot(clearcoat_normal, view_dir), 0.0));
+
+            color = color * (1.0 - coat) + reflection_color * coat;
+        }
     }
 
     // ambient color
@@ -463,14 +709,29 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
         color.z = mapped.z;
     }
 
+    var alpha = in.alpha * object_color.a * material.alpha;
+
+    // transmission (the background is already tone mapped and gamma corrected)
+    if (material.transmission > 0.001)
+    {
+        var roughness = material.roughness;
+        if (has_roughness_texture())
+        {
+            roughness *= texture_channel(textureSample(t_roughness, s_roughness, uvs), CHANNEL_ROUGHNESS);
+        }
+
+        let transmitted = transmission_color(in.clip_position.xy, normal, normalize(in.view_dir), roughness) * object_color.rgb;
+
+        color = mix(color, transmitted, material.transmission);
+        alpha = mix(alpha, 1.0, material.transmission);
+    }
+
     // highlight color
     if (in.highlight > 0.0001)
     {
         color = (color * 0.5) + (material.highlight_color.rgb * 0.5);
     }
 
-    let alpha = in.alpha * object_color.a * material.alpha;
-
     if (alpha < 0.000001)
     {
         discard;
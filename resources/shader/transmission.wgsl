@@ -0,0 +1,36 @@
+// ****************************** inputs ******************************
+
+@group(0) @binding(0)
+var t_background: texture_2d<f32>;
+@group(0) @binding(1)
+var s_background: sampler;
+
+struct VertexOutput
+{
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+// ****************************** vertex ******************************
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput
+{
+    // fullscreen triangle
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+
+    return out;
+}
+
+// ****************************** fragment ******************************
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    // based on the pixel position (not the viewport) - the background has the same size as the output
+    let uv = in.clip_position.xy / vec2<f32>(textureDimensions(t_background));
+
+    return textureSampleLevel(t_background, s_background, uv, 0.0);
+}
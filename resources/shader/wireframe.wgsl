@@ -0,0 +1,78 @@
+// ****************************** inputs ******************************
+
+struct CameraUniform
+{
+    view_pos: vec4<f32>,
+    view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+};
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct WireframeUniform
+{
+    color: vec4<f32>,
+};
+@group(0) @binding(0)
+var<uniform> wireframe: WireframeUniform;
+
+struct VertexInput
+{
+    @location(0) position: vec3<f32>,
+};
+
+struct InstanceInput
+{
+    @location(5) model_matrix_0: vec4<f32>,
+    @location(6) model_matrix_1: vec4<f32>,
+    @location(7) model_matrix_2: vec4<f32>,
+    @location(8) model_matrix_3: vec4<f32>,
+
+    @location(9) alpha: f32,
+    @location(10) highlight: f32,
+};
+
+struct VertexOutput
+{
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) highlight: f32,
+};
+
+// lines are moved slightly towards the camera to prevent z-fighting with the surface
+// (depth bias of the pipeline is not supported for line topologies)
+const DEPTH_BIAS: f32 = 0.0002;
+
+// ****************************** vertex ******************************
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput
+{
+    let model_matrix = mat4x4<f32>
+    (
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * model_matrix * vec4<f32>(model.position, 1.0);
+    out.clip_position.z -= DEPTH_BIAS * out.clip_position.w;
+    out.highlight = instance.highlight;
+
+    return out;
+}
+
+// ****************************** fragment ******************************
+
+// only highlighted (selected) instances
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    if (in.highlight < 0.0001)
+    {
+        discard;
+    }
+
+    return wireframe.color;
+}
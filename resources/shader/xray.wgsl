@@ -0,0 +1,104 @@
+// ****************************** inputs ******************************
+
+struct CameraUniform
+{
+    view_pos: vec4<f32>,
+    view: mat4x4<f32>,
+    view_proj: mat4x4<f32>,
+};
+@group(1) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct MaterialUniform
+{
+    ambient_color: vec4<f32>,
+    base_color: vec4<f32>,
+    specular_color: vec4<f32>,
+    highlight_color: vec4<f32>,
+
+    alpha: f32,
+    shininess: f32,
+    reflectivity: f32,
+    refraction_index: f32,
+
+    normal_map_strength: f32,
+    roughness: f32,
+    receive_shadow: u32,
+
+    unlit_shading: u32,
+
+    textures_used: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> material: MaterialUniform;
+
+struct VertexInput
+{
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normal: vec3<f32>,
+};
+
+struct InstanceInput
+{
+    @location(5) model_matrix_0: vec4<f32>,
+    @location(6) model_matrix_1: vec4<f32>,
+    @location(7) model_matrix_2: vec4<f32>,
+    @location(8) model_matrix_3: vec4<f32>,
+
+    @location(9) alpha: f32,
+    @location(10) highlight: f32,
+};
+
+struct VertexOutput
+{
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) view_dir: vec3<f32>,
+    @location(2) highlight: f32,
+};
+
+const XRAY_ALPHA: f32 = 0.35;
+
+// ****************************** vertex ******************************
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput
+{
+    let model_matrix = mat4x4<f32>
+    (
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+
+    let world_position = model_matrix * vec4<f32>(model.position, 1.0);
+
+    var out: VertexOutput;
+
+    out.clip_position = camera.view_proj * world_position;
+    out.normal = (model_matrix * vec4<f32>(model.normal, 0.0)).xyz;
+    out.view_dir = (camera.view_pos - world_position).xyz;
+    out.highlight = instance.highlight;
+
+    return out;
+}
+
+// ****************************** fragment ******************************
+
+// only highlighted (selected) instances are rendered - without depth test
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32>
+{
+    if (in.highlight < 0.0001)
+    {
+        discard;
+    }
+
+    // stronger at the silhouette so the shape stays readable
+    let rim = 1.0 - abs(dot(normalize(in.normal), normalize(in.view_dir)));
+
+    return vec4<f32>(material.highlight_color.rgb, XRAY_ALPHA * (0.5 + rim * 0.5));
+}
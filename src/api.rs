@@ -0,0 +1,68 @@
+//! Public API to embed the engine into other applications.
+//!
+//! Build a scene, add nodes and render it without a window:
+//!
+//! ```no_run
+//! use rustl::api::*;
+//!
+//! pollster::block_on(async
+//! {
+//!     let mut engine = HeadlessInterface::new(800, 600).await;
+//!
+//!     let mut scene = Scene::new(0, "scene");
+//!     scene.add_default_material();
+//!     scene.add_camera("cam");
+//!     scene.add_light_point("light", Point3::new(2.0, 5.0, 2.0), Vector3::new(1.0, 1.0, 1.0), 100.0);
+//!
+//!     let node = Node::new(scene.id_manager.get_next_node_id(), "node");
+//!     scene.add_node(node);
+//!
+//!     engine.state.scenes.push(Box::new(scene));
+//!
+//!     // objects are loaded in a thread and applied via the main queue
+//!     let main_queue = engine.state.main_thread_execution_queue.clone();
+//!     let loading = std::thread::spawn(move ||
+//!     {
+//!         load_object("objects/cube/cube.obj", 0, main_queue, true, true, false, false, None).unwrap();
+//!     });
+//!     engine.wait_for(loading);
+//!
+//!     // drive updates (frame_scale = 1.0 equals one frame at 60 fps)
+//!     engine.update(1.0);
+//!
+//!     let image = engine.render_image();
+//!     image.save("render.png").unwrap();
+//! });
+//! ```
+
+pub use nalgebra::{Point3, Vector3, Vector2, Matrix4};
+
+pub use crate::interface::main_interface::REFERENCE_UPDATE_FRAMES;
+#[cfg(not(target_arch="wasm32"))]
+pub use crate::interface::headless::{HeadlessInterface, render_to_file};
+
+pub use crate::helper::change_tracker::ChangeTracker;
+pub use crate::input::input_manager::InputManager;
+pub use crate::input::mouse::MouseButton;
+
+pub use crate::state::state::{State, StateItem};
+pub use crate::state::scene::scene::{Scene, SceneItem};
+pub use crate::state::scene::events::SceneEvent;
+pub use crate::state::scene::node::{Node, NodeItem, LAYER_DEFAULT, LAYER_EDITOR, LAYER_ALL};
+pub use crate::state::scene::instance::{Instance, InstanceItem};
+pub use crate::state::scene::camera::{Camera, CameraItem};
+pub use crate::state::scene::light::{Light, LightItem};
+pub use crate::state::scene::texture::{Texture, TextureItem};
+
+pub use crate::state::scene::components::component::{Component, ComponentItem};
+pub use crate::state::scene::components::transformation::Transformation;
+pub use crate::state::scene::components::transformation_animation::TransformationAnimation;
+pub use crate::state::scene::components::mesh::Mesh;
+pub use crate::state::scene::components::material::{Material, MaterialItem, TextureType, DepthCompare};
+pub use crate::state::scene::components::alpha::Alpha;
+pub use crate::state::scene::components::lod::Lod;
+pub use crate::state::scene::components::billboard::{Billboard, BillboardMode};
+pub use crate::state::scene::components::label::Label;
+pub use crate::state::scene::components::script::Script;
+
+pub use crate::state::scene::utilities::scene_utils::{load_object, load_object_with_scenes, load_texture};
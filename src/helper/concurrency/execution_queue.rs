@@ -1,5 +1,7 @@
 use std::{sync::{Arc, RwLock}, collections::VecDeque};
 
+use instant::Instant;
+
 use crate::state::state::State;
 
 use super::thread::sleep_millis;
@@ -115,4 +117,20 @@ impl ExecutionQueue
             Self::run_first(queue.clone(), state);
         }
     }
+
+    // runs items until the time budget is used (at least one) - the rest is processed in the next call
+    pub fn run_for(queue: Arc<RwLock<ExecutionQueue>>, state: &mut State, max_time_ms: f32)
+    {
+        let start = Instant::now();
+
+        while queue.read().unwrap().queue.len() > 0
+        {
+            Self::run_first(queue.clone(), state);
+
+            if start.elapsed().as_micros() as f32 / 1000.0 >= max_time_ms
+            {
+                break;
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -24,6 +24,25 @@ impl<T> Consumable<T>
         &self.data
     }
 
+    // without marking a change
+    pub fn get_mut(&mut self) -> &mut T
+    {
+        &mut self.data
+    }
+
+    pub fn force_change(&mut self)
+    {
+        self.changed = true;
+    }
+
+    pub fn consume_change(&mut self) -> bool
+    {
+        let has_changed = self.changed;
+        self.changed = false;
+
+        has_changed
+    }
+
     pub fn changed(&self) -> bool
     {
         self.changed
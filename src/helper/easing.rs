@@ -0,0 +1,42 @@
+use std::f32::consts::PI;
+
+use strum_macros::{EnumIter, Display};
+
+#[derive(EnumIter, Display, Debug, PartialEq, Clone, Copy)]
+pub enum Easing
+{
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    Cubic,
+    Elastic,
+}
+
+impl Easing
+{
+    // t: 0..1 -> 0..1
+    pub fn apply(&self, t: f32) -> f32
+    {
+        let t = t.clamp(0.0, 1.0);
+
+        match self
+        {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => if t < 0.5 { 2.0 * t * t } else { -1.0 + (4.0 - 2.0 * t) * t },
+            Easing::Cubic => if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 },
+            Easing::Elastic =>
+            {
+                // overshoots (ease out elastic)
+                if t <= 0.0 || t >= 1.0
+                {
+                    return t;
+                }
+
+                2.0f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+            },
+        }
+    }
+}
@@ -1,5 +1,23 @@
 use image::{DynamicImage, ImageBuffer, RgbaImage, GenericImageView, Rgba, GrayImage, Luma};
 
+use super::file::get_extension;
+
+pub fn save_image(img: &DynamicImage, path: &str) -> anyhow::Result<()>
+{
+    let extension = get_extension(path).to_lowercase();
+
+    match extension.as_str()
+    {
+        // jpeg does not support an alpha channel
+        "jpg" | "jpeg" => DynamicImage::ImageRgb8(img.to_rgb8()).save(path)?,
+        // captures are tone mapped into an 8 bit target - there is no hdr data to store
+        "exr" | "hdr" => return Err(anyhow::anyhow!("hdr formats are not supported for captures (the rendering is tone mapped 8 bit)")),
+        _ => img.save(path)?
+    }
+
+    Ok(())
+}
+
 pub fn brga_to_rgba(img: DynamicImage) -> DynamicImage
 {
     let mut converted: RgbaImage = ImageBuffer::new(img.width(), img.height());
@@ -35,4 +53,57 @@ pub fn float32_to_grayscale(img: DynamicImage) -> DynamicImage
     }
 
     DynamicImage::ImageLuma8(converted)
-}
\ No newline at end of file
+}
+
+// raw depth is non linear (most of the values are close to 1) -> linearize it based on the clipping planes
+// normalized: the visible depth range (without background) is mapped to 0..1 - otherwise 0..far
+pub fn float32_depth_to_linear_grayscale(img: DynamicImage, near: f32, far: f32, normalized: bool) -> DynamicImage
+{
+    let mut depths = vec![0.0f32; (img.width() * img.height()) as usize];
+
+    let mut min = f32::MAX;
+    let mut max = f32::MIN;
+
+    for x in 0..img.width()
+    {
+        for y in 0..img.height()
+        {
+            let pixel = img.get_pixel(x, y);
+            let data = [[pixel[0], pixel[1], pixel[2], pixel[3]]];
+            let depth: f32 = bytemuck::cast(data);
+
+            let linear = near * far / (far - depth * (far - near));
+            depths[(y * img.width() + x) as usize] = linear;
+
+            // background
+            if depth < 1.0
+            {
+                min = min.min(linear);
+                max = max.max(linear);
+            }
+        }
+    }
+
+    if !normalized || min > max
+    {
+        min = 0.0;
+        max = far;
+    }
+
+    let range = (max - min).max(0.0001);
+
+    let mut converted: GrayImage = ImageBuffer::new(img.width(), img.height());
+
+    for x in 0..img.width()
+    {
+        for y in 0..img.height()
+        {
+            let linear = depths[(y * img.width() + x) as usize];
+            let value = ((linear - min) / range).clamp(0.0, 1.0);
+
+            converted.put_pixel(x, y, Luma::<u8>([(value * 255.0) as u8]));
+        }
+    }
+
+    DynamicImage::ImageLuma8(converted)
+}
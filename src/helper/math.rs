@@ -2,8 +2,8 @@
 
 use std::f32::consts::PI;
 
-use nalgebra::{Vector4, Vector3, Vector2, Matrix4, Point3};
-use parry3d::query::Ray;
+use nalgebra::{Vector4, Vector3, Vector2, Matrix4, Matrix3, Point3, Rotation3};
+use parry3d::{query::Ray, bounding_volume::Aabb};
 
 pub fn approx_equal(a: f32, b: f32) -> bool
 {
@@ -85,6 +85,176 @@ pub fn inverse_ray(ray: &Ray, trans_inverse: &Matrix4<f32>) -> Ray
     Ray::new(Point3::from_homogeneous(ray_inverse_start).unwrap(), Vector3::from_homogeneous(ray_inverse_dir).unwrap())
 }
 
+pub fn snap_to_grid(point: &Point3<f32>, spacing: f32) -> Point3<f32>
+{
+    if spacing <= 0.0
+    {
+        return *point;
+    }
+
+    Point3::<f32>::new
+    (
+        (point.x / spacing).round() * spacing,
+        (point.y / spacing).round() * spacing,
+        (point.z / spacing).round() * spacing,
+    )
+}
+
+pub fn transform_bounding_sphere(sphere: &(Point3<f32>, f32), transform: &Matrix4<f32>) -> (Point3<f32>, f32)
+{
+    let center = transform.transform_point(&sphere.0);
+
+    // use the largest scale axis to keep the sphere enclosing
+    let scale_x = transform.column(0).xyz().magnitude();
+    let scale_y = transform.column(1).xyz().magnitude();
+    let scale_z = transform.column(2).xyz().magnitude();
+    let scale = scale_x.max(scale_y).max(scale_z);
+
+    (center, sphere.1 * scale)
+}
+
+// axis aligned box enclosing all transformed corners
+pub fn transform_aabb(aabb: &Aabb, transform: &Matrix4<f32>) -> Aabb
+{
+    let mut min = Point3::<f32>::new(std::f32::MAX, std::f32::MAX, std::f32::MAX);
+    let mut max = Point3::<f32>::new(std::f32::MIN, std::f32::MIN, std::f32::MIN);
+
+    for corner in aabb.vertices()
+    {
+        let corner = transform.transform_point(&corner);
+
+        min = min.inf(&corner);
+        max = max.sup(&corner);
+    }
+
+    Aabb::new(min, max)
+}
+
+pub fn merge_bounding_spheres(a: &(Point3<f32>, f32), b: &(Point3<f32>, f32)) -> (Point3<f32>, f32)
+{
+    let dir = b.0 - a.0;
+    let dist = dir.magnitude();
+
+    // one sphere is inside the other one
+    if dist + b.1 <= a.1
+    {
+        return *a;
+    }
+
+    if dist + a.1 <= b.1
+    {
+        return *b;
+    }
+
+    let radius = (dist + a.1 + b.1) / 2.0;
+    let center = a.0 + dir * ((radius - a.1) / dist);
+
+    (center, radius)
+}
+
+// translation * rotation (z * y * x) * scale
+pub fn compose_transform(position: &Vector3<f32>, rotation: &Vector3<f32>, scale: &Vector3<f32>) -> Matrix4<f32>
+{
+    let translation = nalgebra::Isometry3::translation(position.x, position.y, position.z).to_homogeneous();
+    let rotation = Rotation3::from_euler_angles(rotation.x, rotation.y, rotation.z).to_homogeneous();
+    let scale = Matrix4::new_nonuniform_scaling(scale);
+
+    translation * rotation * scale
+}
+
+// returns position, rotation (euler angles) and scale (shearing is lost)
+pub fn decompose_transform(trans: &Matrix4<f32>) -> (Vector3<f32>, Vector3<f32>, Vector3<f32>)
+{
+    let position = trans.column(3).xyz();
+
+    let mut scale = Vector3::<f32>::new
+    (
+        trans.column(0).xyz().magnitude(),
+        trans.column(1).xyz().magnitude(),
+        trans.column(2).xyz().magnitude()
+    );
+
+    // mirrored
+    if trans.fixed_view::<3, 3>(0, 0).clone_owned().determinant() < 0.0
+    {
+        scale.x = -scale.x;
+    }
+
+    for i in 0..3
+    {
+        if approx_zero(scale[i])
+        {
+            scale[i] = 0.00000001;
+        }
+    }
+
+    let rotation_mat = Matrix3::<f32>::from_columns(&
+    [
+        trans.column(0).xyz() / scale.x,
+        trans.column(1).xyz() / scale.y,
+        trans.column(2).xyz() / scale.z
+    ]);
+
+    let (x, y, z) = Rotation3::from_matrix_unchecked(rotation_mat).euler_angles();
+
+    (position, Vector3::<f32>::new(x, y, z), scale)
+}
+
+// converts a f32 into the bits of a half float (used for float textures - there is no f16 type)
+pub fn f32_to_f16(value: f32) -> u16
+{
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    // nan and infinity
+    if exponent == 0xff
+    {
+        return sign | 0x7c00 | if mantissa != 0 { 0x0200 } else { 0 };
+    }
+
+    let exponent = exponent - 127 + 15;
+
+    // too large -> infinity
+    if exponent >= 0x1f
+    {
+        return sign | 0x7c00;
+    }
+
+    // subnormal or zero
+    if exponent <= 0
+    {
+        if exponent < -10
+        {
+            return sign;
+        }
+
+        let mantissa = mantissa | 0x0080_0000;
+        return sign | (mantissa >> (14 - exponent)) as u16;
+    }
+
+    sign | ((exponent as u16) << 10) | (mantissa >> 13) as u16
+}
+
+// scales both sides by the same factor until they are fitting into max_size (the aspect ratio is kept)
+pub fn fit_size(width: u32, height: u32, max_size: u32) -> (u32, u32)
+{
+    let largest = width.max(height);
+
+    if largest <= max_size
+    {
+        return (width.max(1), height.max(1));
+    }
+
+    let scale = max_size as f64 / largest as f64;
+
+    let width = ((width as f64 * scale).floor() as u32).clamp(1, max_size);
+    let height = ((height as f64 * scale).floor() as u32).clamp(1, max_size);
+
+    (width, height)
+}
+
 /*
 pub fn extract_rotation(matrix: Matrix4<f32>) -> Matrix3<f32>
 {
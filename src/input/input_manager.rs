@@ -1,10 +1,11 @@
-use super::{keyboard::Keyboard, mouse::Mouse};
+use super::{keyboard::Keyboard, mouse::Mouse, touch::Touch};
 
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum InputType
 {
     Mouse,
     Keyboard,
+    Touch,
     Unkown
 }
 
@@ -12,6 +13,7 @@ pub struct InputManager
 {
     pub keyboard: Keyboard,
     pub mouse: Mouse,
+    pub touch: Touch,
 
     pub last_input_device: InputType
 }
@@ -24,6 +26,7 @@ impl InputManager
         {
             keyboard: Keyboard::new(),
             mouse: Mouse::new(),
+            touch: Touch::new(),
 
             last_input_device: InputType::Unkown
         }
@@ -39,15 +42,21 @@ impl InputManager
         {
             self.last_input_device = InputType::Mouse;
         }
+        else if self.touch.has_input()
+        {
+            self.last_input_device = InputType::Touch;
+        }
 
 
         self.keyboard.update_states();
         self.mouse.update_states();
+        self.touch.update_states();
     }
 
     pub fn reset(&mut self)
     {
         self.keyboard.reset();
         self.mouse.reset();
+        self.touch.reset();
     }
 }
\ No newline at end of file
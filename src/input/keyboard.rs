@@ -3,6 +3,8 @@
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter, Display, FromRepr};
 
+use crate::helper::consumable::Consumable;
+
 use super::press_state::{PressState, PressStateType, is_pressed_by_state};
 
 #[derive(EnumIter, Debug, PartialEq, Clone, Copy, Display, FromRepr)]
@@ -201,7 +203,7 @@ pub enum Modifier
 
 pub struct Keyboard
 {
-    keys: Vec<PressState>,
+    keys: Vec<Consumable<PressState>>, // a press is consumed by the first one handling it
     modifiers: Vec<PressState>,
 }
 
@@ -212,7 +214,7 @@ impl Keyboard
         let key_vec = Key::iter().collect::<Vec<_>>();
         let modifiers_vec = Modifier::iter().collect::<Vec<_>>();
 
-        let key_states = key_vec.iter().map(|_key| { PressState::new() }).collect::<Vec<_>>();
+        let key_states = key_vec.iter().map(|_key| { Consumable::new(PressState::new()) }).collect::<Vec<_>>();
         let mod_states = modifiers_vec.iter().map(|_key| { PressState::new() }).collect::<Vec<_>>();
 
         Self
@@ -224,7 +226,13 @@ impl Keyboard
 
     pub fn set_key(&mut self, key: Key, status: bool)
     {
-        self.keys[key as usize].update(status);
+        self.keys[key as usize].get_mut().update(status);
+
+        // new press -> not consumed yet
+        if status
+        {
+            self.keys[key as usize].force_change();
+        }
     }
 
     pub fn set_modifier(&mut self, modifier: Modifier, status: bool)
@@ -236,7 +244,7 @@ impl Keyboard
     {
         for key in &mut self.keys
         {
-            key.update_state();
+            key.get_mut().update_state();
         }
 
         for modifier in &mut self.modifiers
@@ -249,7 +257,8 @@ impl Keyboard
     {
         for key in &mut self.keys
         {
-            key.reset(true);
+            key.get_mut().reset(true);
+            key.consume_change();
         }
 
         for modifier in &mut self.modifiers
@@ -262,7 +271,7 @@ impl Keyboard
     {
         for key in &self.keys
         {
-            if key.holding()
+            if key.get().holding()
             {
                 return true;
             }
@@ -281,14 +290,14 @@ impl Keyboard
 
     pub fn is_holding(&self, key: Key) -> bool
     {
-        self.keys[key as usize].holding()
+        self.keys[key as usize].get().holding()
     }
 
     pub fn is_holding_by_keys(&self, keys: Vec<Key>) -> bool
     {
         for key in keys
         {
-            if self.keys[key as usize].holding()
+            if self.keys[key as usize].get().holding()
             {
                 return true;
             }
@@ -302,9 +311,41 @@ impl Keyboard
         self.modifiers[modifier as usize].holding()
     }
 
+    // the press is handled (by the ui for example) -> is_pressed is false until the next press
+    pub fn consume(&mut self, key: Key)
+    {
+        self.keys[key as usize].consume_change();
+    }
+
+    pub fn consume_all(&mut self)
+    {
+        for key in &mut self.keys
+        {
+            key.consume_change();
+        }
+    }
+
+    pub fn is_consumed(&self, key: Key) -> bool
+    {
+        !self.keys[key as usize].changed()
+    }
+
     pub fn get_pressed_state(&mut self, key: Key, wait_until_key_release: bool, ignore_long_press: bool) -> PressStateType
     {
-        self.keys[key as usize].pressed(wait_until_key_release, ignore_long_press)
+        if self.is_consumed(key)
+        {
+            return PressStateType::NotPressed;
+        }
+
+        let state = self.keys[key as usize].get_mut().pressed(wait_until_key_release, ignore_long_press);
+
+        // instant presses are repeated while holding the key
+        if wait_until_key_release && state != PressStateType::NotPressed
+        {
+            self.consume(key);
+        }
+
+        state
     }
 
     pub fn get_pressed_state_modifier(&mut self, modifier: Modifier, wait_until_key_release: bool, ignore_long_press: bool) -> PressStateType
@@ -314,7 +355,7 @@ impl Keyboard
 
     pub fn is_pressed(&mut self, key: Key) -> bool
     {
-        let state = self.keys[key as usize].pressed(true, false);
+        let state = self.get_pressed_state(key, true, false);
         is_pressed_by_state(state)
     }
 
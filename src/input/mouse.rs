@@ -4,12 +4,15 @@ use nalgebra::{Vector2, Point2};
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter, Display, FromRepr};
 
-use crate::{helper::{generic, math, change_tracker::ChangeTracker}, input::input_point::PointState};
+use crate::{helper::{generic, math, change_tracker::ChangeTracker, consumable::Consumable}, input::input_point::PointState};
 
 use super::{press_state::{PressState, is_pressed_by_state}, input_point::InputPoint};
 
 const MOUSE_MAX_CLICK_MOVEMENT: f32 = 12.0;
 
+pub const MOUSE_MAX_SMOOTHING: f32 = 0.95;
+const MOUSE_MIN_SMOOTHING_MOVEMENT: f32 = 0.01;
+
 #[derive(EnumIter, Debug, PartialEq, Clone, Copy, Display, FromRepr)]
 pub enum MouseButton
 {
@@ -31,7 +34,8 @@ pub enum MouseButton
 pub struct Mouse
 {
     pub visible: ChangeTracker<bool>,
-    pub buttons: Vec<PressState>,
+    pub locked: ChangeTracker<bool>, // pointer lock while looking around - the cursor is hidden and the position is kept
+    pub buttons: Vec<Consumable<PressState>>, // a press is consumed by the first one handling it
 
     pub point: InputPoint,
 
@@ -39,6 +43,8 @@ pub struct Mouse
 
     pub wheel_delta_x: f32,
     pub wheel_delta_y: f32,
+
+    locked_distance: f32, // movement while locked (the position does not change then)
 }
 
 impl Mouse
@@ -47,11 +53,12 @@ impl Mouse
     {
         let button_vec = MouseButton::iter().collect::<Vec<_>>();
 
-        let button_states = button_vec.iter().map(|_key| { PressState::new() }).collect::<Vec<_>>();
+        let button_states = button_vec.iter().map(|_key| { Consumable::new(PressState::new()) }).collect::<Vec<_>>();
 
         Self
         {
             visible: ChangeTracker::new(true),
+            locked: ChangeTracker::new(false),
             buttons: button_states,
 
             point: InputPoint::new(0),
@@ -59,7 +66,9 @@ impl Mouse
             last_active_button: MouseButton::Unkown,
 
             wheel_delta_x: 0.0,
-            wheel_delta_y: 0.0
+            wheel_delta_y: 0.0,
+
+            locked_distance: 0.0,
         }
     }
 
@@ -67,7 +76,7 @@ impl Mouse
     {
         for button in &self.buttons
         {
-            if button.holding()
+            if button.get().holding()
             {
                 return true;
             }
@@ -76,9 +85,15 @@ impl Mouse
         false
     }
 
+    // the cursor is captured (hidden) -> relative motion is used instead of the position
+    pub fn is_captured(&self) -> bool
+    {
+        *self.locked.get_ref() || !*self.visible.get_ref()
+    }
+
     pub fn set_button(&mut self, button: MouseButton, status: bool)
     {
-        self.buttons[button as usize].update(status);
+        self.buttons[button as usize].get_mut().update(status);
 
         if self.point.first_action == 0
         {
@@ -88,16 +103,30 @@ impl Mouse
         self.point.last_action = generic::get_millis();
         self.last_active_button = button;
 
+        // new press -> not consumed yet
+        if status
+        {
+            self.buttons[button as usize].force_change();
+            self.locked_distance = 0.0;
+        }
+
         if status && self.point.last_pos.is_some()
         {
             self.point.start_pos = Some(self.point.last_pos.unwrap().clone());
         }
     }
 
-    pub fn set_pos(&mut self, pos: Point2::<f32>, engine_frame: u64, window_width: u32, window_height: u32)
+    pub fn set_pos(&mut self, pos: Point2::<f32>, engine_frame: u64)
     {
+        // the cursor is kept at its position (or warped back to the center) while locked
+        if *self.locked.get_ref()
+        {
+            return;
+        }
+
         let pressed = self.is_any_button_holding();
 
+        // hidden cursor: velocity is based on the relative motion (see add_motion)
         if *self.visible.get_ref()
         {
             if let Some(point_pos) = self.point.pos
@@ -105,10 +134,6 @@ impl Mouse
                 self.point.velocity += pos - point_pos;
             }
         }
-        else
-        {
-            self.point.velocity += pos - Point2::<f32>::new(window_width as f32 / 2.0, window_height as f32 / 2.0);
-        }
 
 
         if self.point.start_pos.is_none()
@@ -148,6 +173,20 @@ impl Mouse
 		self.point.last_action_frame = engine_frame;
     }
 
+    // relative (raw) mouse motion - independent of the cursor position and the screen borders
+    pub fn add_motion(&mut self, delta: Vector2::<f32>)
+    {
+        if !self.is_captured()
+        {
+            return;
+        }
+
+        self.point.velocity += delta;
+        self.locked_distance += delta.magnitude();
+
+        self.point.last_action = generic::get_millis();
+    }
+
     pub fn set_wheel_delta_x(&mut self, delta: f32)
     {
         self.wheel_delta_x = delta;
@@ -172,7 +211,7 @@ impl Mouse
 
         for button in &mut self.buttons
         {
-            button.update_state();
+            button.get_mut().update_state();
         }
 
         self.wheel_delta_x = 0.0;
@@ -183,7 +222,8 @@ impl Mouse
     {
         for button in &mut self.buttons
         {
-            button.reset(true);
+            button.get_mut().reset(true);
+            button.consume_change();
         }
 
         self.point.state = PointState::Stationary;
@@ -191,35 +231,129 @@ impl Mouse
 
     pub fn is_holding(&self, button: MouseButton) -> bool
     {
-        self.buttons[button as usize].holding()
+        self.buttons[button as usize].get().holding()
     }
 
     pub fn is_holding_long(&self, button: MouseButton) -> bool
     {
-        self.buttons[button as usize].holding_long()
+        self.buttons[button as usize].get().holding_long()
+    }
+
+    // the press is handled (by the ui for example) -> is_pressed and clicked are false until the next press
+    pub fn consume(&mut self, button: MouseButton)
+    {
+        self.buttons[button as usize].consume_change();
+    }
+
+    pub fn consume_all(&mut self)
+    {
+        for button in &mut self.buttons
+        {
+            button.consume_change();
+        }
+    }
+
+    pub fn is_consumed(&self, button: MouseButton) -> bool
+    {
+        !self.buttons[button as usize].changed()
+    }
+
+    fn consume_pressed(&mut self, button: MouseButton) -> bool
+    {
+        if self.is_consumed(button)
+        {
+            return false;
+        }
+
+        let state = self.buttons[button as usize].get_mut().pressed(true, false);
+
+        if is_pressed_by_state(state)
+        {
+            self.consume(button);
+            return true;
+        }
+
+        false
     }
 
     pub fn is_pressed(&mut self, button: MouseButton) -> bool
     {
-        let state = self.buttons[button as usize].pressed(true, false);
-        is_pressed_by_state(state)
+        self.consume_pressed(button)
     }
 
     pub fn clicked(&mut self, button: MouseButton) -> bool
     {
-        let distance = self.point.moved_distance();
+        let distance = self.point.moved_distance() + self.locked_distance;
 
         if distance < MOUSE_MAX_CLICK_MOVEMENT
         {
-            let state = self.buttons[button as usize].pressed(true, false);
-            return is_pressed_by_state(state);
+            return self.consume_pressed(button);
         }
 
         false
     }
 
+    // like clicked - but the press is not consumed (to observe clicks which are handled somewhere else)
+    pub fn peek_clicked(&self, button: MouseButton) -> bool
+    {
+        let distance = self.point.moved_distance() + self.locked_distance;
+
+        if distance >= MOUSE_MAX_CLICK_MOVEMENT || self.is_consumed(button)
+        {
+            return false;
+        }
+
+        let mut state = self.buttons[button as usize].get().clone();
+
+        is_pressed_by_state(state.pressed(true, false))
+    }
+
     pub fn has_input(&self) -> bool
     {
         math::approx_zero_vec2(&self.point.velocity) || self.is_any_button_holding()
     }
-}
\ No newline at end of file
+}
+
+// exponential smoothing of the mouse movement - the remaining movement is applied over the next frames (so nothing gets lost)
+pub struct MouseSmoothing
+{
+    remaining: Vector2::<f32>,
+}
+
+impl MouseSmoothing
+{
+    pub fn new() -> Self
+    {
+        Self
+        {
+            remaining: Vector2::<f32>::zeros(),
+        }
+    }
+
+    // smoothing: 0 = off .. 1 = strong (part of the movement which is left after one reference frame)
+    pub fn apply(&mut self, velocity: Vector2::<f32>, smoothing: f32, frame_scale: f32) -> Vector2::<f32>
+    {
+        if smoothing <= 0.0
+        {
+            self.remaining = Vector2::<f32>::zeros();
+            return velocity;
+        }
+
+        self.remaining += velocity;
+
+        // frame rate independent: the same amount is left after the same time
+        let keep = smoothing.min(MOUSE_MAX_SMOOTHING).powf(frame_scale);
+
+        let mut smoothed = self.remaining * (1.0 - keep);
+
+        // stop at some point
+        if (self.remaining - smoothed).magnitude() < MOUSE_MIN_SMOOTHING_MOVEMENT
+        {
+            smoothed = self.remaining;
+        }
+
+        self.remaining -= smoothed;
+
+        smoothed
+    }
+}
@@ -17,6 +17,7 @@ pub fn is_pressed_by_state(state: PressStateType) -> bool
     state == PressStateType::Pressed || state == PressStateType::LongPressed
 }
 
+#[derive(Clone)]
 pub struct PressState
 {
     pub reset_on_pressed: bool, //needed for keys without key up events
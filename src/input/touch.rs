@@ -0,0 +1,179 @@
+#![allow(dead_code)]
+
+use nalgebra::{Vector2, Point2, distance, center};
+
+use crate::helper::{generic, math};
+
+use super::input_point::{InputPoint, PointState};
+
+const TOUCH_MAX_TAP_MOVEMENT: f32 = 20.0;
+const TOUCH_MAX_TAP_TIME: u64 = 300; // ms
+
+pub struct Touch
+{
+    pub points: Vec<InputPoint>, // active touches (id = finger id)
+
+    // two finger gestures (reset every frame)
+    pub pinch_delta: f32, // change of the distance between both fingers
+    pub pan_velocity: Vector2::<f32>, // movement of the center of both fingers
+
+    multi_touch: bool, // there was more than one finger since the first one was placed
+    tap: Option<Point2::<f32>>,
+}
+
+impl Touch
+{
+    pub fn new() -> Self
+    {
+        Self
+        {
+            points: vec![],
+
+            pinch_delta: 0.0,
+            pan_velocity: Vector2::<f32>::zeros(),
+
+            multi_touch: false,
+            tap: None,
+        }
+    }
+
+    pub fn set_touch(&mut self, id: u64, pos: Point2::<f32>, state: PointState, engine_frame: u64)
+    {
+        match state
+        {
+            PointState::Down =>
+            {
+                let mut point = InputPoint::new(id);
+                point.start_pos = Some(pos);
+                point.last_pos = Some(pos);
+                point.pos = Some(pos);
+                point.state = PointState::Down;
+                point.first_action = generic::get_millis();
+                point.first_action_frame = engine_frame;
+                point.last_action = point.first_action;
+                point.last_action_frame = engine_frame;
+
+                self.points.retain(|point| point.id != id);
+                self.points.push(point);
+
+                if self.points.len() > 1
+                {
+                    self.multi_touch = true;
+                }
+            },
+            PointState::Move =>
+            {
+                let (last_distance, last_center) = self.two_finger_state();
+
+                if let Some(point) = self.points.iter_mut().find(|point| point.id == id)
+                {
+                    if let Some(point_pos) = point.pos
+                    {
+                        point.velocity += pos - point_pos;
+                    }
+
+                    point.pos = Some(pos);
+                    point.state = PointState::Move;
+                    point.last_action = generic::get_millis();
+                    point.last_action_frame = engine_frame;
+                }
+
+                if let (Some(last_distance), Some(last_center)) = (last_distance, last_center)
+                {
+                    let (distance, center) = self.two_finger_state();
+
+                    self.pinch_delta += distance.unwrap() - last_distance;
+                    self.pan_velocity += center.unwrap() - last_center;
+                }
+            },
+            PointState::Up | PointState::Stationary =>
+            {
+                if let Some(index) = self.points.iter().position(|point| point.id == id)
+                {
+                    let point = self.points.remove(index);
+
+                    let duration = generic::get_millis() - point.first_action;
+
+                    if !self.multi_touch && point.moved_distance() < TOUCH_MAX_TAP_MOVEMENT && duration < TOUCH_MAX_TAP_TIME
+                    {
+                        self.tap = point.pos;
+                    }
+                }
+
+                if self.points.is_empty()
+                {
+                    self.multi_touch = false;
+                }
+            },
+        }
+    }
+
+    // distance and center of the first two fingers
+    fn two_finger_state(&self) -> (Option<f32>, Option<Point2::<f32>>)
+    {
+        if self.points.len() < 2
+        {
+            return (None, None);
+        }
+
+        let a = self.points[0].pos.unwrap();
+        let b = self.points[1].pos.unwrap();
+
+        (Some(distance(&a, &b)), Some(center(&a, &b)))
+    }
+
+    // single finger movement (like dragging with the mouse)
+    pub fn drag_velocity(&self) -> Vector2::<f32>
+    {
+        if self.points.len() == 1 && !self.multi_touch
+        {
+            return self.points[0].velocity;
+        }
+
+        Vector2::<f32>::zeros()
+    }
+
+    pub fn is_active(&self) -> bool
+    {
+        !self.points.is_empty()
+    }
+
+    // position of a short touch without movement (only valid in the frame the finger was released)
+    pub fn tapped(&self) -> Option<Point2::<f32>>
+    {
+        self.tap
+    }
+
+    pub fn update_states(&mut self)
+    {
+        for point in &mut self.points
+        {
+            point.last_pos = point.pos;
+            point.velocity = Vector2::<f32>::zeros();
+
+            if point.state == PointState::Down
+            {
+                point.state = PointState::Stationary;
+            }
+        }
+
+        self.pinch_delta = 0.0;
+        self.pan_velocity = Vector2::<f32>::zeros();
+        self.tap = None;
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.points.clear();
+        self.multi_touch = false;
+
+        self.pinch_delta = 0.0;
+        self.pan_velocity = Vector2::<f32>::zeros();
+        self.tap = None;
+    }
+
+    pub fn has_input(&self) -> bool
+    {
+        self.is_active() || !math::approx_zero(self.pinch_delta) || !math::approx_zero_vec2(&self.pan_velocity)
+    }
+}
@@ -0,0 +1,242 @@
+use std::mem::swap;
+
+use image::DynamicImage;
+use nalgebra::{Point3, Vector3};
+
+use crate::helper::concurrency::execution_queue::ExecutionQueue;
+use crate::helper::concurrency::thread::{spawn_thread, sleep_millis, ThreadResult};
+use crate::helper::image::save_image;
+use crate::rendering::scene::Scene;
+use crate::rendering::wgpu::WGpu;
+use crate::state::helper::render_item::get_render_item_mut;
+use crate::state::scene::camera::Camera;
+use crate::state::scene::components::material::TextureType;
+use crate::state::scene::utilities::scene_utils::{self, load_object};
+use crate::state::state::State;
+
+const DEFAULT_ENV_MAP: &str = "textures/environment/footprint_court.jpg";
+
+pub struct HeadlessInterface
+{
+    pub state: State,
+    wgpu: WGpu,
+}
+
+impl HeadlessInterface
+{
+    pub async fn new(width: u32, height: u32) -> Self
+    {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let mut state = State::new();
+        state.width = width;
+        state.height = height;
+
+        let mut wgpu = WGpu::new_headless(width, height, &mut state).await;
+
+        state.rendering.msaa.set(state.rendering.msaa.get_ref().clone().min(state.adapter.max_msaa_samples));
+        let samples = *(state.rendering.msaa.get_ref());
+        wgpu.create_msaa_texture(samples);
+
+        state.rendering.anisotropy = state.rendering.anisotropy.min(state.adapter.max_anisotropy);
+
+        Self
+        {
+            state,
+            wgpu
+        }
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32)
+    {
+        self.state.width = width.max(1);
+        self.state.height = height.max(1);
+
+        self.wgpu.resize(self.state.width, self.state.height);
+
+        for scene in &mut self.state.scenes
+        {
+            if scene.render_item.is_none()
+            {
+                continue;
+            }
+
+            let mut render_item = scene.render_item.take();
+
+            let render_scene = get_render_item_mut::<Scene>(render_item.as_mut().unwrap());
+            render_scene.resize(&mut self.wgpu, scene);
+
+            scene.render_item = render_item;
+        }
+    }
+
+    // loaders are executing changes via the main queue -> process it until the thread is done
+    pub fn wait_for(&mut self, thread: ThreadResult<()>)
+    {
+        while !thread.is_finished()
+        {
+            self.run_main_queue();
+            sleep_millis(1);
+        }
+
+        self.run_main_queue();
+    }
+
+    pub fn run_main_queue(&mut self)
+    {
+        let main_queue = self.state.main_thread_execution_queue.clone();
+        ExecutionQueue::run_all(main_queue, &mut self.state);
+    }
+
+    pub fn update(&mut self, frame_scale: f32)
+    {
+        self.run_main_queue();
+
+        let state = &mut self.state;
+        state.update(frame_scale);
+        state.update_texture_color_spaces();
+
+        let samples = *(state.rendering.msaa.get_ref());
+
+        // move out scenes from state to prevent using multiple mut borrows
+        let mut scenes = vec![];
+        swap(&mut state.scenes, &mut scenes);
+
+        for scene in &mut scenes
+        {
+            if scene.render_item.is_none()
+            {
+                let render_item = Scene::new(&mut self.wgpu, state, scene, samples);
+                scene.render_item = Some(Box::new(render_item));
+            }
+
+            let mut render_item = scene.render_item.take();
+
+            let render_scene = get_render_item_mut::<Scene>(render_item.as_mut().unwrap());
+            render_scene.update(&mut self.wgpu, state, scene);
+
+            scene.render_item = render_item;
+        }
+
+        swap(&mut scenes, &mut state.scenes);
+    }
+
+    pub fn render_image(&mut self) -> DynamicImage
+    {
+        let state = &mut self.state;
+
+        let (buffer_dimensions, output_buffer, texture, view, msaa_view, mut encoder) = self.wgpu.start_screenshot_render();
+        {
+            for scene in &mut state.scenes
+            {
+                if !scene.visible || scene.render_item.is_none()
+                {
+                    continue;
+                }
+
+                let mut render_item = scene.render_item.take();
+
+                let render_scene = get_render_item_mut::<Scene>(render_item.as_mut().unwrap());
+                render_scene.distance_sorting = state.rendering.distance_sorting;
+                render_scene.render(&mut self.wgpu, &view, &msaa_view, &mut encoder, scene);
+
+                scene.render_item = render_item;
+            }
+        }
+
+        self.wgpu.end_screenshot_render(buffer_dimensions, output_buffer, texture, encoder)
+    }
+}
+
+// renders a single frame of an object into an image file without creating a window
+// camera: optional (eye position, target) - otherwise the camera is placed to fit the whole object
+pub async fn render_to_file(object_path: &str, output_path: &str, width: u32, height: u32, camera: Option<(Point3<f32>, Point3<f32>)>) -> anyhow::Result<()>
+{
+    let mut interface = HeadlessInterface::new(width, height).await;
+
+    // ********** scene **********
+    let mut scene = crate::state::scene::scene::Scene::new(0, "headless scene");
+    scene.add_default_material();
+
+    let scene_id = scene.id;
+    interface.state.scenes.push(Box::new(scene));
+
+    // ********** load **********
+    let main_queue = interface.state.main_thread_execution_queue.clone();
+    let path = object_path.to_string();
+    let create_mipmaps = interface.state.rendering.create_mipmaps;
+
+    let loading = spawn_thread(move ||
+    {
+        if let Err(err) = load_object(path.as_str(), scene_id, main_queue.clone(), true, true, false, create_mipmaps, None)
+        {
+            println!("can not load {}: {:?}", path, err);
+        }
+
+        scene_utils::load_texture(DEFAULT_ENV_MAP, main_queue.clone(), TextureType::Environment, scene_id, None, create_mipmaps);
+    });
+
+    interface.wait_for(loading);
+
+    // ********** camera **********
+    {
+        let (width, height) = (interface.state.width, interface.state.height);
+        let scene = interface.state.find_scene_by_id_mut(scene_id).unwrap();
+        scene.clear_empty_nodes();
+
+        if scene.cameras.len() == 0 || camera.is_some()
+        {
+            let mut cam = Camera::new(scene.id_manager.get_next_camera_id(), "Cam".to_string());
+            cam.init(0.0, 0.0, 1.0, 1.0, width, height);
+
+            let (eye_pos, target, radius) = match camera
+            {
+                Some((eye_pos, target)) => (eye_pos, target, (target - eye_pos).norm()),
+                None => fit_camera(scene, cam.get_data().fovy)
+            };
+
+            let cam_data = cam.get_data_mut().get_mut();
+            cam_data.eye_pos = eye_pos;
+            cam_data.dir = (target - eye_pos).normalize();
+            cam_data.clipping_near = (radius * 0.001).max(0.001);
+            cam_data.clipping_far = (radius * 100.0).max(1000.0);
+            cam.init_matrices();
+
+            // only the new camera should be used
+            for existing_cam in &mut scene.cameras
+            {
+                existing_cam.enabled = false;
+            }
+
+            scene.cameras.push(Box::new(cam));
+        }
+    }
+
+    // ********** render **********
+    interface.update(0.0);
+    let img_data = interface.render_image();
+
+    save_image(&img_data, output_path)
+}
+
+// returns eye position, target and radius to see all nodes of the scene
+fn fit_camera(scene: &crate::state::scene::scene::Scene, fovy: f32) -> (Point3<f32>, Point3<f32>, f32)
+{
+    let bounding_info = scene.get_bounding_info();
+
+    if bounding_info.is_none()
+    {
+        return (Point3::<f32>::new(0.0, 1.0, 1.5), Point3::<f32>::origin(), 1.0);
+    }
+
+    let (min, max) = bounding_info.unwrap();
+
+    let target = min + (max - min) / 2.0;
+    let radius = ((max - min).norm() / 2.0).max(0.001);
+
+    let distance = radius / (fovy / 2.0).sin();
+    let dir = Vector3::<f32>::new(0.0, 0.5, 1.0).normalize();
+
+    (target + dir * distance, target, radius)
+}
@@ -11,10 +11,12 @@ use winit::window::{Window, Fullscreen, CursorGrabMode};
 
 use crate::component_downcast_mut;
 use crate::helper::concurrency::execution_queue::ExecutionQueue;
-use crate::helper::concurrency::thread::spawn_thread;
+use crate::helper::concurrency::thread::{spawn_thread, sleep_millis};
 use crate::helper::platform;
+use crate::helper::image::save_image;
+use crate::helper::math::fit_size;
 use crate::input::keyboard::{Modifier, Key};
-use crate::interface::winit::winit_map_mouse_button;
+use crate::interface::winit::{winit_map_mouse_button, winit_map_touch_phase};
 use crate::rendering::egui::EGui;
 use crate::rendering::scene::Scene;
 use crate::state::gui::editor::editor::Editor;
@@ -22,12 +24,14 @@ use crate::rendering::wgpu::WGpu;
 use crate::state::helper::render_item::get_render_item_mut;
 use crate::state::scene::camera::Camera;
 use crate::state::scene::camera_controller::target_rotation_controller::TargetRotationController;
-use crate::state::scene::utilities::scene_utils::{self, load_object, execute_on_scene_mut_and_wait};
+use crate::state::scene::utilities::scene_utils::{load_object, execute_on_scene_mut_and_wait};
 use crate::state::state::{State, StateItem, FPS_CHART_VALUES};
 
 use super::winit::winit_map_key;
 
-const REFERENCE_UPDATE_FRAMES: f32 = 60.0;
+pub const REFERENCE_UPDATE_FRAMES: f32 = 60.0;
+const MAIN_QUEUE_MAX_TIME: f32 = 8.0; // ms per frame - to keep the frame rate while loading
+const UNFOCUSED_FRAME_TIME: u64 = 100; // ms - frame time while paused (window not focused)
 
 pub struct MainInterface
 {
@@ -41,6 +45,10 @@ pub struct MainInterface
     wgpu: WGpu,
     window: Window,
     egui: EGui,
+
+    cursor_grab: Option<CursorGrabMode>, // active grab mode if the cursor is captured
+    cursor_pos: PhysicalPosition<f64>,
+    cursor_restore_pos: Option<PhysicalPosition<f64>>,
 }
 
 impl MainInterface
@@ -61,6 +69,8 @@ impl MainInterface
             state.rendering.msaa.set(cmp::min(state.rendering.msaa.get_ref().clone(), state.adapter.max_msaa_samples));
             samlpes = *(state.rendering.msaa.get_ref());
 
+            state.rendering.anisotropy = state.rendering.anisotropy.min(state.adapter.max_anisotropy);
+
             wgpu.create_msaa_texture(samlpes);
         }
 
@@ -84,6 +94,10 @@ impl MainInterface
             wgpu,
             window,
             egui,
+
+            cursor_grab: None,
+            cursor_pos: PhysicalPosition::new(0.0, 0.0),
+            cursor_restore_pos: None,
         };
 
         interface.app_init();
@@ -148,6 +162,78 @@ impl MainInterface
         }
     }
 
+    pub fn render_image(&mut self, state: &mut State, path: &str)
+    {
+        let requested_supersampling = state.render_image_supersampling.max(1);
+        let requested_width = state.render_image_width.max(1);
+        let requested_height = state.render_image_height.max(1);
+
+        // the image and the supersampled render target have to fit into a texture
+        // both sides are scaled by the same factor - so nothing gets stretched
+        let max_size = self.wgpu.max_texture_size();
+        let (width, height) = fit_size(requested_width, requested_height, max_size);
+        let supersampling = requested_supersampling.min(max_size / width.max(height)).max(1);
+
+        if (width, height) != (requested_width, requested_height) || supersampling != requested_supersampling
+        {
+            println!("render image: {}x{} with {}x supersampling is exceeding the max texture size of {} -> using {}x{} with {}x supersampling", requested_width, requested_height, requested_supersampling, max_size, width, height, supersampling);
+        }
+
+        // render with the requested size (cameras and depth buffers are updated on resize)
+        self.wgpu.set_offscreen_size(width * supersampling, height * supersampling);
+
+        for scene in &mut state.scenes
+        {
+            let mut render_item = scene.render_item.take();
+
+            let render_scene = get_render_item_mut::<Scene>(render_item.as_mut().unwrap());
+            render_scene.resize(&mut self.wgpu, scene);
+
+            scene.render_item = render_item;
+        }
+
+        let (buffer_dimensions, output_buffer, texture, view, msaa_view, mut encoder) = self.wgpu.start_screenshot_render();
+        {
+            for scene in &mut state.scenes
+            {
+                let mut render_item = scene.render_item.take();
+
+                let render_scene = get_render_item_mut::<Scene>(render_item.as_mut().unwrap());
+                render_scene.distance_sorting = state.rendering.distance_sorting;
+                render_scene.transparent_clear = state.screenshot_transparent;
+                render_scene.render(&mut self.wgpu, &view, &msaa_view, &mut encoder, scene);
+                render_scene.transparent_clear = false;
+
+                scene.render_item = render_item;
+            }
+        }
+        let mut img_data = self.wgpu.end_screenshot_render(buffer_dimensions, output_buffer, texture, encoder);
+
+        // downsample
+        if supersampling > 1
+        {
+            img_data = img_data.resize_exact(width, height, image::imageops::FilterType::Triangle);
+        }
+
+        if let Err(err) = save_image(&img_data, path)
+        {
+            println!("can not save image {}: {:?}", path, err);
+        }
+
+        // restore window size
+        self.wgpu.resize(state.width, state.height);
+
+        for scene in &mut state.scenes
+        {
+            let mut render_item = scene.render_item.take();
+
+            let render_scene = get_render_item_mut::<Scene>(render_item.as_mut().unwrap());
+            render_scene.resize(&mut self.wgpu, scene);
+
+            scene.render_item = render_item;
+        }
+    }
+
     pub fn app_init(&mut self)
     {
         //init scene
@@ -442,12 +528,8 @@ impl MainInterface
             //scene.update(&mut state.input_manager, state.frame_scale);
             state.scenes.push(Box::new(scene));
 
-            let main_queue_clone = main_queue.clone();
-            spawn_thread(move ||
-            {
-                scene_utils::create_grid(scene_id, main_queue_clone.clone(), 500, 1.0);
-            });
-            //scene_utils::create_grid(&mut scene, 1, 1.0);
+            // editor ground grid
+            state.rendering.grid.enabled = true;
 
             //load default env texture
             state.load_scene_env_map("textures/environment/footprint_court.jpg", scene_id);
@@ -501,6 +583,19 @@ impl MainInterface
 
     pub fn update(&mut self)
     {
+        let paused;
+        {
+            let state = self.state.borrow();
+            paused = state.pause_on_unfocus && !state.in_focus;
+        }
+
+        // throttle while paused (browsers are doing this already for background tabs)
+        #[cfg(not(target_arch = "wasm32"))]
+        if paused
+        {
+            sleep_millis(UNFOCUSED_FRAME_TIME);
+        }
+
         let frame_time = Instant::now();
 
         // update states
@@ -545,22 +640,39 @@ impl MainInterface
                 state.fps += 1;
             }
 
-            // frame scale
-            let elapsed = self.start_time.elapsed();
-            let now = elapsed.as_micros();
-
-            if state.frame_update_time > 0 && now - state.frame_update_time > 0
+            // frame scale (not while paused - to prevent a jump after focusing again)
+            if paused
             {
-                state.frame_scale = REFERENCE_UPDATE_FRAMES / (1000000.0 / (now - state.frame_update_time) as f32);
+                state.frame_update_time = 0;
             }
+            else
+            {
+                let elapsed = self.start_time.elapsed();
+                let now = elapsed.as_micros();
 
-            state.frame_update_time = now;
+                if state.frame_update_time > 0 && now - state.frame_update_time > 0
+                {
+                    state.frame_scale = REFERENCE_UPDATE_FRAMES / (1000000.0 / (now - state.frame_update_time) as f32);
+                }
+
+                state.frame_update_time = now;
+            }
         }
 
         // editor/ui update
         {
             let now = Instant::now();
             let state = &mut *(self.state.borrow_mut());
+
+            // clicks on ui elements are not forwarded to the scene (picking etc)
+            if self.editor_gui.editor_state.visible && self.egui.is_using_pointer()
+            {
+                state.input_manager.mouse.consume_all();
+            }
+
+            // pick events (the editor selection is based on them)
+            state.update_picks();
+
             self.editor_gui.update(state);
 
             state.editor_update_time = now.elapsed().as_micros() as f32 / 1000.0;
@@ -592,7 +704,7 @@ impl MainInterface
         {
             let state = &mut *(self.state.borrow_mut());
             let main_queue = state.main_thread_execution_queue.clone();
-            ExecutionQueue::run_all(main_queue, state);
+            ExecutionQueue::run_for(main_queue, state, MAIN_QUEUE_MAX_TIME);
         }
 
         // update scene
@@ -609,7 +721,12 @@ impl MainInterface
                 self.wgpu.create_msaa_texture(msaa_samples);
             }
 
-            state.update(state.frame_scale);
+            if !paused
+            {
+                state.update(state.frame_scale);
+            }
+
+            state.update_texture_color_spaces();
 
             // move out scenes from state to prevent using multiple mut borrows
             let mut scenes = vec![];
@@ -645,6 +762,7 @@ impl MainInterface
                 let engine_render_time = Instant::now();
 
                 state.draw_calls = 0;
+                state.rendered_vertices = 0;
 
                 for scene in &mut state.scenes
                 {
@@ -658,6 +776,7 @@ impl MainInterface
                     let render_scene = get_render_item_mut::<Scene>(render_item.as_mut().unwrap());
                     render_scene.distance_sorting = state.rendering.distance_sorting;
                     state.draw_calls += render_scene.render(&mut self.wgpu, &view, &msaa_view, &mut encoder, scene);
+                    state.rendered_vertices += render_scene.rendered_vertices;
 
                     scene.render_item = render_item;
                 }
@@ -676,6 +795,18 @@ impl MainInterface
         }
         self.wgpu.end_render(output, encoder);
 
+        // gpu timings and memory
+        {
+            let state = &mut *(self.state.borrow_mut());
+            if let Some(gpu_times) = self.wgpu.gpu_times()
+            {
+                state.gpu_pass_times = gpu_times.clone();
+            }
+
+            state.gpu_buffer_usage = self.wgpu.buffer_stats().list();
+            state.gpu_buffer_uploads = self.wgpu.buffer_stats().consume_uploaded();
+        }
+
         // screenshot
         {
             let state = &mut *(self.state.borrow_mut());
@@ -690,33 +821,46 @@ impl MainInterface
 
                         let render_scene = get_render_item_mut::<Scene>(render_item.as_mut().unwrap());
                         render_scene.distance_sorting = state.rendering.distance_sorting;
+                        render_scene.transparent_clear = state.screenshot_transparent;
                         render_scene.render(&mut self.wgpu, &view, &msaa_view, &mut encoder, scene);
+                        render_scene.transparent_clear = false;
 
                         scene.render_item = render_item;
                     }
 
-                    self.egui.render(&mut self.wgpu, &view, &mut encoder);
+                    // the ui is not part of a transparent screenshot (to be able to composite the rendering)
+                    if !state.screenshot_transparent
+                    {
+                        self.egui.render(&mut self.wgpu, &view, &mut encoder);
+                    }
                 }
                 let img_data = self.wgpu.end_screenshot_render(buffer_dimensions, output_buffer, texture, encoder);
 
-                img_data.save("data/screenshot.png").unwrap();
+                let path = state.screenshot_path.take().unwrap_or("data/screenshot.png".to_string());
+                if let Err(err) = save_image(&img_data, path.as_str())
+                {
+                    println!("can not save screenshot {}: {:?}", path, err);
+                }
+
                 state.save_screenshot = false;
             }
         }
 
-        // update inputs
+        // offscreen render
         {
-            let state = &mut *(self.state.borrow_mut());
-            state.input_manager.update();
+            let state = self.state.clone();
+            let state = &mut *(state.borrow_mut());
+
+            if let Some(path) = state.render_image_path.take()
+            {
+                self.render_image(state, path.as_str());
+            }
         }
 
+        // update inputs
         {
             let state = &mut *(self.state.borrow_mut());
-            let (visible, changed) = state.input_manager.mouse.visible.consume_borrow();
-            if changed
-            {
-                self.window.set_cursor_visible(*visible);
-            }
+            state.input_manager.update();
         }
 
         // frame time
@@ -729,6 +873,40 @@ impl MainInterface
             // frame update
             state.frame += 1;
         }
+
+        // frame rate cap (only without vsync)
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let state = self.state.borrow();
+
+            if let Some(target_fps) = state.rendering.target_fps
+            {
+                if !*state.rendering.v_sync.get_ref() && target_fps > 0
+                {
+                    let target_frame_time = 1000000 / target_fps as u128;
+
+                    // sleep is not accurate enough -> sleep most of the remaining time and spin the rest
+                    loop
+                    {
+                        let elapsed = frame_time.elapsed().as_micros();
+                        if elapsed >= target_frame_time
+                        {
+                            break;
+                        }
+
+                        let remaining = target_frame_time - elapsed;
+                        if remaining > 2000
+                        {
+                            sleep_millis((remaining / 1000 - 1) as u64);
+                        }
+                        else
+                        {
+                            std::hint::spin_loop();
+                        }
+                    }
+                }
+            }
+        }
     }
 
     pub fn check_exit(&mut self) -> bool
@@ -807,7 +985,16 @@ impl MainInterface
                     // invert pos (because x=0, y=0 is bottom left and "normal" window is top left)
                     pos.y = global_state.height as f32 - pos.y;
 
-                    global_state.input_manager.mouse.set_pos(pos, global_state.frame, global_state.width, global_state.height);
+                    global_state.input_manager.mouse.set_pos(pos, global_state.frame);
+
+                    self.cursor_pos = *position;
+                },
+                winit::event::WindowEvent::Touch(touch) =>
+                {
+                    // invert pos (see CursorMoved)
+                    let pos = Point2::<f32>::new(touch.location.x as f32, global_state.height as f32 - touch.location.y as f32);
+
+                    global_state.input_manager.touch.set_touch(touch.id, pos, winit_map_touch_phase(&touch.phase), global_state.frame);
                 },
                 winit::event::WindowEvent::Focused(focus) =>
                 {
@@ -847,34 +1034,86 @@ impl MainInterface
         }
     }
 
+    pub fn device_input(&mut self, event: &winit::event::DeviceEvent)
+    {
+        if let winit::event::DeviceEvent::MouseMotion { delta } = event
+        {
+            let global_state = &mut *(self.state.borrow_mut());
+
+            // invert y (see CursorMoved)
+            global_state.input_manager.mouse.add_motion(Vector2::<f32>::new(delta.0 as f32, -delta.1 as f32));
+        }
+    }
+
     pub fn update_done(&mut self)
     {
         let global_state = &mut *(self.state.borrow_mut());
 
-        let (visible, change) = global_state.input_manager.mouse.visible.consume_clone();
+        let mouse = &mut global_state.input_manager.mouse;
+
+        let visible_changed = mouse.visible.consume_change();
+        let locked_changed = mouse.locked.consume_change();
 
-        if change
+        if visible_changed || locked_changed
         {
-            if visible
+            let captured = mouse.is_captured();
+
+            if captured && self.cursor_grab.is_none()
             {
-                _ = self.window.set_cursor_grab(CursorGrabMode::None);
+                // look mode -> the cursor is moved back after releasing
+                if *mouse.locked.get_ref()
+                {
+                    self.cursor_restore_pos = Some(self.cursor_pos);
+                }
+
+                self.window.set_cursor_visible(false);
+
+                // locked: relative mode without moving the cursor (not supported on windows and x11)
+                // confined: the cursor is warped back to the center each frame
+                let grab_mode;
+                if self.window.set_cursor_grab(CursorGrabMode::Locked).is_ok()
+                {
+                    grab_mode = CursorGrabMode::Locked;
+                }
+                else if self.window.set_cursor_grab(CursorGrabMode::Confined).is_ok()
+                {
+                    grab_mode = CursorGrabMode::Confined;
+                }
+                else
+                {
+                    dbg!("cursor grab is not supported");
+                    grab_mode = CursorGrabMode::None;
+                }
+
+                self.cursor_grab = Some(grab_mode);
             }
-            else
+            else if !captured && self.cursor_grab.is_some()
             {
-                self.window.set_cursor_grab(CursorGrabMode::Confined)
-                .or_else(|_e| self.window.set_cursor_grab(CursorGrabMode::Locked))
-                .unwrap();
+                _ = self.window.set_cursor_grab(CursorGrabMode::None);
+                self.window.set_cursor_visible(true);
+
+                if let Some(pos) = self.cursor_restore_pos.take()
+                {
+                    self.window.set_cursor_position(pos).unwrap_or_else(|e|{
+                        dbg!("Failed to set mouse position: {:?}", e);
+                    });
+                }
+
+                self.cursor_grab = None;
             }
         }
 
-        if !*global_state.input_manager.mouse.visible.get_ref()
+        if let Some(grab_mode) = self.cursor_grab
         {
-            let window_size = self.window.inner_size();
-            let center = PhysicalPosition::new(window_size.width as f64 / 2.0, window_size.height as f64 / 2.0);
+            if grab_mode != CursorGrabMode::Locked
+            {
+                let window_size = self.window.inner_size();
+                let center = PhysicalPosition::new(window_size.width as f64 / 2.0, window_size.height as f64 / 2.0);
 
-            self.window.set_cursor_position(center).unwrap_or_else(|e|{
-                dbg!("Failed to set mouse position: {:?}", e);
-            });
+                self.window.set_cursor_position(center).unwrap_or_else(|e|{
+                    dbg!("Failed to set mouse position: {:?}", e);
+                });
+            }
         }
     }
-}
\ No newline at end of file
+}
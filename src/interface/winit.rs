@@ -1,6 +1,6 @@
 use winit::event::VirtualKeyCode;
 
-use crate::input::{keyboard::Key, mouse::MouseButton};
+use crate::input::{keyboard::Key, mouse::MouseButton, input_point::PointState};
 
 pub fn winit_map_key(key: VirtualKeyCode) -> Key
 {
@@ -201,4 +201,15 @@ pub fn winit_map_mouse_button(button: &winit::event::MouseButton) -> MouseButton
             }
         },
     }
-}
\ No newline at end of file
+}
+
+pub fn winit_map_touch_phase(phase: &winit::event::TouchPhase) -> PointState
+{
+    match phase
+    {
+        winit::event::TouchPhase::Started => PointState::Down,
+        winit::event::TouchPhase::Moved => PointState::Move,
+        winit::event::TouchPhase::Ended => PointState::Up,
+        winit::event::TouchPhase::Cancelled => PointState::Up,
+    }
+}
@@ -11,6 +11,13 @@ mod rendering
     pub(crate) mod uniform;
     pub(crate) mod light;
     pub(crate) mod material;
+    pub(crate) mod grid;
+    pub(crate) mod dof;
+    pub(crate) mod outline;
+    pub(crate) mod wireframe;
+    pub(crate) mod normal_lines;
+    pub(crate) mod labels;
+    pub(crate) mod transmission;
 
     pub(crate) mod bind_groups
     {
@@ -20,6 +27,8 @@ mod rendering
     pub(crate) mod helper
     {
         pub(crate) mod buffer;
+        pub(crate) mod gpu_timer;
+        pub(crate) mod bitmap_font;
     }
 }
 
@@ -37,12 +46,14 @@ mod state
         pub(crate) mod manager
         {
             pub(crate) mod id_manager;
+            pub(crate) mod texture_cache;
         }
 
         pub(crate) mod loader
         {
             pub(crate) mod wavefront;
             pub(crate) mod gltf;
+            pub(crate) mod progress;
         }
 
         pub(crate) mod components
@@ -53,6 +64,10 @@ mod state
             pub(crate) mod material;
             pub(crate) mod alpha;
             pub(crate) mod transformation_animation;
+            pub(crate) mod lod;
+            pub(crate) mod billboard;
+            pub(crate) mod label;
+            pub(crate) mod script;
         }
 
         pub(crate) mod camera_controller
@@ -65,6 +80,7 @@ mod state
         pub(crate) mod utilities
         {
             pub(crate) mod scene_utils;
+            pub(crate) mod ibl;
         }
 
         pub(crate) mod texture;
@@ -73,6 +89,7 @@ mod state
         pub(crate) mod instance;
         pub(crate) mod node;
         pub(crate) mod scene;
+        pub(crate) mod events;
     }
 
     pub(crate) mod gui
@@ -99,6 +116,7 @@ mod state
             pub(crate) mod rendering;
             pub(crate) mod textures;
             pub(crate) mod assets;
+            pub(crate) mod keymap;
         }
     }
 }
@@ -112,6 +130,7 @@ pub(crate) mod input
 
     pub(crate) mod keyboard;
     pub(crate) mod mouse;
+    pub(crate) mod touch;
 
 }
 
@@ -124,6 +143,9 @@ mod interface
 {
     pub(crate) mod main_interface;
     pub(crate) mod winit;
+
+    #[cfg(not(target_arch="wasm32"))]
+    pub(crate) mod headless;
 }
 
 mod helper
@@ -137,6 +159,7 @@ mod helper
     pub(crate) mod generic;
     pub(crate) mod file;
     pub(crate) mod math;
+    pub(crate) mod easing;
     pub(crate) mod image;
     pub(crate) mod crypto;
     pub(crate) mod consumable;
@@ -150,6 +173,8 @@ mod resources
     pub(crate) mod resources;
 }
 
+pub mod api;
+
 #[cfg(target_arch="wasm32")]
 use wasm_bindgen::prelude::*;
 
@@ -157,4 +182,12 @@ use wasm_bindgen::prelude::*;
 pub async fn start()
 {
     window::window::start().await;
+}
+
+// renders one frame of an object (obj/gltf/glb) into an image file without creating a window
+// camera: optional (eye position, target) - otherwise the camera is placed to fit the object
+#[cfg(not(target_arch="wasm32"))]
+pub async fn render_headless(object_path: &str, output_path: &str, width: u32, height: u32, camera: Option<(nalgebra::Point3<f32>, nalgebra::Point3<f32>)>) -> anyhow::Result<()>
+{
+    interface::headless::render_to_file(object_path, output_path, width, height, camera).await
 }
\ No newline at end of file
@@ -1,6 +1,23 @@
-use rustl::start;
+use rustl::{start, render_headless};
 
 fn main()
 {
+    let args: Vec<String> = std::env::args().collect();
+
+    // headless: rustl --render <object> <output> [width] [height]
+    if args.len() >= 4 && args[1] == "--render"
+    {
+        let width = args.get(4).and_then(|w| w.parse::<u32>().ok()).unwrap_or(512);
+        let height = args.get(5).and_then(|h| h.parse::<u32>().ok()).unwrap_or(width);
+
+        if let Err(err) = pollster::block_on(render_headless(args[2].as_str(), args[3].as_str(), width, height, None))
+        {
+            println!("rendering failed: {:?}", err);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     pollster::block_on(start());
 }
@@ -1,9 +1,7 @@
 use nalgebra::{Point3, Matrix4};
-use wgpu::util::DeviceExt;
-
 use crate::{state::{helper::render_item::RenderItem, scene::camera::Camera}, render_item_impl_default};
 
-use super::wgpu::WGpu;
+use super::{wgpu::WGpu, helper::buffer::{TrackedBuffer, BufferType, create_empty_buffer, create_buffer_init, write_buffer}};
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -37,7 +35,7 @@ impl CameraUniform
 pub struct CameraBuffer
 {
     pub name: String,
-    buffer: wgpu::Buffer,
+    buffer: TrackedBuffer,
 }
 
 impl RenderItem for CameraBuffer
@@ -49,13 +47,7 @@ impl CameraBuffer
 {
     pub fn new(wgpu: &mut WGpu, cam: &Camera) -> CameraBuffer
     {
-        let empty_buffer = wgpu.device().create_buffer(&wgpu::BufferDescriptor
-        {
-            label: Some("Empty Buffer"),
-            size: 0,
-            usage: wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        let empty_buffer = create_empty_buffer(wgpu, BufferType::Uniform);
 
         let mut buffer = CameraBuffer
         {
@@ -75,8 +67,10 @@ impl CameraBuffer
         let mut camera_uniform = CameraUniform::new();
         camera_uniform.update_view_proj(data.eye_pos, cam.webgpu_projection(), data.view);
 
-        self.buffer = wgpu.device().create_buffer_init
+        self.buffer = create_buffer_init
         (
+            wgpu,
+            BufferType::Uniform,
             &wgpu::util::BufferInitDescriptor
             {
                 label: Some(&self.name),
@@ -93,7 +87,7 @@ impl CameraBuffer
         let mut camera_uniform = CameraUniform::new();
         camera_uniform.update_view_proj(data.eye_pos, cam.webgpu_projection(), data.view);
 
-        wgpu.queue_mut().write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[camera_uniform]));
+        write_buffer(wgpu, &self.buffer, 0, bytemuck::cast_slice(&[camera_uniform]));
     }
 
     pub fn get_buffer(&self) -> &wgpu::Buffer
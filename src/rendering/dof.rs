@@ -0,0 +1,340 @@
+use wgpu::{BindGroupLayout, BindGroup, ShaderModule, CommandEncoder, TextureView};
+
+use crate::{state::{state::DofSettings, scene::camera::CameraData}, resources::resources};
+
+use super::{wgpu::WGpu, pipeline::Pipeline, uniform, texture::Texture, helper::buffer::{TrackedBuffer, BufferType, create_buffer_init, write_buffer}};
+
+// blur strength at f/1.0 - the circle of confusion is scaled by 1/f-stop
+const APERTURE_SCALE: f32 = 2.0;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DofUniform
+{
+    pub texel_size: [f32; 2],
+    pub near: f32,
+    pub far: f32,
+    pub focus_distance: f32,
+    pub aperture: f32,
+    pub max_blur: f32,
+    _padding: f32,
+}
+
+impl DofUniform
+{
+    pub fn new(settings: &DofSettings, cam_data: Option<&CameraData>, width: u32, height: u32) -> Self
+    {
+        let mut near = 0.1;
+        let mut far = 1000.0;
+
+        if let Some(cam_data) = cam_data
+        {
+            near = cam_data.clipping_near;
+            far = cam_data.clipping_far;
+        }
+
+        Self
+        {
+            texel_size: [1.0 / width.max(1) as f32, 1.0 / height.max(1) as f32],
+            near,
+            far,
+            focus_distance: settings.focus_distance.max(0.01),
+            aperture: APERTURE_SCALE / settings.f_stop.max(0.1),
+            max_blur: settings.max_blur.max(0.0),
+            _padding: 0.0,
+        }
+    }
+}
+
+// depth of field post process
+// the scene is rendered into an offscreen texture which is blurred based on the depth pass into the output view
+pub struct DepthOfField
+{
+    pub enabled: bool,
+    settings: DofSettings,
+    uniform: DofUniform,
+
+    shader: ShaderModule,
+    buffer: TrackedBuffer,
+    bind_group_layout: BindGroupLayout,
+    bind_group: Option<BindGroup>,
+
+    color_texture: wgpu::Texture,
+    color_view: TextureView,
+    sampler: wgpu::Sampler,
+
+    pipeline: Option<wgpu::RenderPipeline>,
+}
+
+impl DepthOfField
+{
+    pub fn new(wgpu: &mut WGpu, settings: &DofSettings, depth_texture: &Texture) -> DepthOfField
+    {
+        let shader_source = resources::load_string("shader/dof.wgsl").unwrap();
+        let shader = Pipeline::create_shader(wgpu.device(), "dof", &shader_source);
+
+        let config = wgpu.surface_config();
+        let uniform = DofUniform::new(settings, None, config.width, config.height);
+
+        let buffer = create_buffer_init
+        (
+            wgpu,
+            BufferType::Uniform,
+            &wgpu::util::BufferInitDescriptor
+            {
+                label: Some("dof buffer"),
+                contents: bytemuck::cast_slice(&[uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+
+        let bind_group_layout = wgpu.device().create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor
+        {
+            entries:
+            &[
+                uniform::uniform_bind_group_layout_entry(0, false, true),
+                wgpu::BindGroupLayoutEntry
+                {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture
+                    {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry
+                {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry
+                {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture
+                    {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("dof_bind_group_layout"),
+        });
+
+        let (color_texture, color_view) = Self::create_color_texture(wgpu);
+
+        let sampler = wgpu.device().create_sampler(&wgpu::SamplerDescriptor
+        {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let mut dof = DepthOfField
+        {
+            enabled: settings.enabled,
+            settings: settings.clone(),
+            uniform,
+
+            shader,
+            buffer,
+            bind_group_layout,
+            bind_group: None,
+
+            color_texture,
+            color_view,
+            sampler,
+
+            pipeline: None,
+        };
+
+        dof.create_bind_group(wgpu, depth_texture);
+
+        dof
+    }
+
+    fn create_color_texture(wgpu: &mut WGpu) -> (wgpu::Texture, TextureView)
+    {
+        let config = wgpu.surface_config();
+
+        let texture = wgpu.device().create_texture(&wgpu::TextureDescriptor
+        {
+            label: Some("dof color texture"),
+            size: wgpu::Extent3d
+            {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        (texture, view)
+    }
+
+    fn create_bind_group(&mut self, wgpu: &mut WGpu, depth_texture: &Texture)
+    {
+        self.bind_group = Some(wgpu.device().create_bind_group(&wgpu::BindGroupDescriptor
+        {
+            layout: &self.bind_group_layout,
+            entries:
+            &[
+                uniform::uniform_bind_group(0, &self.buffer),
+                wgpu::BindGroupEntry
+                {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&self.color_view),
+                },
+                wgpu::BindGroupEntry
+                {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry
+                {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(depth_texture.get_view()),
+                },
+            ],
+            label: Some("dof_bind_group"),
+        }));
+    }
+
+    // needs to be called after the depth pass texture has been recreated (the resolution has changed)
+    pub fn resize(&mut self, wgpu: &mut WGpu, depth_texture: &Texture)
+    {
+        let (color_texture, color_view) = Self::create_color_texture(wgpu);
+        self.color_texture = color_texture;
+        self.color_view = color_view;
+
+        self.create_bind_group(wgpu, depth_texture);
+    }
+
+    pub fn update(&mut self, settings: &DofSettings)
+    {
+        self.enabled = settings.enabled;
+        self.settings = settings.clone();
+    }
+
+    // the scene is rendered into this view if dof is enabled
+    pub fn get_view(&self) -> &TextureView
+    {
+        &self.color_view
+    }
+
+    pub fn create_pipeline(&mut self, wgpu: &mut WGpu)
+    {
+        let device = wgpu.device();
+        let config = wgpu.surface_config();
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor
+        {
+            label: Some("dof pipe Layout"),
+            bind_group_layouts: &[&self.bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor
+        {
+            label: Some("dof pipe"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState
+            {
+                module: &self.shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState
+            {
+                module: &self.shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState
+                {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING), // keeps transparent cleared scenes composable
+                    write_mask: wgpu::ColorWrites::ALL,
+                })]
+            }),
+            primitive: wgpu::PrimitiveState
+            {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState
+            {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        self.pipeline = Some(render_pipeline);
+    }
+
+    pub fn render(&mut self, wgpu: &mut WGpu, view: &TextureView, encoder: &mut CommandEncoder, cam_data: Option<&CameraData>) -> u32
+    {
+        if self.pipeline.is_none() || self.bind_group.is_none()
+        {
+            return 0;
+        }
+
+        let uniform = DofUniform::new(&self.settings, cam_data, self.color_texture.width(), self.color_texture.height());
+        if uniform != self.uniform
+        {
+            write_buffer(wgpu, &self.buffer, 0, bytemuck::cast_slice(&[uniform]));
+            self.uniform = uniform;
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor
+        {
+            label: Some("dof pass"),
+            color_attachments:
+            &[
+                Some(wgpu::RenderPassColorAttachment
+                {
+                    view: view,
+                    resolve_target: None,
+                    ops: wgpu::Operations
+                    {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            ],
+            depth_stencil_attachment: None,
+            timestamp_writes: wgpu.timestamp_writes("dof"),
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(self.pipeline.as_ref().unwrap());
+        render_pass.set_bind_group(0, self.bind_group.as_ref().unwrap(), &[]);
+        render_pass.draw(0..3, 0..1);
+
+        1
+    }
+}
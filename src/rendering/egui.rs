@@ -74,7 +74,13 @@ impl EGui
         r.consumed
     }
 
-    pub fn request_repaint(&self)
+    // the pointer is over an ui element or is dragging one
+    pub fn is_using_pointer(&self) -> bool
+    {
+        self.ctx.wants_pointer_input() || self.ctx.is_using_pointer()
+    }
+
+        pub fn request_repaint(&self)
     {
         self.ctx.request_repaint();
     }
@@ -101,7 +107,7 @@ impl EGui
                     })
                 ],
                 depth_stencil_attachment: None,
-                timestamp_writes: None,
+                timestamp_writes: wgpu.timestamp_writes("egui"),
                 occlusion_query_set: None,
             });
 
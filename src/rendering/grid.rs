@@ -0,0 +1,207 @@
+use nalgebra::Vector3;
+use wgpu::{BindGroupLayout, BindGroup, ShaderModule, RenderPass};
+
+use crate::{state::{state::GridSettings, scene::scene::DEFAULT_UP_AXIS}, resources::resources};
+
+use super::{wgpu::WGpu, pipeline::Pipeline, uniform, texture, helper::buffer::{TrackedBuffer, BufferType, create_buffer_init, write_buffer}};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GridUniform
+{
+    pub color: [f32; 4],
+    pub spacing: f32,
+    pub sub_spacing: f32,
+    pub fade_distance: f32,
+    pub axis_highlight: u32,
+
+    // ground plane (normal and the two axes on the plane)
+    pub up: [f32; 4],
+    pub tangent: [f32; 4],
+    pub bitangent: [f32; 4],
+}
+
+impl GridUniform
+{
+    pub fn new(settings: &GridSettings, up_axis: &Vector3<f32>) -> Self
+    {
+        let up = up_axis.normalize();
+
+        // the first axis on the plane is x (or z if the up axis is close to x)
+        let tangent = if up.x.abs() < 0.9 { Vector3::<f32>::x() } else { Vector3::<f32>::z() };
+        let tangent = (tangent - up * tangent.dot(&up)).normalize();
+        let bitangent = tangent.cross(&up);
+
+        Self
+        {
+            color: [settings.color.x, settings.color.y, settings.color.z, settings.opacity],
+            spacing: settings.spacing.max(0.0001),
+            sub_spacing: settings.snap_spacing().max(0.0001),
+            fade_distance: settings.fade_distance.max(1.0),
+            axis_highlight: settings.axis_highlight as u32,
+
+            up: [up.x, up.y, up.z, 0.0],
+            tangent: [tangent.x, tangent.y, tangent.z, 0.0],
+            bitangent: [bitangent.x, bitangent.y, bitangent.z, 0.0],
+        }
+    }
+}
+
+// infinite ground grid: a camera following quad, the lines are generated in the fragment shader
+pub struct Grid
+{
+    pub enabled: bool,
+    settings: GridSettings,
+    up_axis: Vector3<f32>,
+
+    shader: ShaderModule,
+    buffer: TrackedBuffer,
+    bind_group_layout: BindGroupLayout,
+    bind_group: BindGroup,
+
+    pipeline: Option<wgpu::RenderPipeline>,
+}
+
+impl Grid
+{
+    pub fn new(wgpu: &mut WGpu, settings: &GridSettings) -> Grid
+    {
+        let shader_source = resources::load_string("shader/grid.wgsl").unwrap();
+        let shader = Pipeline::create_shader(wgpu.device(), "grid", &shader_source);
+
+        let buffer = create_buffer_init
+        (
+            wgpu,
+            BufferType::Uniform,
+            &wgpu::util::BufferInitDescriptor
+            {
+                label: Some("grid buffer"),
+                contents: bytemuck::cast_slice(&[GridUniform::new(settings, &DEFAULT_UP_AXIS)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+
+        let bind_group_layout = wgpu.device().create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor
+        {
+            entries:
+            &[
+                uniform::uniform_bind_group_layout_entry(0, true, true),
+            ],
+            label: Some("grid_bind_group_layout"),
+        });
+
+        let bind_group = wgpu.device().create_bind_group(&wgpu::BindGroupDescriptor
+        {
+            layout: &bind_group_layout,
+            entries:
+            &[
+                uniform::uniform_bind_group(0, &buffer),
+            ],
+            label: Some("grid_bind_group"),
+        });
+
+        Grid
+        {
+            enabled: settings.enabled,
+            settings: settings.clone(),
+            up_axis: DEFAULT_UP_AXIS,
+
+            shader,
+            buffer,
+            bind_group_layout,
+            bind_group,
+
+            pipeline: None,
+        }
+    }
+
+    pub fn update(&mut self, wgpu: &mut WGpu, settings: &GridSettings, up_axis: &Vector3<f32>)
+    {
+        self.enabled = settings.enabled;
+
+        if self.settings != *settings || self.up_axis != *up_axis
+        {
+            write_buffer(wgpu, &self.buffer, 0, bytemuck::cast_slice(&[GridUniform::new(settings, up_axis)]));
+            self.settings = settings.clone();
+            self.up_axis = *up_axis;
+        }
+    }
+
+    pub fn create_pipeline(&mut self, wgpu: &mut WGpu, light_cam_scene_bind_layout: &BindGroupLayout, samples: u32)
+    {
+        let device = wgpu.device();
+        let config = wgpu.surface_config();
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor
+        {
+            label: Some("grid pipe Layout"),
+            bind_group_layouts: &[&self.bind_group_layout, light_cam_scene_bind_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor
+        {
+            label: Some("grid pipe"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState
+            {
+                module: &self.shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState
+            {
+                module: &self.shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState
+                {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })]
+            }),
+            primitive: wgpu::PrimitiveState
+            {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None, // visible from both sides
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState
+            {
+                format: texture::Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState
+            {
+                count: samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        self.pipeline = Some(render_pipeline);
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut RenderPass<'a>, light_cam_bind_group: &'a BindGroup) -> u32
+    {
+        if !self.enabled || self.pipeline.is_none()
+        {
+            return 0;
+        }
+
+        pass.set_pipeline(self.pipeline.as_ref().unwrap());
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_bind_group(1, light_cam_bind_group, &[]);
+        pass.draw(0..6, 0..1);
+
+        1
+    }
+}
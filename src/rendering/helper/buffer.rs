@@ -1,6 +1,8 @@
-use std::mem::size_of;
+use std::{mem::size_of, ops::Deref, sync::{Arc, atomic::{AtomicU64, Ordering}}};
 
-use wgpu::BufferView;
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, Display};
+use wgpu::{BufferView, util::DeviceExt};
 
 use crate::rendering::wgpu::WGpu;
 
@@ -45,13 +47,142 @@ pub fn remove_padding(padded_data: &BufferView, buffer_dimensions: &BufferDimens
         .collect::<Vec<_>>()
 }
 
-pub fn create_empty_buffer(wgpu: &mut WGpu) -> wgpu::Buffer
+// ******************** buffer memory accounting ********************
+
+#[derive(EnumIter, Display, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BufferType
+{
+    Vertex,
+    Index,
+    Instance,
+    Uniform
+}
+
+pub type BufferStatsItem = Arc<BufferStats>;
+
+// allocated gpu buffer memory in bytes per buffer type
+pub struct BufferStats
 {
-    wgpu.device().create_buffer(&wgpu::BufferDescriptor
+    sizes: [AtomicU64; 4],
+    uploaded: AtomicU64, // bytes written to buffers since the last consume_uploaded call
+}
+
+impl BufferStats
+{
+    pub fn new() -> BufferStats
+    {
+        BufferStats
+        {
+            sizes: [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)],
+            uploaded: AtomicU64::new(0),
+        }
+    }
+
+    fn add(&self, buffer_type: BufferType, size: u64)
+    {
+        self.sizes[buffer_type as usize].fetch_add(size, Ordering::Relaxed);
+    }
+
+    fn remove(&self, buffer_type: BufferType, size: u64)
+    {
+        self.sizes[buffer_type as usize].fetch_sub(size, Ordering::Relaxed);
+    }
+
+    fn add_upload(&self, size: u64)
+    {
+        self.uploaded.fetch_add(size, Ordering::Relaxed);
+    }
+
+    pub fn consume_uploaded(&self) -> u64
+    {
+        self.uploaded.swap(0, Ordering::Relaxed)
+    }
+
+    pub fn get(&self, buffer_type: BufferType) -> u64
+    {
+        self.sizes[buffer_type as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn list(&self) -> Vec<(String, u64)>
+    {
+        BufferType::iter().map(|buffer_type| (buffer_type.to_string(), self.get(buffer_type))).collect()
+    }
+}
+
+// wgpu buffer which is counted in the buffer stats until it's dropped
+pub struct TrackedBuffer
+{
+    buffer: wgpu::Buffer,
+    buffer_type: BufferType,
+    stats: BufferStatsItem
+}
+
+impl TrackedBuffer
+{
+    fn new(buffer: wgpu::Buffer, buffer_type: BufferType, stats: BufferStatsItem) -> TrackedBuffer
+    {
+        stats.add(buffer_type, buffer.size());
+
+        TrackedBuffer
+        {
+            buffer,
+            buffer_type,
+            stats
+        }
+    }
+}
+
+impl Deref for TrackedBuffer
+{
+    type Target = wgpu::Buffer;
+
+    fn deref(&self) -> &wgpu::Buffer
+    {
+        &self.buffer
+    }
+}
+
+impl Drop for TrackedBuffer
+{
+    fn drop(&mut self)
+    {
+        self.stats.remove(self.buffer_type, self.buffer.size());
+    }
+}
+
+pub fn create_buffer(wgpu: &WGpu, buffer_type: BufferType, desc: &wgpu::BufferDescriptor) -> TrackedBuffer
+{
+    let buffer = wgpu.device().create_buffer(desc);
+    TrackedBuffer::new(buffer, buffer_type, wgpu.buffer_stats())
+}
+
+pub fn create_buffer_init(wgpu: &WGpu, buffer_type: BufferType, desc: &wgpu::util::BufferInitDescriptor) -> TrackedBuffer
+{
+    let buffer = wgpu.device().create_buffer_init(desc);
+    wgpu.buffer_stats().add_upload(desc.contents.len() as u64);
+
+    TrackedBuffer::new(buffer, buffer_type, wgpu.buffer_stats())
+}
+
+pub fn create_empty_buffer(wgpu: &mut WGpu, buffer_type: BufferType) -> TrackedBuffer
+{
+    create_buffer(wgpu, buffer_type, &wgpu::BufferDescriptor
     {
         label: Some("Empty Buffer"),
         size: 0,
         usage: wgpu::BufferUsages::COPY_DST,
         mapped_at_creation: false,
     })
-}
\ No newline at end of file
+}
+
+// writes data into a (sub) range of the buffer - the written bytes are counted as upload
+pub fn write_buffer(wgpu: &mut WGpu, buffer: &TrackedBuffer, offset: wgpu::BufferAddress, data: &[u8])
+{
+    if data.is_empty()
+    {
+        return;
+    }
+
+    wgpu.queue_mut().write_buffer(buffer, offset, data);
+    buffer.stats.add_upload(data.len() as u64);
+}
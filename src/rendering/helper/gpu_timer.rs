@@ -0,0 +1,203 @@
+use std::{sync::{Arc, atomic::{AtomicBool, Ordering}}, mem::swap};
+
+use wgpu::{Device, Queue, QuerySet, Buffer, CommandEncoder, RenderPassTimestampWrites};
+
+// max amount of timed passes per frame (each pass needs a begin and an end timestamp)
+const MAX_PASSES: u32 = 32;
+
+// measures the gpu time of render passes via timestamp queries (requires wgpu::Features::TIMESTAMP_QUERY)
+// results are read back without blocking - so they are a few frames behind
+pub struct GpuTimer
+{
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    read_buffer: Buffer,
+    period: f32, // nanoseconds per tick
+
+    active: bool,
+    labels: Vec<String>,
+
+    resolved: bool,
+    pending: bool,
+    pending_labels: Vec<String>,
+    mapped: Arc<AtomicBool>,
+    map_failed: Arc<AtomicBool>,
+
+    pub results: Vec<(String, f32)>,
+}
+
+impl GpuTimer
+{
+    pub fn new(device: &Device, queue: &Queue) -> GpuTimer
+    {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor
+        {
+            label: Some("gpu timer query set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: MAX_PASSES * 2,
+        });
+
+        let size = (MAX_PASSES * 2) as u64 * wgpu::QUERY_SIZE as u64;
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor
+        {
+            label: Some("gpu timer resolve buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let read_buffer = device.create_buffer(&wgpu::BufferDescriptor
+        {
+            label: Some("gpu timer read buffer"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        GpuTimer
+        {
+            query_set,
+            resolve_buffer,
+            read_buffer,
+            period: queue.get_timestamp_period(),
+
+            active: false,
+            labels: vec![],
+
+            resolved: false,
+            pending: false,
+            pending_labels: vec![],
+            mapped: Arc::new(AtomicBool::new(false)),
+            map_failed: Arc::new(AtomicBool::new(false)),
+
+            results: vec![],
+        }
+    }
+
+    pub fn begin_frame(&mut self, device: &Device)
+    {
+        self.read_results(device);
+
+        self.labels.clear();
+        self.active = true;
+    }
+
+    pub fn timestamp_writes(&mut self, label: &str) -> Option<RenderPassTimestampWrites<'_>>
+    {
+        if !self.active || self.labels.len() as u32 >= MAX_PASSES
+        {
+            return None;
+        }
+
+        let index = self.labels.len() as u32 * 2;
+        self.labels.push(label.to_string());
+
+        Some(RenderPassTimestampWrites
+        {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index),
+            end_of_pass_write_index: Some(index + 1),
+        })
+    }
+
+    // call before the encoder is submitted
+    pub fn resolve(&mut self, encoder: &mut CommandEncoder)
+    {
+        self.active = false;
+
+        // the read buffer is still in use from a previous frame
+        if self.pending || self.labels.is_empty()
+        {
+            return;
+        }
+
+        let count = self.labels.len() as u32 * 2;
+
+        encoder.resolve_query_set(&self.query_set, 0..count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.read_buffer, 0, count as u64 * wgpu::QUERY_SIZE as u64);
+
+        swap(&mut self.pending_labels, &mut self.labels);
+        self.resolved = true;
+    }
+
+    // call after the encoder is submitted
+    pub fn map(&mut self)
+    {
+        if !self.resolved
+        {
+            return;
+        }
+
+        let mapped = self.mapped.clone();
+        let map_failed = self.map_failed.clone();
+        self.read_buffer.slice(..).map_async(wgpu::MapMode::Read, move |result|
+        {
+            if result.is_ok()
+            {
+                mapped.store(true, Ordering::SeqCst);
+            }
+            else
+            {
+                map_failed.store(true, Ordering::SeqCst);
+            }
+        });
+
+        self.resolved = false;
+        self.pending = true;
+    }
+
+    fn read_results(&mut self, device: &Device)
+    {
+        if !self.pending
+        {
+            return;
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+
+        // the buffer was never mapped (nothing to unmap) - drop the results and resolve/map again with the next frame
+        if self.map_failed.load(Ordering::SeqCst)
+        {
+            self.map_failed.store(false, Ordering::SeqCst);
+            self.pending_labels.clear();
+            self.pending = false;
+            return;
+        }
+
+        if !self.mapped.load(Ordering::SeqCst)
+        {
+            return;
+        }
+
+        let mut results: Vec<(String, f32)> = vec![];
+
+        {
+            let data = self.read_buffer.slice(..).get_mapped_range();
+            let timestamps: &[u64] = bytemuck::cast_slice(&data);
+
+            for (i, label) in self.pending_labels.iter().enumerate()
+            {
+                let start = timestamps[i * 2];
+                let end = timestamps[i * 2 + 1];
+                let time = end.saturating_sub(start) as f32 * self.period / 1_000_000.0;
+
+                // sum up passes with the same label (multiple scenes/cameras)
+                if let Some(entry) = results.iter_mut().find(|(entry_label, _)| entry_label == label)
+                {
+                    entry.1 += time;
+                }
+                else
+                {
+                    results.push((label.clone(), time));
+                }
+            }
+        }
+
+        self.read_buffer.unmap();
+        self.mapped.store(false, Ordering::SeqCst);
+        self.pending = false;
+
+        self.results = results;
+    }
+}
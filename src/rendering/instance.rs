@@ -1,16 +1,14 @@
-use std::cell::RefCell;
 use std::mem;
 use std::sync::{Arc, RwLock};
 
 use colored::Colorize;
 use nalgebra::Matrix4;
-use wgpu::util::DeviceExt;
 
 use crate::render_item_impl_default;
 use crate::state::helper::render_item::RenderItem;
 use crate::state::scene::instance::InstanceItem;
 
-use super::helper::buffer::create_empty_buffer;
+use super::helper::buffer::{TrackedBuffer, BufferType, create_empty_buffer, create_buffer_init, write_buffer};
 use super::wgpu::WGpu;
 
 #[repr(C)]
@@ -85,9 +83,12 @@ pub struct InstanceBuffer
 {
     pub name: String,
     count: u32,
-    buffer: wgpu::Buffer,
+    buffer: TrackedBuffer,
 
-    pub transformations: Vec::<Matrix4::<f32>>
+    pub transformations: Vec::<Matrix4::<f32>>,
+    pub highlights: Vec::<bool>,
+    pub alphas: Vec::<f32>,
+    pub layer_masks: Vec::<u32>
 }
 
 impl RenderItem for InstanceBuffer
@@ -103,8 +104,11 @@ impl InstanceBuffer
         {
             name: name.to_string(),
             count: instances.len() as u32,
-            buffer: create_empty_buffer(wgpu),
-            transformations: Vec::with_capacity(instances.len())
+            buffer: create_empty_buffer(wgpu, BufferType::Instance),
+            transformations: Vec::with_capacity(instances.len()),
+            highlights: Vec::with_capacity(instances.len()),
+            alphas: Vec::with_capacity(instances.len()),
+            layer_masks: Vec::with_capacity(instances.len())
         };
 
         instance_buffer.to_buffer(wgpu, instances);
@@ -117,6 +121,9 @@ impl InstanceBuffer
         dbg!("update all instances");
 
         self.transformations = Vec::with_capacity(instances.len());
+        self.highlights = Vec::with_capacity(instances.len());
+        self.alphas = Vec::with_capacity(instances.len());
+        self.layer_masks = Vec::with_capacity(instances.len());
 
         let buffer_data = instances.iter().map(|instance|
         {
@@ -127,6 +134,9 @@ impl InstanceBuffer
             let instance_data = instance.get_data();
 
             self.transformations.push(transform);
+            self.highlights.push(instance_data.highlight);
+            self.alphas.push(alpha);
+            self.layer_masks.push(instance_data.layer_mask);
 
             Instance
             {
@@ -136,8 +146,10 @@ impl InstanceBuffer
             }
         }).collect::<Vec<_>>();
 
-        self.buffer = wgpu.device().create_buffer_init
+        self.buffer = create_buffer_init
         (
+            wgpu,
+            BufferType::Instance,
             &wgpu::util::BufferInitDescriptor
             {
                 label: Some(&self.name),
@@ -172,20 +184,24 @@ impl InstanceBuffer
         };
 
         self.transformations[index] = transform;
+        self.highlights[index] = instance_data.highlight;
+        self.alphas[index] = alpha;
+        self.layer_masks[index] = instance_data.layer_mask;
 
-        wgpu.queue_mut().write_buffer
+        write_buffer
         (
+            wgpu,
             &self.buffer,
             (index * mem::size_of::<Instance>()) as wgpu::BufferAddress,
             bytemuck::bytes_of(&data),
         );
     }
 
-    pub fn update_buffer_range(&mut self, wgpu: &mut WGpu, instances: &Vec<RefCell<InstanceItem>>, range: std::ops::Range<usize>)
+    pub fn update_buffer_range(&mut self, wgpu: &mut WGpu, instances: &Vec<Arc<RwLock<InstanceItem>>>, range: std::ops::Range<usize>)
     {
-        if range.start + 1 > self.count as usize
+        if range.end > self.count as usize || range.end > instances.len()
         {
-            let warning = format!("index {} out of range {} lights are supported", range.start, self.count);
+            let warning = format!("range {:?} out of range {} instances are supported", range, self.count);
             println!("{}", warning.bright_yellow());
             return;
         }
@@ -195,12 +211,15 @@ impl InstanceBuffer
         let mut i = range.start;
         let buffer_data = slice.iter().map(|instance|
         {
-            let instance = instance.borrow();
+            let instance = instance.read().unwrap();
             let transform = instance.get_transform();
             let alpha = instance.get_alpha();
             let instance_data = instance.get_data();
 
             self.transformations[i] = transform;
+            self.highlights[i] = instance_data.highlight;
+            self.alphas[i] = alpha;
+            self.layer_masks[i] = instance_data.layer_mask;
 
             i += 1;
 
@@ -212,14 +231,25 @@ impl InstanceBuffer
             }
         }).collect::<Vec<_>>();
 
-        wgpu.queue_mut().write_buffer
+        write_buffer
         (
+            wgpu,
             &self.buffer,
             (range.start * mem::size_of::<Instance>()) as wgpu::BufferAddress,
             bytemuck::cast_slice(&buffer_data),
         );
     }
 
+    // uploads only the changed instances - neighboring indices are merged into one write
+    // changed_indices needs to be sorted
+    pub fn update_changed(&mut self, wgpu: &mut WGpu, instances: &Vec<Arc<RwLock<InstanceItem>>>, changed_indices: &Vec<usize>)
+    {
+        for range in get_changed_ranges(changed_indices)
+        {
+            self.update_buffer_range(wgpu, instances, range);
+        }
+    }
+
     pub fn get_buffer(&self) -> &wgpu::Buffer
     {
         &self.buffer
@@ -229,4 +259,131 @@ impl InstanceBuffer
     {
         self.count
     }
+
+    pub fn has_highlight(&self) -> bool
+    {
+        self.highlights.iter().any(|highlight| *highlight)
+    }
+
+    // ranges of consecutive instances sharing a layer with the camera (all instances in one range in the common case)
+    pub fn get_instance_ranges(&self, layer_mask: u32) -> Vec<std::ops::Range<u32>>
+    {
+        let mut ranges = vec![];
+        let mut start = None;
+
+        for (i, instance_layer_mask) in self.layer_masks.iter().enumerate()
+        {
+            let visible = instance_layer_mask & layer_mask != 0;
+
+            if visible && start.is_none()
+            {
+                start = Some(i as u32);
+            }
+            else if !visible && start.is_some()
+            {
+                ranges.push(start.unwrap()..i as u32);
+                start = None;
+            }
+        }
+
+        if let Some(start) = start
+        {
+            ranges.push(start..self.layer_masks.len() as u32);
+        }
+
+        ranges
+    }
+
+    // instance alpha (alpha component) -> needs to be sorted like transparent materials
+    pub fn has_transparency(&self) -> bool
+    {
+        self.alphas.iter().any(|alpha| *alpha < 1.0)
+    }
+}
+
+// merges sorted indices into ranges of neighboring indices
+pub fn get_changed_ranges(changed_indices: &Vec<usize>) -> Vec<std::ops::Range<usize>>
+{
+    let mut ranges: Vec<std::ops::Range<usize>> = vec![];
+
+    for index in changed_indices
+    {
+        if let Some(current) = ranges.last_mut()
+        {
+            if current.end == *index
+            {
+                current.end += 1;
+                continue;
+            }
+        }
+
+        ranges.push(*index..*index + 1);
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{mem, sync::{Arc, RwLock}};
+
+    use crate::{rendering::wgpu::WGpu, state::{state::State, scene::{node::Node, instance::{Instance as SceneInstance, InstanceItem}}}};
+
+    use super::{Instance, InstanceBuffer, get_changed_ranges};
+
+    fn upload_size(changed_indices: &Vec<usize>) -> usize
+    {
+        get_changed_ranges(changed_indices).iter().map(|range| range.len() * mem::size_of::<Instance>()).sum()
+    }
+
+    #[test]
+    fn changed_ranges_merge_neighboring_indices()
+    {
+        assert!(get_changed_ranges(&vec![]).is_empty());
+        assert_eq!(get_changed_ranges(&vec![500]), vec![500..501]);
+        assert_eq!(get_changed_ranges(&vec![10, 11, 12, 700]), vec![10..13, 700..701]);
+        assert_eq!(get_changed_ranges(&vec![0, 2, 3, 5]), vec![0..1, 2..4, 5..6]);
+    }
+
+    #[test]
+    fn changed_ranges_cover_only_changed_instances()
+    {
+        let size = mem::size_of::<Instance>();
+
+        assert_eq!(upload_size(&vec![]), 0);
+        assert_eq!(upload_size(&vec![500]), size);
+        assert_eq!(upload_size(&vec![10, 11, 12, 700]), 4 * size);
+        assert_eq!(upload_size(&(0..1000).collect()), 1000 * size);
+    }
+
+    // needs a gpu adapter (headless)
+    #[test]
+    #[ignore]
+    fn update_changed_uploads_only_changed_instances()
+    {
+        let mut state = State::new();
+        let mut wgpu = pollster::block_on(WGpu::new_headless(1, 1, &mut state));
+        let stats = wgpu.buffer_stats();
+
+        let node = Node::new(0, "node");
+        let instances: Vec<Arc<RwLock<InstanceItem>>> = (0..1000).map(|i|
+        {
+            Arc::new(RwLock::new(Box::new(SceneInstance::new(i, format!("instance {}", i), node.clone()))))
+        }).collect();
+
+        let size = mem::size_of::<Instance>() as u64;
+
+        // full rebuild
+        let mut instance_buffer = InstanceBuffer::new(&mut wgpu, "test", &instances);
+        assert_eq!(stats.consume_uploaded(), instances.len() as u64 * size);
+
+        // one changed instance
+        instance_buffer.update_changed(&mut wgpu, &instances, &vec![500]);
+        assert_eq!(stats.consume_uploaded(), size);
+
+        // neighboring indices are merged - but only changed instances are uploaded
+        instance_buffer.update_changed(&mut wgpu, &instances, &vec![10, 11, 12, 700]);
+        assert_eq!(stats.consume_uploaded(), 4 * size);
+    }
 }
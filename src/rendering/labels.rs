@@ -0,0 +1,327 @@
+use nalgebra::Point3;
+use wgpu::{BindGroupLayout, BindGroup, ShaderModule, RenderPass};
+
+use crate::{state::scene::{texture::TextureFilterMode, components::label::Label, node::NodeItem}, resources::resources, component_downcast};
+
+use super::{wgpu::WGpu, pipeline::Pipeline, texture::{self, Texture, TextureFormat}, helper::{bitmap_font, buffer::{TrackedBuffer, BufferType, create_buffer_init}}};
+
+// one corner of a glyph quad - the quad is spanned in the shader along the camera axes
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LabelVertex
+{
+    position: [f32; 3],
+    offset: [f32; 2],
+    tex_coords: [f32; 2],
+    color: [f32; 3],
+}
+
+impl LabelVertex
+{
+    const ATTRIBS: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x2, 3 => Float32x3];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static>
+    {
+        use std::mem;
+
+        wgpu::VertexBufferLayout
+        {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+// text of all label components (see components/label.rs) as camera facing glyph quads
+pub struct Labels
+{
+    shader: ShaderModule,
+    _font_texture: Texture, // kept alive for the bind group
+    bind_group_layout: BindGroupLayout,
+    bind_group: BindGroup,
+
+    vertices: Vec<LabelVertex>,
+    depth_tested_count: u32, // the first vertices are depth tested - the remaining ones are always on top
+    buffer: Option<TrackedBuffer>,
+
+    pipeline: Option<wgpu::RenderPipeline>,
+    on_top_pipeline: Option<wgpu::RenderPipeline>,
+}
+
+impl Labels
+{
+    pub fn new(wgpu: &mut WGpu) -> Labels
+    {
+        let shader_source = resources::load_string("shader/labels.wgsl").unwrap();
+        let shader = Pipeline::create_shader(wgpu.device(), "labels", &shader_source);
+
+        // pixel font -> no filtering
+        let mut font = crate::state::scene::texture::Texture::new_from_image(0, "label font", bitmap_font::create_atlas());
+        {
+            let data = font.get_data_mut().get_mut();
+            data.mag_filter = TextureFilterMode::Nearest;
+            data.min_filter = TextureFilterMode::Nearest;
+        }
+
+        let font_texture = Texture::new_from_texture(wgpu, "label font", &font, TextureFormat::Rgba, 1);
+
+        let bind_group_layout = wgpu.device().create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor
+        {
+            entries: &font_texture.get_bind_group_layout_entries(0),
+            label: Some("labels_bind_group_layout"),
+        });
+
+        let bind_group = wgpu.device().create_bind_group(&wgpu::BindGroupDescriptor
+        {
+            layout: &bind_group_layout,
+            entries: &font_texture.get_bind_group_entries(0),
+            label: Some("labels_bind_group"),
+        });
+
+        Labels
+        {
+            shader,
+            _font_texture: font_texture,
+            bind_group_layout,
+            bind_group,
+
+            vertices: vec![],
+            depth_tested_count: 0,
+            buffer: None,
+
+            pipeline: None,
+            on_top_pipeline: None,
+        }
+    }
+
+    pub fn update(&mut self, wgpu: &mut WGpu, scene: &crate::state::scene::scene::Scene)
+    {
+        let mut depth_tested = vec![];
+        let mut on_top = vec![];
+
+        for node in crate::state::scene::scene::Scene::list_all_child_nodes(&scene.nodes)
+        {
+            let label = node.read().unwrap().find_component::<Label>();
+
+            if let Some(label) = label
+            {
+                component_downcast!(label, Label);
+
+                if !label.get_base().is_enabled || !node.read().unwrap().is_visible()
+                {
+                    continue;
+                }
+
+                let data = label.get_data();
+                let mut position = Self::anchor(&node);
+                position.y += data.offset;
+
+                let vertices = if data.always_on_top { &mut on_top } else { &mut depth_tested };
+                Self::add_text(vertices, &data.text, data.size, &position, [data.color.x, data.color.y, data.color.z]);
+            }
+        }
+
+        let depth_tested_count = depth_tested.len() as u32;
+        depth_tested.extend(on_top);
+
+        // only upload if something has changed
+        if depth_tested == self.vertices && depth_tested_count == self.depth_tested_count
+        {
+            return;
+        }
+
+        self.buffer = None;
+
+        if !depth_tested.is_empty()
+        {
+            self.buffer = Some(create_buffer_init
+            (
+                wgpu,
+                BufferType::Vertex,
+                &wgpu::util::BufferInitDescriptor
+                {
+                    label: Some("labels vertex buffer"),
+                    contents: bytemuck::cast_slice(&depth_tested),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }
+            ));
+        }
+
+        self.vertices = depth_tested;
+        self.depth_tested_count = depth_tested_count;
+    }
+
+    // top center of the bounding box - or the node position if there is no mesh
+    fn anchor(node: &NodeItem) -> Point3<f32>
+    {
+        let node = node.read().unwrap();
+
+        if let Some((min, max)) = node.get_bounding_info(true)
+        {
+            return Point3::<f32>::new((min.x + max.x) / 2.0, max.y, (min.z + max.z) / 2.0);
+        }
+
+        node.get_world_position()
+    }
+
+    // lines are centered horizontally - the last line is placed at the anchor
+    fn add_text(vertices: &mut Vec<LabelVertex>, text: &str, size: f32, position: &Point3<f32>, color: [f32; 3])
+    {
+        let pixel = size / bitmap_font::GLYPH_HEIGHT as f32;
+        let glyph_width = bitmap_font::GLYPH_WIDTH as f32 * pixel;
+        let glyph_height = bitmap_font::GLYPH_HEIGHT as f32 * pixel;
+        let advance = bitmap_font::CELL_WIDTH as f32 * pixel;
+        let line_height = bitmap_font::CELL_HEIGHT as f32 * pixel;
+
+        let lines: Vec<&str> = text.lines().collect();
+
+        for (line_index, line) in lines.iter().enumerate()
+        {
+            let chars = line.chars().count();
+            if chars == 0
+            {
+                continue;
+            }
+
+            let width = chars as f32 * advance - pixel;
+            let y0 = (lines.len() - 1 - line_index) as f32 * line_height;
+            let y1 = y0 + glyph_height;
+
+            for (i, c) in line.chars().enumerate()
+            {
+                if c.is_whitespace()
+                {
+                    continue;
+                }
+
+                let x0 = -width / 2.0 + i as f32 * advance;
+                let x1 = x0 + glyph_width;
+
+                let [u0, v0, u1, v1] = bitmap_font::glyph_uv(c);
+
+                let corners =
+                [
+                    ([x0, y0], [u0, v1]),
+                    ([x1, y0], [u1, v1]),
+                    ([x1, y1], [u1, v0]),
+                    ([x0, y0], [u0, v1]),
+                    ([x1, y1], [u1, v0]),
+                    ([x0, y1], [u0, v0]),
+                ];
+
+                for (offset, tex_coords) in corners
+                {
+                    vertices.push(LabelVertex
+                    {
+                        position: [position.x, position.y, position.z],
+                        offset,
+                        tex_coords,
+                        color,
+                    });
+                }
+            }
+        }
+    }
+
+    fn create_render_pipeline(&self, wgpu: &mut WGpu, light_cam_scene_bind_layout: &BindGroupLayout, samples: u32, depth_compare: wgpu::CompareFunction) -> wgpu::RenderPipeline
+    {
+        let device = wgpu.device();
+        let config = wgpu.surface_config();
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor
+        {
+            label: Some("labels pipe Layout"),
+            bind_group_layouts: &[&self.bind_group_layout, light_cam_scene_bind_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor
+        {
+            label: Some("labels pipe"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState
+            {
+                module: &self.shader,
+                entry_point: "vs_main",
+                buffers:
+                &[
+                    LabelVertex::desc(),
+                ],
+            },
+            fragment: Some(wgpu::FragmentState
+            {
+                module: &self.shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState
+                {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })]
+            }),
+            primitive: wgpu::PrimitiveState
+            {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState
+            {
+                format: texture::Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState
+            {
+                count: samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        })
+    }
+
+    pub fn create_pipeline(&mut self, wgpu: &mut WGpu, light_cam_scene_bind_layout: &BindGroupLayout, samples: u32)
+    {
+        self.pipeline = Some(self.create_render_pipeline(wgpu, light_cam_scene_bind_layout, samples, wgpu::CompareFunction::LessEqual));
+        self.on_top_pipeline = Some(self.create_render_pipeline(wgpu, light_cam_scene_bind_layout, samples, wgpu::CompareFunction::Always));
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut RenderPass<'a>, light_cam_bind_group: &'a BindGroup) -> u32
+    {
+        if self.buffer.is_none() || self.pipeline.is_none() || self.on_top_pipeline.is_none()
+        {
+            return 0;
+        }
+
+        let count = self.vertices.len() as u32;
+        let mut draw_calls = 0;
+
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_bind_group(1, light_cam_bind_group, &[]);
+        pass.set_vertex_buffer(0, self.buffer.as_ref().unwrap().slice(..));
+
+        if self.depth_tested_count > 0
+        {
+            pass.set_pipeline(self.pipeline.as_ref().unwrap());
+            pass.draw(0..self.depth_tested_count, 0..1);
+            draw_calls += 1;
+        }
+
+        if count > self.depth_tested_count
+        {
+            pass.set_pipeline(self.on_top_pipeline.as_ref().unwrap());
+            pass.draw(self.depth_tested_count..count, 0..1);
+            draw_calls += 1;
+        }
+
+        draw_calls
+    }
+}
@@ -13,7 +13,7 @@ use nalgebra::{Vector3, Point3};
 
 use crate::{state::{helper::render_item::RenderItem, scene::light::{Light, LightItem, LightType}}, render_item_impl_default, helper::{change_tracker::ChangeTracker, math::approx_zero_vec3}};
 
-use super::{wgpu::WGpu, helper::buffer::create_empty_buffer};
+use super::{wgpu::WGpu, helper::buffer::{TrackedBuffer, BufferType, create_empty_buffer, create_buffer, write_buffer}};
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
@@ -73,8 +73,8 @@ pub struct LightBuffer
 
     max_lights: usize,
 
-    lights_amount: wgpu::Buffer,
-    lights_buffer: wgpu::Buffer,
+    lights_amount: TrackedBuffer,
+    lights_buffer: TrackedBuffer,
 }
 
 impl RenderItem for LightBuffer
@@ -90,8 +90,8 @@ impl LightBuffer
         {
             name: name,
             max_lights: max_lights as usize,
-            lights_amount: create_empty_buffer(wgpu),
-            lights_buffer: create_empty_buffer(wgpu),
+            lights_amount: create_empty_buffer(wgpu, BufferType::Uniform),
+            lights_buffer: create_empty_buffer(wgpu, BufferType::Uniform),
         };
 
 
@@ -108,7 +108,7 @@ impl LightBuffer
 
     pub fn create_buffer(&mut self, wgpu: &mut WGpu)
     {
-        self.lights_amount = wgpu.device().create_buffer(&wgpu::BufferDescriptor
+        self.lights_amount = create_buffer(wgpu, BufferType::Uniform, &wgpu::BufferDescriptor
         {
             label: Some("lights amount buffer"),
             size: mem::size_of::<u32>() as wgpu::BufferAddress,
@@ -116,7 +116,7 @@ impl LightBuffer
             mapped_at_creation: false,
         });
 
-        self.lights_buffer = wgpu.device().create_buffer(&wgpu::BufferDescriptor
+        self.lights_buffer = create_buffer(wgpu, BufferType::Uniform, &wgpu::BufferDescriptor
         {
             label: Some(&self.name),
             size: Self::uniform_size(self.max_lights),
@@ -129,8 +129,9 @@ impl LightBuffer
     {
         let amount = lights.len().min(self.max_lights) as u32;
 
-        wgpu.queue_mut().write_buffer
+        write_buffer
         (
+            wgpu,
             &self.lights_amount,
             0,
             bytemuck::bytes_of(&amount),
@@ -149,8 +150,9 @@ impl LightBuffer
             let light = light.get_ref();
             let data = LightUniform::new(light.light_type, light.pos, light.dir, light.color, light.intensity, light.max_angle, light.distance_based_intensity);
 
-            wgpu.queue_mut().write_buffer
+            write_buffer
             (
+                wgpu,
                 &self.lights_buffer,
                 (i * mem::size_of::<LightUniform>()) as wgpu::BufferAddress,
                 bytemuck::bytes_of(&data),
@@ -169,8 +171,9 @@ impl LightBuffer
 
         let data = LightUniform::new(light.light_type, light.pos, light.dir, light.color, light.intensity, light.max_angle, light.distance_based_intensity);
 
-        wgpu.queue_mut().write_buffer
+        write_buffer
         (
+            wgpu,
             &self.lights_buffer,
             (index * mem::size_of::<LightUniform>()) as wgpu::BufferAddress,
             bytemuck::bytes_of(&data),
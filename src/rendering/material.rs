@@ -1,10 +1,10 @@
 use std::{mem::swap, collections::HashMap};
 
-use wgpu::{util::DeviceExt, BindGroupLayout, BindGroup};
+use wgpu::{BindGroupLayout, BindGroup};
 
 use crate::{state::{helper::render_item::{RenderItem, get_render_item, RenderItemType}, scene::{components::{material::{Material, TextureType, ALL_TEXTURE_TYPES, TextureState}, component::Component}, texture::TextureItem}}, render_item_impl_default};
 
-use super::{wgpu::WGpu, uniform, texture::{Texture, TextureFormat}};
+use super::{wgpu::WGpu, uniform, texture::{Texture, TextureFormat}, helper::buffer::{TrackedBuffer, BufferType, create_empty_buffer, create_buffer_init}};
 
 //TODO: future: compile shaders for each texture combination to prevent branching/if statements
 
@@ -30,10 +30,46 @@ use super::{wgpu::WGpu, uniform, texture::{Texture, TextureFormat}};
     14: custom 3
 
     15: depth
+
+    16: irradiance (ibl)
+    17: brdf lut (ibl)
+
+    18: clearcoat
+    19: clearcoat roughness
+    20: clearcoat normal
+
+    21: environment cubemap
 */
 
+/*
+    texture_channels (2 bits per texture - 0: r, 1: g, 2: b, 3: a)
+    0: roughness
+    1: ambient occlusion
+    2: reflectivity
+    3: clearcoat
+    4: clearcoat roughness
+*/
+const CHANNEL_TEXTURE_TYPES: [TextureType; 5] =
+[
+    TextureType::Roughness,
+    TextureType::AmbientOcclusion,
+    TextureType::Reflectivity,
+    TextureType::Clearcoat,
+    TextureType::ClearcoatRoughness,
+];
+
 //pub const ADDITIONAL_START_INDEX: u32 = 20;
 
+// after all texture types (see ALL_TEXTURE_TYPES)
+const IBL_IRRADIANCE_BIND_ID: u32 = 35;
+const IBL_BRDF_LUT_BIND_ID: u32 = 37;
+
+// cubemaps are using their own binding (texture_cube) - the 2d environment binding is empty then
+const ENVIRONMENT_CUBE_BIND_ID: u32 = 41;
+
+// irradiance map and brdf lut of the scene environment texture (image based lighting)
+pub type IblMaps = (TextureItem, TextureItem);
+
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct MaterialUniform
@@ -57,7 +93,13 @@ pub struct MaterialUniform
 
     pub textures_used: u32,
 
-    pub __padding: [u32; 3]
+    pub clearcoat: f32,
+    pub clearcoat_roughness: f32,
+
+    pub transmission: f32,
+
+    pub texture_channels: u32,
+    _padding: [u32; 3],
 }
 
 impl MaterialUniform
@@ -83,6 +125,19 @@ impl MaterialUniform
         if material.is_texture_enabled(TextureType::Custom2)                            { textures_used |= 1 << 13; }
         if material.is_texture_enabled(TextureType::Custom3)                            { textures_used |= 1 << 14; }
 
+        if material.is_texture_enabled(TextureType::Clearcoat)                          { textures_used |= 1 << 18; }
+        if material.is_texture_enabled(TextureType::ClearcoatRoughness)                 { textures_used |= 1 << 19; }
+        if material.is_texture_enabled(TextureType::ClearcoatNormal)                    { textures_used |= 1 << 20; }
+
+        let mut texture_channels: u32 = 0;
+        for (i, texture_type) in CHANNEL_TEXTURE_TYPES.iter().enumerate()
+        {
+            if let Some(texture) = material.get_texture_by_type(*texture_type)
+            {
+                texture_channels |= (texture.channel as u32) << (i * 2);
+            }
+        }
+
         MaterialUniform
         {
             ambient_color:
@@ -123,7 +178,13 @@ impl MaterialUniform
             unlit: material_data.unlit_shading as u32,
             textures_used: textures_used,
 
-            __padding: [0, 0, 0]
+            clearcoat: material_data.clearcoat,
+            clearcoat_roughness: material_data.clearcoat_roughness,
+
+            transmission: material_data.transmission,
+
+            texture_channels: texture_channels,
+            _padding: [0; 3],
         }
     }
 }
@@ -132,9 +193,10 @@ pub struct MaterialBuffer
 {
     pub name: String,
 
-    buffer: wgpu::Buffer,
+    buffer: TrackedBuffer,
 
     empty_texture: Texture,
+    empty_cube_texture: Texture,
 
     pub bind_group_layout: Option<BindGroupLayout>,
     pub bind_group: Option<BindGroup>
@@ -147,37 +209,80 @@ impl RenderItem for MaterialBuffer
 
 impl MaterialBuffer
 {
-    pub fn new(wgpu: &mut WGpu, material: &Material, default_env_map: Option<TextureState>, additional_textures: Option<&Vec<(&Texture, u32)>>) -> MaterialBuffer
+    pub fn new(wgpu: &mut WGpu, material: &Material, default_env_map: Option<TextureState>, ibl_maps: Option<IblMaps>, additional_textures: Option<&Vec<(&Texture, u32)>>) -> MaterialBuffer
     {
-        let empty_buffer = wgpu.device().create_buffer(&wgpu::BufferDescriptor
-        {
-            label: Some("Empty Buffer"),
-            size: 0,
-            usage: wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        let empty_buffer = create_empty_buffer(wgpu, BufferType::Uniform);
 
         let empty_texture = Texture::new_empty_texture(wgpu, format!("empty material {} texture", material.get_base().name).as_str(), TextureFormat::Srgba);
+        let empty_cube_texture = Texture::new_empty_cube_texture(wgpu, format!("empty material {} cube texture", material.get_base().name).as_str(), TextureFormat::Srgba);
 
         let mut buffer = MaterialBuffer
         {
             name: material.get_base().name.clone(),
             buffer: empty_buffer,
             empty_texture,
+            empty_cube_texture,
             bind_group_layout: None,
             bind_group: None
         };
 
-        buffer.to_buffer(wgpu, material, default_env_map.clone(), additional_textures);
-        buffer.create_binding_groups(wgpu, material, default_env_map, additional_textures);
+        buffer.to_buffer(wgpu, material, default_env_map.clone(), ibl_maps.clone(), additional_textures);
+        buffer.create_binding_groups(wgpu, material, default_env_map, ibl_maps, additional_textures);
 
         buffer
     }
 
-    pub fn to_buffer(&mut self, wgpu: &mut WGpu, material: &Material, default_env_map: Option<TextureState>, additional_textures: Option<&Vec<(&Texture, u32)>>)
+    // the ibl maps are only used with the default env map and if they are already uploaded
+    fn get_ibl_maps(material: &Material, ibl_maps: Option<IblMaps>) -> Option<IblMaps>
+    {
+        if material.has_texture(TextureType::Environment)
+        {
+            return None;
+        }
+
+        if let Some((irradiance, brdf_lut)) = ibl_maps
+        {
+            if irradiance.read().unwrap().render_item.is_some() && brdf_lut.read().unwrap().render_item.is_some()
+            {
+                return Some((irradiance, brdf_lut));
+            }
+        }
+
+        None
+    }
+
+    fn has_environment_cubemap(material: &Material, default_env_map: &Option<TextureState>) -> bool
+    {
+        let mut texture = default_env_map.clone();
+
+        if material.has_texture(TextureType::Environment)
+        {
+            texture = material.get_texture_by_type(TextureType::Environment).clone();
+        }
+
+        if let Some(texture) = texture
+        {
+            return texture.enabled && texture.get().read().unwrap().is_cubemap();
+        }
+
+        false
+    }
+
+    pub fn to_buffer(&mut self, wgpu: &mut WGpu, material: &Material, default_env_map: Option<TextureState>, ibl_maps: Option<IblMaps>, additional_textures: Option<&Vec<(&Texture, u32)>>)
     {
         let mut material_uniform = MaterialUniform::new(material, default_env_map.is_some());
 
+        if Self::has_environment_cubemap(material, &default_env_map)
+        {
+            material_uniform.textures_used |= 1 << 21;
+        }
+
+        if Self::get_ibl_maps(material, ibl_maps).is_some()
+        {
+            material_uniform.textures_used |= 1 << 16;
+            material_uniform.textures_used |= 1 << 17;
+        }
+
         if let Some(additional_textures) = additional_textures
         {
             for (_texture, texture_id) in additional_textures
@@ -187,8 +292,10 @@ impl MaterialBuffer
             }
         }
 
-        self.buffer = wgpu.device().create_buffer_init
+        self.buffer = create_buffer_init
         (
+            wgpu,
+            BufferType::Uniform,
             &wgpu::util::BufferInitDescriptor
             {
                 label: Some(&self.name),
@@ -212,7 +319,7 @@ impl MaterialBuffer
         &self.buffer
     }
 
-    pub fn create_binding_groups(&mut self, wgpu: &mut WGpu, material: &Material, default_env_map: Option<TextureState>, additional_textures: Option<&Vec<(&Texture, u32)>>)
+    pub fn create_binding_groups(&mut self, wgpu: &mut WGpu, material: &Material, default_env_map: Option<TextureState>, ibl_maps: Option<IblMaps>, additional_textures: Option<&Vec<(&Texture, u32)>>)
     {
         let device = wgpu.device();
 
@@ -229,6 +336,7 @@ impl MaterialBuffer
         // ********* textures *********
         let mut texture_render_items: HashMap<u64, (RenderItemType, TextureItem)> = HashMap::new();
         let mut texture_render_items_dir = vec![];
+        let mut environment_cube_bound = false;
 
         for texture_type in ALL_TEXTURE_TYPES
         {
@@ -260,7 +368,16 @@ impl MaterialBuffer
                         texture_render_items.insert(texture.id, (render_item.unwrap(), texture_arc.clone()));
                     }
 
-                    texture_render_items_dir.push((Some(texture.id), bind_id));
+                    if texture_type == TextureType::Environment && texture.is_cubemap()
+                    {
+                        texture_render_items_dir.push((None, bind_id));
+                        texture_render_items_dir.push((Some(texture.id), ENVIRONMENT_CUBE_BIND_ID));
+                        environment_cube_bound = true;
+                    }
+                    else
+                    {
+                        texture_render_items_dir.push((Some(texture.id), bind_id));
+                    }
                 }
                 else
                 {
@@ -275,6 +392,35 @@ impl MaterialBuffer
             bind_id += 2;
         }
 
+        if !environment_cube_bound
+        {
+            texture_render_items_dir.push((None, ENVIRONMENT_CUBE_BIND_ID));
+        }
+
+        // ********* image based lighting *********
+        if let Some((irradiance, brdf_lut)) = Self::get_ibl_maps(material, ibl_maps)
+        {
+            for (texture_arc, bind_id) in [(irradiance, IBL_IRRADIANCE_BIND_ID), (brdf_lut, IBL_BRDF_LUT_BIND_ID)]
+            {
+                let mut texture = texture_arc.write().unwrap();
+
+                if !texture_render_items.contains_key(&texture.id)
+                {
+                    let mut render_item: Option<Box<dyn RenderItem + Send + Sync>> = None;
+                    swap(&mut texture.render_item, &mut render_item);
+
+                    texture_render_items.insert(texture.id, (render_item.unwrap(), texture_arc.clone()));
+                }
+
+                texture_render_items_dir.push((Some(texture.id), bind_id));
+            }
+        }
+        else
+        {
+            texture_render_items_dir.push((None, IBL_IRRADIANCE_BIND_ID));
+            texture_render_items_dir.push((None, IBL_BRDF_LUT_BIND_ID));
+        }
+
         for (texture_id, bind_id) in &texture_render_items_dir
         {
             if let Some(texture_id) = texture_id
@@ -290,8 +436,10 @@ impl MaterialBuffer
             }
             else
             {
-                let textures_layout_group = self.empty_texture.get_bind_group_layout_entries(*bind_id);
-                let textures_group = self.empty_texture.get_bind_group_entries(*bind_id);
+                let empty_texture = if *bind_id == ENVIRONMENT_CUBE_BIND_ID { &self.empty_cube_texture } else { &self.empty_texture };
+
+                let textures_layout_group = empty_texture.get_bind_group_layout_entries(*bind_id);
+                let textures_group = empty_texture.get_bind_group_entries(*bind_id);
 
                 layout_group_vec.append(&mut textures_layout_group.to_vec());
                 group_vec.append(&mut textures_group.to_vec());
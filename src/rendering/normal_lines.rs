@@ -0,0 +1,201 @@
+use wgpu::{BindGroupLayout, BindGroup, ShaderModule, RenderPass};
+
+use crate::{state::state::NormalsDebugSettings, resources::resources};
+
+use super::{wgpu::WGpu, pipeline::Pipeline, uniform, texture, vertex_buffer::{NormalLineVertex, VertexBuffer}, instance::{Instance, InstanceBuffer}, helper::buffer::{TrackedBuffer, BufferType, create_buffer_init, write_buffer}};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct NormalLinesUniform
+{
+    pub length: f32,
+    _padding: [f32; 3],
+}
+
+impl NormalLinesUniform
+{
+    pub fn new(settings: &NormalsDebugSettings) -> Self
+    {
+        Self
+        {
+            length: settings.length.max(0.0),
+            _padding: [0.0; 3],
+        }
+    }
+}
+
+// debug lines along the normals/tangents of the selected objects (see the normal lines of VertexBuffer)
+pub struct NormalLines
+{
+    pub enabled: bool,
+    pub tangents: bool,
+    settings: NormalsDebugSettings,
+
+    shader: ShaderModule,
+    buffer: TrackedBuffer,
+    bind_group_layout: BindGroupLayout,
+    bind_group: BindGroup,
+
+    pipeline: Option<wgpu::RenderPipeline>,
+}
+
+impl NormalLines
+{
+    pub fn new(wgpu: &mut WGpu, settings: &NormalsDebugSettings) -> NormalLines
+    {
+        let shader_source = resources::load_string("shader/normal_lines.wgsl").unwrap();
+        let shader = Pipeline::create_shader(wgpu.device(), "normal lines", &shader_source);
+
+        let buffer = create_buffer_init
+        (
+            wgpu,
+            BufferType::Uniform,
+            &wgpu::util::BufferInitDescriptor
+            {
+                label: Some("normal lines buffer"),
+                contents: bytemuck::cast_slice(&[NormalLinesUniform::new(settings)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+
+        let bind_group_layout = wgpu.device().create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor
+        {
+            entries:
+            &[
+                uniform::uniform_bind_group_layout_entry(0, true, true),
+            ],
+            label: Some("normal_lines_bind_group_layout"),
+        });
+
+        let bind_group = wgpu.device().create_bind_group(&wgpu::BindGroupDescriptor
+        {
+            layout: &bind_group_layout,
+            entries:
+            &[
+                uniform::uniform_bind_group(0, &buffer),
+            ],
+            label: Some("normal_lines_bind_group"),
+        });
+
+        NormalLines
+        {
+            enabled: settings.enabled,
+            tangents: settings.tangents,
+            settings: settings.clone(),
+
+            shader,
+            buffer,
+            bind_group_layout,
+            bind_group,
+
+            pipeline: None,
+        }
+    }
+
+    pub fn update(&mut self, wgpu: &mut WGpu, settings: &NormalsDebugSettings)
+    {
+        self.enabled = settings.enabled;
+        self.tangents = settings.tangents;
+
+        if self.settings != *settings
+        {
+            write_buffer(wgpu, &self.buffer, 0, bytemuck::cast_slice(&[NormalLinesUniform::new(settings)]));
+            self.settings = settings.clone();
+        }
+    }
+
+    pub fn create_pipeline(&mut self, wgpu: &mut WGpu, light_cam_scene_bind_layout: &BindGroupLayout, samples: u32)
+    {
+        let device = wgpu.device();
+        let config = wgpu.surface_config();
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor
+        {
+            label: Some("normal lines pipe Layout"),
+            bind_group_layouts: &[&self.bind_group_layout, light_cam_scene_bind_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor
+        {
+            label: Some("normal lines pipe"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState
+            {
+                module: &self.shader,
+                entry_point: "vs_main",
+                buffers:
+                &[
+                    NormalLineVertex::desc(),
+                    Instance::desc()
+                ],
+            },
+            fragment: Some(wgpu::FragmentState
+            {
+                module: &self.shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState
+                {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })]
+            }),
+            primitive: wgpu::PrimitiveState
+            {
+                topology: wgpu::PrimitiveTopology::LineList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState
+            {
+                format: texture::Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState
+            {
+                count: samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        self.pipeline = Some(render_pipeline);
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut RenderPass<'a>, vertex_buffer: &'a VertexBuffer, instance_buffer: &'a InstanceBuffer, light_cam_bind_group: &'a BindGroup) -> u32
+    {
+        let normal_line_buffer = vertex_buffer.get_normal_line_buffer();
+
+        if !self.enabled || self.pipeline.is_none() || normal_line_buffer.is_none() || vertex_buffer.get_normal_line_count() == 0
+        {
+            return 0;
+        }
+
+        // normals first - tangents and bitangents afterwards
+        let mut count = vertex_buffer.get_normal_line_count();
+        if self.tangents
+        {
+            count *= 3;
+        }
+
+        pass.set_pipeline(self.pipeline.as_ref().unwrap());
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_bind_group(1, light_cam_bind_group, &[]);
+
+        pass.set_vertex_buffer(0, normal_line_buffer.unwrap().slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.get_buffer().slice(..));
+
+        pass.draw(0..count, 0..instance_buffer.get_count() as _);
+
+        1
+    }
+}
@@ -0,0 +1,329 @@
+use wgpu::{BindGroupLayout, BindGroup, ShaderModule, CommandEncoder, TextureView};
+
+use crate::{state::state::OutlineSettings, resources::resources};
+
+use super::{wgpu::WGpu, pipeline::Pipeline, uniform, helper::buffer::{TrackedBuffer, BufferType, create_buffer_init, write_buffer}};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct OutlineUniform
+{
+    pub color: [f32; 4],
+    pub thickness: f32,
+    _padding: [f32; 3],
+}
+
+impl OutlineUniform
+{
+    pub fn new(settings: &OutlineSettings) -> Self
+    {
+        Self
+        {
+            color: [settings.color.x, settings.color.y, settings.color.z, 1.0],
+            thickness: settings.thickness.max(1.0),
+            _padding: [0.0; 3],
+        }
+    }
+}
+
+// sample count of the mask render target (the mask pipeline needs the same) and of its resolve target (only with msaa)
+pub fn get_mask_sample_counts(samples: u32) -> (u32, Option<u32>)
+{
+    if samples > 1
+    {
+        (samples, Some(1))
+    }
+    else
+    {
+        (1, None)
+    }
+}
+
+// selection outline
+// highlighted instances are rendered into a mask (see Scene::render_outline_mask) which is dilated into the output view
+// with msaa the mask is rendered multisampled and resolved - so the outline matches the (resolved) edges of the scene
+pub struct Outline
+{
+    pub enabled: bool,
+    settings: OutlineSettings,
+
+    shader: ShaderModule,
+    buffer: TrackedBuffer,
+    bind_group_layout: BindGroupLayout,
+    bind_group: Option<BindGroup>,
+
+    samples: u32,
+    mask_view: TextureView,
+    msaa_mask_view: Option<TextureView>,
+
+    pipeline: Option<wgpu::RenderPipeline>,
+}
+
+impl Outline
+{
+    pub fn new(wgpu: &mut WGpu, settings: &OutlineSettings, samples: u32) -> Outline
+    {
+        let shader_source = resources::load_string("shader/outline.wgsl").unwrap();
+        let shader = Pipeline::create_shader(wgpu.device(), "outline", &shader_source);
+
+        let buffer = create_buffer_init
+        (
+            wgpu,
+            BufferType::Uniform,
+            &wgpu::util::BufferInitDescriptor
+            {
+                label: Some("outline buffer"),
+                contents: bytemuck::cast_slice(&[OutlineUniform::new(settings)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+
+        let bind_group_layout = wgpu.device().create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor
+        {
+            entries:
+            &[
+                uniform::uniform_bind_group_layout_entry(0, false, true),
+                wgpu::BindGroupLayoutEntry
+                {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture
+                    {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("outline_bind_group_layout"),
+        });
+
+        let (mask_view, msaa_mask_view) = Self::create_mask_views(wgpu, samples);
+
+        let mut outline = Outline
+        {
+            enabled: settings.enabled,
+            settings: settings.clone(),
+
+            shader,
+            buffer,
+            bind_group_layout,
+            bind_group: None,
+
+            samples,
+            mask_view,
+            msaa_mask_view,
+
+            pipeline: None,
+        };
+
+        outline.create_bind_group(wgpu);
+
+        outline
+    }
+
+    // single sampled mask (bound in the outline pass) and the multisampled mask to render into (only with msaa)
+    fn create_mask_views(wgpu: &mut WGpu, samples: u32) -> (TextureView, Option<TextureView>)
+    {
+        let (render_samples, resolve_samples) = get_mask_sample_counts(samples);
+
+        match resolve_samples
+        {
+            Some(resolve_samples) => (Self::create_mask_view(wgpu, resolve_samples), Some(Self::create_mask_view(wgpu, render_samples))),
+            None => (Self::create_mask_view(wgpu, render_samples), None)
+        }
+    }
+
+    fn create_mask_view(wgpu: &mut WGpu, samples: u32) -> TextureView
+    {
+        let config = wgpu.surface_config();
+
+        // same format as the surface - so the mask can be rendered with the default pipeline setup
+        let texture = wgpu.device().create_texture(&wgpu::TextureDescriptor
+        {
+            label: Some("outline mask texture"),
+            size: wgpu::Extent3d
+            {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: samples,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: if samples > 1 { wgpu::TextureUsages::RENDER_ATTACHMENT } else { wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING },
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_bind_group(&mut self, wgpu: &mut WGpu)
+    {
+        self.bind_group = Some(wgpu.device().create_bind_group(&wgpu::BindGroupDescriptor
+        {
+            layout: &self.bind_group_layout,
+            entries:
+            &[
+                uniform::uniform_bind_group(0, &self.buffer),
+                wgpu::BindGroupEntry
+                {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&self.mask_view),
+                },
+            ],
+            label: Some("outline_bind_group"),
+        }));
+    }
+
+    pub fn resize(&mut self, wgpu: &mut WGpu)
+    {
+        (self.mask_view, self.msaa_mask_view) = Self::create_mask_views(wgpu, self.samples);
+
+        self.create_bind_group(wgpu);
+    }
+
+    pub fn msaa_sample_size_update(&mut self, wgpu: &mut WGpu, samples: u32)
+    {
+        self.samples = samples;
+        self.resize(wgpu);
+    }
+
+    pub fn update(&mut self, wgpu: &mut WGpu, settings: &OutlineSettings)
+    {
+        self.enabled = settings.enabled;
+
+        if self.settings != *settings
+        {
+            write_buffer(wgpu, &self.buffer, 0, bytemuck::cast_slice(&[OutlineUniform::new(settings)]));
+            self.settings = settings.clone();
+        }
+    }
+
+    // sample count of the mask pipeline
+    pub fn get_mask_sample_count(&self) -> u32
+    {
+        get_mask_sample_counts(self.samples).0
+    }
+
+    // view to render the mask into and the resolve target (if multisampled)
+    pub fn get_mask_views(&self) -> (&TextureView, Option<&TextureView>)
+    {
+        if let Some(msaa_mask_view) = &self.msaa_mask_view
+        {
+            return (msaa_mask_view, Some(&self.mask_view));
+        }
+
+        (&self.mask_view, None)
+    }
+
+    pub fn create_pipeline(&mut self, wgpu: &mut WGpu)
+    {
+        let device = wgpu.device();
+        let config = wgpu.surface_config();
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor
+        {
+            label: Some("outline pipe Layout"),
+            bind_group_layouts: &[&self.bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor
+        {
+            label: Some("outline pipe"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState
+            {
+                module: &self.shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState
+            {
+                module: &self.shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState
+                {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })]
+            }),
+            primitive: wgpu::PrimitiveState
+            {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState
+            {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        self.pipeline = Some(render_pipeline);
+    }
+
+    pub fn render(&self, wgpu: &mut WGpu, view: &TextureView, encoder: &mut CommandEncoder) -> u32
+    {
+        if self.pipeline.is_none() || self.bind_group.is_none()
+        {
+            return 0;
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor
+        {
+            label: Some("outline pass"),
+            color_attachments:
+            &[
+                Some(wgpu::RenderPassColorAttachment
+                {
+                    view: view,
+                    resolve_target: None,
+                    ops: wgpu::Operations
+                    {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            ],
+            depth_stencil_attachment: None,
+            timestamp_writes: wgpu.timestamp_writes("outline"),
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(self.pipeline.as_ref().unwrap());
+        render_pass.set_bind_group(0, self.bind_group.as_ref().unwrap(), &[]);
+        render_pass.draw(0..3, 0..1);
+
+        1
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::get_mask_sample_counts;
+
+    #[test]
+    fn mask_sample_counts_match_msaa()
+    {
+        // without msaa: single sampled mask and pipeline - nothing to resolve
+        assert_eq!(get_mask_sample_counts(1), (1, None));
+
+        // with msaa: multisampled mask and pipeline - resolved into the single sampled mask
+        assert_eq!(get_mask_sample_counts(4), (4, Some(1)));
+    }
+}
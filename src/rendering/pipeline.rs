@@ -1,23 +1,102 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::{HashMap, HashSet}, hash::{Hash, Hasher}};
 
 use wgpu::{ShaderModule, Device, BindGroupLayout};
 
+use crate::state::scene::components::material::{DepthCompare, MaterialData};
+
 use super::{wgpu::WGpu, vertex_buffer::Vertex, texture::{self}, instance::Instance};
 
+// depth settings of a material (see MaterialData)
+// every distinct combination needs its own pipeline: they are only created for combinations used by the scene materials
+// and cached until the set of used combinations changes -> keep the bias values coarse (the ui is using steps)
+#[derive(Debug, Copy, Clone)]
+pub struct DepthVariant
+{
+    pub depth_write: bool,
+    pub depth_compare: DepthCompare,
+    pub depth_bias: i32,
+    pub slope_scale: f32,
+}
+
+impl DepthVariant
+{
+    pub fn new(data: &MaterialData) -> DepthVariant
+    {
+        DepthVariant
+        {
+            depth_write: data.depth_write,
+            depth_compare: data.depth_test,
+            depth_bias: data.depth_bias,
+            slope_scale: data.depth_bias_slope_scale,
+        }
+    }
+}
+
+impl PartialEq for DepthVariant
+{
+    fn eq(&self, other: &Self) -> bool
+    {
+        self.depth_write == other.depth_write
+        && self.depth_compare == other.depth_compare
+        && self.depth_bias == other.depth_bias
+        && self.slope_scale.to_bits() == other.slope_scale.to_bits()
+    }
+}
+
+impl Eq for DepthVariant {}
+
+impl Hash for DepthVariant
+{
+    fn hash<H: Hasher>(&self, state: &mut H)
+    {
+        self.depth_write.hash(state);
+        self.depth_compare.hash(state);
+        self.depth_bias.hash(state);
+        self.slope_scale.to_bits().hash(state);
+    }
+}
+
+pub const DEFAULT_DEPTH_VARIANT: DepthVariant = DepthVariant
+{
+    depth_write: true,
+    depth_compare: DepthCompare::Less,
+    depth_bias: 0,
+    slope_scale: 0.0,
+};
+
+pub fn depth_compare_to_wgpu(depth_compare: DepthCompare) -> wgpu::CompareFunction
+{
+    match depth_compare
+    {
+        DepthCompare::Less => wgpu::CompareFunction::Less,
+        DepthCompare::LessEqual => wgpu::CompareFunction::LessEqual,
+        DepthCompare::Equal => wgpu::CompareFunction::Equal,
+        DepthCompare::GreaterEqual => wgpu::CompareFunction::GreaterEqual,
+        DepthCompare::Greater => wgpu::CompareFunction::Greater,
+        DepthCompare::NotEqual => wgpu::CompareFunction::NotEqual,
+        DepthCompare::Always => wgpu::CompareFunction::Always,
+        DepthCompare::Never => wgpu::CompareFunction::Never,
+    }
+}
+
 pub struct Pipeline
 {
     pub name: String,
     pub fragment_attachment: bool,
+    pub additive_blending: bool, // adds up all fragments without depth test (overdraw)
 
     max_lights: u32,
 
     shader: ShaderModule,
     pipeline: Option<wgpu::RenderPipeline>,
+    pipeline_double_sided: Option<wgpu::RenderPipeline>, // without backface culling
+
+    depth_variants: HashMap<DepthVariant, (wgpu::RenderPipeline, wgpu::RenderPipeline)>, // non default material depth settings (single sided, double sided)
 }
 
 impl Pipeline
 {
-    pub fn new(wgpu: &mut WGpu, name: &str, shader_source: &String, bind_group_layouts: &[&BindGroupLayout], max_lights: u32, depth_stencil: bool, fragment_attachment: bool, samples: u32) -> Pipeline
+    pub fn new(wgpu: &mut WGpu, name: &str, shader_source: &String, bind_group_layouts: &[&BindGroupLayout], max_lights: u32, depth_stencil: bool, fragment_attachment: bool, additive_blending: bool, samples: u32) -> Pipeline
     {
         let shader;
         {
@@ -33,11 +112,15 @@ impl Pipeline
         {
             name: name.to_string(),
             fragment_attachment,
+            additive_blending,
 
             max_lights: max_lights,
 
             shader,
             pipeline: None,
+            pipeline_double_sided: None,
+
+            depth_variants: HashMap::new(),
         };
 
         pipe.create(wgpu, bind_group_layouts, depth_stencil, fragment_attachment, samples);
@@ -55,11 +138,52 @@ impl Pipeline
     }
 
     pub fn create(&mut self, wgpu: &mut WGpu, bind_group_layouts: &[&BindGroupLayout], depth_stencil: bool, fragment_attachment: bool, samples: u32)
+    {
+        self.pipeline = Some(self.create_pipeline(wgpu, bind_group_layouts, depth_stencil, fragment_attachment, samples, Some(wgpu::Face::Back), None));
+        self.pipeline_double_sided = Some(self.create_pipeline(wgpu, bind_group_layouts, depth_stencil, fragment_attachment, samples, None, None));
+    }
+
+    // additional pipelines for materials with custom depth settings (only for pipelines with depth test)
+    pub fn create_depth_variants(&mut self, wgpu: &mut WGpu, bind_group_layouts: &[&BindGroupLayout], samples: u32, variants: &HashSet<DepthVariant>)
+    {
+        self.depth_variants.clear();
+
+        if self.additive_blending
+        {
+            return;
+        }
+
+        for variant in variants
+        {
+            if *variant == DEFAULT_DEPTH_VARIANT
+            {
+                continue;
+            }
+
+            let pipeline = self.create_pipeline(wgpu, bind_group_layouts, true, self.fragment_attachment, samples, Some(wgpu::Face::Back), Some(*variant));
+            let pipeline_double_sided = self.create_pipeline(wgpu, bind_group_layouts, true, self.fragment_attachment, samples, None, Some(*variant));
+
+            self.depth_variants.insert(*variant, (pipeline, pipeline_double_sided));
+        }
+    }
+
+    fn create_pipeline(&self, wgpu: &mut WGpu, bind_group_layouts: &[&BindGroupLayout], depth_stencil: bool, fragment_attachment: bool, samples: u32, cull_mode: Option<wgpu::Face>, depth_variant: Option<DepthVariant>) -> wgpu::RenderPipeline
     {
         let device = wgpu.device();
         let config = wgpu.surface_config();
 
-        let layout_name = format!("{} Layout", self.name);
+        let mut name = match cull_mode
+        {
+            Some(_) => self.name.clone(),
+            None => format!("{} double sided", self.name),
+        };
+
+        if let Some(depth_variant) = depth_variant
+        {
+            name = format!("{} (depth write: {}, depth test: {}, depth bias: {}, slope scale: {})", name, depth_variant.depth_write, depth_variant.depth_compare, depth_variant.depth_bias, depth_variant.slope_scale);
+        }
+
+        let layout_name = format!("{} Layout", name);
         let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor
         {
             label: Some(layout_name.as_str()),
@@ -70,16 +194,61 @@ impl Pipeline
         let mut depth_stencil_state = None;
         if depth_stencil
         {
+            let mut depth_write_enabled = !self.additive_blending;
+            let mut depth_compare = if self.additive_blending { wgpu::CompareFunction::Always } else { wgpu::CompareFunction::Less }; // front to back
+            let mut bias = wgpu::DepthBiasState::default();
+
+            if let Some(depth_variant) = depth_variant
+            {
+                depth_write_enabled = depth_variant.depth_write;
+                depth_compare = depth_compare_to_wgpu(depth_variant.depth_compare);
+
+                // polygon offset (coplanar geometry like decals)
+                bias = wgpu::DepthBiasState
+                {
+                    constant: depth_variant.depth_bias,
+                    slope_scale: depth_variant.slope_scale,
+                    clamp: 0.0,
+                };
+            }
+
             depth_stencil_state = Some(wgpu::DepthStencilState
             {
                 format: texture::Texture::DEPTH_FORMAT,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less, // front to back
+                depth_write_enabled,
+                depth_compare,
                 stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
+                bias,
             });
         }
 
+        let mut blend_state = wgpu::BlendState
+        {
+            color: wgpu::BlendComponent
+            {
+                operation: wgpu::BlendOperation::Add,
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            },
+            alpha: wgpu::BlendComponent
+            {
+                operation: wgpu::BlendOperation::Add,
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+            },
+            //alpha: wgpu::BlendComponent::REPLACE,
+        };
+
+        if self.additive_blending
+        {
+            blend_state.color = wgpu::BlendComponent
+            {
+                operation: wgpu::BlendOperation::Add,
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+            };
+        }
+
         let fragment_targets = &[Some(wgpu::ColorTargetState
         {
             format: config.format,
@@ -90,22 +259,7 @@ impl Pipeline
                 alpha: wgpu::BlendComponent::REPLACE,
             }),
             */
-            blend: Some(wgpu::BlendState
-            {
-                color: wgpu::BlendComponent
-                {
-                    operation: wgpu::BlendOperation::Add,
-                    src_factor: wgpu::BlendFactor::SrcAlpha,
-                    dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-                },
-                alpha: wgpu::BlendComponent
-                {
-                    operation: wgpu::BlendOperation::Add,
-                    src_factor: wgpu::BlendFactor::One,
-                    dst_factor: wgpu::BlendFactor::One,
-                },
-                //alpha: wgpu::BlendComponent::REPLACE,
-            }),
+            blend: Some(blend_state),
             write_mask: wgpu::ColorWrites::ALL,
         })];
 
@@ -122,7 +276,7 @@ impl Pipeline
 
         let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor
         {
-            label: Some(&self.name),
+            label: Some(&name),
             layout: Some(&render_pipeline_layout),
             vertex: wgpu::VertexState
             {
@@ -140,8 +294,7 @@ impl Pipeline
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back), // backface culling
-                //cull_mode: None,
+                cull_mode: cull_mode, // backface culling (None for double sided materials)
                 // Setting this to anything other than Fill requires Features::POLYGON_MODE_LINE
                 // or Features::POLYGON_MODE_POINT
                 polygon_mode: wgpu::PolygonMode::Fill,
@@ -160,7 +313,7 @@ impl Pipeline
             multiview: None,
         });
 
-        self.pipeline = Some(render_pipeline);
+        render_pipeline
     }
 
     pub fn re_create(&mut self, wgpu: &mut WGpu, bind_group_layouts: &[&BindGroupLayout], depth_stencil: bool, fragment_attachment: bool, samples: u32)
@@ -183,4 +336,21 @@ impl Pipeline
     {
         self.pipeline.as_ref().unwrap()
     }
+
+    pub fn get_for_material(&self, backface_culling: bool, depth_variant: DepthVariant) -> &wgpu::RenderPipeline
+    {
+        if let Some((pipeline, pipeline_double_sided)) = self.depth_variants.get(&depth_variant)
+        {
+            return if backface_culling { pipeline } else { pipeline_double_sided };
+        }
+
+        if backface_culling
+        {
+            self.pipeline.as_ref().unwrap()
+        }
+        else
+        {
+            self.pipeline_double_sided.as_ref().unwrap()
+        }
+    }
 }
@@ -1,11 +1,12 @@
-use std::{sync::{RwLockReadGuard, Arc, RwLock}, mem::swap};
+use std::{sync::{RwLockReadGuard, Arc, RwLock}, mem::swap, collections::HashSet, cmp::Ordering};
 
+use image::DynamicImage;
 use nalgebra::{Point3, distance_squared};
-use wgpu::{CommandEncoder, TextureView, RenderPassColorAttachment, BindGroup, util::DeviceExt};
+use wgpu::{CommandEncoder, TextureView, RenderPassColorAttachment, BindGroup};
 
-use crate::{state::{state::State, scene::{components::{component::{Component, ComponentBox}, transformation::Transformation, alpha::Alpha, mesh::Mesh, material::TextureType}, node::{Node, NodeItem}, camera::CameraData, scene::SceneData}, helper::render_item::{get_render_item, get_render_item_mut, RenderItem}}, helper::image::float32_to_grayscale, resources::resources, render_item_impl_default, component_downcast, component_downcast_mut};
+use crate::{state::{state::{State, DebugChannel}, scene::{components::{component::{Component, ComponentBox, ComponentItem}, transformation::Transformation, alpha::Alpha, mesh::Mesh, material::TextureType, lod::Lod}, node::{Node, NodeItem, LAYER_EDITOR}, camera::CameraData, scene::SceneData}, helper::render_item::{get_render_item, get_render_item_mut, RenderItem}}, helper::image::{float32_to_grayscale, float32_depth_to_linear_grayscale}, resources::resources, render_item_impl_default, component_downcast, component_downcast_mut};
 
-use super::{wgpu::WGpu, pipeline::Pipeline, texture::{Texture, TextureFormat}, camera::CameraBuffer, instance::InstanceBuffer, vertex_buffer::VertexBuffer, light::LightBuffer, bind_groups::light_cam_scene::LightCamSceneBindGroup, material::MaterialBuffer, helper::buffer::create_empty_buffer};
+use super::{wgpu::WGpu, pipeline::{Pipeline, DepthVariant, DEFAULT_DEPTH_VARIANT}, texture::{Texture, TextureFormat}, camera::CameraBuffer, instance::InstanceBuffer, vertex_buffer::VertexBuffer, light::LightBuffer, bind_groups::light_cam_scene::LightCamSceneBindGroup, material::MaterialBuffer, helper::buffer::{TrackedBuffer, BufferType, create_empty_buffer, create_buffer_init, write_buffer}, grid::Grid, dof::DepthOfField, outline::Outline, transmission::Transmission, wireframe::Wireframe, normal_lines::NormalLines, labels::Labels};
 
 type MaterialComponent = crate::state::scene::components::material::Material;
 //type MeshComponent = crate::state::scene::components::mesh::Mesh;
@@ -14,11 +15,64 @@ pub struct RenderData<'a>
 {
     node: &'a RwLockReadGuard<'a, Box<Node>>,
     material: &'a RwLockReadGuard<'a, ComponentBox>,
-    meshes: &'a Vec<RwLockReadGuard<'a, ComponentBox>>,
+    meshes: &'a Vec<RwLockReadGuard<'a, ComponentBox>>, // meshes to render (node meshes or a LOD level - see select_lod_meshes)
+    node_meshes: &'a Vec<RwLockReadGuard<'a, ComponentBox>>,
+    lod_meshes: &'a Vec<Vec<RwLockReadGuard<'a, ComponentBox>>>, // one entry per LOD level
 
     has_transparency: bool,
+    has_transmission: bool,
+    backface_culling: bool,
+    depth_variant: DepthVariant,
+    layer_mask: u32,
     alpha_index: u64,
-    middle: Point3::<f32>
+    middle: Point3::<f32>,
+    instance_index: Option<u32>, // transparent instances are sorted (and drawn) one by one
+}
+
+// draw order: opaque -> transparent -> without depth write
+// transparent items are ordered by their alpha index first (always - also without distance sorting) and by distance afterwards (back to front)
+// ties are resolved by node id and instance index to prevent flickering (without distance sorting the scene order is kept)
+fn compare_render_data(a: &RenderData, b: &RenderData, cam_pos: Option<&Point3<f32>>) -> Ordering
+{
+    // without depth write (overlays, decals) -> on top of everything else
+    if a.depth_variant.depth_write != b.depth_variant.depth_write
+    {
+        return b.depth_variant.depth_write.cmp(&a.depth_variant.depth_write);
+    }
+
+    // opaque first (blended items need the opaque scene behind)
+    if a.has_transparency != b.has_transparency
+    {
+        return a.has_transparency.cmp(&b.has_transparency);
+    }
+
+    // lower alpha index first: a higher index forces the item to be drawn later (like glass in front of water)
+    if a.alpha_index != b.alpha_index
+    {
+        return a.alpha_index.cmp(&b.alpha_index);
+    }
+
+    if cam_pos.is_none()
+    {
+        return Ordering::Equal;
+    }
+
+    // we do not need the exact distance here - squared is fine
+    let a_dist = distance_squared(&a.middle, cam_pos.unwrap());
+    let b_dist = distance_squared(&b.middle, cam_pos.unwrap());
+
+    b_dist.partial_cmp(&a_dist).unwrap_or(Ordering::Equal)
+        .then_with(|| a.node.id.cmp(&b.node.id))
+        .then_with(|| a.instance_index.cmp(&b.instance_index))
+}
+
+// color pass split for transmissive materials (see Transmission)
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ColorPhase
+{
+    All,
+    Opaque,
+    Transmissive,
 }
 
 #[repr(C)]
@@ -27,11 +81,13 @@ pub struct SceneUniform
 {
     pub gamma: f32,
     pub exposure: f32,
+    pub environment_intensity: f32,
+    pub debug_channel: u32,
 }
 
 impl SceneUniform
 {
-    pub fn new(scene_data: &SceneData) -> Self
+    pub fn new(scene_data: &SceneData, debug_channel: DebugChannel) -> Self
     {
         let gamma = if let Some(gamma) = scene_data.gamma { gamma } else { 0.0 };
         let exposure = if let Some(exposure) = scene_data.exposure { exposure } else { 0.0 };
@@ -40,6 +96,8 @@ impl SceneUniform
         {
             gamma: gamma,
             exposure: exposure,
+            environment_intensity: scene_data.environment_intensity,
+            debug_channel: debug_channel as u32,
         }
     }
 }
@@ -50,14 +108,33 @@ pub struct Scene
 
     color_shader: String,
     depth_shader: String,
+    outline_mask_shader: String,
+    xray_shader: String,
 
     samples: u32,
     pub distance_sorting: bool,
+    pub transparent_clear: bool,
+    xray_selected: bool,
+    debug_channel: DebugChannel,
+    anisotropy: u8,
+    pub rendered_vertices: u32,
 
     depth_pipe: Option<Pipeline>,
     color_pipe: Option<Pipeline>,
+    outline_mask_pipe: Option<Pipeline>,
+    xray_pipe: Option<Pipeline>,
+    overdraw_pipe: Option<Pipeline>,
+    depth_variants: HashSet<DepthVariant>, // material depth settings used in the scene
+
+    grid: Grid,
+    dof: DepthOfField,
+    outline: Outline,
+    wireframe: Wireframe,
+    normal_lines: NormalLines,
+    labels: Labels,
+    transmission: Transmission,
 
-    buffer: wgpu::Buffer,
+    buffer: TrackedBuffer,
 
     depth_pass_buffer_texture: Texture,
     depth_buffer_texture: Texture,
@@ -75,7 +152,11 @@ impl Scene
         // shader source
         let color_shader = resources::load_string("shader/phong.wgsl").unwrap();
         let depth_shader = resources::load_string("shader/depth.wgsl").unwrap();
+        let outline_mask_shader = resources::load_string("shader/outline_mask.wgsl").unwrap();
+        let xray_shader = resources::load_string("shader/xray.wgsl").unwrap();
 
+        let depth_pass_buffer_texture = Texture::new_depth_texture(wgpu, 1);
+        let dof = DepthOfField::new(wgpu, &state.rendering.dof, &depth_pass_buffer_texture);
 
         let mut render_scene = Self
         {
@@ -83,17 +164,36 @@ impl Scene
 
             color_shader,
             depth_shader,
+            outline_mask_shader,
+            xray_shader,
 
             samples,
             distance_sorting: true,
+            transparent_clear: false,
+            xray_selected: false,
+            debug_channel: state.rendering.debug_channel,
+            anisotropy: state.rendering.anisotropy,
+            rendered_vertices: 0,
 
             color_pipe: None,
             depth_pipe: None,
+            outline_mask_pipe: None,
+            xray_pipe: None,
+            overdraw_pipe: None,
+            depth_variants: HashSet::new(),
 
-            buffer: create_empty_buffer(wgpu),
+            grid: Grid::new(wgpu, &state.rendering.grid),
+            dof,
+            outline: Outline::new(wgpu, &state.rendering.outline, samples),
+            wireframe: Wireframe::new(wgpu, &state.rendering.wireframe),
+            normal_lines: NormalLines::new(wgpu, &state.rendering.normals),
+            labels: Labels::new(wgpu),
+            transmission: Transmission::new(wgpu),
+
+            buffer: create_empty_buffer(wgpu, BufferType::Uniform),
 
             depth_buffer_texture: Texture::new_depth_texture(wgpu, samples),
-            depth_pass_buffer_texture: Texture::new_depth_texture(wgpu, 1),
+            depth_pass_buffer_texture,
         };
 
         render_scene.to_buffer(wgpu, scene);
@@ -108,10 +208,12 @@ impl Scene
     {
         let data = scene.get_data();
 
-        let scene_uniform = SceneUniform::new(data);
+        let scene_uniform = SceneUniform::new(data, self.debug_channel);
 
-        self.buffer = wgpu.device().create_buffer_init
+        self.buffer = create_buffer_init
         (
+            wgpu,
+            BufferType::Uniform,
             &wgpu::util::BufferInitDescriptor
             {
                 label: Some(&scene.name),
@@ -125,9 +227,9 @@ impl Scene
     {
         let data = scene.get_data();
 
-        let scene_uniform = SceneUniform::new(data);
+        let scene_uniform = SceneUniform::new(data, self.debug_channel);
 
-        wgpu.queue_mut().write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[scene_uniform]));
+        write_buffer(wgpu, &self.buffer, 0, bytemuck::cast_slice(&[scene_uniform]));
     }
 
     pub fn get_buffer(&self) -> &wgpu::Buffer
@@ -157,7 +259,7 @@ impl Scene
         // ********** depth pass **********
         if !re_create
         {
-            self.depth_pipe = Some(Pipeline::new(wgpu, "depth pipe", &self.depth_shader, &bind_group_layouts, scene.get_data().max_lights, true, true, 1));
+            self.depth_pipe = Some(Pipeline::new(wgpu, "depth pipe", &self.depth_shader, &bind_group_layouts, scene.get_data().max_lights, true, true, false, 1));
         }
         else
         {
@@ -168,17 +270,76 @@ impl Scene
         let mut additional_textures = vec![];
         additional_textures.push(&self.depth_pass_buffer_texture);
 
+        // with the transmission background
+        let color_bind_group_layouts =
+        [
+            material_bind_layout,
+            &light_cam_scene_bind_layout,
+            self.transmission.get_bind_group_layout()
+        ];
+
         if !re_create
         {
-            self.color_pipe = Some(Pipeline::new(wgpu, "color pipe", &self.color_shader, &bind_group_layouts, scene.get_data().max_lights, true, true, self.samples));
+            self.color_pipe = Some(Pipeline::new(wgpu, "color pipe", &self.color_shader, &color_bind_group_layouts, scene.get_data().max_lights, true, true, false, self.samples));
         }
         else
         {
-            self.color_pipe.as_mut().unwrap().re_create(wgpu, &bind_group_layouts, true, true, self.samples);
+            self.color_pipe.as_mut().unwrap().re_create(wgpu, &color_bind_group_layouts, true, true, self.samples);
         }
+
+        // ********** material depth settings (depth and color pass) **********
+        self.depth_pipe.as_mut().unwrap().create_depth_variants(wgpu, &bind_group_layouts, 1, &self.depth_variants);
+        self.color_pipe.as_mut().unwrap().create_depth_variants(wgpu, &color_bind_group_layouts, self.samples, &self.depth_variants);
+
+        // ********** overdraw (color pass with additive blending) **********
+        if !re_create
+        {
+            self.overdraw_pipe = Some(Pipeline::new(wgpu, "overdraw pipe", &self.color_shader, &color_bind_group_layouts, scene.get_data().max_lights, true, true, true, self.samples));
+        }
+        else
+        {
+            self.overdraw_pipe.as_mut().unwrap().re_create(wgpu, &color_bind_group_layouts, true, true, self.samples);
+        }
+
+        // ********** outline mask **********
+        if !re_create
+        {
+            self.outline_mask_pipe = Some(Pipeline::new(wgpu, "outline mask pipe", &self.outline_mask_shader, &bind_group_layouts, scene.get_data().max_lights, false, true, false, self.outline.get_mask_sample_count()));
+        }
+        else
+        {
+            self.outline_mask_pipe.as_mut().unwrap().re_create(wgpu, &bind_group_layouts, false, true, self.outline.get_mask_sample_count());
+        }
+
+        // ********** x-ray (without depth test) **********
+        if !re_create
+        {
+            self.xray_pipe = Some(Pipeline::new(wgpu, "xray pipe", &self.xray_shader, &bind_group_layouts, scene.get_data().max_lights, false, true, false, 1));
+        }
+        else
+        {
+            self.xray_pipe.as_mut().unwrap().re_create(wgpu, &bind_group_layouts, false, true, 1);
+        }
+
+        // ********** grid **********
+        self.grid.create_pipeline(wgpu, &light_cam_scene_bind_layout, self.samples);
+
+        // ********** wireframe **********
+        self.wireframe.create_pipeline(wgpu, &light_cam_scene_bind_layout, self.samples);
+
+        // ********** normal lines **********
+        self.normal_lines.create_pipeline(wgpu, &light_cam_scene_bind_layout, self.samples);
+
+        // ********** labels **********
+        self.labels.create_pipeline(wgpu, &light_cam_scene_bind_layout, self.samples);
+
+        // ********** post processing **********
+        self.dof.create_pipeline(wgpu);
+        self.outline.create_pipeline(wgpu);
+        self.transmission.create_pipeline(wgpu);
     }
 
-    pub fn update_textures(&mut self, wgpu: &mut WGpu, scene: &mut crate::state::scene::scene::Scene)
+    pub fn update_textures(&mut self, wgpu: &mut WGpu, scene: &mut crate::state::scene::scene::Scene, max_texture_resolution: Option<u32>, anisotropy_changed: bool, max_anisotropy: u8)
     {
         // check if the scene env texture has changed
         if let Some(env_tex) = &scene.get_data().environment_texture
@@ -208,25 +369,37 @@ impl Scene
 
             {
                 let mut texture = texture.write().unwrap();
-                let texture_changed = texture.get_data_mut().consume_change();
 
-                // check if buffer recreation is needed
+                // also applied on already loaded textures (after changing the setting)
+                if let Some(max_texture_resolution) = max_texture_resolution
+                {
+                    texture.limit_resolution(max_texture_resolution);
+                }
+
+                let mut texture_changed = texture.get_data_mut().consume_change();
+
+                // samplers are using the global anisotropy if there is no override
+                if anisotropy_changed && texture.get_data().anisotropy.is_none()
+                {
+                    texture_changed = true;
+                }
+
+                // color space: see State::update_texture_color_spaces
+                let format = TextureFormat::from_texture(texture.channels(), texture.is_hdr(), texture.color_space);
+
+                // check if buffer recreation is needed (also if the texture is used in another slot with a different color space)
                 // TODO: check if this even needed anymore (because of the changetracker data from texture)
                 if let Some(render_item) = &texture.render_item
                 {
                     let render_item = get_render_item::<Texture>(render_item);
-                    buffer_recreate_needed = render_item.width != texture.width() || render_item.height != texture.height();
+                    buffer_recreate_needed = render_item.width != texture.width() || render_item.height != texture.height() || render_item.get_format() != format;
                 }
 
                 if texture.render_item.is_none() || buffer_recreate_needed || texture_changed
                 {
-                    let mut format = TextureFormat::Srgba;
-                    if texture.channels() == 1
-                    {
-                        format = TextureFormat::Gray;
-                    }
+                    let anisotropy = texture.get_data().anisotropy.unwrap_or(self.anisotropy).min(max_anisotropy);
 
-                    let render_item = Texture::new_from_texture(wgpu, texture.name.as_str(), &texture, format);
+                    let render_item = Texture::new_from_texture(wgpu, texture.name.as_str(), &texture, format, anisotropy);
                     texture.render_item = Some(Box::new(render_item));
                     buffer_recreate_needed = true;
                 }
@@ -268,6 +441,15 @@ impl Scene
     {
         let default_env_map = scene.get_data().environment_texture.clone();
 
+        let mut ibl_maps = None;
+        if let (Some(env_tex), Some(irradiance), Some(brdf_lut)) = (&default_env_map, &scene.get_data().environment_irradiance, &scene.get_data().environment_brdf_lut)
+        {
+            if env_tex.enabled
+            {
+                ibl_maps = Some((irradiance.clone(), brdf_lut.clone()));
+            }
+        }
+
         for (_material_id, material) in &mut scene.materials
         {
             let mut material = material.write().unwrap();
@@ -278,7 +460,7 @@ impl Scene
             if material_changed || material.get_base().render_item.is_none()
             {
                 dbg!("material render item recreate");
-                let render_item: MaterialBuffer = MaterialBuffer::new(wgpu, &material, default_env_map.clone(), None);
+                let render_item: MaterialBuffer = MaterialBuffer::new(wgpu, &material, default_env_map.clone(), ibl_maps.clone(), None);
                 material.get_base_mut().render_item = Some(Box::new(render_item));
             }
             else if material_changed || force
@@ -287,8 +469,8 @@ impl Scene
 
                 {
                     let render_item = get_render_item_mut::<MaterialBuffer>(render_item.as_mut().unwrap());
-                    render_item.to_buffer(wgpu, material, default_env_map.clone(), None);
-                    render_item.create_binding_groups(wgpu, material, default_env_map.clone(), None);
+                    render_item.to_buffer(wgpu, material, default_env_map.clone(), ibl_maps.clone(), None);
+                    render_item.create_binding_groups(wgpu, material, default_env_map.clone(), ibl_maps.clone(), None);
                 }
 
                 material.get_base_mut().render_item = render_item;
@@ -298,6 +480,26 @@ impl Scene
         }
     }
 
+    fn get_depth_variants(scene: &crate::state::scene::scene::Scene) -> HashSet<DepthVariant>
+    {
+        let mut depth_variants = HashSet::new();
+
+        for (_material_id, material) in &scene.materials
+        {
+            let material = material.read().unwrap();
+            let material = material.as_any().downcast_ref::<MaterialComponent>().unwrap();
+            let data = material.get_data();
+
+            let depth_variant = DepthVariant::new(data);
+            if depth_variant != DEFAULT_DEPTH_VARIANT
+            {
+                depth_variants.insert(depth_variant);
+            }
+        }
+
+        depth_variants
+    }
+
     pub fn update_light_cameras(&mut self, wgpu: &mut WGpu, scene: &mut crate::state::scene::scene::Scene, force: bool)
     {
         // ********** lights: all **********
@@ -387,16 +589,29 @@ impl Scene
                 let node = node.write().unwrap();
                 let mesh = node.find_component::<crate::state::scene::components::mesh::Mesh>();
 
+                // debug lines are only needed for selected nodes
+                let normal_lines = self.normal_lines.enabled && node.instances.get_ref().iter().any(|instance| instance.read().unwrap().get_data().highlight);
+
                 if let Some(mesh) = mesh
                 {
                     component_downcast_mut!(mesh, crate::state::scene::components::mesh::Mesh);
 
-                    let (mesh_data, mesh_data_changed) = mesh.get_data_mut().consume_borrow_mut();
+                    Self::update_vertex_buffer(wgpu, mesh, "vertex buffer", normal_lines);
+                }
+
+                // lod meshes
+                let lod = node.find_component::<Lod>();
 
-                    if mesh_data_changed
+                if let Some(lod) = lod
+                {
+                    component_downcast!(lod, Lod);
+
+                    for level in &lod.get_data().levels
                     {
-                        let vertex_buffer = VertexBuffer::new(wgpu, "vertex buffer", mesh_data);
-                        mesh.get_base_mut().render_item = Some(Box::new(vertex_buffer));
+                        let mesh = level.mesh.clone();
+                        component_downcast_mut!(mesh, Mesh);
+
+                        Self::update_vertex_buffer(wgpu, mesh, "lod vertex buffer", normal_lines);
                     }
                 }
             }
@@ -499,6 +714,8 @@ impl Scene
                     let node = node.read().unwrap();
                     let instances_ref = node.instances.get_ref();
 
+                    let mut changed_instances = vec![];
+
                     for (i, instance) in instances_ref.iter().enumerate()
                     {
                         let mut instance = instance.write().unwrap();
@@ -509,12 +726,16 @@ impl Scene
 
                         if instance_changed
                         {
-                            let render_item = get_render_item_mut::<InstanceBuffer>(render_item.as_mut().unwrap());
-                            render_item.update_buffer(wgpu, &instance, i);
-
-                            //dbg!(" ============ ONE instance updated");
+                            changed_instances.push(i);
                         }
                     }
+
+                    // only the changed slices of the instance buffer are uploaded
+                    if !changed_instances.is_empty() && render_item.is_some()
+                    {
+                        let render_item = get_render_item_mut::<InstanceBuffer>(render_item.as_mut().unwrap());
+                        render_item.update_changed(wgpu, instances_ref, &changed_instances);
+                    }
                 }
 
                 {
@@ -527,6 +748,30 @@ impl Scene
         }
     }
 
+    fn update_vertex_buffer(wgpu: &mut WGpu, mesh: &mut Mesh, name: &str, normal_lines: bool)
+    {
+        let normal_lines_missing = mesh.get_base().render_item.as_ref().map_or(true, |render_item| !get_render_item::<VertexBuffer>(render_item).has_normal_lines());
+
+        let (mesh_data, mesh_data_changed) = mesh.get_data_mut().consume_borrow_mut();
+
+        let mut lines = None;
+        if normal_lines && (mesh_data_changed || normal_lines_missing)
+        {
+            lines = Some(VertexBuffer::create_normal_lines(wgpu, name, mesh_data));
+        }
+
+        if mesh_data_changed
+        {
+            let vertex_buffer = VertexBuffer::new(wgpu, name, mesh_data);
+            mesh.get_base_mut().render_item = Some(Box::new(vertex_buffer));
+        }
+
+        if let (Some(lines), Some(render_item)) = (lines, mesh.get_base_mut().render_item.as_mut())
+        {
+            get_render_item_mut::<VertexBuffer>(render_item).set_normal_lines(lines);
+        }
+    }
+
     /*
     pub fn find_changed_parent_data(node: Arc<RwLock<Box<Node>>>) -> bool
     {
@@ -612,18 +857,54 @@ impl Scene
                 r: clear_color.x as f64,
                 g: clear_color.y as f64,
                 b: clear_color.z as f64,
-                a: 1.0,
+                a: clear_color.w as f64,
             };
         }
 
+        // ********** grid **********
+        self.grid.update(wgpu, &state.rendering.grid, &scene.get_up_axis());
+
+        // ********** wireframe **********
+        self.wireframe.update(wgpu, &state.rendering.wireframe);
+
+        // ********** normal lines **********
+        self.normal_lines.update(wgpu, &state.rendering.normals);
+
+        // ********** labels **********
+        self.labels.update(wgpu, scene);
+
+        // ********** post processing **********
+        self.dof.update(&state.rendering.dof);
+        self.outline.update(wgpu, &state.rendering.outline);
+
+        // ********** selection **********
+        self.xray_selected = state.rendering.xray_selected;
+
+        // ********** debug channel **********
+        let debug_channel_changed = self.debug_channel != state.rendering.debug_channel;
+        self.debug_channel = state.rendering.debug_channel;
+
         // ********** dynamic items **********
-        self.update_textures(wgpu, scene);
+        let anisotropy_changed = self.anisotropy != state.rendering.anisotropy;
+        self.anisotropy = state.rendering.anisotropy;
+
+        self.update_textures(wgpu, scene, state.rendering.max_texture_resolution, anisotropy_changed, state.adapter.max_anisotropy);
 
         let scene_changed = scene.get_data_mut().consume_change();
 
         self.update_materials(wgpu, scene, scene_changed);
         self.update_light_cameras(wgpu, scene, scene_changed);
 
+        if debug_channel_changed && !scene_changed
+        {
+            self.update_buffer(wgpu, scene);
+        }
+
+        // pipelines for custom material depth settings
+        let depth_variants = Self::get_depth_variants(scene);
+        let depth_variants_changed = depth_variants != self.depth_variants;
+        self.depth_variants = depth_variants;
+
         if scene_changed
         {
             dbg!("scene data changed -> recreate materials/lights/pipelines");
@@ -634,6 +915,10 @@ impl Scene
             // update pipelines
             self.create_pipelines(wgpu, scene, true);
         }
+        else if depth_variants_changed && self.color_pipe.is_some()
+        {
+            self.create_pipelines(wgpu, scene, true);
+        }
 
         let mut all_nodes = Scene::list_all_child_nodes(&scene.nodes, false);
         self.update_nodes(wgpu, &mut all_nodes);
@@ -676,12 +961,19 @@ impl Scene
             state.save_image = false;
         }
 
+        // the depth is linearized based on the first camera (like for dof)
+        let mut clipping = None;
+        if state.depth_image_linearized
+        {
+            clipping = scene.cameras.iter().find(|cam| cam.enabled).map(|cam| (cam.get_data().clipping_near, cam.get_data().clipping_far));
+        }
+
         if state.save_depth_pass_image
         {
             let img_data = self.depth_pass_buffer_texture.to_image(wgpu);
             img_data.save("data/depth_pass.png").unwrap();
 
-            let img_data_gray = float32_to_grayscale(img_data);
+            let img_data_gray = Self::depth_to_grayscale(img_data, clipping, state.depth_image_normalized);
             img_data_gray.save("data/depth_pass_gray.png").unwrap();
 
             state.save_depth_pass_image = false;
@@ -692,18 +984,29 @@ impl Scene
             let img_data = self.depth_buffer_texture.to_image(wgpu);
             img_data.save("data/depth_buffer.png").unwrap();
 
-            let img_data_gray = float32_to_grayscale(img_data);
+            let img_data_gray = Self::depth_to_grayscale(img_data, clipping, state.depth_image_normalized);
             img_data_gray.save("data/depth_buffer_gray.png").unwrap();
 
             state.save_depth_buffer_image = false;
         }
     }
 
+    fn depth_to_grayscale(img_data: DynamicImage, clipping: Option<(f32, f32)>, normalized: bool) -> DynamicImage
+    {
+        if let Some((near, far)) = clipping
+        {
+            return float32_depth_to_linear_grayscale(img_data, near, far, normalized);
+        }
+
+        float32_to_grayscale(img_data)
+    }
+
     pub fn msaa_sample_size_update(&mut self, wgpu: &mut WGpu, scene: &mut crate::state::scene::scene::Scene, samples: u32)
     {
         self.samples = samples;
 
         self.depth_buffer_texture = Texture::new_depth_texture(wgpu, self.samples);
+        self.outline.msaa_sample_size_update(wgpu, self.samples);
 
         //self.update_materials(wgpu, scene, true);
         self.create_pipelines(wgpu, scene, true);
@@ -720,6 +1023,10 @@ impl Scene
 
         self.depth_buffer_texture = Texture::new_depth_texture(wgpu, self.samples);
         self.depth_pass_buffer_texture = Texture::new_depth_texture(wgpu, 1);
+
+        self.dof.resize(wgpu, &self.depth_pass_buffer_texture);
+        self.outline.resize(wgpu);
+        self.transmission.resize(wgpu);
     }
 
     pub fn list_all_child_nodes(nodes: &Vec<NodeItem>, check_visibility: bool) -> Vec<NodeItem>
@@ -777,6 +1084,9 @@ impl Scene
         let default_material_arc = default_material.unwrap();
         let default_material = &default_material_arc.read().unwrap();
 
+        let mut lod_meshes = vec![];
+        let mut lod_meshes_read = vec![];
+
         for node in &all_nodes
         {
             let read_node = node.read().unwrap();
@@ -785,6 +1095,8 @@ impl Scene
 
             if node_meshes.len() > 0
             {
+                lod_meshes.push(Self::get_lod_meshes(&read_node));
+
                 if let Some(mat) = mat
                 {
                     nodes_read.push(read_node);
@@ -812,6 +1124,12 @@ impl Scene
             meshes_read.push(mesh_read);
         }
 
+        for levels in &lod_meshes
+        {
+            let levels_read: Vec<Vec<_>> = levels.iter().map(|mesh_item| vec![mesh_item.read().unwrap()]).collect();
+            lod_meshes_read.push(levels_read);
+        }
+
         let mut render_data = Vec::with_capacity(materials_read.len());
         for (i, material) in materials_read.iter().enumerate()
         {
@@ -829,6 +1147,7 @@ impl Scene
             let meshes = meshes_read.get(i).unwrap();
 
             let mut item_middle = Point3::<f32>::new(0.0, 0.0, 0.0);
+            let mut instance_middles = vec![];
 
             // ***** get center for depth sorting (alpha blending)
             if self.distance_sorting
@@ -865,6 +1184,8 @@ impl Scene
                         item_middle.x += p.x;
                         item_middle.y += p.y;
                         item_middle.z += p.z;
+
+                        instance_middles.push(p);
                     }
 
                     let len_f32 = instance_buffer.transformations.len() as f32;
@@ -875,28 +1196,107 @@ impl Scene
             }
 
             let has_transparency;
+            let has_transmission;
+            let backface_culling;
+            let depth_variant;
             {
                 let mat = mat.as_any().downcast_ref::<MaterialComponent>().unwrap();
-                has_transparency = mat.has_transparency();
+                has_transparency = mat.has_transparency() || node.instance_render_item.as_ref().map(|render_item| get_render_item::<InstanceBuffer>(render_item).has_transparency()).unwrap_or(false);
+                has_transmission = mat.has_transmission();
+                backface_culling = mat.get_data().backface_cullig;
+                depth_variant = DepthVariant::new(mat.get_data());
             }
 
-            render_data.push
-            (
-                RenderData
-                {
-                    node: nodes_read.get(i).unwrap(),
-                    material: mat,
-                    meshes: meshes,
+            // transparent instances are sorted by their own position (the node center is not enough to blend them correctly)
+            let mut middles = vec![(item_middle, None)];
+            if has_transparency && instance_middles.len() > 1
+            {
+                middles = instance_middles.iter().enumerate().map(|(instance_index, middle)| (*middle, Some(instance_index as u32))).collect();
+            }
+
+            let layer_mask = node.get_layer_mask();
+
+            for (middle, instance_index) in middles
+            {
+                render_data.push
+                (
+                    RenderData
+                    {
+                        node: nodes_read.get(i).unwrap(),
+                        material: mat,
+                        meshes: meshes,
+                        node_meshes: meshes,
+                        lod_meshes: lod_meshes_read.get(i).unwrap(),
+
+                        has_transparency: has_transparency,
+                        has_transmission: has_transmission,
+                        backface_culling: backface_culling,
+                        depth_variant: depth_variant,
+                        layer_mask: layer_mask,
+                        alpha_index: node.alpha_index,
+                        middle: middle,
+                        instance_index: instance_index
+                    }
+                );
+            }
+        }
+
+        // LOD levels for the statistics: based on the first enabled camera (each camera selects its own levels before rendering)
+        if let Some(cam) = scene.cameras.iter().find(|cam| cam.enabled)
+        {
+            let cam_pos = cam.get_data().eye_pos;
+
+            for data in &mut render_data
+            {
+                Self::select_lod_meshes(data, &cam_pos);
+            }
+        }
+
+        // ***** rendered vertices (statistics)
+        self.rendered_vertices = 0;
+        for data in &render_data
+        {
+            if !data.node.visible
+            {
+                continue;
+            }
 
-                    has_transparency: has_transparency,
-                    alpha_index: node.alpha_index,
-                    middle: item_middle
+            let mut instances = data.node.instances.get_ref().len() as u32;
+            if data.instance_index.is_some()
+            {
+                instances = 1;
+            }
+
+            for mesh in data.meshes
+            {
+                let mesh = mesh.as_any().downcast_ref::<Mesh>().unwrap();
+                if mesh.get_base().is_enabled
+                {
+                    self.rendered_vertices += mesh.get_data().vertices.len() as u32 * instances;
                 }
-            );
+            }
         }
 
         let mut draw_calls: u32 = 0;
 
+        // selection overlays (only if something is highlighted)
+        let has_highlight = render_data.iter().any(|data|
+        {
+            data.node.visible && data.node.instance_render_item.as_ref().map_or(false, |item| get_render_item::<InstanceBuffer>(item).has_highlight())
+        });
+
+        let outline = self.outline.enabled && has_highlight;
+        let xray = self.xray_selected && has_highlight;
+
+        let transmission = render_data.iter().any(|data| data.node.visible && data.has_transmission);
+
+        // with post processing the scene is rendered offscreen first
+        let mut target_view = view;
+        if self.dof.enabled
+        {
+            target_view = self.dof.get_view();
+        }
+
         let mut i = 0;
         for cam in &scene.cameras
         {
@@ -904,39 +1304,16 @@ impl Scene
 
             let cam_data = cam.get_data();
 
-            // sort
-            if self.distance_sorting
+            // LOD levels based on the distance to this camera
+            for data in &mut render_data
             {
-
-                let cam_pos = cam_data.eye_pos;
-                render_data.sort_by(|a, b|
-                {
-                    if a.has_transparency != b.has_transparency
-                    {
-                        b.has_transparency.cmp(&a.has_transparency)
-                    }
-                    else if a.alpha_index != b.alpha_index
-                    {
-                        a.alpha_index.cmp(&b.alpha_index)
-                    }
-                    else
-                    {
-                        // we do not need the exact distance here - squared is fine
-                        let a_dist = distance_squared(&a.middle, &cam_pos);
-                        let b_dist = distance_squared(&b.middle, &cam_pos);
-
-                        b_dist.partial_cmp(&a_dist).unwrap()
-                    }
-
-                    /*
-                    a.alpha_index.cmp(&b.alpha_index)
-                    .then_with(|| b_dist.partial_cmp(&a_dist).unwrap())
-                    */
-
-                    //b.partial_cmp(&a).unwrap()
-                });
+                Self::select_lod_meshes(data, &cam_data.eye_pos);
             }
 
+            // sort (the alpha index is also applied without distance sorting)
+            let cam_pos = if self.distance_sorting { Some(cam_data.eye_pos) } else { None };
+            render_data.sort_by(|a, b| compare_render_data(a, b, cam_pos.as_ref()));
+
             let clear;
             if i == 0 { clear = true; } else { clear = false; }
 
@@ -944,16 +1321,113 @@ impl Scene
             let bind_group_render_item = cam.bind_group_render_item.as_ref().unwrap();
             let bind_group_render_item = get_render_item::<LightCamSceneBindGroup>(bind_group_render_item);
 
-            draw_calls += self.render_depth(wgpu, view, encoder, &render_data, cam_data, &bind_group_render_item.bind_group, clear);
-            draw_calls += self.render_color(wgpu, view, msaa_view, encoder, &render_data, cam_data, &bind_group_render_item.bind_group, clear);
+            draw_calls += self.render_depth(wgpu, target_view, encoder, &render_data, cam_data, &bind_group_render_item.bind_group, clear);
+
+            if transmission
+            {
+                // opaque scene into the background -> copy to the output -> transmissive materials on top (sampling the background)
+                draw_calls += self.render_color(wgpu, self.transmission.get_view(), msaa_view, encoder, &render_data, cam_data, &bind_group_render_item.bind_group, clear, ColorPhase::Opaque);
+                draw_calls += self.transmission.render_background(wgpu, target_view, encoder, cam_data);
+                draw_calls += self.render_color(wgpu, target_view, msaa_view, encoder, &render_data, cam_data, &bind_group_render_item.bind_group, false, ColorPhase::Transmissive);
+            }
+            else
+            {
+                draw_calls += self.render_color(wgpu, target_view, msaa_view, encoder, &render_data, cam_data, &bind_group_render_item.bind_group, clear, ColorPhase::All);
+            }
+
+            if xray
+            {
+                draw_calls += self.render_xray(wgpu, target_view, encoder, &render_data, cam_data, &bind_group_render_item.bind_group);
+            }
+
+            if outline
+            {
+                draw_calls += self.render_outline_mask(wgpu, encoder, &render_data, cam_data, &bind_group_render_item.bind_group, clear);
+            }
 
             i += 1;
         }
 
+        // ********** post processing **********
+        if self.dof.enabled
+        {
+            // the depth is linearized based on the first camera
+            let cam_data = scene.cameras.iter().find(|cam| cam.enabled).map(|cam| cam.get_data());
+            draw_calls += self.dof.render(wgpu, view, encoder, cam_data);
+        }
+
+        if outline
+        {
+            draw_calls += self.outline.render(wgpu, view, encoder);
+        }
+
         draw_calls
     }
 
-    pub fn render_depth(&mut self, _wgpu: &mut WGpu, view: &TextureView, encoder: &mut CommandEncoder, nodes: &Vec<RenderData>, cam_data: &CameraData, light_cam_bind_group: &BindGroup, clear: bool) -> u32
+    // meshes of all LOD levels (empty without an enabled LOD component)
+    fn get_lod_meshes(node: &Node) -> Vec<ComponentItem>
+    {
+        let lod = node.find_component::<Lod>();
+
+        if lod.is_none()
+        {
+            return vec![];
+        }
+
+        let lod = lod.unwrap();
+        component_downcast!(lod, Lod);
+
+        if !lod.is_enabled()
+        {
+            return vec![];
+        }
+
+        lod.get_data().levels.iter().map(|level| level.mesh.clone()).collect()
+    }
+
+    // selects the node meshes or the LOD level mesh based on the distance of the nearest instance to the camera
+    fn select_lod_meshes(data: &mut RenderData, cam_pos: &Point3<f32>)
+    {
+        data.meshes = data.node_meshes;
+
+        if data.lod_meshes.is_empty() || data.node_meshes.is_empty()
+        {
+            return;
+        }
+
+        let instance_render_item = data.node.instance_render_item.as_ref();
+        if instance_render_item.is_none()
+        {
+            return;
+        }
+
+        let mesh_middle;
+        {
+            let mesh = data.node_meshes.first().unwrap().as_any().downcast_ref::<Mesh>().unwrap();
+            mesh_middle = mesh.get_data().b_box.center();
+        }
+
+        // use the nearest instance
+        let instance_buffer = get_render_item::<InstanceBuffer>(instance_render_item.unwrap());
+
+        let mut distance = std::f32::MAX;
+        for transform in &instance_buffer.transformations
+        {
+            let p = transform.transform_point(&mesh_middle);
+            distance = distance.min(nalgebra::distance(&p, cam_pos));
+        }
+
+        let lod = data.node.find_component::<Lod>().unwrap();
+        component_downcast!(lod, Lod);
+
+        let lod_meshes = data.lod_meshes;
+        if let Some(meshes) = lod.get_level_for_distance(distance).and_then(|level| lod_meshes.get(level))
+        {
+            data.meshes = meshes;
+        }
+    }
+
+    pub fn render_depth(&self, wgpu: &mut WGpu, view: &TextureView, encoder: &mut CommandEncoder, nodes: &Vec<RenderData>, cam_data: &CameraData, light_cam_bind_group: &BindGroup, clear: bool) -> u32
     {
         let mut clear_color = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
         let mut clear_depth = wgpu::LoadOp::Clear(1.0);
@@ -1000,7 +1474,7 @@ impl Scene
                 }),
                 stencil_ops: None,
             }),
-            timestamp_writes: None,
+            timestamp_writes: wgpu.timestamp_writes("depth"),
             occlusion_query_set: None,
         });
 
@@ -1012,10 +1486,10 @@ impl Scene
 
         render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
 
-        self.draw_phase(&mut render_pass, &self.depth_pipe.as_ref().unwrap(), nodes, light_cam_bind_group)
+        self.draw_phase(&mut render_pass, &self.depth_pipe.as_ref().unwrap(), nodes, light_cam_bind_group, false, ColorPhase::All, cam_data.layer_mask)
     }
 
-    pub fn render_color(&mut self, _wgpu: &mut WGpu, view: &TextureView, msaa_view: &Option<TextureView>, encoder: &mut CommandEncoder, nodes: &Vec<RenderData>, cam_data: &CameraData, light_cam_bind_group: &BindGroup, clear: bool) -> u32
+    fn render_color(&self, wgpu: &mut WGpu, view: &TextureView, msaa_view: &Option<TextureView>, encoder: &mut CommandEncoder, nodes: &Vec<RenderData>, cam_data: &CameraData, light_cam_bind_group: &BindGroup, clear: bool, phase: ColorPhase) -> u32
     {
         let mut render_pass_view = view;
         let mut render_pass_resolve_target = None;
@@ -1025,7 +1499,21 @@ impl Scene
             render_pass_resolve_target = Some(view);
         }
 
-        let mut clear_color = wgpu::LoadOp::Clear(self.clear_color);
+        let mut color = self.clear_color;
+        if self.transparent_clear
+        {
+            color.a = 0.0;
+        }
+
+        // the overdraw heatmap starts at black
+        let mut pipe = self.color_pipe.as_ref().unwrap();
+        if self.debug_channel == DebugChannel::Overdraw
+        {
+            color = wgpu::Color::BLACK;
+            pipe = self.overdraw_pipe.as_ref().unwrap();
+        }
+
+        let mut clear_color = wgpu::LoadOp::Clear(color);
         let mut clear_depth = wgpu::LoadOp::Clear(1.0);
 
         if !clear
@@ -1060,7 +1548,153 @@ impl Scene
                 }),
                 stencil_ops: None,
             }),
-            timestamp_writes: None,
+            timestamp_writes: wgpu.timestamp_writes("color"),
+            occlusion_query_set: None,
+        });
+
+        let x = cam_data.viewport_x * cam_data.resolution_width as f32;
+        let y = cam_data.viewport_y * cam_data.resolution_height as f32;
+
+        let width = cam_data.viewport_width * cam_data.resolution_width as f32;
+        let height = cam_data.viewport_height * cam_data.resolution_height as f32;
+
+        render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+
+        // the background can only be sampled if it's not the render target
+        render_pass.set_bind_group(2, self.transmission.get_bind_group(phase == ColorPhase::Transmissive), &[]);
+
+        let mut draw_calls = self.draw_phase(&mut render_pass, pipe, nodes, light_cam_bind_group, false, phase, cam_data.layer_mask);
+
+        // selection lines, grid and labels are rendered last (without depth write)
+        if phase != ColorPhase::Opaque
+        {
+            draw_calls += self.draw_selection_lines(&mut render_pass, nodes, light_cam_bind_group, cam_data.layer_mask);
+
+            if cam_data.layer_mask & LAYER_EDITOR != 0
+            {
+                draw_calls += self.grid.draw(&mut render_pass, light_cam_bind_group);
+            }
+
+            draw_calls += self.labels.draw(&mut render_pass, light_cam_bind_group);
+        }
+
+        draw_calls
+    }
+
+    // wireframe and normal lines of the selected objects
+    fn draw_selection_lines<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, nodes: &'a Vec<RenderData>, light_cam_bind_group: &'a BindGroup, layer_mask: u32) -> u32
+    {
+        let mut draw_calls: u32 = 0;
+
+        if !self.wireframe.enabled && !self.normal_lines.enabled
+        {
+            return 0;
+        }
+
+        for data in nodes
+        {
+            let node = data.node;
+
+            // per instance entries (transparent instances) -> lines are drawn for all instances at once
+            if !node.visible || data.layer_mask & layer_mask == 0 || data.instance_index.unwrap_or(0) > 0
+            {
+                continue;
+            }
+
+            let instance_render_item = node.instance_render_item.as_ref().unwrap();
+            let instance_buffer: &InstanceBuffer = *get_render_item::<InstanceBuffer>(instance_render_item);
+
+            if !instance_buffer.has_highlight()
+            {
+                continue;
+            }
+
+            for mesh in data.meshes
+            {
+                let mesh = mesh.as_any().downcast_ref::<Mesh>().unwrap();
+
+                if !mesh.get_base().is_enabled
+                {
+                    continue;
+                }
+
+                if let Some(render_item) = mesh.get_base().render_item.as_ref()
+                {
+                    let vertex_buffer: &VertexBuffer = *get_render_item::<VertexBuffer>(render_item);
+                    draw_calls += self.wireframe.draw(pass, vertex_buffer, instance_buffer, light_cam_bind_group);
+                    draw_calls += self.normal_lines.draw(pass, vertex_buffer, instance_buffer, light_cam_bind_group);
+                }
+            }
+        }
+
+        draw_calls
+    }
+
+    // selected objects as semi transparent overlay on top of the scene (visible through other objects)
+    pub fn render_xray(&self, wgpu: &mut WGpu, view: &TextureView, encoder: &mut CommandEncoder, nodes: &Vec<RenderData>, cam_data: &CameraData, light_cam_bind_group: &BindGroup) -> u32
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor
+        {
+            label: Some("xray pass"),
+            color_attachments:
+            &[
+                Some(wgpu::RenderPassColorAttachment
+                {
+                    view: view,
+                    resolve_target: None,
+                    ops: wgpu::Operations
+                    {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            ],
+            depth_stencil_attachment: None,
+            timestamp_writes: wgpu.timestamp_writes("xray"),
+            occlusion_query_set: None,
+        });
+
+        let x = cam_data.viewport_x * cam_data.resolution_width as f32;
+        let y = cam_data.viewport_y * cam_data.resolution_height as f32;
+
+        let width = cam_data.viewport_width * cam_data.resolution_width as f32;
+        let height = cam_data.viewport_height * cam_data.resolution_height as f32;
+
+        render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+
+        self.draw_phase(&mut render_pass, &self.xray_pipe.as_ref().unwrap(), nodes, light_cam_bind_group, true, ColorPhase::All, cam_data.layer_mask)
+    }
+
+    pub fn render_outline_mask(&self, wgpu: &mut WGpu, encoder: &mut CommandEncoder, nodes: &Vec<RenderData>, cam_data: &CameraData, light_cam_bind_group: &BindGroup, clear: bool) -> u32
+    {
+        let mut clear_color = wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT);
+
+        if !clear
+        {
+            clear_color = wgpu::LoadOp::Load;
+        }
+
+        // multisampled like the color pass (resolved into the mask texture)
+        let (mask_view, resolve_target) = self.outline.get_mask_views();
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor
+        {
+            label: Some("outline mask pass"),
+            color_attachments:
+            &[
+                Some(wgpu::RenderPassColorAttachment
+                {
+                    view: mask_view,
+                    resolve_target,
+                    ops: wgpu::Operations
+                    {
+                        load: clear_color,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            ],
+            depth_stencil_attachment: None,
+            timestamp_writes: wgpu.timestamp_writes("outline mask"),
             occlusion_query_set: None,
         });
 
@@ -1072,10 +1706,10 @@ impl Scene
 
         render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
 
-        self.draw_phase(&mut render_pass, &self.color_pipe.as_ref().unwrap(), nodes, light_cam_bind_group)
+        self.draw_phase(&mut render_pass, &self.outline_mask_pipe.as_ref().unwrap(), nodes, light_cam_bind_group, true, ColorPhase::All, cam_data.layer_mask)
     }
 
-    fn draw_phase<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, pipeline: &'a Pipeline, nodes: &'a Vec<RenderData>, light_cam_bind_group: &'a BindGroup) -> u32
+    fn draw_phase<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, pipeline: &'a Pipeline, nodes: &'a Vec<RenderData>, light_cam_bind_group: &'a BindGroup, highlighted_only: bool, phase: ColorPhase, layer_mask: u32) -> u32
     {
         let mut draw_calls: u32 = 0;
 
@@ -1085,7 +1719,8 @@ impl Scene
             let meshes = data.meshes;
             let mat = data.material;
 
-            if !node.visible
+            // hidden or not on a layer of the camera
+            if !node.visible || data.layer_mask & layer_mask == 0
             {
                 continue;
             }
@@ -1095,6 +1730,11 @@ impl Scene
                 continue;
             }
 
+            if (phase == ColorPhase::Opaque && data.has_transmission) || (phase == ColorPhase::Transmissive && !data.has_transmission)
+            {
+                continue;
+            }
+
             let material_render_item = mat.get_base().render_item.as_ref();
             let material_render_item = get_render_item::<MaterialBuffer>(material_render_item.as_ref().unwrap());
             let material_bind_group = material_render_item.bind_group.as_ref().unwrap();
@@ -1115,7 +1755,12 @@ impl Scene
                     let instance_render_item = node.instance_render_item.as_ref().unwrap();
                     let instance_buffer = get_render_item::<InstanceBuffer>(instance_render_item);
 
-                    pass.set_pipeline(&pipeline.get());
+                    if highlighted_only && !instance_buffer.has_highlight()
+                    {
+                        continue;
+                    }
+
+                    pass.set_pipeline(pipeline.get_for_material(data.backface_culling, data.depth_variant));
                     pass.set_bind_group(0, material_bind_group, &[]);
                     pass.set_bind_group(1, light_cam_bind_group, &[]);
 
@@ -1125,9 +1770,19 @@ impl Scene
                     pass.set_vertex_buffer(1, instance_buffer.get_buffer().slice(..));
 
                     pass.set_index_buffer(vertex_buffer.get_index_buffer().slice(..), wgpu::IndexFormat::Uint32);
-                    pass.draw_indexed(0..vertex_buffer.get_index_count(), 0, 0..instance_buffer.get_count() as _);
 
-                    draw_calls += 1;
+                    let instance_ranges = match data.instance_index
+                    {
+                        Some(index) if instance_buffer.layer_masks[index as usize] & layer_mask != 0 => vec![index..index + 1],
+                        Some(_) => vec![],
+                        None => instance_buffer.get_instance_ranges(layer_mask),
+                    };
+
+                    for instance_range in instance_ranges
+                    {
+                        pass.draw_indexed(0..vertex_buffer.get_index_count(), 0, instance_range);
+                        draw_calls += 1;
+                    }
                 }
             }
         }
@@ -1135,4 +1790,142 @@ impl Scene
         draw_calls
     }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::{RwLock, RwLockReadGuard};
+
+    use nalgebra::Point3;
+
+    use crate::{rendering::pipeline::DEFAULT_DEPTH_VARIANT, state::scene::{node::{Node, NodeItem}, components::{component::ComponentBox, material::Material}}};
+
+    use super::{RenderData, compare_render_data};
+
+    fn render_data<'a>(node: &'a RwLockReadGuard<'a, Box<Node>>, material: &'a RwLockReadGuard<'a, ComponentBox>, meshes: &'a Vec<RwLockReadGuard<'a, ComponentBox>>, lod_meshes: &'a Vec<Vec<RwLockReadGuard<'a, ComponentBox>>>, middle: Point3<f32>, instance_index: Option<u32>) -> RenderData<'a>
+    {
+        RenderData
+        {
+            node: node,
+            material: material,
+            meshes: meshes,
+            node_meshes: meshes,
+            lod_meshes: lod_meshes,
+
+            has_transparency: true,
+            has_transmission: false,
+            backface_culling: true,
+            depth_variant: DEFAULT_DEPTH_VARIANT,
+            layer_mask: node.get_layer_mask(),
+            alpha_index: node.alpha_index,
+            middle: middle,
+            instance_index: instance_index
+        }
+    }
+
+    // (node id, instance index) of each item in the sorted order
+    fn sorted_ids(mut items: Vec<RenderData>, cam_pos: Option<&Point3<f32>>) -> Vec<(u64, Option<u32>)>
+    {
+        items.sort_by(|a, b| compare_render_data(a, b, cam_pos));
+        items.iter().map(|item| (item.node.id, item.instance_index)).collect()
+    }
+
+    #[test]
+    fn equidistant_items_are_sorted_by_node_and_instance()
+    {
+        let node_a: NodeItem = Node::new(1, "a");
+        let node_b: NodeItem = Node::new(2, "b");
+        let material: RwLock<ComponentBox> = RwLock::new(Box::new(Material::new(0, "material")));
+
+        let node_a = node_a.read().unwrap();
+        let node_b = node_b.read().unwrap();
+        let material = material.read().unwrap();
+        let meshes = vec![];
+        let lod_meshes = vec![];
+
+        let cam_pos = Point3::<f32>::new(0.0, 0.0, 0.0);
+
+        // all items are on a circle around the camera
+        let items = vec!
+        [
+            render_data(&node_b, &material, &meshes, &lod_meshes, Point3::<f32>::new(0.0, 0.0, -5.0), Some(1)),
+            render_data(&node_a, &material, &meshes, &lod_meshes, Point3::<f32>::new(5.0, 0.0, 0.0), Some(1)),
+            render_data(&node_b, &material, &meshes, &lod_meshes, Point3::<f32>::new(-5.0, 0.0, 0.0), Some(0)),
+            render_data(&node_a, &material, &meshes, &lod_meshes, Point3::<f32>::new(0.0, 5.0, 0.0), Some(0)),
+        ];
+
+        assert_eq!(sorted_ids(items, Some(&cam_pos)), vec![(1, Some(0)), (1, Some(1)), (2, Some(0)), (2, Some(1))]);
+
+        // the distance is still used first (back to front)
+        let items = vec!
+        [
+            render_data(&node_a, &material, &meshes, &lod_meshes, Point3::<f32>::new(0.0, 0.0, -5.0), Some(0)),
+            render_data(&node_b, &material, &meshes, &lod_meshes, Point3::<f32>::new(0.0, 0.0, -10.0), Some(1)),
+            render_data(&node_b, &material, &meshes, &lod_meshes, Point3::<f32>::new(0.0, 0.0, -5.0), Some(0)),
+        ];
+
+        assert_eq!(sorted_ids(items, Some(&cam_pos)), vec![(2, Some(1)), (1, Some(0)), (2, Some(0))]);
+    }
+
+    #[test]
+    fn alpha_index_flips_overlapping_planes()
+    {
+        let near: NodeItem = Node::new(1, "near plane");
+        let far: NodeItem = Node::new(2, "far plane");
+        let opaque: NodeItem = Node::new(3, "opaque");
+        let material: RwLock<ComponentBox> = RwLock::new(Box::new(Material::new(0, "material")));
+
+        let cam_pos = Point3::<f32>::new(0.0, 0.0, 0.0);
+        let near_middle = Point3::<f32>::new(0.0, 0.0, -5.0);
+        let far_middle = Point3::<f32>::new(0.0, 0.0, -6.0);
+
+        // same alpha index: back to front
+        {
+            let near = near.read().unwrap();
+            let far = far.read().unwrap();
+            let material = material.read().unwrap();
+            let meshes = vec![];
+            let lod_meshes = vec![];
+
+            let items = vec![render_data(&near, &material, &meshes, &lod_meshes, near_middle, None), render_data(&far, &material, &meshes, &lod_meshes, far_middle, None)];
+            assert_eq!(sorted_ids(items, Some(&cam_pos)), vec![(2, None), (1, None)]);
+
+            // without distance sorting the scene order is kept
+            let items = vec![render_data(&near, &material, &meshes, &lod_meshes, near_middle, None), render_data(&far, &material, &meshes, &lod_meshes, far_middle, None)];
+            assert_eq!(sorted_ids(items, None), vec![(1, None), (2, None)]);
+        }
+
+        // a higher alpha index on the far plane forces it to be drawn last (with and without distance sorting)
+        far.write().unwrap().alpha_index = 1;
+
+        {
+            let near = near.read().unwrap();
+            let far = far.read().unwrap();
+            let material = material.read().unwrap();
+            let meshes = vec![];
+            let lod_meshes = vec![];
+
+            let items = vec![render_data(&far, &material, &meshes, &lod_meshes, far_middle, None), render_data(&near, &material, &meshes, &lod_meshes, near_middle, None)];
+            assert_eq!(sorted_ids(items, Some(&cam_pos)), vec![(1, None), (2, None)]);
+
+            let items = vec![render_data(&far, &material, &meshes, &lod_meshes, far_middle, None), render_data(&near, &material, &meshes, &lod_meshes, near_middle, None)];
+            assert_eq!(sorted_ids(items, None), vec![(1, None), (2, None)]);
+        }
+
+        // opaque items are drawn before all transparent items
+        {
+            let near = near.read().unwrap();
+            let opaque = opaque.read().unwrap();
+            let material = material.read().unwrap();
+            let meshes = vec![];
+            let lod_meshes = vec![];
+
+            let mut opaque_item = render_data(&opaque, &material, &meshes, &lod_meshes, Point3::<f32>::new(0.0, 0.0, -1.0), None);
+            opaque_item.has_transparency = false;
+
+            let items = vec![render_data(&near, &material, &meshes, &lod_meshes, near_middle, None), opaque_item];
+            assert_eq!(sorted_ids(items, Some(&cam_pos)), vec![(3, None), (1, None)]);
+        }
+    }
+}
@@ -1,19 +1,45 @@
+use std::borrow::Cow;
+
 use image::{DynamicImage, ImageBuffer, Rgba};
 use wgpu::{BindGroupEntry, BindGroupLayoutEntry, Device, Sampler};
 
-use crate::{state::helper::render_item::RenderItem, render_item_impl_default};
+use crate::{state::{helper::render_item::RenderItem, scene::components::material::TextureColorSpace}, render_item_impl_default, helper::math::f32_to_f16};
 
 use super::{wgpu::WGpu, helper::buffer::{BufferDimensions, remove_padding}};
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TextureFormat
 {
     Srgba,
     Rgba,
     Gray,
+    Hdr,
     Depth
 }
 
+impl TextureFormat
+{
+    // format of a scene texture - single channel and hdr textures are always linear
+    pub fn from_texture(channels: u32, hdr: bool, color_space: TextureColorSpace) -> TextureFormat
+    {
+        if channels == 1
+        {
+            return TextureFormat::Gray;
+        }
+
+        if hdr
+        {
+            return TextureFormat::Hdr;
+        }
+
+        match color_space
+        {
+            TextureColorSpace::Srgb => TextureFormat::Srgba,
+            TextureColorSpace::Linear => TextureFormat::Rgba,
+        }
+    }
+}
+
 pub struct Texture
 {
     pub name: String,
@@ -23,6 +49,7 @@ pub struct Texture
 
     format: TextureFormat,
     is_depth_texture: bool,
+    is_cube: bool,
 
     texture: wgpu::Texture,
     view: wgpu::TextureView,
@@ -40,8 +67,9 @@ impl Texture
     pub const SRGBA_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
     pub const RGBA_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
     pub const GRAY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+    pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float; // Rgba32Float is not filterable
 
-    pub fn new_from_texture(wgpu: &mut WGpu, name: &str, scene_texture: &crate::state::scene::texture::Texture, format: TextureFormat) -> Texture
+    pub fn new_from_texture(wgpu: &mut WGpu, name: &str, scene_texture: &crate::state::scene::texture::Texture, format: TextureFormat, anisotropy: u8) -> Texture
     {
         let device = wgpu.device();
         let queue = wgpu.queue_mut();
@@ -58,14 +86,19 @@ impl Texture
             TextureFormat::Srgba => wgpu_format = Self::SRGBA_FORMAT,
             TextureFormat::Rgba => wgpu_format = Self::RGBA_FORMAT,
             TextureFormat::Gray => wgpu_format = Self::GRAY_FORMAT,
+            TextureFormat::Hdr => wgpu_format = Self::HDR_FORMAT,
             TextureFormat::Depth => wgpu_format = Self::DEPTH_FORMAT,
         }
 
+        // cubemaps: the faces are stacked vertically -> one layer per face
+        let is_cube = scene_texture.is_cubemap();
+        let layers = if is_cube { 6 } else { 1 };
+
         let texture_size = wgpu::Extent3d
         {
             width: scene_texture.width(),
-            height: scene_texture.height(),
-            depth_or_array_layers: 1,
+            height: scene_texture.height() / layers,
+            depth_or_array_layers: layers,
         };
 
         let texture_name = format!("{} Texture", name);
@@ -86,7 +119,7 @@ impl Texture
             }
         );
 
-        let channels = scene_texture.channels();
+        let bytes_per_pixel = scene_texture.gpu_bytes_per_pixel();
 
         // upload texture
         queue.write_texture
@@ -98,12 +131,12 @@ impl Texture
                 origin: wgpu::Origin3d::ZERO,
                 aspect: wgpu::TextureAspect::All,
             },
-            scene_texture.raw_data(),
+            &Self::upload_data(scene_texture.get_dynamic_image()),
             wgpu::ImageDataLayout
             {
                 offset: 0,
-                bytes_per_row: Some(scene_texture.width() * channels),
-                rows_per_image: Some(scene_texture.height()),
+                bytes_per_row: Some(scene_texture.width() * bytes_per_pixel),
+                rows_per_image: Some(texture_size.height),
             },
             texture_size,
         );
@@ -114,8 +147,8 @@ impl Texture
             let texture_size = wgpu::Extent3d
             {
                 width: mipmap.width(),
-                height: mipmap.height(),
-                depth_or_array_layers: 1,
+                height: mipmap.height() / layers,
+                depth_or_array_layers: layers,
             };
 
             queue.write_texture
@@ -127,29 +160,41 @@ impl Texture
                     origin: wgpu::Origin3d::ZERO,
                     aspect: wgpu::TextureAspect::All,
                 },
-                mipmap.as_bytes(),
+                &Self::upload_data(mipmap),
                 wgpu::ImageDataLayout
                 {
                     offset: 0,
-                    bytes_per_row: Some(mipmap.width() * channels),
-                    rows_per_image: Some(mipmap.height()),
+                    bytes_per_row: Some(mipmap.width() * bytes_per_pixel),
+                    rows_per_image: Some(texture_size.height),
                 },
                 texture_size,
             );
         }
 
-        let sampler = Self::create_sampler(device, scene_texture);
-        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = Self::create_sampler(device, scene_texture, anisotropy);
+
+        let mut view_dimension = None;
+        if is_cube
+        {
+            view_dimension = Some(wgpu::TextureViewDimension::Cube);
+        }
+
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor
+        {
+            dimension: view_dimension,
+            ..Default::default()
+        });
 
         Self
         {
             name: name.to_string(),
 
-            width: scene_texture.width(),
-            height: scene_texture.height(),
+            width: texture_size.width,
+            height: texture_size.height,
 
             format: format,
             is_depth_texture: false,
+            is_cube: is_cube,
 
             texture: texture,
             view: texture_view,
@@ -157,7 +202,31 @@ impl Texture
         }
     }
 
+    fn upload_data(image: &DynamicImage) -> Cow<'_, [u8]>
+    {
+        match image
+        {
+            // float images are converted to half floats
+            DynamicImage::ImageRgba32F(hdr) =>
+            {
+                Cow::Owned(hdr.as_raw().iter().flat_map(|value| f32_to_f16(*value).to_le_bytes()).collect())
+            },
+            _ => Cow::Borrowed(image.as_bytes())
+        }
+    }
+
     pub fn new_empty_texture(wgpu: &mut WGpu, name: &str, format: TextureFormat) -> Texture
+    {
+        Self::new_empty(wgpu, name, format, false)
+    }
+
+    // placeholder for unused cubemap bindings
+    pub fn new_empty_cube_texture(wgpu: &mut WGpu, name: &str, format: TextureFormat) -> Texture
+    {
+        Self::new_empty(wgpu, name, format, true)
+    }
+
+    fn new_empty(wgpu: &mut WGpu, name: &str, format: TextureFormat, is_cube: bool) -> Texture
     {
         let device = wgpu.device();
 
@@ -170,6 +239,7 @@ impl Texture
             TextureFormat::Srgba => wgpu_format = Self::SRGBA_FORMAT,
             TextureFormat::Rgba => wgpu_format = Self::RGBA_FORMAT,
             TextureFormat::Gray => wgpu_format = Self::GRAY_FORMAT,
+            TextureFormat::Hdr => wgpu_format = Self::HDR_FORMAT,
             TextureFormat::Depth => wgpu_format = Self::DEPTH_FORMAT,
         }
 
@@ -177,7 +247,7 @@ impl Texture
         {
             width: width,
             height: height,
-            depth_or_array_layers: 1,
+            depth_or_array_layers: if is_cube { 6 } else { 1 },
         };
 
         let texture_name = format!("{} Empty Texture", name);
@@ -198,7 +268,17 @@ impl Texture
             }
         );
 
-        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut view_dimension = None;
+        if is_cube
+        {
+            view_dimension = Some(wgpu::TextureViewDimension::Cube);
+        }
+
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor
+        {
+            dimension: view_dimension,
+            ..Default::default()
+        });
 
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor
         {
@@ -220,6 +300,7 @@ impl Texture
 
             format: format,
             is_depth_texture: false,
+            is_cube: is_cube,
 
             texture: texture,
             view: texture_view,
@@ -279,6 +360,7 @@ impl Texture
 
             format: TextureFormat::Depth,
             is_depth_texture: true,
+            is_cube: false,
 
             texture,
             view,
@@ -287,7 +369,7 @@ impl Texture
 
     }
 
-    pub fn create_sampler(device: &Device, scene_texture: &crate::state::scene::texture::Texture) -> Sampler
+    pub fn create_sampler(device: &Device, scene_texture: &crate::state::scene::texture::Texture, anisotropy: u8) -> Sampler
     {
         let tex_data = scene_texture.get_data();
 
@@ -339,6 +421,13 @@ impl Texture
             crate::state::scene::texture::TextureFilterMode::Linear => mipmap_filter = wgpu::FilterMode::Linear,
         }
 
+        // anisotropic filtering requires linear filtering for all filters
+        let mut anisotropy_clamp = 1;
+        if mag_filter == wgpu::FilterMode::Linear && min_filter == wgpu::FilterMode::Linear && mipmap_filter == wgpu::FilterMode::Linear
+        {
+            anisotropy_clamp = anisotropy.clamp(1, 16) as u16;
+        }
+
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor
         {
             address_mode_u: address_mode_u,
@@ -347,6 +436,7 @@ impl Texture
             mag_filter: mag_filter,
             min_filter: min_filter,
             mipmap_filter: mipmap_filter,
+            anisotropy_clamp: anisotropy_clamp,
             ..Default::default()
         });
 
@@ -390,10 +480,15 @@ impl Texture
             texture_size,
         );
 
-        self.sampler = Self::create_sampler(device, scene_texture);
+        self.sampler = Self::create_sampler(device, scene_texture, 1);
     }
      */
 
+    pub fn get_format(&self) -> TextureFormat
+    {
+        self.format
+    }
+
     pub fn get_texture(&self) -> &wgpu::Texture
     {
         &self.texture
@@ -418,6 +513,12 @@ impl Texture
             sample_type = wgpu::TextureSampleType::Depth
         }
 
+        let mut view_dimension = wgpu::TextureViewDimension::D2;
+        if self.is_cube
+        {
+            view_dimension = wgpu::TextureViewDimension::Cube;
+        }
+
         let mut binding_type = wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering);
         if self.is_depth_texture
         {
@@ -433,7 +534,7 @@ impl Texture
                 ty: wgpu::BindingType::Texture
                 {
                     multisampled: false,
-                    view_dimension: wgpu::TextureViewDimension::D2,
+                    view_dimension: view_dimension,
                     sample_type: sample_type,
                 },
                 count: None,
@@ -530,3 +631,35 @@ impl Texture
 
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use crate::state::scene::components::material::{TextureType, ALL_TEXTURE_TYPES};
+
+    use super::TextureFormat;
+
+    #[test]
+    fn texture_format_per_slot()
+    {
+        let srgb_slots = [TextureType::Base, TextureType::AmbientEmissive, TextureType::Specular, TextureType::Environment];
+
+        for texture_type in ALL_TEXTURE_TYPES
+        {
+            let format = TextureFormat::from_texture(4, false, texture_type.color_space());
+
+            if srgb_slots.contains(&texture_type)
+            {
+                assert_eq!(format, TextureFormat::Srgba, "{:?}", texture_type);
+            }
+            else
+            {
+                assert_eq!(format, TextureFormat::Rgba, "{:?}", texture_type);
+            }
+
+            // single channel and hdr textures are linear in every slot
+            assert_eq!(TextureFormat::from_texture(1, false, texture_type.color_space()), TextureFormat::Gray);
+            assert_eq!(TextureFormat::from_texture(4, true, texture_type.color_space()), TextureFormat::Hdr);
+        }
+    }
+}
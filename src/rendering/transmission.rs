@@ -0,0 +1,280 @@
+use wgpu::{BindGroupLayout, BindGroup, ShaderModule, CommandEncoder, TextureView};
+
+use crate::{state::scene::camera::CameraData, resources::resources};
+
+use super::{wgpu::WGpu, pipeline::Pipeline};
+
+// transmission (KHR_materials_transmission)
+// the opaque scene is rendered into a background texture first - transmissive materials are sampling it (refracted and blurred) in a second color pass
+pub struct Transmission
+{
+    shader: ShaderModule,
+    bind_group_layout: BindGroupLayout,
+    bind_group: Option<BindGroup>,
+    empty_bind_group: Option<BindGroup>,
+
+    background_view: TextureView,
+    empty_view: TextureView,
+    sampler: wgpu::Sampler,
+
+    pipeline: Option<wgpu::RenderPipeline>,
+}
+
+impl Transmission
+{
+    pub fn new(wgpu: &mut WGpu) -> Transmission
+    {
+        let shader_source = resources::load_string("shader/transmission.wgsl").unwrap();
+        let shader = Pipeline::create_shader(wgpu.device(), "transmission", &shader_source);
+
+        let bind_group_layout = wgpu.device().create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor
+        {
+            entries:
+            &[
+                wgpu::BindGroupLayoutEntry
+                {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture
+                    {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry
+                {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("transmission_bind_group_layout"),
+        });
+
+        let background_view = Self::create_texture_view(wgpu, "transmission background texture", None);
+
+        // the background can not be bound while it is rendered -> this one is used for all other color passes
+        let empty_view = Self::create_texture_view(wgpu, "transmission empty texture", Some(1));
+
+        let sampler = wgpu.device().create_sampler(&wgpu::SamplerDescriptor
+        {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let mut transmission = Transmission
+        {
+            shader,
+            bind_group_layout,
+            bind_group: None,
+            empty_bind_group: None,
+
+            background_view,
+            empty_view,
+            sampler,
+
+            pipeline: None,
+        };
+
+        transmission.create_bind_groups(wgpu);
+
+        transmission
+    }
+
+    fn create_texture_view(wgpu: &mut WGpu, label: &str, size: Option<u32>) -> TextureView
+    {
+        let config = wgpu.surface_config();
+
+        let (width, height) = match size
+        {
+            Some(size) => (size, size),
+            None => (config.width, config.height),
+        };
+
+        // same format as the surface - so the background can be rendered with the default pipeline setup
+        let texture = wgpu.device().create_texture(&wgpu::TextureDescriptor
+        {
+            label: Some(label),
+            size: wgpu::Extent3d
+            {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    fn create_bind_group(&self, wgpu: &mut WGpu, view: &TextureView, label: &str) -> BindGroup
+    {
+        wgpu.device().create_bind_group(&wgpu::BindGroupDescriptor
+        {
+            layout: &self.bind_group_layout,
+            entries:
+            &[
+                wgpu::BindGroupEntry
+                {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry
+                {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+            label: Some(label),
+        })
+    }
+
+    fn create_bind_groups(&mut self, wgpu: &mut WGpu)
+    {
+        self.bind_group = Some(self.create_bind_group(wgpu, &self.background_view, "transmission_bind_group"));
+        self.empty_bind_group = Some(self.create_bind_group(wgpu, &self.empty_view, "transmission_empty_bind_group"));
+    }
+
+    pub fn resize(&mut self, wgpu: &mut WGpu)
+    {
+        self.background_view = Self::create_texture_view(wgpu, "transmission background texture", None);
+
+        self.create_bind_groups(wgpu);
+    }
+
+    pub fn get_bind_group_layout(&self) -> &BindGroupLayout
+    {
+        &self.bind_group_layout
+    }
+
+    // the opaque scene is rendered into this view if there are transmissive materials
+    pub fn get_view(&self) -> &TextureView
+    {
+        &self.background_view
+    }
+
+    pub fn get_bind_group(&self, background: bool) -> &BindGroup
+    {
+        if background
+        {
+            self.bind_group.as_ref().unwrap()
+        }
+        else
+        {
+            self.empty_bind_group.as_ref().unwrap()
+        }
+    }
+
+    pub fn create_pipeline(&mut self, wgpu: &mut WGpu)
+    {
+        let device = wgpu.device();
+        let config = wgpu.surface_config();
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor
+        {
+            label: Some("transmission pipe Layout"),
+            bind_group_layouts: &[&self.bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor
+        {
+            label: Some("transmission pipe"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState
+            {
+                module: &self.shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState
+            {
+                module: &self.shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState
+                {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })]
+            }),
+            primitive: wgpu::PrimitiveState
+            {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState
+            {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        self.pipeline = Some(render_pipeline);
+    }
+
+    // copies the background (opaque scene) into the output view - the transmissive materials are rendered on top of it
+    pub fn render_background(&self, wgpu: &mut WGpu, view: &TextureView, encoder: &mut CommandEncoder, cam_data: &CameraData) -> u32
+    {
+        if self.pipeline.is_none() || self.bind_group.is_none()
+        {
+            return 0;
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor
+        {
+            label: Some("transmission background pass"),
+            color_attachments:
+            &[
+                Some(wgpu::RenderPassColorAttachment
+                {
+                    view: view,
+                    resolve_target: None,
+                    ops: wgpu::Operations
+                    {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            ],
+            depth_stencil_attachment: None,
+            timestamp_writes: wgpu.timestamp_writes("transmission background"),
+            occlusion_query_set: None,
+        });
+
+        let x = cam_data.viewport_x * cam_data.resolution_width as f32;
+        let y = cam_data.viewport_y * cam_data.resolution_height as f32;
+
+        let width = cam_data.viewport_width * cam_data.resolution_width as f32;
+        let height = cam_data.viewport_height * cam_data.resolution_height as f32;
+
+        render_pass.set_viewport(x, y, width, height, 0.0, 1.0);
+
+        render_pass.set_pipeline(self.pipeline.as_ref().unwrap());
+        render_pass.set_bind_group(0, self.bind_group.as_ref().unwrap(), &[]);
+        render_pass.draw(0..3, 0..1);
+
+        1
+    }
+}
@@ -1,8 +1,12 @@
+use std::collections::HashSet;
+
 use crate::{state::{scene::components::mesh::{Mesh, MeshData}, helper::render_item::RenderItem}, render_item_impl_default};
 
-use super::wgpu::WGpu;
+use super::{wgpu::WGpu, helper::buffer::{TrackedBuffer, BufferType, create_buffer_init}};
 use nalgebra::{Point2, Vector3, Vector2};
-use wgpu::util::DeviceExt;
+
+// debug lines are only created for (at most) this amount of vertices - the other ones are skipped
+const NORMAL_LINES_MAX_VERTICES: usize = 10000;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -32,13 +36,63 @@ impl Vertex
     }
 }
 
+// start (offset 0) and end point (offset 1) of a normal, tangent or bitangent debug line
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct NormalLineVertex
+{
+    position: [f32; 3],
+    direction: [f32; 3],
+    offset: f32,
+    kind: f32, // 0 = normal, 1 = tangent, 2 = bitangent
+}
+
+impl NormalLineVertex
+{
+    const ATTRIBS: [wgpu::VertexAttribute; 4] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32, 3 => Float32];
+
+    pub fn desc() -> wgpu::VertexBufferLayout<'static>
+    {
+        use std::mem;
+
+        wgpu::VertexBufferLayout
+        {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+// same tangent space as used for the shading
+fn tangent_space(n: &Vector3<f32>) -> (Vector3<f32>, Vector3<f32>)
+{
+    let mut tangent = n.cross(&Vector3::<f32>::new(0.0, 1.0, 0.0));
+
+    if tangent.magnitude()  <= 0.0001
+    {
+        tangent = n.cross(&Vector3::<f32>::new(0.0, 0.0, 1.0));
+    }
+
+    tangent = tangent.normalize();
+    let bitangent = n.cross(&tangent).normalize();
+
+    (tangent, bitangent)
+}
+
 pub struct VertexBuffer
 {
     pub name: String,
     vertex_count: u32,
     index_count: u32,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
+    edge_index_count: u32,
+    vertex_buffer: TrackedBuffer,
+    index_buffer: TrackedBuffer,
+    edge_index_buffer: TrackedBuffer, // unique triangle edges as line list (wireframe)
+
+    // debug lines (created on demand - see create_normal_lines)
+    normal_line_count: u32,
+    normal_line_buffer: Option<TrackedBuffer>,
 }
 
 impl RenderItem for VertexBuffer
@@ -50,8 +104,6 @@ impl VertexBuffer
 {
     pub fn new(wgpu: &mut WGpu, name: &str, mesh_data: &MeshData) -> VertexBuffer
     {
-        let device = wgpu.device();
-
         let mut vertices = vec![];
 
         for i in  0..mesh_data.vertices.len()
@@ -70,15 +122,7 @@ impl VertexBuffer
                 uv = mesh_data.uvs_1[i];
             }
 
-            let mut tangent = n.cross(&Vector3::<f32>::new(0.0, 1.0, 0.0));
-
-            if tangent.magnitude()  <= 0.0001
-            {
-                tangent = n.cross(&Vector3::<f32>::new(0.0, 0.0, 1.0));
-            }
-
-            tangent = tangent.normalize();
-            let bitangent = n.cross(&tangent).normalize();
+            let (tangent, bitangent) = tangent_space(&n);
 
             vertices.push(Vertex
             {
@@ -91,8 +135,10 @@ impl VertexBuffer
         }
 
         let vertex_buffer_name = format!("{} Vertex Buffer", name);
-        let vertex_buffer = device.create_buffer_init
+        let vertex_buffer = create_buffer_init
         (
+            wgpu,
+            BufferType::Vertex,
             &wgpu::util::BufferInitDescriptor
             {
                 label: Some(vertex_buffer_name.as_str()),
@@ -102,8 +148,10 @@ impl VertexBuffer
         );
 
         let index_buffer_name = format!("{} Index Buffer", name);
-        let index_buffer = device.create_buffer_init
+        let index_buffer = create_buffer_init
         (
+            wgpu,
+            BufferType::Index,
             &wgpu::util::BufferInitDescriptor
             {
                 label: Some(index_buffer_name.as_str()),
@@ -112,17 +160,127 @@ impl VertexBuffer
             }
         );
 
+        // edges shared by two triangles are only added once
+        let mut edges = HashSet::new();
+        let mut edge_indices: Vec<u32> = vec![];
+        for face in &mesh_data.indices
+        {
+            for (a, b) in [(face[0], face[1]), (face[1], face[2]), (face[2], face[0])]
+            {
+                if edges.insert((a.min(b), a.max(b)))
+                {
+                    edge_indices.push(a);
+                    edge_indices.push(b);
+                }
+            }
+        }
+
+        let edge_index_buffer_name = format!("{} Edge Index Buffer", name);
+        let edge_index_buffer = create_buffer_init
+        (
+            wgpu,
+            BufferType::Index,
+            &wgpu::util::BufferInitDescriptor
+            {
+                label: Some(edge_index_buffer_name.as_str()),
+                contents: bytemuck::cast_slice(edge_indices.as_slice()),
+                usage: wgpu::BufferUsages::INDEX,
+            }
+        );
+
         Self
         {
             name: name.to_string(),
             vertex_count: vertices.len() as u32,
             index_count: (mesh_data.indices.len() as u32) * 3,
+            edge_index_count: edge_indices.len() as u32,
 
             vertex_buffer: vertex_buffer,
             index_buffer: index_buffer,
+            edge_index_buffer,
+
+            normal_line_count: 0,
+            normal_line_buffer: None,
         }
     }
 
+    // lines for normals, tangents and bitangents (in this order - each block has get_normal_line_count vertices)
+    // dense meshes are strided to stay below NORMAL_LINES_MAX_VERTICES
+    pub fn create_normal_lines(wgpu: &mut WGpu, name: &str, mesh_data: &MeshData) -> (TrackedBuffer, u32)
+    {
+        let vertex_amount = mesh_data.vertices.len().min(mesh_data.normals.len());
+        let stride = vertex_amount.div_ceil(NORMAL_LINES_MAX_VERTICES).max(1);
+
+        let mut normals = vec![];
+        let mut tangents = vec![];
+        let mut bitangents = vec![];
+
+        for i in (0..vertex_amount).step_by(stride)
+        {
+            let v = mesh_data.vertices[i];
+            let n = mesh_data.normals[i];
+            let (tangent, bitangent) = tangent_space(&n);
+
+            for (lines, direction, kind) in [(&mut normals, n, 0.0), (&mut tangents, tangent, 1.0), (&mut bitangents, bitangent, 2.0)]
+            {
+                for offset in [0.0, 1.0]
+                {
+                    lines.push(NormalLineVertex
+                    {
+                        position: [v.x, v.y, v.z],
+                        direction: [direction.x, direction.y, direction.z],
+                        offset,
+                        kind,
+                    });
+                }
+            }
+        }
+
+        let count = normals.len() as u32;
+
+        let mut lines = normals;
+        lines.append(&mut tangents);
+        lines.append(&mut bitangents);
+
+        let buffer_name = format!("{} Normal Line Buffer", name);
+        let buffer = create_buffer_init
+        (
+            wgpu,
+            BufferType::Vertex,
+            &wgpu::util::BufferInitDescriptor
+            {
+                label: Some(buffer_name.as_str()),
+                contents: bytemuck::cast_slice(lines.as_slice()),
+                usage: wgpu::BufferUsages::VERTEX,
+            }
+        );
+
+        (buffer, count)
+    }
+
+    pub fn set_normal_lines(&mut self, normal_lines: (TrackedBuffer, u32))
+    {
+        let (buffer, count) = normal_lines;
+
+        self.normal_line_buffer = Some(buffer);
+        self.normal_line_count = count;
+    }
+
+    pub fn has_normal_lines(&self) -> bool
+    {
+        self.normal_line_buffer.is_some()
+    }
+
+    pub fn get_normal_line_buffer(&self) -> Option<&wgpu::Buffer>
+    {
+        self.normal_line_buffer.as_deref()
+    }
+
+    pub fn get_normal_line_count(&self) -> u32
+    {
+        self.normal_line_count
+    }
+
     pub fn get_vertex_buffer(&self) -> &wgpu::Buffer
     {
         &self.vertex_buffer
@@ -133,6 +291,16 @@ impl VertexBuffer
         &self.index_buffer
     }
 
+    pub fn get_edge_index_buffer(&self) -> &wgpu::Buffer
+    {
+        &self.edge_index_buffer
+    }
+
+    pub fn get_edge_index_count(&self) -> u32
+    {
+        self.edge_index_count
+    }
+
     pub fn get_vertex_count(&self) -> u32
     {
         self.vertex_count
@@ -1,21 +1,26 @@
+use std::sync::Arc;
+
 use image::{DynamicImage, ImageBuffer, Rgba};
-use wgpu::{Device, Queue, Surface, SurfaceCapabilities, SurfaceConfiguration, CommandEncoder, TextureView, SurfaceTexture, Buffer, Texture};
+use wgpu::{Device, Queue, Surface, SurfaceCapabilities, SurfaceConfiguration, CommandEncoder, TextureView, SurfaceTexture, Buffer, Texture, Adapter, Instance};
 
-use crate::{helper::{image::brga_to_rgba, platform::is_windows, concurrency::thread::sleep_millis}, state::state::State};
+use crate::{helper::{image::brga_to_rgba, platform::is_windows, concurrency::thread::sleep_millis, math::fit_size}, state::state::State};
 
-use super::helper::buffer::{BufferDimensions, remove_padding};
+use super::helper::{buffer::{BufferDimensions, remove_padding, BufferStats, BufferStatsItem}, gpu_timer::GpuTimer};
 
 pub struct WGpu
 {
     device: Device,
     queue: Queue,
-    surface: Surface,
+    surface: Option<Surface>,
 
     msaa_samples: u32,
     msaa_texture: Option<wgpu::Texture>,
 
     surface_config: SurfaceConfiguration,
-    pub surface_caps: SurfaceCapabilities,
+    pub surface_caps: Option<SurfaceCapabilities>,
+
+    gpu_timer: Option<GpuTimer>,
+    buffer_stats: BufferStatsItem,
 }
 
 impl WGpu
@@ -24,27 +29,132 @@ impl WGpu
     {
         let dimensions = window.inner_size();
 
-        let mut instance_desc = wgpu::InstanceDescriptor::default();
+        let instance = Self::create_instance();
+        let surface = unsafe { instance.create_surface(window) }.unwrap();
 
-        if is_windows()
+        let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions
         {
-            instance_desc.backends = wgpu::Backends::VULKAN;
-            //instance_desc.backends = wgpu::Backends::DX12;
+            compatible_surface: Some(&surface),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+        let (device, queue) = Self::create_device(&adapter, state).await;
+
+        let surface_caps = surface.get_capabilities(&adapter);
+
+        let mut present_mode = wgpu::PresentMode::Fifo;
+        if !state.rendering.v_sync.get_ref()
+        {
+            present_mode = wgpu::PresentMode::Immediate;
         }
 
-        let instance = wgpu::Instance::new(instance_desc);
-        let surface = unsafe { instance.create_surface(window) }.unwrap();
+        let surface_config = wgpu::SurfaceConfiguration
+        {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            width: dimensions.width,
+            height: dimensions.height,
+            present_mode: present_mode,
+            //alpha_mode: surface_caps.alpha_modes[0], //wgpu::CompositeAlphaMode::Auto
+            alpha_mode: surface_caps.alpha_modes[0], //wgpu::CompositeAlphaMode::Auto
+            format: surface_caps.formats[0],
+            view_formats: vec![],
+        };
+
+        surface.configure(&device, &surface_config);
+
+        Self::apply_adapter_info(&adapter, surface_config.format, state);
+
+        let msaa_samples = *state.rendering.msaa.get_ref();
+
+        let mut wgpu = Self
+        {
+            device,
+            surface: Some(surface),
+            msaa_samples,
+            msaa_texture: None,
+            queue,
+            surface_caps: Some(surface_caps),
+            surface_config,
+            gpu_timer: None,
+            buffer_stats: Arc::new(BufferStats::new()),
+        };
+
+        wgpu.create_gpu_timer();
+
+        wgpu.create_msaa_texture(1);
+
+        wgpu
+    }
+
+    // surfaceless setup: everything is rendered into textures (see start_screenshot_render)
+    pub async fn new_headless(width: u32, height: u32, state: &mut State) -> Self
+    {
+        let instance = Self::create_instance();
 
         let adapter = instance.request_adapter(&wgpu::RequestAdapterOptions
         {
-            compatible_surface: Some(&surface),
+            compatible_surface: None,
             ..Default::default()
         })
         .await
         .unwrap();
 
+        let (device, queue) = Self::create_device(&adapter, state).await;
+
+        // same format as used for screenshots
+        let surface_config = wgpu::SurfaceConfiguration
+        {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            width: width.max(1),
+            height: height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            view_formats: vec![],
+        };
+
+        Self::apply_adapter_info(&adapter, surface_config.format, state);
+
+        let msaa_samples = *state.rendering.msaa.get_ref();
+
+        let mut wgpu = Self
+        {
+            device,
+            surface: None,
+            msaa_samples,
+            msaa_texture: None,
+            queue,
+            surface_caps: None,
+            surface_config,
+            gpu_timer: None,
+            buffer_stats: Arc::new(BufferStats::new()),
+        };
+
+        wgpu.create_gpu_timer();
+
+        wgpu.create_msaa_texture(1);
+
+        wgpu
+    }
+
+    fn create_instance() -> Instance
+    {
+        let mut instance_desc = wgpu::InstanceDescriptor::default();
+
+        if is_windows()
+        {
+            instance_desc.backends = wgpu::Backends::VULKAN;
+            //instance_desc.backends = wgpu::Backends::DX12;
+        }
+
+        wgpu::Instance::new(instance_desc)
+    }
+
+    async fn create_device(adapter: &Adapter, state: &mut State) -> (Device, Queue)
+    {
         println!(" ********** info **********");
-        let adapter_info = adapter.get_info();
         dbg!(adapter.get_info());
 
         println!(" ********** features possible **********");
@@ -53,13 +163,21 @@ impl WGpu
         println!(" ********** limits possible **********");
         dbg!(adapter.limits());
 
+        let mut features = wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES; // for multisampling
+
+        // gpu timings (statistics)
+        if adapter.features().contains(wgpu::Features::TIMESTAMP_QUERY)
+        {
+            features |= wgpu::Features::TIMESTAMP_QUERY;
+        }
+
         let (device, queue) = adapter.request_device
         (
             &wgpu::DeviceDescriptor
             {
                 label: None,
                 //features: wgpu::Features::empty(),
-                features: wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES, // for multisampling
+                features,
                 // WebGL doesn't support all of wgpu's features, so if building for the web: disable some
                 limits: if cfg!(target_arch = "wasm32")
                 {
@@ -81,43 +199,33 @@ impl WGpu
         println!(" ********** limits used **********");
         dbg!(device.limits());
 
-        let surface_caps = surface.get_capabilities(&adapter);
-
-        let mut present_mode = wgpu::PresentMode::Fifo;
-        if !state.rendering.v_sync.get_ref()
-        {
-            present_mode = wgpu::PresentMode::Immediate;
-        }
+        // storage support
+        let supports_storage_resources = adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::VERTEX_STORAGE) && device.limits().max_storage_buffers_per_shader_stage > 0;
+        state.adapter.storage_buffer_array_support = supports_storage_resources;
 
-        let surface_config = wgpu::SurfaceConfiguration
-        {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            width: dimensions.width,
-            height: dimensions.height,
-            present_mode: present_mode,
-            //alpha_mode: surface_caps.alpha_modes[0], //wgpu::CompositeAlphaMode::Auto
-            alpha_mode: surface_caps.alpha_modes[0], //wgpu::CompositeAlphaMode::Auto
-            format: surface_caps.formats[0],
-            view_formats: vec![],
-        };
+        state.adapter.timestamp_query_support = device.features().contains(wgpu::Features::TIMESTAMP_QUERY);
 
-        surface.configure(&device, &surface_config);
+        (device, queue)
+    }
 
+    fn apply_adapter_info(adapter: &Adapter, format: wgpu::TextureFormat, state: &mut State)
+    {
         // msaa
-        let texture_features = adapter.get_texture_format_features(surface_caps.formats[0]);
+        let texture_features = adapter.get_texture_format_features(format);
 
         if texture_features.flags.sample_count_supported(2) { state.adapter.max_msaa_samples = 2; }
         if texture_features.flags.sample_count_supported(4) { state.adapter.max_msaa_samples = 4; }
         if texture_features.flags.sample_count_supported(8) { state.adapter.max_msaa_samples = 8; }
         if texture_features.flags.sample_count_supported(16) { state.adapter.max_msaa_samples = 16; }
 
-        let msaa_samples = *state.rendering.msaa.get_ref();
-
-        // storage support
-        let supports_storage_resources = adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::VERTEX_STORAGE) && device.limits().max_storage_buffers_per_shader_stage > 0;
-        state.adapter.storage_buffer_array_support = supports_storage_resources;
+        // anisotropic filtering
+        if adapter.get_downlevel_capabilities().flags.contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING)
+        {
+            state.adapter.max_anisotropy = 16;
+        }
 
         // apply adapter infos
+        let adapter_info = adapter.get_info();
         state.adapter.name = adapter_info.name.clone();
         state.adapter.driver = adapter_info.driver.clone();
         state.adapter.driver_info = adapter_info.driver_info.clone();
@@ -132,21 +240,36 @@ impl WGpu
             wgpu::Backend::Gl => state.adapter.backend = "Gl".to_string(),
             wgpu::Backend::BrowserWebGpu => state.adapter.backend = "BrowserWebGpu".to_string(),
         }
+    }
 
-        let mut wgpu = Self
+    fn create_gpu_timer(&mut self)
+    {
+        if self.device.features().contains(wgpu::Features::TIMESTAMP_QUERY)
         {
-            device,
-            surface,
-            msaa_samples,
-            msaa_texture: None,
-            queue,
-            surface_caps,
-            surface_config
-        };
+            self.gpu_timer = Some(GpuTimer::new(&self.device, &self.queue));
+        }
+    }
 
-        wgpu.create_msaa_texture(1);
+    // timestamp writes for a render pass (only while rendering a frame via start_render/end_render)
+    pub fn timestamp_writes(&mut self, label: &str) -> Option<wgpu::RenderPassTimestampWrites<'_>>
+    {
+        self.gpu_timer.as_mut()?.timestamp_writes(label)
+    }
 
-        wgpu
+    // gpu time in ms per pass of a previous frame
+    pub fn gpu_times(&self) -> Option<&Vec<(String, f32)>>
+    {
+        self.gpu_timer.as_ref().map(|timer| &timer.results)
+    }
+
+    pub fn buffer_stats(&self) -> BufferStatsItem
+    {
+        self.buffer_stats.clone()
+    }
+
+    pub fn is_headless(&self) -> bool
+    {
+        self.surface.is_none()
     }
 
     pub fn device(&self) -> &Device
@@ -199,8 +322,31 @@ impl WGpu
         self.surface_config.width = width;
         self.surface_config.height = height;
 
-        self.surface.configure(&self.device, &self.surface_config);
+        if let Some(surface) = &self.surface
+        {
+            surface.configure(&self.device, &self.surface_config);
+        }
+        self.create_msaa_texture(self.msaa_samples);
+    }
+
+    pub fn max_texture_size(&self) -> u32
+    {
+        self.device.limits().max_texture_dimension_2d
+    }
+
+    // changes the render size without reconfiguring the surface (used for offscreen rendering)
+    // too large sizes are scaled down uniformly - the used size is returned
+    // call resize afterwards to restore the surface size
+    pub fn set_offscreen_size(&mut self, width: u32, height: u32) -> (u32, u32)
+    {
+        let (width, height) = fit_size(width, height, self.max_texture_size());
+
+        self.surface_config.width = width;
+        self.surface_config.height = height;
+
         self.create_msaa_texture(self.msaa_samples);
+
+        (width, height)
     }
 
     pub fn set_vsync(&mut self, v_sync: bool)
@@ -213,7 +359,10 @@ impl WGpu
 
         self.surface_config.present_mode = present_mode;
 
-        self.surface.configure(&self.device, &self.surface_config);
+        if let Some(surface) = &self.surface
+        {
+            surface.configure(&self.device, &self.surface_config);
+        }
         self.create_msaa_texture(self.msaa_samples);
     }
 
@@ -223,10 +372,12 @@ impl WGpu
         // thread 'main' panicked at 'called `Result::unwrap()` on an `Err` value: Timeout', src\rendering\wgpu.rs:200:57
         //let output = self.surface.get_current_texture().unwrap();
 
+        let surface = self.surface.as_ref().expect("no surface available in headless mode");
+
         let mut output: Result<wgpu::SurfaceTexture, wgpu::SurfaceError>;
         loop
         {
-            output = self.surface.get_current_texture();
+            output = surface.get_current_texture();
 
             if output.is_ok()
             {
@@ -245,6 +396,11 @@ impl WGpu
 
         let encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
 
+        if let Some(gpu_timer) = &mut self.gpu_timer
+        {
+            gpu_timer.begin_frame(&self.device);
+        }
+
         let mut msaa_view = None;
         if self.msaa_texture.is_some()
         {
@@ -254,10 +410,20 @@ impl WGpu
         (output, view, msaa_view, encoder)
     }
 
-    pub fn end_render(&mut self, output: SurfaceTexture, encoder: CommandEncoder)
+    pub fn end_render(&mut self, output: SurfaceTexture, mut encoder: CommandEncoder)
     {
+        if let Some(gpu_timer) = &mut self.gpu_timer
+        {
+            gpu_timer.resolve(&mut encoder);
+        }
+
         self.queue.submit(std::iter::once(encoder.finish()));
         output.present();
+
+        if let Some(gpu_timer) = &mut self.gpu_timer
+        {
+            gpu_timer.map();
+        }
     }
 
     pub fn start_screenshot_render(&mut self) -> (BufferDimensions, Buffer, Texture, TextureView, Option<TextureView>, CommandEncoder)
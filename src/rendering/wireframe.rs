@@ -0,0 +1,188 @@
+use wgpu::{BindGroupLayout, BindGroup, ShaderModule, RenderPass};
+
+use crate::{state::state::WireframeSettings, resources::resources};
+
+use super::{wgpu::WGpu, pipeline::Pipeline, uniform, texture, vertex_buffer::{Vertex, VertexBuffer}, instance::{Instance, InstanceBuffer}, helper::buffer::{TrackedBuffer, BufferType, create_buffer_init, write_buffer}};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct WireframeUniform
+{
+    pub color: [f32; 4],
+}
+
+impl WireframeUniform
+{
+    pub fn new(settings: &WireframeSettings) -> Self
+    {
+        Self
+        {
+            color: [settings.color.x, settings.color.y, settings.color.z, settings.opacity],
+        }
+    }
+}
+
+// edges of the selected objects on top of the shaded surface (see the edge index buffer of VertexBuffer)
+pub struct Wireframe
+{
+    pub enabled: bool,
+    settings: WireframeSettings,
+
+    shader: ShaderModule,
+    buffer: TrackedBuffer,
+    bind_group_layout: BindGroupLayout,
+    bind_group: BindGroup,
+
+    pipeline: Option<wgpu::RenderPipeline>,
+}
+
+impl Wireframe
+{
+    pub fn new(wgpu: &mut WGpu, settings: &WireframeSettings) -> Wireframe
+    {
+        let shader_source = resources::load_string("shader/wireframe.wgsl").unwrap();
+        let shader = Pipeline::create_shader(wgpu.device(), "wireframe", &shader_source);
+
+        let buffer = create_buffer_init
+        (
+            wgpu,
+            BufferType::Uniform,
+            &wgpu::util::BufferInitDescriptor
+            {
+                label: Some("wireframe buffer"),
+                contents: bytemuck::cast_slice(&[WireframeUniform::new(settings)]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            }
+        );
+
+        let bind_group_layout = wgpu.device().create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor
+        {
+            entries:
+            &[
+                uniform::uniform_bind_group_layout_entry(0, true, true),
+            ],
+            label: Some("wireframe_bind_group_layout"),
+        });
+
+        let bind_group = wgpu.device().create_bind_group(&wgpu::BindGroupDescriptor
+        {
+            layout: &bind_group_layout,
+            entries:
+            &[
+                uniform::uniform_bind_group(0, &buffer),
+            ],
+            label: Some("wireframe_bind_group"),
+        });
+
+        Wireframe
+        {
+            enabled: settings.enabled,
+            settings: settings.clone(),
+
+            shader,
+            buffer,
+            bind_group_layout,
+            bind_group,
+
+            pipeline: None,
+        }
+    }
+
+    pub fn update(&mut self, wgpu: &mut WGpu, settings: &WireframeSettings)
+    {
+        self.enabled = settings.enabled;
+
+        if self.settings != *settings
+        {
+            write_buffer(wgpu, &self.buffer, 0, bytemuck::cast_slice(&[WireframeUniform::new(settings)]));
+            self.settings = settings.clone();
+        }
+    }
+
+    pub fn create_pipeline(&mut self, wgpu: &mut WGpu, light_cam_scene_bind_layout: &BindGroupLayout, samples: u32)
+    {
+        let device = wgpu.device();
+        let config = wgpu.surface_config();
+
+        let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor
+        {
+            label: Some("wireframe pipe Layout"),
+            bind_group_layouts: &[&self.bind_group_layout, light_cam_scene_bind_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor
+        {
+            label: Some("wireframe pipe"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState
+            {
+                module: &self.shader,
+                entry_point: "vs_main",
+                buffers:
+                &[
+                    Vertex::desc(),
+                    Instance::desc()
+                ],
+            },
+            fragment: Some(wgpu::FragmentState
+            {
+                module: &self.shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState
+                {
+                    format: config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })]
+            }),
+            primitive: wgpu::PrimitiveState
+            {
+                topology: wgpu::PrimitiveTopology::LineList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState
+            {
+                format: texture::Texture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState
+            {
+                count: samples,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        self.pipeline = Some(render_pipeline);
+    }
+
+    pub fn draw<'a>(&'a self, pass: &mut RenderPass<'a>, vertex_buffer: &'a VertexBuffer, instance_buffer: &'a InstanceBuffer, light_cam_bind_group: &'a BindGroup) -> u32
+    {
+        if !self.enabled || self.pipeline.is_none() || vertex_buffer.get_edge_index_count() == 0
+        {
+            return 0;
+        }
+
+        pass.set_pipeline(self.pipeline.as_ref().unwrap());
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_bind_group(1, light_cam_bind_group, &[]);
+
+        pass.set_vertex_buffer(0, vertex_buffer.get_vertex_buffer().slice(..));
+        pass.set_vertex_buffer(1, instance_buffer.get_buffer().slice(..));
+
+        pass.set_index_buffer(vertex_buffer.get_edge_index_buffer().slice(..), wgpu::IndexFormat::Uint32);
+        pass.draw_indexed(0..vertex_buffer.get_edge_index_count(), 0, 0..instance_buffer.get_count() as _);
+
+        1
+    }
+}
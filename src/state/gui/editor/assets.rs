@@ -55,6 +55,8 @@ pub fn create_asset_list(editor_state: &mut EditorState, state: &mut State, ui:
 
                 ui.checkbox(&mut editor_state.reuse_materials_by_name, "Reuse Materials by name");
             }
+
+            ui.checkbox(&mut editor_state.orient_z_up, "Z-up to Y-up").on_hover_text("rotates Z-up models upright (-90° around X on the root node)\nthe up axis of the file or the bounding box is used for the detection");
         });
 
         ScrollArea::vertical().show(ui, |ui|
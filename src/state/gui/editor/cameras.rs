@@ -1,6 +1,6 @@
 use egui::{Ui, RichText, Color32};
 
-use crate::state::{scene::camera::CameraItem, state::State, gui::helper::generic_items::{collapse_with_title, self}};
+use crate::{state::{scene::camera::CameraItem, state::State, gui::helper::generic_items::{collapse_with_title, self}}, input::mouse::MOUSE_MAX_SMOOTHING};
 
 use super::editor_state::{EditorState, SelectionType, SettingsPanel};
 
@@ -56,6 +56,8 @@ pub fn create_camera_settings(editor_state: &mut EditorState, state: &mut State,
     if camera_id.is_none() { return; }
     let camera_id = camera_id.unwrap();
 
+    let mut fit_clipping = false;
+
     if let Some(camera) = scene.get_camera_by_id_mut(camera_id)
     {
         collapse_with_title(ui, "camera_general_settings", true, "⛭ General Settings", |ui|
@@ -112,11 +114,22 @@ pub fn create_camera_settings(editor_state: &mut EditorState, state: &mut State,
             });
 
             ui.checkbox(&mut camera.enabled, "enabled");
+
+            let mut layer_mask = camera.get_data().layer_mask;
+            if generic_items::layer_mask(ui, &mut layer_mask)
+            {
+                camera.get_data_mut().get_mut().layer_mask = layer_mask;
+            }
         });
 
         collapse_with_title(ui, "camera_settings", true, "📷 Camera Settings", |ui|
         {
             camera.ui(ui);
+
+            if ui.button("Auto-fit clipping").on_hover_text("near/far clipping based on the bounding box of the scene").clicked()
+            {
+                fit_clipping = true;
+            }
         });
 
         if let Some(controller) = &mut camera.controller
@@ -157,6 +170,12 @@ pub fn create_camera_settings(editor_state: &mut EditorState, state: &mut State,
             },
             |ui|
             {
+                ui.horizontal(|ui|
+                {
+                    ui.label("Mouse Smoothing: ");
+                    ui.add(egui::Slider::new(&mut controller.get_base_mut().mouse_smoothing, 0.0..=MOUSE_MAX_SMOOTHING));
+                });
+
                 controller.ui(ui);
             });
 
@@ -169,6 +188,18 @@ pub fn create_camera_settings(editor_state: &mut EditorState, state: &mut State,
         }
     }
 
+    // auto-fit clipping (the scene bounding box is needed -> not while the camera is borrowed)
+    if fit_clipping
+    {
+        if let Some((min, max)) = scene.get_bounding_info()
+        {
+            if let Some(camera) = scene.get_camera_by_id_mut(camera_id)
+            {
+                camera.fit_clipping(min, max);
+            }
+        }
+    }
+
     // delete camera
     ui.with_layout(egui::Layout::top_down_justified(egui::Align::Center), |ui|
     {
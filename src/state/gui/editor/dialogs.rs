@@ -2,11 +2,33 @@ use std::sync::{Arc, RwLock};
 
 use rfd::FileDialog;
 
-use crate::{helper::concurrency::execution_queue::ExecutionQueue, state::scene::{components::material::TextureType, utilities::scene_utils::load_texture}};
+use crate::{helper::concurrency::execution_queue::ExecutionQueue, state::scene::{components::material::TextureType, utilities::scene_utils::{load_texture, load_environment_cubemap}}};
+
+pub fn save_screenshot_dialog(file_name: &str) -> Option<String>
+{
+    let path = FileDialog::new()
+        .add_filter("PNG", &["png"])
+        .add_filter("JPEG", &["jpg", "jpeg"])
+        .set_directory("/")
+        .set_file_name(file_name)
+        .save_file()?;
+
+    Some(path.display().to_string())
+}
+
+pub fn open_asset_dialog() -> Option<String>
+{
+    let path = FileDialog::new()
+        .add_filter("Scene/Object", &["gltf", "glb", "obj"])
+        .set_directory("/")
+        .pick_file()?;
+
+    Some(path.display().to_string())
+}
 
 pub fn load_texture_dialog(main_queue: Arc<RwLock<ExecutionQueue>>, texture_type: TextureType, scene_id: u64, material_id: Option<u64>, mipmapping: bool)
 {
-    if let Some(path) = FileDialog::new().add_filter("Image", &["jpg", "png"]).set_directory("/").pick_file()
+    if let Some(path) = FileDialog::new().add_filter("Image", &["jpg", "png", "hdr", "exr"]).set_directory("/").pick_file()
     {
         let name: Option<&std::ffi::OsStr> = path.file_stem().clone();
         let extension = path.extension().clone();
@@ -24,4 +46,14 @@ pub fn load_texture_dialog(main_queue: Arc<RwLock<ExecutionQueue>>, texture_type
         let path = &path.display().to_string();
         load_texture(path.as_str(), main_queue, texture_type, scene_id, material_id, mipmapping);
     }
-}
\ No newline at end of file
+}
+
+// six images (one per face) - single images with a cross or strip layout can be loaded with load_texture_dialog
+pub fn load_cubemap_dialog(main_queue: Arc<RwLock<ExecutionQueue>>, scene_id: u64, mipmapping: bool)
+{
+    if let Some(paths) = FileDialog::new().add_filter("Image", &["jpg", "png", "hdr", "exr"]).set_directory("/").pick_files()
+    {
+        let paths: Vec<String> = paths.iter().map(|path| path.display().to_string()).collect();
+        load_environment_cubemap(&paths, main_queue, scene_id, mipmapping);
+    }
+}
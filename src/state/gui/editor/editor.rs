@@ -4,13 +4,15 @@ use egui::FullOutput;
 
 use nalgebra::{Vector3, Matrix4, Point2, Point3, Vector2};
 
-use crate::{state::{state::State, scene::{components::{transformation::Transformation, component::ComponentItem, transformation_animation::TransformationAnimation, alpha::Alpha}, node::{NodeItem, Node}, utilities::scene_utils::{load_object, execute_on_scene_mut_and_wait, self}, light::Light, camera::Camera, camera_controller::target_rotation_controller::TargetRotationController, scene::Scene}}, rendering::egui::EGui, input::{mouse::MouseButton, keyboard::{Key, Modifier}}, component_downcast_mut, helper::{concurrency::thread::spawn_thread, change_tracker::ChangeTracker, platform, math::{approx_equal, approx_equal_vec}}};
+use crate::{state::{state::State, scene::{components::{transformation::{Transformation, apply_lock}, mesh::Mesh, material::{Material, MaterialItem}, component::ComponentItem, transformation_animation::TransformationAnimation, alpha::Alpha}, node::{NodeItem, Node}, events::SceneEvent, utilities::scene_utils::{load_object_with_scenes, execute_on_scene_mut_and_wait, is_z_up}, loader::gltf, light::Light, camera::Camera, camera_controller::target_rotation_controller::TargetRotationController, scene::Scene}}, rendering::egui::EGui, new_component, input::{mouse::MouseButton, keyboard::{Key, Modifier}}, component_downcast_mut, helper::{concurrency::thread::spawn_thread, change_tracker::ChangeTracker, platform, file::get_extension, math::{approx_equal, approx_equal_vec, snap_to_grid}}};
 
-use super::{editor_state::{EditorState, SelectionType, SettingsPanel, EditMode, AssetType}, main_frame};
+use super::{editor_state::{EditorState, SelectionType, SettingsPanel, EditMode, AssetType, KeyAction, EditorCommand, SceneChoice}, main_frame};
 
 const OBJECTS_DIR: &str = "objects/";
 const SCENES_DIR: &str = "scenes/";
 
+const BOUNDING_SPHERE_NODE: &str = "bounding sphere";
+
 pub struct Editor
 {
     pub editor_state: EditorState,
@@ -30,36 +32,70 @@ impl Editor
     {
         self.editor_state.load_asset_entries(SCENES_DIR, state, AssetType::Scene, egui);
         self.editor_state.load_asset_entries(OBJECTS_DIR, state, AssetType::Object, egui);
+
+        self.editor_state.keymap.load();
+        self.editor_state.load_recent_files();
     }
 
     pub fn update(&mut self, state: &mut State)
     {
+        // rebind shortcut (the pressed key is consumed)
+        self.editor_state.keymap.capture(&mut state.input_manager.keyboard);
+
         // start try out mde
-        if !self.editor_state.try_out && (state.input_manager.keyboard.is_holding_modifier(Modifier::Ctrl) || state.input_manager.keyboard.is_holding_modifier(Modifier::Logo)) && state.input_manager.keyboard.is_pressed(Key::R)
+        if !self.editor_state.try_out && self.editor_state.keymap.is_pressed(KeyAction::TryOut, &mut state.input_manager.keyboard)
         {
             self.editor_state.set_try_out(state, true);
         }
 
         // end try out mode
-        if self.editor_state.try_out && state.input_manager.keyboard.is_pressed(Key::Escape)
+        if self.editor_state.try_out && self.editor_state.keymap.is_pressed(KeyAction::ExitTryOut, &mut state.input_manager.keyboard)
         {
             self.editor_state.set_try_out(state, false);
         }
 
         // hide ui
-        if state.input_manager.keyboard.is_pressed(Key::H)
+        if self.editor_state.keymap.is_pressed(KeyAction::ToggleUi, &mut state.input_manager.keyboard)
         {
             self.editor_state.visible = !self.editor_state.visible;
         }
 
         // full screen
-        if state.input_manager.keyboard.is_pressed(Key::F)
+        if self.editor_state.keymap.is_pressed(KeyAction::Fullscreen, &mut state.input_manager.keyboard)
         {
             state.rendering.fullscreen.set(!*state.rendering.fullscreen.get_ref());
         }
 
+        // command palette
+        if self.editor_state.keymap.is_pressed(KeyAction::CommandPalette, &mut state.input_manager.keyboard)
+        {
+            self.editor_state.dialog_command_palette = !self.editor_state.dialog_command_palette;
+            self.editor_state.command_filter.clear();
+        }
+
+        if let Some(command) = self.editor_state.command.take()
+        {
+            self.execute_command(state, command);
+        }
+
+        // load from the file menu (placed in the center of the screen)
+        if let Some((asset_type, path)) = self.editor_state.load_request.take()
+        {
+            let center = Point2::<f32>::new(state.width as f32 / 2.0, state.height as f32 / 2.0);
+            self.load_asset(state, path, asset_type, center);
+        }
+
+        // import the scenes chosen by the user
+        if self.editor_state.scene_choice.as_ref().map_or(false, |choice| choice.confirmed)
+        {
+            let choice = self.editor_state.scene_choice.take().unwrap();
+            let scenes = choice.scenes.iter().filter(|(_, _, selected)| *selected).map(|(index, _, _)| *index).collect();
+
+            self.load_asset_with_scenes(state, choice.path, choice.asset_type, choice.pos, Some(scenes));
+        }
+
         // escape
-        if state.input_manager.keyboard.is_pressed(Key::Escape)
+        if self.editor_state.keymap.is_pressed(KeyAction::Cancel, &mut state.input_manager.keyboard)
         {
             if self.editor_state.edit_mode.is_some()
             {
@@ -71,8 +107,14 @@ impl Editor
             }
         }
 
-        // update grid based on camera pos
-        self.update_grid(state);
+        // remove highlights of the previous selection
+        self.editor_state.check_selection_change(state);
+
+        // update bounding sphere of the selected object
+        self.update_bounding_sphere(state);
+
+        // measure distances
+        self.measure(state);
 
         // select/pick objects
         self.select_object(state);
@@ -83,70 +125,262 @@ impl Editor
         // edit mode
         self.move_object(state);
 
+        // depth of field focus
+        self.pick_focus(state);
     }
 
-    pub fn update_grid(&self, state: &mut State)
+    pub fn execute_command(&mut self, state: &mut State, command: EditorCommand)
     {
+        if let Some(primitive) = command.primitive()
+        {
+            // selected scene or the first one
+            let scene_id = self.editor_state.selected_scene_id.or(state.scenes.first().map(|scene| scene.id));
+
+            if let Some(scene) = scene_id.and_then(|scene_id| state.find_scene_by_id_mut(scene_id))
+            {
+                main_frame::add_primitive(scene, primitive);
+            }
+
+            return;
+        }
+
+        match command
+        {
+            EditorCommand::TryOut => self.editor_state.set_try_out(state, true),
+            EditorCommand::ToggleUi => self.editor_state.visible = !self.editor_state.visible,
+            EditorCommand::Fullscreen => state.rendering.fullscreen.set(!*state.rendering.fullscreen.get_ref()),
+            EditorCommand::Delete => self.delete_selected(state),
+            EditorCommand::Move | EditorCommand::Rotate | EditorCommand::Scale =>
+            {
+                if !self.editor_state.selected_object.is_empty() && self.editor_state.selected_type == SelectionType::Object
+                {
+                    if let Some(start_pos) = state.input_manager.mouse.point.pos
+                    {
+                        self.editor_state.edit_mode = match command
+                        {
+                            EditorCommand::Move => Some(EditMode::Movement(start_pos, true, false, true)),
+                            EditorCommand::Rotate => Some(EditMode::Rotate(start_pos, false, true, false)),
+                            _ => Some(EditMode::Scale(start_pos, true, true, true)),
+                        };
+                    }
+                }
+            },
+            EditorCommand::ToggleSnapping => self.editor_state.snap = !self.editor_state.snap,
+            EditorCommand::ToggleMeasure => self.editor_state.measure = !self.editor_state.measure,
+            EditorCommand::PickFocus => self.editor_state.pick_focus = true,
+            EditorCommand::ToggleBoundingSphere => self.editor_state.show_bounding_sphere = !self.editor_state.show_bounding_sphere,
+            EditorCommand::RenderingSettings => self.editor_state.settings = SettingsPanel::Rendering,
+            EditorCommand::SceneSettings => self.editor_state.settings = SettingsPanel::Scene,
+            EditorCommand::KeymapSettings => self.editor_state.settings = SettingsPanel::Keymap,
+            _ => {}
+        }
+    }
+
+    pub fn update_bounding_sphere(&mut self, state: &mut State)
+    {
+        // get the world bounding sphere of the selected object
+        let mut bounding_sphere = None;
+        if self.editor_state.show_bounding_sphere
+        {
+            if let (Some(scene), Some(node), instance_id) = self.editor_state.get_selected_node(state)
+            {
+                let node = node.read().unwrap();
+
+                let instance_id = instance_id.or(node.instances.get_ref().first().map(|instance| instance.read().unwrap().id));
+                if let Some(instance_id) = instance_id
+                {
+                    if let Some(sphere) = node.get_world_bounding_sphere(instance_id)
+                    {
+                        bounding_sphere = Some((scene.id, sphere));
+                    }
+                }
+            }
+        }
+
         for scene in &mut state.scenes
         {
-            let grid = scene.find_node_by_name("grid");
-            if let Some(grid) = grid
+            let sphere = bounding_sphere.filter(|(scene_id, _)| *scene_id == scene.id).map(|(_, sphere)| sphere);
+
+            let mut helper = scene.find_node_by_name(BOUNDING_SPHERE_NODE);
+            if helper.is_none()
+            {
+                // only create the helper node if it's needed
+                if sphere.is_none()
+                {
+                    continue;
+                }
+
+                helper = Some(Self::create_bounding_sphere_node(scene));
+            }
+
+            let helper = helper.unwrap();
+            let mut helper = helper.write().unwrap();
+
+            if helper.visible != sphere.is_some()
             {
-                let mut grid = grid.write().unwrap();
+                helper.visible = sphere.is_some();
+            }
 
-                let mut transformation = grid.find_component::<Transformation>();
+            if let Some((center, radius)) = sphere
+            {
+                let mut transformation = helper.find_component::<Transformation>();
                 if transformation.is_none()
                 {
-                    grid.add_component(Arc::new(RwLock::new(Box::new(Transformation::identity(scene.id_manager.get_next_component_id(), "Transform")))));
-                    transformation = grid.find_component::<Transformation>();
+                    helper.add_component(Arc::new(RwLock::new(Box::new(Transformation::identity(scene.id_manager.get_next_component_id(), "Transform")))));
+                    transformation = helper.find_component::<Transformation>();
                 }
 
-                let camera = scene.get_active_camera();
-                if let Some(camera) = camera
+                let transformation = transformation.unwrap();
+                component_downcast_mut!(transformation, Transformation);
+
+                let pos = Vector3::<f32>::new(center.x, center.y, center.z);
+                let scale = Vector3::<f32>::new(radius, radius, radius);
+
+                if !approx_equal_vec(&pos, &transformation.get_data().position) || !approx_equal_vec(&scale, &transformation.get_data().scale)
                 {
-                    let camera_data = camera.get_data();
+                    let data = transformation.get_data_mut().get_mut();
+                    data.position = pos;
+                    data.scale = scale;
 
-                    let pos = &camera_data.eye_pos;
-                    let pos = Vector3::<f32>::new(pos.x.round(), 0.0, pos.z.round());
+                    transformation.calc_transform();
+                }
+            }
+        }
+    }
+
+    fn create_bounding_sphere_node(scene: &mut Box<Scene>) -> NodeItem
+    {
+        let node_arc = Node::new(scene.id_manager.get_next_node_id(), BOUNDING_SPHERE_NODE);
 
-                    let transformation = transformation.unwrap();
-                    component_downcast_mut!(transformation, Transformation);
+        let material_id = scene.id_manager.get_next_component_id();
+        let mut material = Material::new(material_id, BOUNDING_SPHERE_NODE);
+        {
+            let data = material.get_data_mut().get_mut();
+            data.base_color = Vector3::<f32>::new(0.0, 1.0, 0.0);
+            data.alpha = 0.2;
+            data.unlit_shading = true;
+            data.cast_shadow = false;
+            data.receive_shadow = false;
+            data.backface_cullig = false;
+        }
 
-                    if !approx_equal_vec(&pos, &transformation.get_data().position)
-                    {
-                        transformation.set_translation(Vector3::<f32>::new(pos.x, pos.y, pos.z));
-                    }
+        let material_arc: MaterialItem = new_component!(material);
+        scene.add_material(material_id, &material_arc);
+
+        {
+            let mut node = node_arc.write().unwrap();
+
+            let mesh = Mesh::new_sphere(scene.id_manager.get_next_component_id(), "mesh", 1.0, 32, 16);
+            node.add_component(new_component!(mesh));
+            node.add_component(material_arc);
+
+            let instance_id = scene.id_manager.get_next_instance_id();
+            node.create_default_instance(node_arc.clone(), instance_id);
+
+            // the helper should not be selectable
+            if let Some(instance) = node.instances.get_mut().first()
+            {
+                instance.write().unwrap().pickable = false;
+            }
+        }
+
+        scene.add_node(node_arc.clone());
+
+        node_arc
+    }
+
+    pub fn measure(&mut self, state: &mut State)
+    {
+        if self.editor_state.try_out || !self.editor_state.measure || self.editor_state.pick_focus
+        {
+            return;
+        }
+
+        if !state.input_manager.mouse.clicked(MouseButton::Left)
+        {
+            return;
+        }
+
+        if let Some(pos) = state.input_manager.mouse.point.pos
+        {
+            if let Some(point) = self.pick_point(state, pos)
+            {
+                let mut point = point;
+
+                if self.editor_state.snap || state.input_manager.keyboard.is_holding_modifier(Modifier::Ctrl) || state.input_manager.keyboard.is_holding_modifier(Modifier::Logo)
+                {
+                    point = snap_to_grid(&point, state.rendering.grid.snap_spacing());
                 }
+
+                // start a new measurement
+                if self.editor_state.measure_points.len() >= 2
+                {
+                    self.editor_state.measure_points.clear();
+                }
+
+                self.editor_state.measure_points.push(point);
             }
         }
     }
 
+    pub fn pick_focus(&mut self, state: &mut State)
+    {
+        if self.editor_state.try_out || !self.editor_state.pick_focus
+        {
+            return;
+        }
+
+        if !state.input_manager.mouse.clicked(MouseButton::Left)
+        {
+            return;
+        }
+
+        if let Some(pos) = state.input_manager.mouse.point.pos
+        {
+            if let Some(point) = self.pick_point(state, pos)
+            {
+                // distance from the camera under the cursor
+                let cam_pos = state.scenes.iter().flat_map(|scene| scene.cameras.iter()).find(|cam| cam.enabled && cam.is_point_in_viewport(&pos)).map(|cam| cam.get_data().eye_pos);
+
+                if let Some(cam_pos) = cam_pos
+                {
+                    state.rendering.dof.focus_distance = nalgebra::distance(&cam_pos, &point);
+                }
+            }
+        }
+
+        self.editor_state.pick_focus = false;
+    }
+
     pub fn select_object(&mut self, state: &mut State)
     {
-        if !self.editor_state.try_out && (self.editor_state.selectable || self.editor_state.pick_mode != SelectionType::None) && self.editor_state.edit_mode.is_none()
+        if !self.editor_state.try_out && !self.editor_state.measure && !self.editor_state.pick_focus && (self.editor_state.selectable || self.editor_state.pick_mode != SelectionType::None) && self.editor_state.edit_mode.is_none()
         {
-            let left_mouse_button = state.input_manager.mouse.clicked(MouseButton::Left);
+            // a tap is handled like a left click
+            let tap_pos = state.input_manager.touch.tapped();
+
+            let left_mouse_button = state.input_manager.mouse.clicked(MouseButton::Left) || tap_pos.is_some();
             let right_mouse_button = state.input_manager.mouse.clicked(MouseButton::Right);
 
             if left_mouse_button || right_mouse_button
             {
-                let pos = state.input_manager.mouse.point.pos;
-
-                let mut hit: Option<(f32, Point3<f32>, Option<Vector3<f32>>, NodeItem, u64, Option<u32>)> = None;
+                // the hit is taken from the pick events of this frame (see State::update_picks)
+                let mut hit: Option<(NodeItem, u64)> = None;
                 let mut scene_id: u64 = 0;
 
-                if let Some(pos) = pos
+                if let Some((pick_scene_id, SceneEvent::Picked { node_id, instance_id, .. })) = state.picks.first()
                 {
-                    let pick_res = self.pick(state, pos, false);
-
-                    if let Some(pick_res) = pick_res
+                    if let Some(scene) = state.find_scene_by_id(*pick_scene_id)
                     {
-                        scene_id = pick_res.0;
-                        hit = Some(pick_res.1);
+                        if let Some(node) = scene.find_node_by_id(*node_id)
+                        {
+                            scene_id = *pick_scene_id;
+                            hit = Some((node, *instance_id));
+                        }
                     }
                 }
 
-                if let Some((_t, _point, _normal, hit_item, instance_id,_face_id)) = hit
+                if let Some((hit_item, instance_id)) = hit
                 {
                     // pick camera target
                     if self.editor_state.pick_mode == SelectionType::Camera
@@ -262,199 +496,133 @@ impl Editor
     }
 
     pub fn delete_objct(&mut self, state: &mut State)
+    {
+        if !self.editor_state.selected_object.is_empty() && self.editor_state.keymap.is_pressed(KeyAction::Delete, &mut state.input_manager.keyboard)
+        {
+            self.delete_selected(state);
+        }
+    }
+
+    pub fn delete_selected(&mut self, state: &mut State)
     {
         if !self.editor_state.selected_object.is_empty()
         {
-            //if state.input_manager.keyboard.is_pressed(Key::X) || state.input_manager.keyboard.is_pressed(Key::Delete)
-            if state.input_manager.keyboard.is_pressed(Key::Delete) || state.input_manager.keyboard.is_pressed(Key::Backspace)
+            // object
+            if self.editor_state.selected_type == SelectionType::Object
             {
-                // object
-                if self.editor_state.selected_type == SelectionType::Object
+                if let (Some(scene), Some(node), instance_id) = self.editor_state.get_selected_node(state)
                 {
-                    if let (Some(scene), Some(node), instance_id) = self.editor_state.get_selected_node(state)
-                    {
-                        let instances_amount = node.read().unwrap().instances.get_ref().len();
-
-                        //scene.delete_node_by_id(id)
-                        if instance_id.is_some() && instances_amount > 1
-                        {
-                            let instance_id = instance_id.unwrap();
-                            node.write().unwrap().delete_instance_by_id(instance_id);
-                        }
-                        else
-                        {
-                            scene.delete_node_by_id(node.read().unwrap().id);
-                        }
+                    let instances_amount = node.read().unwrap().instances.get_ref().len();
 
-                        self.editor_state.de_select_current_item(state);
+                    //scene.delete_node_by_id(id)
+                    if instance_id.is_some() && instances_amount > 1
+                    {
+                        let instance_id = instance_id.unwrap();
+                        node.write().unwrap().delete_instance_by_id(instance_id);
                     }
+                    else
+                    {
+                        scene.delete_node_by_id(node.read().unwrap().id);
+                    }
+
+                    self.editor_state.de_select_current_item(state);
                 }
+            }
 
-                // camera
-                if self.editor_state.selected_type == SelectionType::Camera
+            // camera
+            if self.editor_state.selected_type == SelectionType::Camera
+            {
+                let (camera_id, _) = self.editor_state.get_object_ids();
+                let scene = self.editor_state.get_selected_scene(state);
+                if let (Some(camera_id), Some(scene)) = (camera_id, scene)
                 {
-                    let (camera_id, _) = self.editor_state.get_object_ids();
-                    let scene = self.editor_state.get_selected_scene(state);
-                    if let (Some(camera_id), Some(scene)) = (camera_id, scene)
-                    {
-                        scene.delete_camera_by_id(camera_id);
-                    }
+                    scene.delete_camera_by_id(camera_id);
                 }
+            }
 
-                // light
-                if self.editor_state.selected_type == SelectionType::Light
+            // light
+            if self.editor_state.selected_type == SelectionType::Light
+            {
+                let (light_id, _) = self.editor_state.get_object_ids();
+                let scene = self.editor_state.get_selected_scene(state);
+                if let (Some(light_id), Some(scene)) = (light_id, scene)
                 {
-                    let (light_id, _) = self.editor_state.get_object_ids();
-                    let scene = self.editor_state.get_selected_scene(state);
-                    if let (Some(light_id), Some(scene)) = (light_id, scene)
-                    {
-                        scene.delete_light_by_id(light_id);
-                    }
+                    scene.delete_light_by_id(light_id);
                 }
+            }
 
-                // material
-                if self.editor_state.selected_type == SelectionType::Material
+            // material
+            if self.editor_state.selected_type == SelectionType::Material
+            {
+                let (material_id, _) = self.editor_state.get_object_ids();
+                let scene = self.editor_state.get_selected_scene(state);
+                if let (Some(material_id), Some(scene)) = (material_id, scene)
                 {
-                    let (material_id, _) = self.editor_state.get_object_ids();
-                    let scene = self.editor_state.get_selected_scene(state);
-                    if let (Some(material_id), Some(scene)) = (material_id, scene)
-                    {
-                        scene.delete_material_by_id(material_id);
-                    }
+                    scene.delete_material_by_id(material_id);
                 }
+            }
 
-                // texture
-                if self.editor_state.selected_type == SelectionType::Texture
+            // texture
+            if self.editor_state.selected_type == SelectionType::Texture
+            {
+                let (texture_id, _) = self.editor_state.get_object_ids();
+                let scene = self.editor_state.get_selected_scene(state);
+                if let (Some(texture_id), Some(scene)) = (texture_id, scene)
                 {
-                    let (texture_id, _) = self.editor_state.get_object_ids();
-                    let scene = self.editor_state.get_selected_scene(state);
-                    if let (Some(texture_id), Some(scene)) = (texture_id, scene)
-                    {
-                        scene.delete_texture_by_id(texture_id);
-                    }
+                    scene.delete_texture_by_id(texture_id);
                 }
             }
         }
     }
 
-    pub fn pick(&self, state: &State, pos: Point2::<f32>, allow_grid_picking: bool) -> Option<(u64, (f32, Point3<f32>, Option<Vector3<f32>>, NodeItem, u64, Option<u32>))>
+    // picks a point on objects or on the ground grid (plane through the origin - orthogonal to the up axis) if enabled - whatever is nearer
+    pub fn pick_point(&self, state: &State, pos: Point2::<f32>) -> Option<Point3<f32>>
     {
-        let scenes = &state.scenes;
-        let width = state.width;
-        let height = state.height;
+        let mut nearest: Option<(f32, Point3<f32>)> = None;
 
-        let mut hit: Option<(f32, Point3<f32>, Option<Vector3<f32>>, NodeItem, u64, Option<u32>)> = None;
-        let mut scene_id: u64 = 0;
-
-        for scene in scenes
+        if let Some((_scene_id, (t, point, ..))) = state.pick(pos)
         {
-            let set_grid_picking = |scene: &Box<Scene>, state: bool|
-            {
-                // find grid
-                let grid = scene.find_node_by_name("grid");
+            nearest = Some((t, point));
+        }
 
-                if let Some(grid) = grid
-                {
-                    let mut grid = grid.write().unwrap();
-                    let grid_instance = grid.instances.get_mut().first();
-                    if let Some(grid_instance) = grid_instance
-                    {
-                        grid_instance.write().unwrap().pickable = state;
-                    }
-                }
-            };
+        if !state.rendering.grid.enabled
+        {
+            return nearest.map(|(_t, point)| point);
+        }
 
-            /*
-            if allow_grid_picking
-            {
-                set_grid_picking(scene, true);
-            }
-            */
+        for scene in &state.scenes
+        {
+            let up = scene.get_up_axis();
 
             for camera in &scene.cameras
             {
-                // check if click is insight
-                if camera.is_point_in_viewport(&pos)
+                if !camera.enabled || !camera.is_point_in_viewport(&pos)
                 {
-                    dbg!(allow_grid_picking);
-                    let ray = camera.get_ray_from_viewport_coordinates(&pos, width, height);
-
-                    let mut grid_hit = None;
-                    if allow_grid_picking
-                    {
-                        let grid = scene.find_node_by_name("grid");
-                        if let Some(grid) = grid
-                        {
-                            set_grid_picking(scene, true);
-                            grid_hit = scene.pick_node(grid, &ray, false, true);
-                            set_grid_picking(scene, false);
-                        }
-                    }
-
-                    let scene_hit = scene.pick(&ray, false, false);
-
-                    dbg!(scene_hit.is_some());
-                    dbg!(grid_hit.is_some());
-
-                    // check if grid hit is closer or scene hit
-                    let mut new_hit = grid_hit;
-                    if let Some(scene_hit_ref) = scene_hit.as_ref()
-                    {
-                        if let Some(new_hit_ref) = new_hit.as_ref()
-                        {
-                            if scene_hit_ref.0 < new_hit_ref.0
-                            {
-                                new_hit = scene_hit;
-                            }
-                        }
-                        else
-                        {
-                            new_hit = scene_hit;
-                        }
-                    }
-
-                    dbg!(new_hit.is_some());
+                    continue;
+                }
 
-                    let mut save_hit = false;
+                let ray = camera.get_ray_from_viewport_coordinates(&pos, state.width, state.height);
 
-                    if let Some(new_hit) = new_hit.as_ref()
-                    {
-                        if let Some(hit) = hit.as_ref()
-                        {
-                            // check if the new hit is near
-                            if new_hit.0 < hit.0
-                            {
-                                save_hit = true;
-                            }
-                        }
-                        else
-                        {
-                            save_hit = true;
-                        }
-                    }
+                let dir_up = ray.dir.dot(&up);
+                if dir_up.abs() <= std::f32::EPSILON
+                {
+                    continue;
+                }
 
-                    if save_hit
-                    {
-                        hit = new_hit;
-                        scene_id = scene_id;
-                    }
+                let t = -ray.origin.coords.dot(&up) / dir_up;
+                if t < 0.0
+                {
+                    continue;
                 }
-            }
 
-            /*
-            if allow_grid_picking
-            {
-                set_grid_picking(scene, false);
+                if nearest.is_none() || t < nearest.unwrap().0
+                {
+                    nearest = Some((t, ray.point_at(t)));
+                }
             }
-            */
-        }
-
-        if let Some(hit) = hit
-        {
-            return Some((scene_id, hit));
         }
 
-        None
+        nearest.map(|(_t, point)| point)
     }
 
     pub fn apply_drag(&mut self, state: &mut State, ctx: &egui::Context)
@@ -475,7 +643,7 @@ impl Editor
 
                         if pos.x >= 0.0 && pos.y >= 0.0 && pos.x < state.width as f32 && pos.y <= state.height as f32
                         {
-                            self.load_asset(state, drag_id.clone(), Point2::<f32>::new(pos.x, state.height as f32 - pos.y));
+                            self.load_asset(state, drag_id.clone(), self.editor_state.asset_type, Point2::<f32>::new(pos.x, state.height as f32 - pos.y));
                         }
                     }
                 }
@@ -487,78 +655,65 @@ impl Editor
 
     pub fn move_object(&mut self, state: &mut State)
     {
-        let step_size = 1.0;
-        let angle_steps = PI / 8.0;
+        let step_size;
+        if self.editor_state.snap_to_grid_spacing
+        {
+            step_size = state.rendering.grid.snap_spacing();
+        }
+        else
+        {
+            step_size = self.editor_state.snap_translation.max(0.0001);
+        }
+
+        let angle_steps = self.editor_state.snap_rotation.max(0.01).to_radians();
+        let scale_steps = self.editor_state.snap_scale.max(0.0001);
         let factor = 0.01;
 
+        // snapping: persistent toggle or hold ctrl
+        let snap = self.editor_state.snap || state.input_manager.keyboard.is_holding_modifier(Modifier::Ctrl) || state.input_manager.keyboard.is_holding_modifier(Modifier::Logo);
+
         if !self.editor_state.selected_object.is_empty() && self.editor_state.selected_type == SelectionType::Object && state.input_manager.mouse.point.pos.is_some()
         {
-            if state.input_manager.keyboard.is_pressed(Key::G)
+            if self.editor_state.keymap.is_pressed(KeyAction::Move, &mut state.input_manager.keyboard)
             {
                 let start_pos = state.input_manager.mouse.point.pos.unwrap();
                 self.editor_state.edit_mode = Some(EditMode::Movement(start_pos, true, false, true));
             }
-            if state.input_manager.keyboard.is_pressed(Key::R)
+            if self.editor_state.keymap.is_pressed(KeyAction::Rotate, &mut state.input_manager.keyboard)
             {
                 let start_pos = state.input_manager.mouse.point.pos.unwrap();
                 self.editor_state.edit_mode = Some(EditMode::Rotate(start_pos, false, true, false));
             }
+            if self.editor_state.keymap.is_pressed(KeyAction::Scale, &mut state.input_manager.keyboard)
+            {
+                let start_pos = state.input_manager.mouse.point.pos.unwrap();
+                self.editor_state.edit_mode = Some(EditMode::Scale(start_pos, true, true, true));
+            }
 
             if self.editor_state.edit_mode.is_some() && state.input_manager.mouse.is_pressed(MouseButton::Left)
             {
                 self.editor_state.edit_mode = None;
             }
 
-            if self.editor_state.edit_mode.is_some()
+            if let Some(edit_mode) = self.editor_state.edit_mode
             {
-                let moving;
-                let start_pos;
-                match self.editor_state.edit_mode.as_ref().unwrap()
-                {
-                    EditMode::Movement(pos, _, _, _) => { moving = true; start_pos = pos.clone(); },
-                    EditMode::Rotate(pos, _, _, _) => { moving = false; start_pos = pos.clone(); },
-                }
+                let start_pos = edit_mode.start_pos();
+                let shift = state.input_manager.keyboard.is_holding_modifier(Modifier::Shift);
 
-                if state.input_manager.keyboard.is_pressed(Key::X)
+                // shift excludes the axis
+                if self.editor_state.keymap.is_pressed(KeyAction::AxisX, &mut state.input_manager.keyboard)
                 {
-                    if !state.input_manager.keyboard.is_holding_modifier(Modifier::Shift)
-                    {
-                        if moving { self.editor_state.edit_mode = Some(EditMode::Movement(start_pos.clone(), true, false, false)); }
-                        else      { self.editor_state.edit_mode = Some(EditMode::Rotate  (start_pos.clone(), true, false, false)); }
-                    }
-                    else
-                    {
-                        if moving { self.editor_state.edit_mode = Some(EditMode::Movement(start_pos, false, true, true)); }
-                        else      { self.editor_state.edit_mode = Some(EditMode::Rotate  (start_pos, false, true, true)); }
-                    }
+                    self.editor_state.edit_mode = Some(edit_mode.with_axes(start_pos, !shift, shift, shift));
                 }
 
-                if state.input_manager.keyboard.is_pressed(Key::Y)
+                if self.editor_state.keymap.is_pressed(KeyAction::AxisY, &mut state.input_manager.keyboard)
                 {
-                    if !state.input_manager.keyboard.is_holding_modifier(Modifier::Shift)
-                    {
-                        if moving { self.editor_state.edit_mode = Some(EditMode::Movement(start_pos, false, true, false)); }
-                        else      { self.editor_state.edit_mode = Some(EditMode::Rotate  (start_pos, false, true, false)); }
-                    }
-                    else
-                    {
-                        if moving { self.editor_state.edit_mode = Some(EditMode::Movement(start_pos, true, false, true)); }
-                        else      { self.editor_state.edit_mode = Some(EditMode::Rotate  (start_pos, true, false, true)); }
-                    }
+                    self.editor_state.edit_mode = Some(edit_mode.with_axes(start_pos, shift, !shift, shift));
                 }
 
-                if state.input_manager.keyboard.is_pressed(Key::Z)
+                if self.editor_state.keymap.is_pressed(KeyAction::AxisZ, &mut state.input_manager.keyboard)
                 {
-                    if !state.input_manager.keyboard.is_holding_modifier(Modifier::Shift)
-                    {
-                        if moving { self.editor_state.edit_mode = Some(EditMode::Movement(start_pos, false, false, true)); }
-                        else      { self.editor_state.edit_mode = Some(EditMode::Rotate  (start_pos, false, false, true)); }
-                    }
-                    else
-                    {
-                        if moving { self.editor_state.edit_mode = Some(EditMode::Movement(start_pos, true, true, false)); }
-                        else      { self.editor_state.edit_mode = Some(EditMode::Rotate  (start_pos, true, true, false)); }
-                    }
+                    self.editor_state.edit_mode = Some(edit_mode.with_axes(start_pos, shift, shift, !shift));
                 }
 
                 let edit_mode = self.editor_state.edit_mode.unwrap();
@@ -656,7 +811,7 @@ impl Editor
                             let mut vec = Vector3::<f32>::zeros();
                             if x
                             {
-                                if state.input_manager.keyboard.is_holding_modifier(Modifier::Ctrl) || state.input_manager.keyboard.is_holding_modifier(Modifier::Logo)
+                                if snap
                                 {
                                     let sign = movement.x.signum();
                                     if movement.x.abs() >= step_size
@@ -674,7 +829,7 @@ impl Editor
 
                             if y
                             {
-                                if state.input_manager.keyboard.is_holding_modifier(Modifier::Ctrl) || state.input_manager.keyboard.is_holding_modifier(Modifier::Logo)
+                                if snap
                                 {
                                     let sign = movement.z.signum();
                                     if movement.z.abs() >= step_size
@@ -692,7 +847,7 @@ impl Editor
 
                             if z
                             {
-                                if state.input_manager.keyboard.is_holding_modifier(Modifier::Ctrl) || state.input_manager.keyboard.is_holding_modifier(Modifier::Logo)
+                                if snap
                                 {
                                     let sign = -movement.z.signum();
                                     if movement.z.abs() >= step_size
@@ -710,6 +865,7 @@ impl Editor
 
                             if applied
                             {
+                                let vec = apply_lock(vec, &edit_transformation.get_data().position_lock);
                                 edit_transformation.apply_translation(vec);
                             }
 
@@ -725,7 +881,7 @@ impl Editor
                             let mut vec = Vector3::<f32>::zeros();
                             if x
                             {
-                                if state.input_manager.keyboard.is_holding_modifier(Modifier::Ctrl) || state.input_manager.keyboard.is_holding_modifier(Modifier::Logo)
+                                if snap
                                 {
                                     let sign = movement.z.signum();
                                     if movement.z.abs() >= angle_steps
@@ -743,7 +899,7 @@ impl Editor
 
                             if y
                             {
-                                if state.input_manager.keyboard.is_holding_modifier(Modifier::Ctrl) || state.input_manager.keyboard.is_holding_modifier(Modifier::Logo)
+                                if snap
                                 {
                                     let sign = movement.x.signum();
                                     if movement.x.abs() >= angle_steps
@@ -761,7 +917,7 @@ impl Editor
 
                             if z
                             {
-                                if state.input_manager.keyboard.is_holding_modifier(Modifier::Ctrl) || state.input_manager.keyboard.is_holding_modifier(Modifier::Logo)
+                                if snap
                                 {
                                     let sign = movement.x.signum();
                                     if movement.x.abs() >= angle_steps
@@ -779,6 +935,7 @@ impl Editor
 
                             if applied
                             {
+                                let vec = apply_lock(vec, &edit_transformation.get_data().rotation_lock);
                                 edit_transformation.apply_rotation(vec);
                             }
 
@@ -787,14 +944,73 @@ impl Editor
                                 self.editor_state.edit_mode = Some(EditMode::Rotate(mouse_pos, x, y, z));
                             }
                         },
+                        EditMode::Scale(_, x, y, z) =>
+                        {
+                            // horizontal mouse movement scales the object
+                            let mut amount = (mouse_pos.x - start_pos.x) * factor;
+                            let mut applied = true;
+
+                            if snap
+                            {
+                                if amount.abs() >= scale_steps
+                                {
+                                    amount = scale_steps * amount.signum();
+                                }
+                                else
+                                {
+                                    applied = false;
+                                }
+                            }
+
+                            if applied
+                            {
+                                let mut vec = Vector3::<f32>::zeros();
+                                if x { vec.x = amount; }
+                                if y { vec.y = amount; }
+                                if z { vec.z = amount; }
+
+                                let vec = apply_lock(vec, &edit_transformation.get_data().scale_lock);
+                                edit_transformation.apply_scale(vec, false);
+
+                                self.editor_state.edit_mode = Some(EditMode::Scale(mouse_pos, x, y, z));
+                            }
+                        },
                     }
                 }
             }
         }
     }
 
-    pub fn load_asset(&mut self, state: &mut State, path: String, pos: Point2::<f32>)
+    pub fn load_asset(&mut self, state: &mut State, path: String, asset_type: AssetType, pos: Point2::<f32>)
     {
+        // let the user choose if there are multiple scenes in the file
+        let extension = get_extension(&path);
+        if extension == "gltf" || extension == "glb"
+        {
+            if let Ok(scenes) = gltf::get_scenes(&path)
+            {
+                if scenes.len() > 1
+                {
+                    // the default scene (or the first one) is preselected
+                    let mut scenes: Vec<(usize, String, bool)> = scenes;
+                    if !scenes.iter().any(|(_, _, default)| *default)
+                    {
+                        scenes[0].2 = true;
+                    }
+
+                    self.editor_state.scene_choice = Some(SceneChoice { asset_type, path, pos, scenes, confirmed: false });
+                    return;
+                }
+            }
+        }
+
+        self.load_asset_with_scenes(state, path, asset_type, pos, None);
+    }
+
+    pub fn load_asset_with_scenes(&mut self, state: &mut State, path: String, asset_type: AssetType, pos: Point2::<f32>, scenes: Option<Vec<usize>>)
+    {
+        self.editor_state.add_recent_file(asset_type, &path);
+
         let main_queue = state.main_thread_execution_queue.clone();
 
         let mut scene_id = None;
@@ -802,7 +1018,7 @@ impl Editor
         {
             scene_id = Some(scene.id);
 
-            if self.editor_state.asset_type == AssetType::Scene
+            if asset_type == AssetType::Scene
             {
                 scene.clear();
             }
@@ -817,36 +1033,39 @@ impl Editor
 
         let scene_id = scene_id.unwrap();
 
-        let main_queue_clone = main_queue.clone();
-        if self.editor_state.asset_type == AssetType::Scene
-        {
-            spawn_thread(move ||
-            {
-                scene_utils::create_grid(scene_id, main_queue_clone.clone(), 500, 1.0);
-            });
-        };
-
         // pick
-        let pick_res = self.pick(state, pos, true);
-
-        let mut pos = None;
-        if let Some(pick_res) = pick_res
-        {
-            pos = Some(pick_res.1.1);
-        }
+        let pos = self.pick_point(state, pos);
 
         let create_mipmaps = state.rendering.create_mipmaps;
-        let create_root_node = if self.editor_state.asset_type == AssetType::Object { true } else { false };
-        let object_only = if self.editor_state.asset_type == AssetType::Object { true } else { false };
-        let reuse_materials = if self.editor_state.asset_type == AssetType::Object && self.editor_state.reuse_materials_by_name  { true } else { false };
+        let orient_z_up = self.editor_state.orient_z_up;
+
+        // the orientation is applied to the root node - so there needs to be one
+        let create_root_node = if asset_type == AssetType::Object || orient_z_up { true } else { false };
+        let object_only = if asset_type == AssetType::Object { true } else { false };
+        let reuse_materials = if asset_type == AssetType::Object && self.editor_state.reuse_materials_by_name  { true } else { false };
 
         let editor_state = self.editor_state.loading.clone();
+        let progress = self.editor_state.loading_progress.clone();
         spawn_thread(move ||
         {
             dbg!("loading ...");
             *editor_state.write().unwrap() = true;
+            progress.write().unwrap().reset();
+
+            // an explicit up axis of the file is preferred over the bounding box heuristic
+            let mut up_axis_hint = None;
+            if orient_z_up
+            {
+                let extension = get_extension(&path);
+                if extension == "gltf" || extension == "glb"
+                {
+                    up_axis_hint = gltf::get_up_axis(&path).unwrap_or(None);
+                }
+            }
+
+            let loaded = load_object_with_scenes(path.as_str(), scene_id, main_queue.clone(), create_root_node, reuse_materials, object_only, create_mipmaps, scenes, Some(progress.clone()));
 
-            let loaded = load_object(path.as_str(), scene_id, main_queue.clone(), create_root_node, reuse_materials, object_only, create_mipmaps);
+            progress.write().unwrap().reset();
 
             if loaded.is_err()
             {
@@ -861,24 +1080,61 @@ impl Editor
             {
                 scene.clear_empty_nodes();
 
-                if let Some(pos) = pos
+                // one root node per imported scene
+                let mut root_nodes = vec![];
+                if create_root_node
                 {
-                    if create_root_node
+                    for id in &loaded_ids
                     {
-                        let mut root_node = None;
-                        for id in &loaded_ids
+                        if let Some(node) = scene.find_node_by_id(*id)
                         {
-                            if let Some(node) = scene.find_node_by_id(*id)
+                            if node.read().unwrap().root_node
                             {
-                                if node.read().unwrap().root_node
-                                {
-                                    root_node = Some(node.clone());
-                                    break;
-                                }
+                                root_nodes.push(node.clone());
                             }
                         }
+                    }
+                }
+
+                // z-up -> y-up: rotation of the root node (the mesh data is not changed)
+                if orient_z_up
+                {
+                    let mut oriented = false;
 
-                        if let Some(root_node) = root_node
+                    for root_node in &root_nodes
+                    {
+                        let z_up = match up_axis_hint
+                        {
+                            Some(up_axis) => approx_equal_vec(&up_axis, &Vector3::<f32>::z()),
+                            None => root_node.read().unwrap().get_bounding_info(true).map_or(false, |(min, max)| is_z_up(&(max - min))),
+                        };
+
+                        if !z_up
+                        {
+                            continue;
+                        }
+
+                        let component_id = scene.id_manager.get_next_component_id();
+
+                        let mut transform = Transformation::identity(component_id, "Transform");
+                        transform.apply_rotation(Vector3::<f32>::new(-PI / 2.0, 0.0, 0.0));
+
+                        root_node.write().unwrap().add_component(Arc::new(RwLock::new(Box::new(transform))));
+                        oriented = true;
+                    }
+
+                    // the up axis hint of the file was already applied to the scene
+                    if oriented && !approx_equal_vec(&scene.get_up_axis(), &Vector3::<f32>::y())
+                    {
+                        scene.set_up_axis(Vector3::<f32>::y());
+                    }
+                }
+
+                if let Some(pos) = pos
+                {
+                    if asset_type == AssetType::Object
+                    {
+                        for root_node in &root_nodes
                         {
                             // find offset based on bounding box
                             let mut offset = 0.0;
@@ -893,12 +1149,24 @@ impl Editor
                                 }
                             }
 
-                            let component_id = scene.id_manager.get_next_component_id();
+                            let translation = Vector3::<f32>::new(pos.x, pos.y + offset, pos.z);
 
-                            let mut transform = Transformation::identity(component_id, "Transform");
-                            transform.apply_translation(Vector3::<f32>::new(pos.x, pos.y + offset, pos.z));
+                            // the orientation transform is reused
+                            let transform = root_node.read().unwrap().find_component::<Transformation>();
+                            if let Some(transform) = transform
+                            {
+                                component_downcast_mut!(transform, Transformation);
+                                transform.apply_translation(translation);
+                            }
+                            else
+                            {
+                                let component_id = scene.id_manager.get_next_component_id();
+
+                                let mut transform = Transformation::identity(component_id, "Transform");
+                                transform.apply_translation(translation);
 
-                            root_node.write().unwrap().add_component(Arc::new(RwLock::new(Box::new(transform))));
+                                root_node.write().unwrap().add_component(Arc::new(RwLock::new(Box::new(transform))));
+                            }
                         }
                     }
                 }
@@ -934,8 +1202,10 @@ impl Editor
                 // add camera
                 if scene.cameras.len() == 0
                 {
+                    let up_axis = scene.get_up_axis();
                     let mut cam = Camera::new(scene.id_manager.get_next_camera_id(), "Cam".to_string());
                     let cam_data = cam.get_data_mut().get_mut();
+                    cam_data.up = up_axis;
                     cam_data.fovy = 45.0f32.to_radians();
                     cam_data.eye_pos = Point3::<f32>::new(0.0, 1.0, 1.5);
                     cam_data.dir = Vector3::<f32>::new(-cam_data.eye_pos.x, -cam_data.eye_pos.y, -cam_data.eye_pos.z);
@@ -979,4 +1249,4 @@ impl Editor
 
         full_output
     }
-}
\ No newline at end of file
+}
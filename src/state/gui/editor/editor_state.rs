@@ -1,13 +1,21 @@
 use std::{sync::{RwLock, Arc}, fmt::format};
 
 use image::{ImageFormat, EncodableLayout};
-use nalgebra::Point2;
+use nalgebra::{Point2, Point3};
 
-use crate::{state::{scene::{scene::Scene, node::NodeItem}, state::State}, resources::resources::{read_files_recursive, exists, load_binary}, helper::file::{get_extension, get_stem}, rendering::egui::EGui};
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, Display};
+
+use crate::{state::{scene::{scene::Scene, node::NodeItem, loader::progress::{LoadingProgress, LoadingProgressItem}}, state::State}, resources::resources::{read_files_recursive, exists, load_binary}, helper::file::{get_extension, get_stem}, rendering::egui::EGui, input::keyboard::{Key, Keyboard, Modifier}};
 
 const THUMB_EXTENSION: &str = "png";
 const THUMB_SUFFIX_NAME: &str = "_thumb.png";
 
+const KEYMAP_FILE: &str = "data/keymap.txt";
+
+const RECENT_FILES_FILE: &str = "data/recent_files.txt";
+const RECENT_FILES_MAX: usize = 10;
+
 #[derive(PartialEq, Eq)]
 pub enum SettingsPanel
 {
@@ -18,7 +26,8 @@ pub enum SettingsPanel
     Light,
     Scene,
     Object,
-    Rendering
+    Rendering,
+    Keymap
 }
 
 #[derive(PartialEq, Eq)]
@@ -32,6 +41,23 @@ pub enum SelectionType
     None
 }
 
+// drop position of a dragged hierarchy node (node ids)
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum HierarchyDropTarget
+{
+    Into(u64),
+    Before(u64),
+    After(u64),
+    Root
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum ListSorting
+{
+    Id,
+    Name
+}
+
 #[derive(PartialEq, Eq)]
 pub enum BottomPanel
 {
@@ -41,7 +67,7 @@ pub enum BottomPanel
     None
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy)]
 pub enum AssetType
 {
     Scene,
@@ -50,11 +76,379 @@ pub enum AssetType
     Material
 }
 
+#[derive(EnumIter, Display, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum KeyAction
+{
+    TryOut,
+    ExitTryOut,
+    ToggleUi,
+    Fullscreen,
+    Cancel,
+    Delete,
+    Move,
+    Rotate,
+    Scale,
+    AxisX,
+    AxisY,
+    AxisZ,
+    CommandPalette
+}
+
+// all actions of the command palette (new actions are listed automatically)
+#[derive(EnumIter, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum EditorCommand
+{
+    TryOut,
+    ToggleUi,
+    Fullscreen,
+    Delete,
+    Move,
+    Rotate,
+    Scale,
+    AddBox,
+    AddSphere,
+    AddPlane,
+    AddCylinder,
+    AddCone,
+    ToggleSnapping,
+    ToggleMeasure,
+    PickFocus,
+    ToggleBoundingSphere,
+    RenderingSettings,
+    SceneSettings,
+    KeymapSettings,
+}
+
+impl EditorCommand
+{
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            EditorCommand::TryOut => "Try Out",
+            EditorCommand::ToggleUi => "Toggle UI",
+            EditorCommand::Fullscreen => "Toggle Fullscreen",
+            EditorCommand::Delete => "Delete Selected",
+            EditorCommand::Move => "Move Selected",
+            EditorCommand::Rotate => "Rotate Selected",
+            EditorCommand::Scale => "Scale Selected",
+            EditorCommand::AddBox => "Add Primitive: Box",
+            EditorCommand::AddSphere => "Add Primitive: Sphere",
+            EditorCommand::AddPlane => "Add Primitive: Plane",
+            EditorCommand::AddCylinder => "Add Primitive: Cylinder",
+            EditorCommand::AddCone => "Add Primitive: Cone",
+            EditorCommand::ToggleSnapping => "Toggle Snapping",
+            EditorCommand::ToggleMeasure => "Toggle Measure",
+            EditorCommand::PickFocus => "Pick Focus (Depth of Field)",
+            EditorCommand::ToggleBoundingSphere => "Toggle Bounding Sphere",
+            EditorCommand::RenderingSettings => "Show Rendering Settings",
+            EditorCommand::SceneSettings => "Show Scene Settings",
+            EditorCommand::KeymapSettings => "Show Shortcuts",
+        }
+    }
+
+    // shortcut of the command (if there is one)
+    pub fn key_action(&self) -> Option<KeyAction>
+    {
+        match self
+        {
+            EditorCommand::TryOut => Some(KeyAction::TryOut),
+            EditorCommand::ToggleUi => Some(KeyAction::ToggleUi),
+            EditorCommand::Fullscreen => Some(KeyAction::Fullscreen),
+            EditorCommand::Delete => Some(KeyAction::Delete),
+            EditorCommand::Move => Some(KeyAction::Move),
+            EditorCommand::Rotate => Some(KeyAction::Rotate),
+            EditorCommand::Scale => Some(KeyAction::Scale),
+            _ => None
+        }
+    }
+
+    pub fn primitive(&self) -> Option<&'static str>
+    {
+        match self
+        {
+            EditorCommand::AddBox => Some("Box"),
+            EditorCommand::AddSphere => Some("Sphere"),
+            EditorCommand::AddPlane => Some("Plane"),
+            EditorCommand::AddCylinder => Some("Cylinder"),
+            EditorCommand::AddCone => Some("Cone"),
+            _ => None
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct KeyBinding
+{
+    pub key: Key,
+    pub ctrl: bool, // ctrl or logo (mac)
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding
+{
+    pub fn new(key: Key) -> KeyBinding
+    {
+        KeyBinding { key, ctrl: false, shift: false, alt: false }
+    }
+
+    pub fn new_ctrl(key: Key) -> KeyBinding
+    {
+        KeyBinding { key, ctrl: true, shift: false, alt: false }
+    }
+
+    pub fn to_string(&self) -> String
+    {
+        let mut parts = vec![];
+
+        if self.ctrl { parts.push("Ctrl".to_string()); }
+        if self.shift { parts.push("Shift".to_string()); }
+        if self.alt { parts.push("Alt".to_string()); }
+
+        parts.push(self.key.to_string());
+
+        parts.join("+")
+    }
+
+    pub fn from_string(string: &str) -> Option<KeyBinding>
+    {
+        let mut binding = KeyBinding::new(Key::Escape);
+        let mut key = None;
+
+        for part in string.trim().split('+')
+        {
+            match part
+            {
+                "Ctrl" => binding.ctrl = true,
+                "Shift" => binding.shift = true,
+                "Alt" => binding.alt = true,
+                _ => key = Key::iter().find(|k| k.to_string() == part)
+            }
+        }
+
+        binding.key = key?;
+
+        Some(binding)
+    }
+}
+
+pub struct Keymap
+{
+    pub bindings: Vec<(KeyAction, KeyBinding)>,
+    pub rebind: Option<usize>,
+}
+
+impl Keymap
+{
+    pub fn new() -> Keymap
+    {
+        Keymap
+        {
+            bindings: Self::default_bindings(),
+            rebind: None,
+        }
+    }
+
+    pub fn default_bindings() -> Vec<(KeyAction, KeyBinding)>
+    {
+        vec!
+        [
+            (KeyAction::TryOut, KeyBinding::new_ctrl(Key::R)),
+            (KeyAction::ExitTryOut, KeyBinding::new(Key::Escape)),
+            (KeyAction::ToggleUi, KeyBinding::new(Key::H)),
+            (KeyAction::Fullscreen, KeyBinding::new(Key::F)),
+            (KeyAction::Cancel, KeyBinding::new(Key::Escape)),
+            (KeyAction::Delete, KeyBinding::new(Key::Delete)),
+            (KeyAction::Delete, KeyBinding::new(Key::Backspace)),
+            (KeyAction::Move, KeyBinding::new(Key::G)),
+            (KeyAction::Rotate, KeyBinding::new(Key::R)),
+            (KeyAction::Scale, KeyBinding::new(Key::T)),
+            (KeyAction::AxisX, KeyBinding::new(Key::X)),
+            (KeyAction::AxisY, KeyBinding::new(Key::Y)),
+            (KeyAction::AxisZ, KeyBinding::new(Key::Z)),
+            (KeyAction::CommandPalette, KeyBinding::new_ctrl(Key::P)),
+        ]
+    }
+
+    // first binding of the action (for display)
+    pub fn binding_string(&self, action: KeyAction) -> Option<String>
+    {
+        self.bindings.iter().find(|(binding_action, _)| *binding_action == action).map(|(_, binding)| binding.to_string())
+    }
+
+    // modifiers of the binding must be hold - others are ignored (shift is used to exclude an axis for example)
+    pub fn is_pressed(&self, action: KeyAction, keyboard: &mut Keyboard) -> bool
+    {
+        for (binding_action, binding) in &self.bindings
+        {
+            if *binding_action != action
+            {
+                continue;
+            }
+
+            let ctrl = keyboard.is_holding_modifier(Modifier::Ctrl) || keyboard.is_holding_modifier(Modifier::Logo);
+            let shift = keyboard.is_holding_modifier(Modifier::Shift);
+            let alt = keyboard.is_holding_modifier(Modifier::Alt);
+
+            if (binding.ctrl && !ctrl) || (binding.shift && !shift) || (binding.alt && !alt)
+            {
+                continue;
+            }
+
+            if keyboard.is_pressed(binding.key)
+            {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    // assigns the next pressed key to the binding which should be rebound
+    pub fn capture(&mut self, keyboard: &mut Keyboard)
+    {
+        if self.rebind.is_none()
+        {
+            return;
+        }
+
+        let index = self.rebind.unwrap();
+
+        let modifier_keys = [Key::LControl, Key::RControl, Key::LShift, Key::RShift, Key::LAlt, Key::RAlt, Key::LWin, Key::RWin];
+
+        for key in Key::iter()
+        {
+            if modifier_keys.contains(&key) || !keyboard.is_holding(key)
+            {
+                continue;
+            }
+
+            // consume the press
+            keyboard.is_pressed(key);
+
+            if let Some((_, binding)) = self.bindings.get_mut(index)
+            {
+                binding.key = key;
+                binding.ctrl = keyboard.is_holding_modifier(Modifier::Ctrl) || keyboard.is_holding_modifier(Modifier::Logo);
+                binding.shift = keyboard.is_holding_modifier(Modifier::Shift);
+                binding.alt = keyboard.is_holding_modifier(Modifier::Alt);
+            }
+
+            self.rebind = None;
+            self.save();
+
+            return;
+        }
+    }
+
+    pub fn load(&mut self)
+    {
+        let content = std::fs::read_to_string(KEYMAP_FILE);
+
+        if content.is_err()
+        {
+            return;
+        }
+
+        let mut bindings = vec![];
+
+        for line in content.unwrap().lines()
+        {
+            let parts: Vec<&str> = line.splitn(2, '=').collect();
+
+            if parts.len() != 2
+            {
+                continue;
+            }
+
+            let action = KeyAction::iter().find(|action| action.to_string() == parts[0].trim());
+            let binding = KeyBinding::from_string(parts[1]);
+
+            if let (Some(action), Some(binding)) = (action, binding)
+            {
+                bindings.push((action, binding));
+            }
+            else
+            {
+                println!("invalid keymap entry: {}", line);
+            }
+        }
+
+        // keep defaults for actions without a binding in the file
+        for (action, binding) in Self::default_bindings()
+        {
+            if !bindings.iter().any(|(binding_action, _)| *binding_action == action)
+            {
+                bindings.push((action, binding));
+            }
+        }
+
+        self.bindings = bindings;
+    }
+
+    pub fn save(&self)
+    {
+        let mut content = String::new();
+
+        for (action, binding) in &self.bindings
+        {
+            content += format!("{}={}\n", action, binding.to_string()).as_str();
+        }
+
+        if let Err(err) = std::fs::write(KEYMAP_FILE, content)
+        {
+            println!("can not save keymap {}: {:?}", KEYMAP_FILE, err);
+        }
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.bindings = Self::default_bindings();
+        self.rebind = None;
+        self.save();
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum EditMode
 {
     Movement(Point2::<f32>, bool, bool, bool),
-    Rotate(Point2::<f32>, bool, bool, bool)
+    Rotate(Point2::<f32>, bool, bool, bool),
+    Scale(Point2::<f32>, bool, bool, bool)
+}
+
+impl EditMode
+{
+    pub fn start_pos(&self) -> Point2::<f32>
+    {
+        match self
+        {
+            EditMode::Movement(pos, _, _, _) => *pos,
+            EditMode::Rotate(pos, _, _, _) => *pos,
+            EditMode::Scale(pos, _, _, _) => *pos,
+        }
+    }
+
+    // same mode with other axes
+    pub fn with_axes(&self, pos: Point2::<f32>, x: bool, y: bool, z: bool) -> EditMode
+    {
+        match self
+        {
+            EditMode::Movement(..) => EditMode::Movement(pos, x, y, z),
+            EditMode::Rotate(..) => EditMode::Rotate(pos, x, y, z),
+            EditMode::Scale(..) => EditMode::Scale(pos, x, y, z),
+        }
+    }
+}
+
+pub struct SceneChoice
+{
+    pub asset_type: AssetType,
+    pub path: String,
+    pub pos: Point2::<f32>,
+    pub scenes: Vec<(usize, String, bool)>, // index, name, selected
+    pub confirmed: bool,
 }
 
 pub struct Asset
@@ -69,6 +463,7 @@ pub struct EditorState
 {
     pub visible: bool,
     pub loading: Arc<RwLock<bool>>,
+    pub loading_progress: LoadingProgressItem,
 
     pub try_out: bool,
     pub selectable: bool,
@@ -78,6 +473,14 @@ pub struct EditorState
 
     pub edit_mode: Option<EditMode>,
 
+    pub snap: bool,
+    pub snap_to_grid_spacing: bool,
+    pub snap_translation: f32,
+    pub snap_rotation: f32, // degrees
+    pub snap_scale: f32,
+
+    pub keymap: Keymap,
+
     pub bottom: BottomPanel,
     pub asset_type: AssetType,
 
@@ -85,19 +488,46 @@ pub struct EditorState
 
     pub hierarchy_expand_all: bool,
     pub hierarchy_filter: String,
+    pub hierarchy_drag: Option<(u64, u64)>, // scene id, node id
+    pub hierarchy_drop: Option<HierarchyDropTarget>,
+
+    pub material_filter: String,
+    pub material_sorting: ListSorting,
+    pub texture_filter: String,
+    pub texture_sorting: ListSorting,
 
     pub selected_scene_id: Option<u64>,
     pub selected_type: SelectionType,
     pub selected_object: String,
+    last_selection: (Option<u64>, String), // to detect selection changes (see clear_stray_highlights)
 
     pub drag_id: Option<String>,
 
+    pub show_bounding_sphere: bool,
+    pub object_tag: String,
+
+    pub measure: bool,
+    pub measure_points: Vec<Point3<f32>>,
+
+    pub pick_focus: bool,
+
     pub dialog_add_component: bool,
+    pub dialog_command_palette: bool,
+    pub command_filter: String,
+    pub command: Option<EditorCommand>, // executed with the next editor update
     pub add_component_id: usize,
     pub add_component_name: String,
 
     pub asset_filter: String,
     pub reuse_materials_by_name: bool,
+    pub orient_z_up: bool, // rotates z-up imports to y-up (root node transformation)
+    pub merge_material: Option<u64>, // material for merged meshes (None: one mesh per material)
+    pub merge_info: Option<String>, // result of the last merge of duplicate materials/textures
+
+    pub recent_files: Vec<(AssetType, String)>, // newest first
+    pub load_request: Option<(AssetType, String)>, // loaded with the next editor update
+    pub scene_choice: Option<SceneChoice>, // files with multiple scenes: the user chooses which ones to import
+
     pub objects: Vec<Asset>,
     pub scenes: Vec<Asset>,
 }
@@ -110,6 +540,7 @@ impl EditorState
         {
             visible: true,
             loading: Arc::new(RwLock::new(false)),
+            loading_progress: LoadingProgress::new_item(),
 
             try_out: false,
             selectable: true,
@@ -119,6 +550,14 @@ impl EditorState
 
             edit_mode: None,
 
+            snap: false,
+            snap_to_grid_spacing: true,
+            snap_translation: 1.0,
+            snap_rotation: 22.5,
+            snap_scale: 0.1,
+
+            keymap: Keymap::new(),
+
             bottom: BottomPanel::Assets,
             asset_type: AssetType::Object,
 
@@ -126,19 +565,46 @@ impl EditorState
 
             hierarchy_expand_all: true,
             hierarchy_filter: String::new(),
+            hierarchy_drag: None,
+            hierarchy_drop: None,
+
+            material_filter: String::new(),
+            material_sorting: ListSorting::Id,
+            texture_filter: String::new(),
+            texture_sorting: ListSorting::Id,
 
             selected_scene_id: None,
             selected_type: SelectionType::None,
             selected_object: String::new(), // type_nodeID/elementID_instanceID
+            last_selection: (None, String::new()),
 
             drag_id: None,
 
+            show_bounding_sphere: false,
+            object_tag: String::new(),
+
+            measure: false,
+            measure_points: vec![],
+
+            pick_focus: false,
+
             dialog_add_component: false,
+            dialog_command_palette: false,
+            command_filter: String::new(),
+            command: None,
             add_component_id: 0,
             add_component_name: "Component".to_string(),
 
             asset_filter: "".to_string(),
             reuse_materials_by_name: false,
+            orient_z_up: false,
+            merge_material: None,
+            merge_info: None,
+
+            recent_files: vec![],
+            load_request: None,
+            scene_choice: None,
+
             objects: vec![],
             scenes: vec![],
         }
@@ -230,8 +696,9 @@ impl EditorState
                 continue;
             }
 
+            // the id is only a node id for objects (cameras, lights, ... are using their own ids)
             let (node_id, _deselect_instance_id) = self.get_object_ids();
-            if let Some(node_id) = node_id
+            if let (Some(node_id), SelectionType::Object) = (node_id, self.selected_type)
             {
                 if let Some(node) = scene.find_node_by_id(node_id)
                 {
@@ -256,6 +723,138 @@ impl EditorState
         self.selected_object.clear();
         self.selected_scene_id = None;
         self.selected_type = SelectionType::None;
+
+        // highlights of selections which were changed without a de-select
+        self.clear_stray_highlights(state);
+    }
+
+    // removes the highlight of all instances which are not part of the selected object (node with children)
+    pub fn clear_stray_highlights(&self, state: &mut State)
+    {
+        let mut keep = vec![];
+
+        if self.selected_type == SelectionType::Object
+        {
+            let (node_id, _instance_id) = self.get_object_ids();
+            let scene = self.selected_scene_id.and_then(|scene_id| state.find_scene_by_id(scene_id));
+
+            if let (Some(scene), Some(node_id)) = (scene, node_id)
+            {
+                if let Some(node) = scene.find_node_by_id(node_id)
+                {
+                    keep.push(node_id);
+                    keep.extend(Scene::list_all_child_nodes(&node.read().unwrap().nodes).iter().map(|child| child.read().unwrap().id));
+                }
+            }
+        }
+
+        for scene in &state.scenes
+        {
+            let selected_scene = self.selected_scene_id == Some(scene.id);
+
+            for node in Scene::list_all_child_nodes(&scene.nodes)
+            {
+                let node = node.read().unwrap();
+
+                if selected_scene && keep.contains(&node.id)
+                {
+                    continue;
+                }
+
+                for instance in node.instances.get_ref()
+                {
+                    // only write if needed (a write marks the instance as changed)
+                    if instance.read().unwrap().get_data().highlight
+                    {
+                        let mut instance = instance.write().unwrap();
+                        instance.get_data_mut().get_mut().highlight = false;
+                    }
+                }
+            }
+        }
+    }
+
+    // safety sweep after the selection was changed (selections are changed from many places - not all of them are de-selecting first)
+    // this is called every frame: the check is only a compare of the scene id and the selection string - the sweep over all instances only runs on a change
+    pub fn check_selection_change(&mut self, state: &mut State)
+    {
+        if self.last_selection.0 == self.selected_scene_id && self.last_selection.1 == self.selected_object
+        {
+            return;
+        }
+
+        self.last_selection = (self.selected_scene_id, self.selected_object.clone());
+        self.clear_stray_highlights(state);
+    }
+
+    pub fn load_recent_files(&mut self)
+    {
+        let content = std::fs::read_to_string(RECENT_FILES_FILE);
+
+        if content.is_err()
+        {
+            return;
+        }
+
+        self.recent_files.clear();
+
+        for line in content.unwrap().lines()
+        {
+            let parts: Vec<&str> = line.splitn(2, '=').collect();
+
+            if parts.len() != 2
+            {
+                continue;
+            }
+
+            let asset_type = match parts[0].trim()
+            {
+                "scene" => AssetType::Scene,
+                "object" => AssetType::Object,
+                _ => continue
+            };
+
+            self.recent_files.push((asset_type, parts[1].trim().to_string()));
+        }
+
+        self.recent_files.truncate(RECENT_FILES_MAX);
+    }
+
+    pub fn save_recent_files(&self)
+    {
+        let mut content = String::new();
+
+        for (asset_type, path) in &self.recent_files
+        {
+            let asset_type = if *asset_type == AssetType::Scene { "scene" } else { "object" };
+            content += format!("{}={}\n", asset_type, path).as_str();
+        }
+
+        if let Err(err) = std::fs::write(RECENT_FILES_FILE, content)
+        {
+            println!("can not save recent files {}: {:?}", RECENT_FILES_FILE, err);
+        }
+    }
+
+    pub fn add_recent_file(&mut self, asset_type: AssetType, path: &str)
+    {
+        if asset_type != AssetType::Scene && asset_type != AssetType::Object
+        {
+            return;
+        }
+
+        self.recent_files.retain(|(_, recent_path)| recent_path != path);
+        self.recent_files.insert(0, (asset_type, path.to_string()));
+        self.recent_files.truncate(RECENT_FILES_MAX);
+
+        self.save_recent_files();
+    }
+
+    // removes files which does not exist anymore
+    pub fn prune_recent_files(&mut self)
+    {
+        self.recent_files.retain(|(_, path)| exists(path));
+        self.save_recent_files();
     }
 
     pub fn set_try_out(&mut self, state: &mut State, try_out: bool)
@@ -331,4 +930,69 @@ impl EditorState
             self.objects = assets;
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra::{Point3, Vector3};
+
+    use crate::state::{state::State, scene::{scene::Scene, components::mesh::Mesh, node::NodeItem}};
+
+    use super::{EditorState, SelectionType};
+
+    fn has_highlight(node: &NodeItem) -> bool
+    {
+        node.read().unwrap().instances.get_ref().iter().any(|instance| instance.read().unwrap().get_data().highlight)
+    }
+
+    #[test]
+    fn selecting_a_light_clears_the_object_highlight()
+    {
+        let mut state = State::new();
+
+        let mut scene = Scene::new(1, "test");
+        scene.add_default_material();
+
+        let mesh = Mesh::new_box(scene.id_manager.get_next_component_id(), "box", Vector3::<f32>::new(2.0, 2.0, 2.0));
+        let node = scene.add_mesh_node("box", mesh);
+        let node_id = node.read().unwrap().id;
+
+        let light_id = scene.add_light_point("light", Point3::<f32>::new(0.0, 5.0, 0.0), Vector3::<f32>::new(1.0, 1.0, 1.0), 1.0).borrow().get_ref().id;
+
+        state.scenes.push(Box::new(scene));
+
+        let mut editor_state = EditorState::new();
+
+        // select the object (like a pick)
+        editor_state.selected_object = format!("objects_{}", node_id);
+        editor_state.selected_scene_id = Some(1);
+        editor_state.selected_type = SelectionType::Object;
+
+        for instance in node.read().unwrap().instances.get_ref()
+        {
+            instance.write().unwrap().get_data_mut().get_mut().highlight = true;
+        }
+
+        // the selected object keeps its highlight
+        editor_state.check_selection_change(&mut state);
+        assert!(has_highlight(&node));
+
+        // select the light without a de-select (like the light panel)
+        editor_state.selected_object = format!("light_{}", light_id);
+        editor_state.selected_scene_id = Some(1);
+        editor_state.selected_type = SelectionType::Light;
+
+        editor_state.check_selection_change(&mut state);
+        assert!(!has_highlight(&node));
+
+        // de-select of the light (the light id must not be used as node id)
+        for instance in node.read().unwrap().instances.get_ref()
+        {
+            instance.write().unwrap().get_data_mut().get_mut().highlight = true;
+        }
+
+        editor_state.de_select_current_item(&mut state);
+        assert!(!has_highlight(&node));
+    }
+}
@@ -0,0 +1,80 @@
+use egui::{Ui, RichText, Color32};
+use strum::IntoEnumIterator;
+
+use crate::{state::gui::helper::generic_items::collapse_with_title, input::keyboard::Key};
+
+use super::editor_state::{EditorState, KeyAction, KeyBinding};
+
+pub fn create_keymap_settings(editor_state: &mut EditorState, ui: &mut Ui)
+{
+    collapse_with_title(ui, "keymap", true, "⌨ Shortcuts", |ui|
+    {
+        let keymap = &mut editor_state.keymap;
+        let mut remove = None;
+
+        egui::Grid::new("keymap_grid").num_columns(3).striped(true).show(ui, |ui|
+        {
+            for (i, (action, binding)) in keymap.bindings.iter().enumerate()
+            {
+                ui.label(action.to_string());
+
+                let text;
+                if keymap.rebind == Some(i)
+                {
+                    text = RichText::new("press a key...").color(Color32::YELLOW);
+                }
+                else
+                {
+                    text = RichText::new(binding.to_string()).strong();
+                }
+
+                if ui.button(text).on_hover_text("click to rebind").clicked()
+                {
+                    if keymap.rebind == Some(i)
+                    {
+                        keymap.rebind = None;
+                    }
+                    else
+                    {
+                        keymap.rebind = Some(i);
+                    }
+                }
+
+                if ui.button(RichText::new("🗑").color(Color32::LIGHT_RED)).on_hover_text("remove binding").clicked()
+                {
+                    remove = Some(i);
+                }
+
+                ui.end_row();
+            }
+        });
+
+        if let Some(remove) = remove
+        {
+            keymap.bindings.remove(remove);
+            keymap.rebind = None;
+            keymap.save();
+        }
+
+        ui.horizontal(|ui|
+        {
+            // add a new binding for the selected action
+            egui::ComboBox::from_id_source("keymap_add").selected_text("add binding").show_ui(ui, |ui|
+            {
+                for action in KeyAction::iter()
+                {
+                    if ui.selectable_label(false, action.to_string()).clicked()
+                    {
+                        keymap.bindings.push((action, KeyBinding::new(Key::Escape)));
+                        keymap.rebind = Some(keymap.bindings.len() - 1);
+                    }
+                }
+            });
+
+            if ui.button("reset to defaults").clicked()
+            {
+                keymap.reset();
+            }
+        });
+    });
+}
@@ -3,15 +3,20 @@ use crate::state::gui::helper::generic_items::collapse_with_title;
 use crate::state::{gui::editor::editor_state::EditorState, state::State};
 use crate::state::gui::editor::editor_state::SettingsPanel;
 use crate::state::scene::scene::Scene;
-use egui::{Visuals, Style, ScrollArea, Ui, RichText, Color32};
+use crate::state::scene::components::mesh::Mesh;
+use crate::resources::resources::exists;
+use nalgebra::{Point3, Vector3};
+use egui::{Visuals, Style, ScrollArea, Ui, RichText, Color32, Pos2, LayerId, Order, Id, Stroke, Align2};
 
 use super::assets::create_asset_section;
+use super::dialogs::open_asset_dialog;
 use super::cameras::{build_camera_list, create_camera_settings};
-use super::editor_state::{SelectionType, BottomPanel};
+use super::editor_state::{SelectionType, BottomPanel, HierarchyDropTarget, AssetType};
+use super::keymap::create_keymap_settings;
 use super::lights::{build_light_list, create_light_settings};
 use super::materials::{build_material_list, create_material_settings};
-use super::modals::create_component_add_modal;
-use super::objects::{build_objects_list, create_object_settings, create_component_settings};
+use super::modals::{create_component_add_modal, create_command_palette_modal, create_loading_progress_modal, create_scene_choice_modal};
+use super::objects::{build_objects_list, create_object_settings, create_component_settings, apply_hierarchy_drop};
 use super::rendering::create_rendering_settings;
 use super::scenes::create_scene_settings;
 use super::statistics::{create_chart, create_statistic};
@@ -37,7 +42,7 @@ pub fn create_frame(ctx: &egui::Context, editor_state: &mut EditorState, state:
     {
         ui.horizontal(|ui|
         {
-            create_file_menu(state, ui);
+            create_file_menu(editor_state, state, ui);
         });
     });
 
@@ -101,14 +106,195 @@ pub fn create_frame(ctx: &egui::Context, editor_state: &mut EditorState, state:
         //});
     });
 
+    // measurement
+    create_measure_overlay(ctx, editor_state, state);
+
+    // markers
+    create_marker_overlay(ctx, state);
+
     // create component
     create_component_add_modal(editor_state, state, ctx);
+
+    // command palette
+    create_command_palette_modal(editor_state, ctx);
+
+    // scene choice (files with multiple scenes)
+    create_scene_choice_modal(editor_state, ctx);
+
+    // loading progress
+    create_loading_progress_modal(editor_state, ctx);
+}
+
+fn create_measure_overlay(ctx: &egui::Context, editor_state: &mut EditorState, state: &mut State)
+{
+    if !editor_state.measure || editor_state.measure_points.len() == 0
+    {
+        return;
+    }
+
+    // line between the measured points
+    let mut camera = None;
+    for scene in &state.scenes
+    {
+        camera = scene.cameras.iter().find(|camera| camera.enabled);
+
+        if camera.is_some()
+        {
+            break;
+        }
+    }
+
+    if let Some(camera) = camera
+    {
+        let to_screen = |point: &Point3<f32>| -> Option<Pos2>
+        {
+            // viewport coordinates are bottom left based
+            let pos = camera.get_viewport_coordinates_from_point(point, state.width, state.height)?;
+            Some(Pos2::new(pos.x / state.scale_factor, (state.height as f32 - pos.y) / state.scale_factor))
+        };
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Background, Id::new("measure_line")));
+        let points: Vec<Option<Pos2>> = editor_state.measure_points.iter().map(|point| to_screen(point)).collect();
+
+        if let (Some(Some(p0)), Some(Some(p1))) = (points.get(0), points.get(1))
+        {
+            painter.line_segment([*p0, *p1], Stroke::new(2.0, Color32::YELLOW));
+        }
+
+        for point in &points
+        {
+            if let Some(point) = point
+            {
+                painter.circle_filled(*point, 4.0, Color32::YELLOW);
+            }
+        }
+    }
+
+    // info
+    egui::Area::new("measure_info").anchor(Align2::CENTER_TOP, [0.0, 60.0]).show(ctx, |ui|
+    {
+        egui::Frame::popup(ui.style()).show(ui, |ui|
+        {
+            if editor_state.measure_points.len() < 2
+            {
+                ui.label("📏 pick the second point");
+            }
+            else
+            {
+                let delta = editor_state.measure_points[1] - editor_state.measure_points[0];
+
+                ui.label(RichText::new(format!("📏 distance: {:.3}", delta.magnitude())).strong());
+                ui.label(format!("dx: {:.3} dy: {:.3} dz: {:.3}", delta.x, delta.y, delta.z));
+            }
+        });
+    });
+}
+
+fn create_marker_overlay(ctx: &egui::Context, state: &State)
+{
+    let painter = ctx.layer_painter(LayerId::new(Order::Background, Id::new("marker_icons")));
+
+    for scene in &state.scenes
+    {
+        if !scene.visible
+        {
+            continue;
+        }
+
+        let camera = scene.cameras.iter().find(|camera| camera.enabled);
+        if camera.is_none()
+        {
+            continue;
+        }
+        let camera = camera.unwrap();
+
+        for marker in scene.find_markers("")
+        {
+            let marker = marker.read().unwrap();
+
+            if !marker.visible
+            {
+                continue;
+            }
+
+            // viewport coordinates are bottom left based
+            if let Some(pos) = camera.get_viewport_coordinates_from_point(&marker.get_world_position(), state.width, state.height)
+            {
+                let pos = Pos2::new(pos.x / state.scale_factor, (state.height as f32 - pos.y) / state.scale_factor);
+                let kind = marker.marker.clone().unwrap_or_default();
+
+                painter.text(pos, Align2::CENTER_BOTTOM, "📍", egui::FontId::proportional(16.0), Color32::LIGHT_GREEN);
+                painter.text(pos, Align2::CENTER_TOP, kind, egui::FontId::proportional(10.0), Color32::LIGHT_GREEN);
+            }
+        }
+    }
 }
 
-fn create_file_menu(state: &mut State, ui: &mut Ui)
+fn create_file_menu(editor_state: &mut EditorState, state: &mut State, ui: &mut Ui)
 {
     ui.menu_button("File", |ui|
     {
+        if ui.button("Open Scene...").clicked()
+        {
+            ui.close_menu();
+
+            if let Some(path) = open_asset_dialog()
+            {
+                editor_state.load_request = Some((AssetType::Scene, path));
+            }
+        }
+
+        if ui.button("Open Object...").clicked()
+        {
+            ui.close_menu();
+
+            if let Some(path) = open_asset_dialog()
+            {
+                editor_state.load_request = Some((AssetType::Object, path));
+            }
+        }
+
+        ui.menu_button("Recent", |ui|
+        {
+            if editor_state.recent_files.is_empty()
+            {
+                ui.label("no recent files");
+            }
+
+            let mut load = None;
+            for (asset_type, path) in &editor_state.recent_files
+            {
+                let icon = if *asset_type == AssetType::Scene { "🎬" } else { "◼" };
+
+                // missing files are disabled
+                if ui.add_enabled(exists(path), egui::Button::new(format!("{} {}", icon, path))).clicked()
+                {
+                    load = Some((*asset_type, path.clone()));
+                }
+            }
+
+            if load.is_some()
+            {
+                ui.close_menu();
+                editor_state.load_request = load;
+            }
+
+            ui.separator();
+
+            if ui.button("Remove Missing").clicked()
+            {
+                editor_state.prune_recent_files();
+            }
+
+            if ui.button("Clear").clicked()
+            {
+                editor_state.recent_files.clear();
+                editor_state.save_recent_files();
+            }
+        });
+
+        ui.separator();
+
         if ui.button("Exit").clicked()
         {
             state.exit = true;
@@ -137,6 +323,46 @@ fn create_tool_menu(editor_state: &mut EditorState, state: &mut State, ui: &mut
             }
 
             ui.toggle_value(&mut editor_state.fly_camera, RichText::new("✈").size(icon_size)).on_hover_text("fly camera");
+
+            // measure
+            if ui.toggle_value(&mut editor_state.measure, RichText::new("📏").size(icon_size)).on_hover_text("measure distance (hold ctrl to snap to grid)").changed()
+            {
+                editor_state.measure_points.clear();
+
+                if editor_state.measure
+                {
+                    editor_state.de_select_current_item(state);
+                }
+            }
+
+            // x-ray
+            ui.toggle_value(&mut state.rendering.xray_selected, RichText::new("👻").size(icon_size)).on_hover_text("x-ray: show selected objects through other objects");
+
+            // snapping
+            ui.toggle_value(&mut editor_state.snap, RichText::new("🧲").size(icon_size)).on_hover_text("snap while moving, rotating and scaling (or hold ctrl)");
+
+            ui.menu_button(RichText::new("⏷").size(icon_size * 0.6), |ui|
+            {
+                ui.checkbox(&mut editor_state.snap_to_grid_spacing, "use grid spacing");
+
+                ui.horizontal(|ui|
+                {
+                    ui.label("translation:");
+                    ui.add_enabled(!editor_state.snap_to_grid_spacing, egui::DragValue::new(&mut editor_state.snap_translation).speed(0.01).clamp_range(0.001..=1000.0));
+                });
+
+                ui.horizontal(|ui|
+                {
+                    ui.label("rotation:");
+                    ui.add(egui::DragValue::new(&mut editor_state.snap_rotation).speed(0.5).clamp_range(0.1..=180.0).suffix("°"));
+                });
+
+                ui.horizontal(|ui|
+                {
+                    ui.label("scale:");
+                    ui.add(egui::DragValue::new(&mut editor_state.snap_scale).speed(0.01).clamp_range(0.001..=100.0));
+                });
+            }).response.on_hover_text("snap increments");
         });
 
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui|
@@ -232,6 +458,7 @@ fn create_right_sidebar(editor_state: &mut EditorState, state: &mut State, ui: &
         }
 
         ui.selectable_value(&mut editor_state.settings, SettingsPanel::Rendering, "📷 Rendering");
+        ui.selectable_value(&mut editor_state.settings, SettingsPanel::Keymap, "⌨ Keymap");
     });
     ui.separator();
 
@@ -253,6 +480,7 @@ fn create_right_sidebar(editor_state: &mut EditorState, state: &mut State, ui: &
             SettingsPanel::Light => if light_settings { create_light_settings(editor_state, state, ui); },
             SettingsPanel::Scene => create_scene_settings(editor_state, state, ui),
             SettingsPanel::Rendering => create_rendering_settings(editor_state, state, ui),
+            SettingsPanel::Keymap => create_keymap_settings(editor_state, ui),
         }
     });
 }
@@ -268,12 +496,14 @@ fn create_hierarchy(editor_state: &mut EditorState, state: &mut State, ui: &mut
         ui.toggle_value(&mut editor_state.hierarchy_expand_all, "⊞").on_hover_text("expand all items");
     });
 
+    editor_state.hierarchy_drop = None;
+
     for scene in &mut state.scenes
     {
         let scene_id = scene.id;
         let id = format!("scene_{}", scene_id);
-        let ui_id = ui.make_persistent_id(id.clone());
-        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), ui_id, editor_state.hierarchy_expand_all).show_header(ui, |ui|
+        let ui_id = ui.make_persistent_id(hierarchy_state_id(editor_state, &id));
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), ui_id, hierarchy_default_open(editor_state)).show_header(ui, |ui|
         {
             ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui|
             {
@@ -310,6 +540,82 @@ fn create_hierarchy(editor_state: &mut EditorState, state: &mut State, ui: &mut
             create_hierarchy_type_entries(editor_state, scene, ui);
         });
     }
+
+    // drop the dragged node (on release)
+    if let Some((scene_id, node_id)) = editor_state.hierarchy_drag
+    {
+        if !ui.input(|i| i.pointer.any_down())
+        {
+            if let (Some(target), Some(scene)) = (editor_state.hierarchy_drop, state.find_scene_by_id_mut(scene_id))
+            {
+                apply_hierarchy_drop(scene, node_id, target);
+            }
+
+            editor_state.hierarchy_drag = None;
+            editor_state.hierarchy_drop = None;
+        }
+    }
+}
+
+// the filtered hierarchy has its own collapsing state (see build_objects_list)
+fn hierarchy_state_id(editor_state: &EditorState, id: &str) -> String
+{
+    if editor_state.hierarchy_filter.trim().is_empty()
+    {
+        id.to_string()
+    }
+    else
+    {
+        format!("{}_filtered", id)
+    }
+}
+
+fn hierarchy_default_open(editor_state: &EditorState) -> bool
+{
+    editor_state.hierarchy_expand_all || !editor_state.hierarchy_filter.trim().is_empty()
+}
+
+fn create_add_primitive_menu(scene: &mut Box<Scene>, ui: &mut Ui)
+{
+    let mut primitive = None;
+
+    for name in ["Box", "Sphere", "Plane", "Cylinder", "Cone"]
+    {
+        if ui.button(name).clicked()
+        {
+            primitive = Some(name);
+        }
+    }
+
+    if let Some(name) = primitive
+    {
+        ui.close_menu();
+        add_primitive(scene, name);
+    }
+}
+
+pub fn add_primitive(scene: &mut Box<Scene>, name: &str)
+{
+    let id = scene.id_manager.get_next_component_id();
+
+    let mesh = match name
+    {
+        "Box" => Mesh::new_box(id, "mesh", Vector3::<f32>::new(1.0, 1.0, 1.0)),
+        "Sphere" => Mesh::new_sphere(id, "mesh", 0.5, 32, 16),
+        "Cylinder" => Mesh::new_cylinder(id, "mesh", 0.5, 1.0, 32),
+        "Cone" => Mesh::new_cone(id, "mesh", 0.5, 1.0, 32),
+        _ =>
+        {
+            let x0 = Point3::<f32>::new(-0.5, 0.0, 0.5);
+            let x1 = Point3::<f32>::new(0.5, 0.0, 0.5);
+            let x2 = Point3::<f32>::new(0.5, 0.0, -0.5);
+            let x3 = Point3::<f32>::new(-0.5, 0.0, -0.5);
+
+            Mesh::new_plane(id, "mesh", x0, x1, x2, x3)
+        }
+    };
+
+    scene.add_mesh_node(name, mesh);
 }
 
 fn create_hierarchy_type_entries(editor_state: &mut EditorState, scene: &mut Box<Scene>, ui: &mut Ui)
@@ -319,13 +625,35 @@ fn create_hierarchy_type_entries(editor_state: &mut EditorState, scene: &mut Box
     // objects
     {
         let id = format!("objects_{}", scene.id);
-        let ui_id = ui.make_persistent_id(id.clone());
-        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), ui_id, editor_state.hierarchy_expand_all).show_header(ui, |ui|
+        let ui_id = ui.make_persistent_id(hierarchy_state_id(editor_state, &id));
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), ui_id, hierarchy_default_open(editor_state)).show_header(ui, |ui|
         {
             ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui|
             {
                 let mut selection; if editor_state.selected_scene_id == Some(scene_id) && editor_state.selected_object.is_empty() &&  editor_state.selected_type == SelectionType::Object { selection = true; } else { selection = false; }
-                if ui.toggle_value(&mut selection, RichText::new("◼ Objects").color(Color32::LIGHT_GREEN).strong()).clicked()
+
+                let toggle = ui.toggle_value(&mut selection, RichText::new("◼ Objects").color(Color32::LIGHT_GREEN).strong());
+
+                // dropping on the objects item: move to the scene root
+                if let Some((drag_scene_id, _)) = editor_state.hierarchy_drag
+                {
+                    let pos = ui.ctx().pointer_interact_pos();
+
+                    if drag_scene_id == scene_id && ui.input(|i| i.pointer.is_decidedly_dragging()) && pos.is_some() && toggle.rect.contains(pos.unwrap())
+                    {
+                        ui.painter().rect_stroke(toggle.rect, 2.0, Stroke::new(2.0, Color32::YELLOW));
+                        editor_state.hierarchy_drop = Some(HierarchyDropTarget::Root);
+                    }
+                }
+                let toggle = toggle.context_menu(|ui|
+                {
+                    ui.menu_button("Add Primitive", |ui|
+                    {
+                        create_add_primitive_menu(scene, ui);
+                    });
+                });
+
+                if toggle.clicked()
                 {
                     if selection
                     {
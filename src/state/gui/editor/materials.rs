@@ -2,18 +2,38 @@ use std::collections::HashMap;
 
 use egui::{Ui, RichText, Color32};
 
-use crate::{state::{scene::{components::material::{MaterialItem, ALL_TEXTURE_TYPES, Material}, scene::Scene}, state::State, gui::{helper::{generic_items::{collapse_with_title, self}, info_box::info_box}, editor::dialogs::load_texture_dialog}}, component_downcast_mut, helper::concurrency::thread::spawn_thread};
+use crate::{state::{scene::{components::material::{MaterialItem, ALL_TEXTURE_TYPES, Material, TextureChannel}, scene::Scene}, state::State, gui::{helper::{generic_items::{collapse_with_title, self}, info_box::info_box}, editor::dialogs::load_texture_dialog}}, component_downcast_mut, helper::concurrency::thread::spawn_thread};
 
-use super::editor_state::{EditorState, SelectionType, SettingsPanel};
+use super::editor_state::{EditorState, SelectionType, SettingsPanel, ListSorting};
 
 pub fn build_material_list(editor_state: &mut EditorState, materials: &HashMap<u64, MaterialItem>, ui: &mut Ui, scene_id: u64)
 {
+    ui.horizontal(|ui|
+    {
+        ui.label("🔍");
+        ui.add(egui::TextEdit::singleline(&mut editor_state.material_filter).desired_width(80.0));
+
+        ui.selectable_value(&mut editor_state.material_sorting, ListSorting::Id, "id").on_hover_text("sort by id");
+        ui.selectable_value(&mut editor_state.material_sorting, ListSorting::Name, "name").on_hover_text("sort by name");
+    });
+
+    // hash map order is random -> sort by id or name
+    let filter = editor_state.material_filter.trim().to_lowercase();
+
+    let mut items: Vec<(u64, String)> = materials.iter().map(|(material_id, material)| (*material_id, material.read().unwrap().get_base().name.clone())).collect();
+    items.retain(|(_, name)| filter.is_empty() || name.to_lowercase().contains(&filter));
+
+    match editor_state.material_sorting
+    {
+        ListSorting::Id => items.sort_by_key(|(material_id, _)| *material_id),
+        ListSorting::Name => items.sort_by(|a, b| a.1.to_lowercase().cmp(&b.1.to_lowercase()).then(a.0.cmp(&b.0))),
+    }
+
     ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui|
     {
-        for (material_id, material) in materials
+        for (material_id, name) in items
         {
-            let material = material.read().unwrap();
-            let headline_name = format!("⚫ {}: {}", material_id, material.get_base().name);
+            let headline_name = format!("⚫ {}: {}", material_id, name);
 
             let id = format!("material_{}", material_id);
 
@@ -113,6 +133,7 @@ pub fn create_material_settings(editor_state: &mut EditorState, state: &mut Stat
                     let texture = material.get_texture_by_type(texture_type);
                     let texture = texture.unwrap();
                     let mut enabled = texture.enabled;
+                    let mut channel = texture.channel;
                     let texture = texture.get();
                     let mut texture = texture.write().unwrap();
                     let texture_id = texture.id;
@@ -122,6 +143,7 @@ pub fn create_material_settings(editor_state: &mut EditorState, state: &mut Stat
 
                     let mut remove_texture = false;
                     let mut changed = false;
+                    let mut channel_changed = false;
 
                     generic_items::collapse(ui, id, true, |ui|
                     {
@@ -154,6 +176,23 @@ pub fn create_material_settings(editor_state: &mut EditorState, state: &mut Stat
                     },
                     |ui|
                     {
+                        // channel selection for packed textures
+                        if texture_type.is_single_channel()
+                        {
+                            ui.horizontal(|ui|
+                            {
+                                ui.label("Channel:");
+
+                                egui::ComboBox::from_id_source(format!("texture_channel_{}", texture_type.to_string())).selected_text(format!("{channel:?}")).show_ui(ui, |ui|
+                                {
+                                    channel_changed = ui.selectable_value(&mut channel, TextureChannel::R, "R").changed() || channel_changed;
+                                    channel_changed = ui.selectable_value(&mut channel, TextureChannel::G, "G").changed() || channel_changed;
+                                    channel_changed = ui.selectable_value(&mut channel, TextureChannel::B, "B").changed() || channel_changed;
+                                    channel_changed = ui.selectable_value(&mut channel, TextureChannel::A, "A").changed() || channel_changed;
+                                });
+                            });
+                        }
+
                         texture.ui_info(ui);
                     });
 
@@ -162,6 +201,11 @@ pub fn create_material_settings(editor_state: &mut EditorState, state: &mut Stat
                         material.set_texture_state(texture_type , enabled);
                     }
 
+                    if channel_changed
+                    {
+                        material.set_texture_channel(texture_type, channel);
+                    }
+
                     if remove_texture
                     {
                         material.remove_texture(texture_type)
@@ -1,6 +1,9 @@
+use egui::RichText;
+use strum::IntoEnumIterator;
+
 use crate::state::{state::State, gui::helper::generic_items::modal_with_title};
 
-use super::editor_state::EditorState;
+use super::editor_state::{EditorState, EditorCommand};
 
 pub fn create_component_add_modal(editor_state: &mut EditorState, state: &mut State, ctx: &egui::Context)
 {
@@ -67,4 +70,181 @@ pub fn create_component_add_modal(editor_state: &mut EditorState, state: &mut St
     {
         editor_state.dialog_add_component = dialog_add_component;
     }
-}
\ No newline at end of file
+}
+pub fn create_command_palette_modal(editor_state: &mut EditorState, ctx: &egui::Context)
+{
+    let mut dialog_command_palette = editor_state.dialog_command_palette;
+    let mut execute = None;
+
+    modal_with_title(ctx, &mut dialog_command_palette, "Command Palette", |ui|
+    {
+        ui.add(egui::TextEdit::singleline(&mut editor_state.command_filter).hint_text("search...").desired_width(300.0)).request_focus();
+
+        // best matches first (same score: order of the command list)
+        let mut commands: Vec<(EditorCommand, i32)> = EditorCommand::iter().filter_map(|command|
+        {
+            fuzzy_score(&editor_state.command_filter, command.name()).map(|score| (command, score))
+        }).collect();
+        commands.sort_by(|a, b| b.1.cmp(&a.1));
+
+        if ui.input(|i| i.key_pressed(egui::Key::Enter))
+        {
+            execute = commands.first().map(|(command, _)| *command);
+        }
+
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui|
+        {
+            egui::Grid::new("command_palette_grid").num_columns(2).striped(true).show(ui, |ui|
+            {
+                for (command, _) in &commands
+                {
+                    if ui.selectable_label(false, command.name()).clicked()
+                    {
+                        execute = Some(*command);
+                    }
+
+                    let shortcut = command.key_action().and_then(|action| editor_state.keymap.binding_string(action));
+                    ui.label(RichText::new(shortcut.unwrap_or_default()).weak());
+
+                    ui.end_row();
+                }
+            });
+        });
+    });
+
+    if ctx.input(|i| i.key_pressed(egui::Key::Escape))
+    {
+        dialog_command_palette = false;
+    }
+
+    if let Some(command) = execute
+    {
+        editor_state.command = Some(command);
+        dialog_command_palette = false;
+    }
+
+    editor_state.dialog_command_palette = dialog_command_palette;
+}
+
+pub fn create_scene_choice_modal(editor_state: &mut EditorState, ctx: &egui::Context)
+{
+    if editor_state.scene_choice.is_none()
+    {
+        return;
+    }
+
+    let mut open = true;
+
+    modal_with_title(ctx, &mut open, "Import Scenes", |ui|
+    {
+        let choice = editor_state.scene_choice.as_mut().unwrap();
+
+        ui.label("The file contains multiple scenes.\nEach selected scene is imported as its own root node.");
+
+        for (_, name, selected) in &mut choice.scenes
+        {
+            ui.checkbox(selected, name.as_str());
+        }
+
+        let any_selected = choice.scenes.iter().any(|(_, _, selected)| *selected);
+
+        if ui.add_enabled(any_selected, egui::Button::new("Import")).clicked()
+        {
+            choice.confirmed = true;
+        }
+    });
+
+    if !open
+    {
+        editor_state.scene_choice = None;
+    }
+}
+
+pub fn create_loading_progress_modal(editor_state: &mut EditorState, ctx: &egui::Context)
+{
+    let progress = editor_state.loading_progress.clone();
+
+    // only loaders with progress reporting
+    if !progress.read().unwrap().is_active()
+    {
+        return;
+    }
+
+    let mut open = true;
+
+    modal_with_title(ctx, &mut open, "Loading", |ui|
+    {
+        let (stage, current, total, percentage, canceled);
+        {
+            let progress = progress.read().unwrap();
+            stage = progress.stage.clone();
+            current = progress.current;
+            total = progress.total;
+            percentage = progress.percentage();
+            canceled = progress.is_canceled();
+        }
+
+        ui.add(egui::ProgressBar::new(percentage).text(format!("{} {}/{}", stage, current, total)).desired_width(300.0));
+
+        if canceled
+        {
+            ui.label("canceling...");
+        }
+        else if ui.button("Cancel").clicked()
+        {
+            progress.write().unwrap().cancel();
+        }
+    });
+
+    // closing the window cancels the loading
+    if !open
+    {
+        progress.write().unwrap().cancel();
+    }
+
+    ctx.request_repaint();
+}
+
+// all characters of the pattern needs to be found in order - consecutive characters and word starts are rated higher
+fn fuzzy_score(pattern: &str, text: &str) -> Option<i32>
+{
+    let pattern: Vec<char> = pattern.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let mut score = 0;
+    let mut pattern_index = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (i, c) in text.iter().enumerate()
+    {
+        if pattern_index >= pattern.len()
+        {
+            break;
+        }
+
+        if *c == pattern[pattern_index]
+        {
+            score += 1;
+
+            if last_match.is_some() && last_match.unwrap() + 1 == i
+            {
+                score += 5;
+            }
+
+            if i == 0 || !text[i - 1].is_alphanumeric()
+            {
+                score += 3;
+            }
+
+            last_match = Some(i);
+            pattern_index += 1;
+        }
+    }
+
+    if pattern_index < pattern.len()
+    {
+        return None;
+    }
+
+    Some(score)
+}
@@ -1,14 +1,24 @@
 use egui::{Ui, RichText, Color32};
+use nalgebra::Matrix4;
 
-use crate::{state::{scene::{node::NodeItem, components::{mesh::Mesh, material::Material}, scene::Scene}, state::State, gui::helper::generic_items::{collapse_with_title, self}}, component_downcast};
+use crate::{state::{scene::{node::{NodeItem, Node}, components::{mesh::Mesh, material::{Material, MaterialItem}, transformation::Transformation, alpha::Alpha}, scene::Scene}, state::State, gui::helper::generic_items::{collapse_with_title, self}}, component_downcast, component_downcast_mut};
 
-use super::editor_state::{EditorState, SelectionType, SettingsPanel};
+use super::editor_state::{EditorState, SelectionType, SettingsPanel, HierarchyDropTarget};
 
 pub fn build_objects_list(editor_state: &mut EditorState, ui: &mut Ui, nodes: &Vec<NodeItem>, scene_id: u64, parent_visible: bool)
 {
+    let filter = editor_state.hierarchy_filter.trim().to_lowercase();
+
     for node_arc in nodes
     {
         let node = node_arc.read().unwrap();
+
+        // hide nodes where neither the node itself nor a descendant matches (name or tag)
+        if !filter.is_empty() && !node.matches_filter_recursive(&filter)
+        {
+            continue;
+        }
+
         let child_nodes = &node.nodes.clone();
 
         let visible = node.visible && parent_visible;
@@ -17,8 +27,22 @@ pub fn build_objects_list(editor_state: &mut EditorState, ui: &mut Ui, nodes: &V
         let node_id = node.id;
 
         let id = format!("objects_{}", node_id);
-        let ui_id = ui.make_persistent_id(id.clone());
-        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), ui_id, editor_state.hierarchy_expand_all).show_header(ui, |ui|
+
+        // while filtering: separate collapsing state (branches with matches are expanded) - so the previous state is restored after clearing the filter
+        let ui_id;
+        let default_open;
+        if filter.is_empty()
+        {
+            ui_id = ui.make_persistent_id(id.clone());
+            default_open = editor_state.hierarchy_expand_all;
+        }
+        else
+        {
+            ui_id = ui.make_persistent_id(format!("objects_filtered_{}", node_id));
+            default_open = child_nodes.iter().any(|child| child.read().unwrap().matches_filter_recursive(&filter));
+        }
+
+        egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), ui_id, default_open).show_header(ui, |ui|
         {
             ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui|
             {
@@ -36,7 +60,7 @@ pub fn build_objects_list(editor_state: &mut EditorState, ui: &mut Ui, nodes: &V
                     headline_name = format!("◻ {}: {}", node_id, name.clone());
                 }
 
-                let heading;
+                let mut heading;
                 if visible
                 {
                     heading = RichText::new(headline_name).strong()
@@ -46,8 +70,15 @@ pub fn build_objects_list(editor_state: &mut EditorState, ui: &mut Ui, nodes: &V
                     heading = RichText::new(headline_name).strikethrough();
                 }
 
+                if !filter.is_empty() && node.matches_filter(&filter)
+                {
+                    heading = heading.color(Color32::YELLOW);
+                }
+
                 let mut selection; if editor_state.selected_object == id { selection = true; } else { selection = false; }
-                if ui.toggle_value(&mut selection, heading).clicked()
+                let toggle = ui.toggle_value(&mut selection, heading).interact(egui::Sense::drag());
+
+                if toggle.clicked()
                 {
                     if editor_state.selected_object != id
                     {
@@ -66,6 +97,22 @@ pub fn build_objects_list(editor_state: &mut EditorState, ui: &mut Ui, nodes: &V
                         editor_state.selected_scene_id = None;
                     }
                 }
+
+                // drag and drop (reparent / reorder)
+                if toggle.drag_started()
+                {
+                    editor_state.hierarchy_drag = Some((scene_id, node_id));
+                }
+
+                if editor_state.hierarchy_drag == Some((scene_id, node_id)) && ui.input(|i| i.pointer.is_decidedly_dragging())
+                {
+                    egui::show_tooltip_at_pointer(ui.ctx(), egui::Id::new("hierarchy_drag"), |ui|
+                    {
+                        ui.label(name.clone());
+                    });
+                }
+
+                set_hierarchy_drop_target(editor_state, ui, &toggle, scene_id, node_id);
             });
 
         }).body(|ui|
@@ -83,6 +130,90 @@ pub fn build_objects_list(editor_state: &mut EditorState, ui: &mut Ui, nodes: &V
     }
 }
 
+// upper/lower quarter of the item: reorder (before/after) - center: reparent (into)
+fn set_hierarchy_drop_target(editor_state: &mut EditorState, ui: &mut Ui, response: &egui::Response, scene_id: u64, node_id: u64)
+{
+    if let Some((drag_scene_id, drag_node_id)) = editor_state.hierarchy_drag
+    {
+        if drag_scene_id != scene_id || drag_node_id == node_id || !ui.input(|i| i.pointer.is_decidedly_dragging())
+        {
+            return;
+        }
+
+        let pos = ui.ctx().pointer_interact_pos();
+
+        if pos.is_none() || !response.rect.contains(pos.unwrap())
+        {
+            return;
+        }
+
+        let pos = pos.unwrap();
+        let rect = response.rect;
+        let stroke = egui::Stroke::new(2.0, Color32::YELLOW);
+
+        if pos.y < rect.top() + rect.height() * 0.25
+        {
+            ui.painter().hline(rect.x_range(), rect.top(), stroke);
+            editor_state.hierarchy_drop = Some(HierarchyDropTarget::Before(node_id));
+        }
+        else if pos.y > rect.bottom() - rect.height() * 0.25
+        {
+            ui.painter().hline(rect.x_range(), rect.bottom(), stroke);
+            editor_state.hierarchy_drop = Some(HierarchyDropTarget::After(node_id));
+        }
+        else
+        {
+            ui.painter().rect_stroke(rect, 2.0, stroke);
+            editor_state.hierarchy_drop = Some(HierarchyDropTarget::Into(node_id));
+        }
+    }
+}
+
+// applies the drop of a dragged hierarchy node (the world transformation is kept)
+pub fn apply_hierarchy_drop(scene: &mut Box<Scene>, node_id: u64, target: HierarchyDropTarget)
+{
+    match target
+    {
+        HierarchyDropTarget::Root => { scene.reparent_node(node_id, None, None); },
+        HierarchyDropTarget::Into(parent_id) => { scene.reparent_node(node_id, Some(parent_id), None); },
+        HierarchyDropTarget::Before(sibling_id) | HierarchyDropTarget::After(sibling_id) =>
+        {
+            let sibling = scene.find_node_by_id(sibling_id);
+
+            if sibling.is_none()
+            {
+                return;
+            }
+
+            let parent = sibling.unwrap().read().unwrap().parent.clone();
+            let parent_id;
+            let index;
+
+            if let Some(parent) = parent
+            {
+                let parent = parent.read().unwrap();
+                parent_id = Some(parent.id);
+                index = parent.nodes.iter().position(|node| node.read().unwrap().id == sibling_id);
+            }
+            else
+            {
+                parent_id = None;
+                index = scene.nodes.iter().position(|node| node.read().unwrap().id == sibling_id);
+            }
+
+            if let Some(mut index) = index
+            {
+                if let HierarchyDropTarget::After(_) = target
+                {
+                    index += 1;
+                }
+
+                scene.reparent_node(node_id, parent_id, Some(index));
+            }
+        },
+    }
+}
+
 pub fn build_instances_list(editor_state: &mut EditorState, ui: &mut Ui, node: NodeItem, scene_id: u64, parent_visible: bool)
 {
     let node = node.read().unwrap();
@@ -225,6 +356,14 @@ pub fn create_object_settings(editor_state: &mut EditorState, state: &mut State,
 
     let bounding_box_info = node.read().unwrap().get_bounding_info(true);
 
+    let bounding_sphere_info;
+    {
+        let node = node.read().unwrap();
+
+        let sphere_instance_id = instance_id.or(node.instances.get_ref().first().map(|instance| instance.read().unwrap().id));
+        bounding_sphere_info = sphere_instance_id.and_then(|instance_id| node.get_world_bounding_sphere(instance_id));
+    }
+
     // General
     collapse_with_title(ui, "object_data", true, "ℹ Object Data", |ui|
     {
@@ -234,12 +373,56 @@ pub fn create_object_settings(editor_state: &mut EditorState, state: &mut State,
             ui.label(format!("name: {}", node.name));
             ui.label(format!("id: {}", node.id));
 
+            if let Some(source_index) = node.source_index
+            {
+                ui.label(format!("source: {} (#{})", node.source_name.as_deref().unwrap_or("unnamed"), source_index)).on_hover_text("original name and index of the node in the loaded file");
+            }
+
+            if let Some(marker) = &node.marker
+            {
+                ui.label(format!("marker: {}", marker)).on_hover_text("see Scene::find_markers");
+            }
+
+            // custom properties of the loaded file (read only)
+            let mut extras = vec![("node".to_string(), node.extras.clone())];
+            for component in &node.components
+            {
+                let component = component.read().unwrap();
+                let base = component.get_base();
+                extras.push((format!("{} {}", base.icon, base.name), base.extras.clone()));
+            }
+
+            for (owner, extras) in extras
+            {
+                if extras.is_empty()
+                {
+                    continue;
+                }
+
+                let mut extras: Vec<(String, String)> = extras.into_iter().collect();
+                extras.sort();
+
+                ui.label(RichText::new(format!("🏷 extras ({})", owner)).strong());
+                for (key, value) in extras
+                {
+                    ui.label(format!(" ⚫ {}: {}", key, value));
+                }
+            }
+
             if let Some(bounding_box_info) = bounding_box_info
             {
                 ui.label(format!("bbox min: x={:.3} y={:.3} z={:.3}", bounding_box_info.0.x, bounding_box_info.0.y, bounding_box_info.0.z));
                 ui.label(format!("bbox max: x={:.3} y={:.3} z={:.3}", bounding_box_info.1.x, bounding_box_info.1.y, bounding_box_info.1.z));
             }
+
+            if let Some((center, radius)) = bounding_sphere_info
+            {
+                ui.label(format!("bsphere center: x={:.3} y={:.3} z={:.3}", center.x, center.y, center.z));
+                ui.label(format!("bsphere radius: {:.3}", radius));
+            }
         }
+
+        ui.checkbox(&mut editor_state.show_bounding_sphere, "show bounding sphere");
     });
 
 
@@ -259,6 +442,56 @@ pub fn create_object_settings(editor_state: &mut EditorState, state: &mut State,
         ui.label(format!(" ⚫ meshes: {}", all_meshes_amout));
         ui.label(format!(" ⚫ vertices: {}", all_vertices_amout));
         ui.label(format!(" ⚫ indices: {}", all_indices_amout));
+
+        // validation of the own mesh
+        let mut split = false;
+        let mesh = node.read().unwrap().find_component::<Mesh>();
+        if let Some(mesh) = mesh
+        {
+            component_downcast_mut!(mesh, Mesh);
+
+            ui.label(RichText::new("🔍 mesh validation").strong());
+
+            if let Some(validation) = mesh.get_validation()
+            {
+                if validation.is_valid()
+                {
+                    ui.label(" ⚫ no issues found");
+                }
+                else
+                {
+                    ui.label(format!(" ⚫ degenerate triangles: {}", validation.degenerate_triangles));
+                    ui.label(format!(" ⚫ duplicate triangles: {}", validation.duplicate_triangles));
+                    ui.label(format!(" ⚫ invalid vertices: {}", validation.invalid_vertices)).on_hover_text("NaN or inf");
+                    ui.label(format!(" ⚫ out of range indices: {}", validation.out_of_range_indices));
+                }
+            }
+
+            ui.horizontal(|ui|
+            {
+                if ui.button("validate").clicked()
+                {
+                    mesh.validate();
+                }
+
+                if ui.button("clean mesh").on_hover_text("removes degenerate and duplicate triangles and clamps indices").clicked()
+                {
+                    mesh.clean();
+                    mesh.validate();
+                }
+
+                if ui.button("split parts").on_hover_text("creates a node for each connected part of the mesh (faces sharing vertices)\nthe source node is hidden").clicked()
+                {
+                    split = true;
+                }
+            });
+        }
+
+        if split
+        {
+            let scene = state.find_scene_by_id_mut(scene_id).unwrap();
+            scene.split_connected_components(node.clone());
+        }
     });
 
     // Settings
@@ -269,13 +502,17 @@ pub fn create_object_settings(editor_state: &mut EditorState, state: &mut State,
         let mut visible;
         let mut root_node: bool;
         let mut render_children_first;
+        let mut is_static;
         let mut alpha_index;
         let mut name;
+        let mut layer_mask;
         {
             let node = node.read().unwrap();
             visible = node.visible;
+            layer_mask = node.layer_mask;
             root_node = node.root_node;
             render_children_first = node.render_children_first;
+            is_static = node.is_static;
             alpha_index = node.alpha_index;
             name = node.name.clone();
         }
@@ -289,9 +526,51 @@ pub fn create_object_settings(editor_state: &mut EditorState, state: &mut State,
         changed = ui.checkbox(&mut root_node, "root node").changed() || changed;
         changed = ui.checkbox(&mut render_children_first, "render children first").changed() || changed;
         ui.horizontal(|ui|
+        {
+            changed = ui.checkbox(&mut is_static, "static").changed() || changed;
+            ui.label("ℹ").on_hover_text("world matrices are cached and components are not updated - changing the transformation updates the node once");
+        });
+        ui.horizontal(|ui|
         {
             ui.label("alpha index: ");
             changed = ui.add(egui::DragValue::new(&mut alpha_index).speed(1)).changed() || changed;
+            ui.label("ℹ").on_hover_text("draw order of transparent objects: higher indices are drawn later\nhas precedence over the distance sorting");
+        });
+        changed = generic_items::layer_mask(ui, &mut layer_mask) || changed;
+
+        // tags
+        let mut tags: Vec<String> = node.read().unwrap().tags.iter().cloned().collect();
+        tags.sort();
+
+        let mut remove_tag = None;
+        ui.horizontal_wrapped(|ui|
+        {
+            ui.label("tags: ");
+
+            for tag in &tags
+            {
+                if ui.button(format!("{} ❌", tag)).on_hover_text("remove tag").clicked()
+                {
+                    remove_tag = Some(tag.clone());
+                }
+            }
+        });
+
+        if let Some(remove_tag) = remove_tag
+        {
+            node.write().unwrap().remove_tag(&remove_tag);
+        }
+
+        ui.horizontal(|ui|
+        {
+            let text = ui.add(egui::TextEdit::singleline(&mut editor_state.object_tag).desired_width(120.0).hint_text("new tag"));
+            let enter = text.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            if (ui.button("add tag").clicked() || enter) && !editor_state.object_tag.trim().is_empty()
+            {
+                node.write().unwrap().add_tag(&editor_state.object_tag);
+                editor_state.object_tag.clear();
+            }
         });
 
         if changed
@@ -300,10 +579,58 @@ pub fn create_object_settings(editor_state: &mut EditorState, state: &mut State,
             node.visible = visible;
             node.root_node = root_node;
             node.render_children_first = render_children_first;
+            node.is_static = is_static;
+            node.mark_dirty();
             node.alpha_index = alpha_index;
             node.name = name;
+            node.layer_mask = layer_mask;
+        }
+
+        // materials of the node and all descendants (for merging)
+        let mut merge_materials: Vec<(u64, String, MaterialItem)> = vec![];
+        {
+            let mut nodes = vec![node.clone()];
+            nodes.extend(Scene::list_all_child_nodes(&node.read().unwrap().nodes));
+
+            for node in nodes
+            {
+                if let Some(material) = node.read().unwrap().find_component::<Material>()
+                {
+                    let (id, name) = { let material = material.read().unwrap(); (material.id(), material.get_base().name.clone()) };
+
+                    if !merge_materials.iter().any(|(material_id, _, _)| *material_id == id)
+                    {
+                        merge_materials.push((id, name, material));
+                    }
+                }
+            }
         }
 
+        if editor_state.merge_material.is_some() && !merge_materials.iter().any(|(id, _, _)| Some(*id) == editor_state.merge_material)
+        {
+            editor_state.merge_material = None;
+        }
+
+        ui.horizontal(|ui|
+        {
+            ui.label("merge material: ");
+
+            let selected = merge_materials.iter().find(|(id, _, _)| Some(*id) == editor_state.merge_material).map_or("keep (one mesh per material)".to_string(), |(_, name, _)| name.clone());
+
+            egui::ComboBox::from_id_source("merge_material").selected_text(selected).show_ui(ui, |ui|
+            {
+                ui.style_mut().wrap = Some(false);
+                ui.set_min_width(40.0);
+
+                ui.selectable_value(&mut editor_state.merge_material, None, "keep (one mesh per material)");
+
+                for (id, name, _) in &merge_materials
+                {
+                    ui.selectable_value(&mut editor_state.merge_material, Some(*id), name.as_str());
+                }
+            });
+        });
+
         ui.with_layout(egui::Layout::top_down_justified(egui::Align::Center), |ui|
         {
             if ui.button(RichText::new("Create Default Instance").heading().strong().color(Color32::LIGHT_GREEN)).clicked()
@@ -312,6 +639,23 @@ pub fn create_object_settings(editor_state: &mut EditorState, state: &mut State,
                 node.write().unwrap().create_default_instance(node.clone(), scene.id_manager.get_next_instance_id());
             }
 
+            if ui.button(RichText::new("Bake Transformation").heading().strong().color(Color32::WHITE)).on_hover_text("applies the world transformation to the mesh vertices and resets the transformation\nthe children keep their world transformation").clicked()
+            {
+                let scene = state.find_scene_by_id_mut(scene_id).unwrap();
+                Node::bake_transform(node.clone(), &mut scene.id_manager);
+            }
+
+            if ui.button(RichText::new("Merge Meshes").heading().strong().color(Color32::WHITE)).on_hover_text("merges the meshes of this node and all descendants into a new node (in world space)\nthe source node is hidden").clicked()
+            {
+                let material = merge_materials.iter().find(|(id, _, _)| Some(*id) == editor_state.merge_material).map(|(_, _, material)| material.clone());
+
+                let scene = state.find_scene_by_id_mut(scene_id).unwrap();
+                if let Some(merged) = scene.merge_meshes(node.clone(), material)
+                {
+                    editor_state.selected_object = format!("objects_{}", merged.read().unwrap().id);
+                }
+            }
+
             if ui.button(RichText::new("Dispose Node").heading().strong().color(ui.visuals().error_fg_color)).clicked()
             {
                 let scene = state.find_scene_by_id_mut(scene_id).unwrap();
@@ -320,6 +664,27 @@ pub fn create_object_settings(editor_state: &mut EditorState, state: &mut State,
         });
     });
 
+    // debug (applies to all selected objects)
+    collapse_with_title(ui, "object_debug", false, "🐛 Debug", |ui|
+    {
+        let normals = &mut state.rendering.normals;
+
+        ui.checkbox(&mut normals.enabled, "show normals");
+
+        ui.add_enabled_ui(normals.enabled, |ui|
+        {
+            ui.checkbox(&mut normals.tangents, "show tangents and bitangents");
+
+            ui.horizontal(|ui|
+            {
+                ui.label("length: ");
+                ui.add(egui::DragValue::new(&mut normals.length).speed(0.01).clamp_range(0.001..=1000.0));
+            });
+        });
+
+        ui.label(RichText::new("normal: blue, tangent: red, bitangent: green").weak());
+    });
+
     if let Some(instance_id) = instance_id
     {
         create_instance_settings(editor_state, state, scene_id, node, instance_id, ui);
@@ -360,10 +725,12 @@ pub fn create_instance_settings(editor_state: &mut EditorState, state: &mut Stat
         let mut highlight;
         let mut name;
         let mut pickable;
+        let mut layer_mask;
         {
             let instance = instance.read().unwrap();
             let instance_data = instance.get_data();
             visible = instance_data.visible;
+            layer_mask = instance_data.layer_mask;
             collision = instance_data.collision;
             highlight = instance_data.highlight;
             name = instance.name.clone();
@@ -379,6 +746,7 @@ pub fn create_instance_settings(editor_state: &mut EditorState, state: &mut Stat
         changed = ui.checkbox(&mut collision, "collision").changed() || changed;
         changed = ui.checkbox(&mut highlight, "highlight").changed() || changed;
         changed = ui.checkbox(&mut pickable, "pickable").changed() || changed;
+        changed = generic_items::layer_mask(ui, &mut layer_mask) || changed;
 
         if changed
         {
@@ -387,6 +755,7 @@ pub fn create_instance_settings(editor_state: &mut EditorState, state: &mut Stat
             instance_data.visible = visible;
             instance_data.collision = collision;
             instance_data.highlight = highlight;
+            instance_data.layer_mask = layer_mask;
             instance.name = name;
             instance.pickable = pickable;
         }
@@ -445,6 +814,14 @@ pub fn create_component_settings(editor_state: &mut EditorState, state: &mut Sta
         let mut delete_component_id = None;
 
         let node_read = node.read().unwrap();
+
+        // parent world transformation (for world space editing)
+        let mut parent_trans = Matrix4::<f32>::identity();
+        if let Some(parent) = &node_read.parent
+        {
+            parent_trans = parent.read().unwrap().get_full_transform();
+        }
+
         for component in &node_read.components
         {
             let component_id;
@@ -515,6 +892,11 @@ pub fn create_component_settings(editor_state: &mut EditorState, state: &mut Sta
                 ui.label(format!("Name: {}", name));
 
                 let mut component = component.write().unwrap();
+                if let Some(transformation) = component.as_any_mut().downcast_mut::<Transformation>()
+                {
+                    transformation.set_parent_transform(parent_trans);
+                }
+
                 component.ui(ui);
             });
         }
@@ -530,12 +912,16 @@ pub fn create_component_settings(editor_state: &mut EditorState, state: &mut Sta
     if let Some(instance_id) = instance_id
     {
         let mut delete_component_id = None;
+        let mut instance_changed = false;
 
         let node_read: std::sync::RwLockReadGuard<'_, Box<crate::state::scene::node::Node>> = node.read().unwrap();
         let instance = node_read.find_instance_by_id(instance_id);
 
         if let Some(instance) = instance
         {
+            // instance transformations are relative to the node
+            let parent_trans = node_read.get_full_transform();
+
             {
                 let instance = instance.read().unwrap();
 
@@ -595,7 +981,22 @@ pub fn create_component_settings(editor_state: &mut EditorState, state: &mut Sta
                         ui.label(format!("Name: {}", name));
 
                         let mut component = component.write().unwrap();
+                        if let Some(transformation) = component.as_any_mut().downcast_mut::<Transformation>()
+                        {
+                            transformation.set_parent_transform(parent_trans);
+                        }
+
                         component.ui(ui);
+
+                        // instance changes of static nodes are only applied if the node is marked as dirty
+                        if let Some(transformation) = component.as_any().downcast_ref::<Transformation>()
+                        {
+                            instance_changed = transformation.get_data_tracker().changed() || instance_changed;
+                        }
+                        else if let Some(alpha) = component.as_any().downcast_ref::<Alpha>()
+                        {
+                            instance_changed = alpha.get_data_tracker().changed() || instance_changed;
+                        }
                     });
                 }
             }
@@ -604,8 +1005,16 @@ pub fn create_component_settings(editor_state: &mut EditorState, state: &mut Sta
             {
                 let mut instance = instance.write().unwrap();
                 instance.remove_component_by_id(delete_component_id);
+                instance_changed = true;
             }
         }
+
+        drop(node_read);
+
+        if instance_changed
+        {
+            node.write().unwrap().mark_dirty();
+        }
     }
 
     ui.with_layout(egui::Layout::top_down_justified(egui::Align::Center), |ui|
@@ -1,9 +1,10 @@
 use egui::{Ui, Color32};
-use nalgebra::Vector3;
+use nalgebra::{Vector3, Vector4};
+use strum::IntoEnumIterator;
 
-use crate::state::{state::State, gui::helper::generic_items::collapse_with_title};
+use crate::state::{state::{State, DebugChannel}, gui::helper::generic_items::collapse_with_title};
 
-use super::editor_state::EditorState;
+use super::{editor_state::EditorState, dialogs::save_screenshot_dialog};
 
 pub fn create_rendering_settings(editor_state: &mut EditorState, state: &mut State, ui: &mut Ui)
 {
@@ -17,17 +18,21 @@ pub fn create_rendering_settings(editor_state: &mut EditorState, state: &mut Sta
             let r = (clear_color.x * 255.0) as u8;
             let g = (clear_color.y * 255.0) as u8;
             let b = (clear_color.z * 255.0) as u8;
-            let mut color = Color32::from_rgb(r, g, b);
+            let a = (clear_color.w * 255.0) as u8;
+            let mut color = [r, g, b, a];
 
+            // alpha (without premultiplication) is used for transparent screenshots/compositing
             ui.label("clear color:");
-            let changed = ui.color_edit_button_srgba(&mut color).changed();
+            let changed = ui.color_edit_button_srgba_unmultiplied(&mut color).changed();
+            ui.label("ℹ").on_hover_text("alpha < 1 results in a (semi) transparent background for screenshots and compositing");
 
             if changed
             {
-                let r = ((color.r() as f32) / 255.0).clamp(0.0, 1.0);
-                let g = ((color.g() as f32) / 255.0).clamp(0.0, 1.0);
-                let b = ((color.b() as f32) / 255.0).clamp(0.0, 1.0);
-                state.rendering.clear_color.set(Vector3::<f32>::new(r, g, b));
+                let r = ((color[0] as f32) / 255.0).clamp(0.0, 1.0);
+                let g = ((color[1] as f32) / 255.0).clamp(0.0, 1.0);
+                let b = ((color[2] as f32) / 255.0).clamp(0.0, 1.0);
+                let a = ((color[3] as f32) / 255.0).clamp(0.0, 1.0);
+                state.rendering.clear_color.set(Vector4::<f32>::new(r, g, b, a));
             }
         });
 
@@ -47,16 +52,95 @@ pub fn create_rendering_settings(editor_state: &mut EditorState, state: &mut Sta
             }
         }
 
+        ui.add_enabled_ui(!*state.rendering.v_sync.get_ref(), |ui|
+        {
+            ui.horizontal(|ui|
+            {
+                let mut fps_cap = state.rendering.target_fps.is_some();
+                let mut target_fps = state.rendering.target_fps.unwrap_or(60);
+
+                let mut changed = ui.checkbox(&mut fps_cap, "FPS cap").changed();
+                changed = ui.add_enabled(fps_cap, egui::DragValue::new(&mut target_fps).clamp_range(1..=1000).suffix(" fps")).changed() || changed;
+
+                if changed
+                {
+                    state.rendering.target_fps = if fps_cap { Some(target_fps) } else { None };
+                }
+
+                ui.label("ℹ").on_hover_text("ignored with vSync");
+            });
+        });
+
+        ui.horizontal(|ui|
+        {
+            let mut fixed_update = state.fixed_update_rate.is_some();
+            let mut fixed_update_rate = state.fixed_update_rate.unwrap_or(60);
+
+            let mut changed = ui.checkbox(&mut fixed_update, "Fixed update rate").changed();
+            changed = ui.add_enabled(fixed_update, egui::DragValue::new(&mut fixed_update_rate).clamp_range(1..=1000).suffix(" Hz")).changed() || changed;
+
+            if changed
+            {
+                state.fixed_update_rate = if fixed_update { Some(fixed_update_rate) } else { None };
+                state.fixed_update_accumulator = 0.0;
+            }
+
+            ui.label("ℹ").on_hover_text("nodes and components (animations) are updated with a constant time step - independent of the frame rate");
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.checkbox(&mut state.pause_on_unfocus, "Pause on unfocus");
+            ui.label("ℹ").on_hover_text("no scene updates and a low frame rate while the window is not focused");
+        });
+
         ui.horizontal(|ui|
         {
             ui.checkbox(&mut state.rendering.create_mipmaps, "create mipmaps");
             ui.label("ℹ").on_hover_text("applied only for new loaded objects");
         });
 
+        ui.horizontal(|ui|
+        {
+            ui.label("Max texture size:");
+
+            let selected = state.rendering.max_texture_resolution.map_or("unlimited".to_string(), |size| size.to_string());
+
+            egui::ComboBox::from_id_source("max_texture_resolution").selected_text(selected).show_ui(ui, |ui|
+            {
+                ui.selectable_value(&mut state.rendering.max_texture_resolution, None, "unlimited");
+
+                for size in [512, 1024, 2048, 4096]
+                {
+                    ui.selectable_value(&mut state.rendering.max_texture_resolution, Some(size), size.to_string());
+                }
+            });
+
+            ui.label("ℹ").on_hover_text("larger textures are scaled down (loaded ones as well) to reduce the gpu memory usage\nraising the limit again needs a reload of the objects/textures");
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Anisotropy:");
+
+            egui::ComboBox::from_id_source("anisotropy").selected_text(format!("{}x", state.rendering.anisotropy)).show_ui(ui, |ui|
+            {
+                for value in [1, 2, 4, 8, 16]
+                {
+                    if value <= state.adapter.max_anisotropy
+                    {
+                        ui.selectable_value(&mut state.rendering.anisotropy, value, format!("{}x", value));
+                    }
+                }
+            });
+
+            ui.label("ℹ").on_hover_text("default anisotropic filtering of all textures (can be overwritten per texture)\nsharper textures at grazing angles - only applied if all filters of the texture are linear");
+        });
+
         ui.horizontal(|ui|
         {
             ui.checkbox(&mut state.rendering.distance_sorting, "Distance Sorting");
-            ui.label("ℹ").on_hover_text("for better alpha blending");
+            ui.label("ℹ").on_hover_text("for better alpha blending (back to front)\nthe alpha index of the objects has precedence");
         });
 
         ui.horizontal(|ui|
@@ -78,6 +162,191 @@ pub fn create_rendering_settings(editor_state: &mut EditorState, state: &mut Sta
                 state.rendering.msaa.set(msaa)
             }
         });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Debug channel:");
+
+            egui::ComboBox::from_id_source("debug_channel").selected_text(state.rendering.debug_channel.to_string()).show_ui(ui, |ui|
+            {
+                for channel in DebugChannel::iter()
+                {
+                    ui.selectable_value(&mut state.rendering.debug_channel, channel, channel.to_string());
+                }
+            });
+
+            ui.label("ℹ").on_hover_text("output of the material shader (overdraw: brighter = more fragments per pixel)");
+        });
+    });
+
+    // grid
+    collapse_with_title(ui, "grid_settings", true, "▦ Grid", |ui|
+    {
+        let grid = &mut state.rendering.grid;
+
+        ui.checkbox(&mut grid.enabled, "show grid");
+
+        ui.horizontal(|ui|
+        {
+            ui.label("spacing:");
+            ui.add(egui::DragValue::new(&mut grid.spacing).speed(0.01).clamp_range(0.01..=1000.0));
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("subdivisions:");
+            ui.add(egui::DragValue::new(&mut grid.subdivisions).speed(1).clamp_range(1..=100));
+            ui.label("ℹ").on_hover_text("the spacing of the subdivisions is also used for snapping");
+        });
+
+        ui.horizontal(|ui|
+        {
+            let r = (grid.color.x * 255.0) as u8;
+            let g = (grid.color.y * 255.0) as u8;
+            let b = (grid.color.z * 255.0) as u8;
+            let mut color = Color32::from_rgb(r, g, b);
+
+            ui.label("color:");
+            if ui.color_edit_button_srgba(&mut color).changed()
+            {
+                let r = ((color.r() as f32) / 255.0).clamp(0.0, 1.0);
+                let g = ((color.g() as f32) / 255.0).clamp(0.0, 1.0);
+                let b = ((color.b() as f32) / 255.0).clamp(0.0, 1.0);
+                grid.color = Vector3::<f32>::new(r, g, b);
+            }
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("opacity:");
+            ui.add(egui::Slider::new(&mut grid.opacity, 0.0..=1.0));
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("fade distance:");
+            ui.add(egui::DragValue::new(&mut grid.fade_distance).speed(1.0).clamp_range(1.0..=10000.0));
+        });
+
+        ui.checkbox(&mut grid.axis_highlight, "highlight axes");
+    });
+
+    // selection outline
+    collapse_with_title(ui, "outline_settings", true, "⬚ Selection Outline", |ui|
+    {
+        let outline = &mut state.rendering.outline;
+
+        ui.checkbox(&mut outline.enabled, "show outline");
+
+        ui.horizontal(|ui|
+        {
+            let r = (outline.color.x * 255.0) as u8;
+            let g = (outline.color.y * 255.0) as u8;
+            let b = (outline.color.z * 255.0) as u8;
+            let mut color = Color32::from_rgb(r, g, b);
+
+            ui.label("color:");
+            if ui.color_edit_button_srgba(&mut color).changed()
+            {
+                let r = ((color.r() as f32) / 255.0).clamp(0.0, 1.0);
+                let g = ((color.g() as f32) / 255.0).clamp(0.0, 1.0);
+                let b = ((color.b() as f32) / 255.0).clamp(0.0, 1.0);
+                outline.color = Vector3::<f32>::new(r, g, b);
+            }
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("thickness:");
+            ui.add(egui::Slider::new(&mut outline.thickness, 1.0..=10.0).suffix("px"));
+        });
+    });
+
+    // selection wireframe
+    collapse_with_title(ui, "wireframe_settings", true, "◇ Selection Wireframe", |ui|
+    {
+        let wireframe = &mut state.rendering.wireframe;
+
+        ui.checkbox(&mut wireframe.enabled, "show wireframe overlay");
+
+        ui.horizontal(|ui|
+        {
+            let r = (wireframe.color.x * 255.0) as u8;
+            let g = (wireframe.color.y * 255.0) as u8;
+            let b = (wireframe.color.z * 255.0) as u8;
+            let mut color = Color32::from_rgb(r, g, b);
+
+            ui.label("color:");
+            if ui.color_edit_button_srgba(&mut color).changed()
+            {
+                let r = ((color.r() as f32) / 255.0).clamp(0.0, 1.0);
+                let g = ((color.g() as f32) / 255.0).clamp(0.0, 1.0);
+                let b = ((color.b() as f32) / 255.0).clamp(0.0, 1.0);
+                wireframe.color = Vector3::<f32>::new(r, g, b);
+            }
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("opacity:");
+            ui.add(egui::Slider::new(&mut wireframe.opacity, 0.0..=1.0));
+        });
+    });
+
+    // depth of field
+    collapse_with_title(ui, "dof_settings", true, "📷 Depth of Field", |ui|
+    {
+        let dof = &mut state.rendering.dof;
+
+        ui.checkbox(&mut dof.enabled, "enabled");
+
+        ui.horizontal(|ui|
+        {
+            ui.label("focus distance:");
+            ui.add(egui::DragValue::new(&mut dof.focus_distance).speed(0.1).clamp_range(0.01..=10000.0));
+
+            ui.toggle_value(&mut editor_state.pick_focus, "🎯 pick").on_hover_text("click on a surface in the scene to focus on it");
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("aperture:");
+            ui.add(egui::DragValue::new(&mut dof.f_stop).speed(0.1).clamp_range(0.5..=32.0).prefix("f/"));
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("max blur:");
+            ui.add(egui::Slider::new(&mut dof.max_blur, 0.0..=32.0).suffix("px"));
+        });
+    });
+
+    // offscreen rendering
+    collapse_with_title(ui, "render_image", true, "🖼 Render Image", |ui|
+    {
+        ui.horizontal(|ui|
+        {
+            ui.label("resolution:");
+            ui.add(egui::DragValue::new(&mut state.render_image_width).speed(1).clamp_range(1..=16384).suffix("px"));
+            ui.label("x");
+            ui.add(egui::DragValue::new(&mut state.render_image_height).speed(1).clamp_range(1..=16384).suffix("px"));
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("supersampling:");
+            ui.selectable_value(&mut state.render_image_supersampling, 1, "1x");
+            ui.selectable_value(&mut state.render_image_supersampling, 2, "2x");
+            ui.selectable_value(&mut state.render_image_supersampling, 4, "4x");
+        });
+
+        ui.with_layout(egui::Layout::top_down_justified(egui::Align::Center), |ui|
+        {
+            if ui.button("Render Image").clicked()
+            {
+                state.render_image_path = save_screenshot_dialog("render.png");
+            }
+        });
     });
 //});
 }
\ No newline at end of file
@@ -2,7 +2,7 @@ use egui::{Ui, RichText, Color32};
 
 use crate::{state::{state::State, scene::{scene::Scene, components::{mesh::Mesh, material::TextureType}}, gui::helper::generic_items::{collapse_with_title, self}}, component_downcast, helper::concurrency::thread::spawn_thread};
 
-use super::{editor_state::EditorState, dialogs::load_texture_dialog};
+use super::{editor_state::EditorState, dialogs::{load_texture_dialog, load_cubemap_dialog, save_screenshot_dialog}};
 
 pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State, ui: &mut Ui)
 {
@@ -15,6 +15,11 @@ pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State,
     }
 
     let scene_id = scene_id.unwrap();
+
+    // buffers are tracked for all scenes
+    let buffer_memory_usage = state.gpu_buffer_usage.iter().map(|(_, size)| *size).sum::<u64>() as f32 / 1024.0 / 1024.0;
+    let buffer_usage: Vec<(String, f32)> = state.gpu_buffer_usage.iter().map(|(name, size)| (name.to_lowercase(), *size as f32 / 1024.0 / 1024.0)).collect();
+
     let scene = state.find_scene_by_id_mut(scene_id);
 
     if scene.is_none()
@@ -81,7 +86,11 @@ pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State,
 
         ui.label(RichText::new("🖵 GPU memory usage").strong());
         ui.label(format!(" ⚫ textures: {:.2} MB", gpu_memory_usage));
-        ui.label(format!(" ⚫ buffers: TODO"));
+        ui.label(format!(" ⚫ buffers: {:.2} MB", buffer_memory_usage)).on_hover_text("all scenes");
+        for (name, usage) in &buffer_usage
+        {
+            ui.label(format!("    ⚪ {}: {:.2} MB", name, usage));
+        }
     });
 
     // Settings
@@ -90,6 +99,30 @@ pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State,
         scene.ui(ui);
     });
 
+    // Cleanup
+    collapse_with_title(ui, "scene_cleanup", true, "🗜 Cleanup", |ui|
+    {
+        ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui|
+        {
+            if ui.button("Merge Duplicate Materials").on_hover_text("materials with the same settings and textures").clicked()
+            {
+                let merged = scene.merge_duplicate_materials();
+                editor_state.merge_info = Some(format!("{} material(s) merged", merged));
+            }
+
+            if ui.button("Merge Duplicate Textures").on_hover_text("textures with the same content and sampling settings").clicked()
+            {
+                let merged = scene.merge_duplicate_textures();
+                editor_state.merge_info = Some(format!("{} texture(s) merged", merged));
+            }
+
+            if let Some(merge_info) = &editor_state.merge_info
+            {
+                ui.label(merge_info);
+            }
+        });
+    });
+
     // Env Texture
     if let Some(texture) = scene.get_data().environment_texture.clone()
     {
@@ -101,8 +134,14 @@ pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State,
         let id = format!("texture_{}", TextureType::Environment.to_string());
 
         let mut remove_texture = false;
+        let mut replace_texture = false;
+        let mut reload_texture = false;
         let mut changed = false;
 
+        let environment_path = scene.get_data().environment_path.clone();
+        let mut intensity = scene.get_data().environment_intensity;
+        let mut intensity_changed = false;
+
         generic_items::collapse(ui, id, true, |ui|
         {
             ui.label(RichText::new(title).heading().strong());
@@ -113,6 +152,16 @@ pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State,
                     remove_texture = true;
                 }
 
+                if ui.button("📂").on_hover_text("replace").clicked()
+                {
+                    replace_texture = true;
+                }
+
+                if environment_path.is_some() && ui.button("⟳").on_hover_text("reload").clicked()
+                {
+                    reload_texture = true;
+                }
+
                 // enabled toggle
                 let toggle_text;
                 if enabled
@@ -134,6 +183,17 @@ pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State,
         |ui|
         {
             texture.ui_info(ui);
+
+            if let Some(environment_path) = &environment_path
+            {
+                ui.label(format!("path: {}", environment_path));
+            }
+
+            ui.horizontal(|ui|
+            {
+                ui.label("Intensity:");
+                intensity_changed = ui.add(egui::Slider::new(&mut intensity, 0.0..=10.0)).changed();
+            });
         });
 
         if changed
@@ -149,6 +209,30 @@ pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State,
             let scene_data = scene.get_data_mut();
             let scene_data = scene_data.get_mut();
             scene_data.environment_texture = None;
+            scene_data.environment_path = None;
+            scene_data.environment_irradiance = None;
+            scene_data.environment_brdf_lut = None;
+        }
+
+        if intensity_changed
+        {
+            let scene_data = scene.get_data_mut();
+            let scene_data = scene_data.get_mut();
+            scene_data.environment_intensity = intensity;
+        }
+
+        if replace_texture
+        {
+            let main_queue = state.main_thread_execution_queue.clone();
+
+            spawn_thread(move ||
+            {
+                load_texture_dialog(main_queue.clone(), TextureType::Environment, scene_id, None, true);
+            });
+        }
+        else if reload_texture
+        {
+            state.load_scene_env_map(environment_path.unwrap().as_str(), scene_id);
         }
     }
     else
@@ -184,6 +268,16 @@ pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State,
                         load_texture_dialog(main_queue.clone(), TextureType::Environment, scene_id, None, true);
                     });
                 }
+
+                if ui.button(RichText::new("Load Cubemap").heading().strong()).on_hover_text("6 images (+x, -x, +y, -y, +z, -z)").clicked()
+                {
+                    let main_queue = state.main_thread_execution_queue.clone();
+
+                    spawn_thread(move ||
+                    {
+                        load_cubemap_dialog(main_queue.clone(), scene_id, true);
+                    });
+                }
             });
         });
     }
@@ -208,10 +302,31 @@ pub fn create_scene_settings(editor_state: &mut EditorState, state: &mut State,
                 state.save_depth_buffer_image = true;
             }
 
+            ui.horizontal(|ui|
+            {
+                ui.checkbox(&mut state.depth_image_linearized, "linearize depth");
+                ui.add_enabled(state.depth_image_linearized, egui::Checkbox::new(&mut state.depth_image_normalized, "normalize"));
+                ui.label("ℹ").on_hover_text("linearized with the clipping planes of the first camera\nnormalized: the visible depth range is mapped to black..white");
+            });
+
             if ui.button("save screenshot").clicked()
             {
                 state.save_screenshot = true;
             }
+
+            ui.horizontal(|ui|
+            {
+                if ui.button("save screenshot as...").clicked()
+                {
+                    if let Some(path) = save_screenshot_dialog("screenshot.png")
+                    {
+                        state.screenshot_path = Some(path);
+                        state.save_screenshot = true;
+                    }
+                }
+
+                ui.checkbox(&mut state.screenshot_transparent, "transparent background");
+            });
         });
     });
 }
\ No newline at end of file
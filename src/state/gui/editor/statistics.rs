@@ -73,8 +73,27 @@ pub fn create_statistic(editor_state: &mut EditorState, state: &mut State, ui: &
     ui.label(format!(" ⚫ update time: {:.3} ms", state.engine_update_time));
     ui.label(format!(" ⚫ render time: {:.3} ms", state.engine_render_time));
     ui.label(format!(" ⚫ draw calls: {}", state.draw_calls));
+    ui.label(format!(" ⚫ rendered vertices: {}", state.rendered_vertices));
     ui.label(format!(" ⚫ textures: {}", textures));
     ui.label(format!(" ⚫ materials: {}", materials));
+    ui.label(format!(" ⚫ texture cache: {} ({} hits, {} misses)", state.texture_cache.count(), state.texture_cache.hits, state.texture_cache.misses)).on_hover_text("textures reused by content hash (also across scenes)");
+
+    ui.label(RichText::new("⏱ GPU").strong());
+    if !state.adapter.timestamp_query_support
+    {
+        ui.label(" ⚫ pass times: n/a").on_hover_text("timestamp queries are not supported by the adapter");
+    }
+    else
+    {
+        let mut total = 0.0;
+        for (pass, time) in &state.gpu_pass_times
+        {
+            ui.label(format!(" ⚫ {} pass: {:.3} ms", pass, time));
+            total += time;
+        }
+        ui.label(format!(" ⚫ total: {:.3} ms", total));
+    }
+    ui.label(format!(" ⚫ buffer uploads: {:.2} KB", state.gpu_buffer_uploads as f32 / 1024.0)).on_hover_text("bytes written to gpu buffers in the last frame");
 
     ui.label(RichText::new("✏ Editor").strong());
     ui.label(format!(" ⚫ update time: {:.3} ms", state.egui_update_time));
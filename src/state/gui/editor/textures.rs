@@ -5,18 +5,38 @@ use rfd::FileDialog;
 
 use crate::{state::{state::State, gui::helper::{generic_items::collapse_with_title, info_box::info_box}, scene::{texture::TextureItem, components::{material::Material, component::Component}}}, rendering::material, component_downcast};
 
-use super::editor_state::{EditorState, SelectionType, SettingsPanel};
+use super::editor_state::{EditorState, SelectionType, SettingsPanel, ListSorting};
 
 pub fn build_texture_list(editor_state: &mut EditorState, textures: &HashMap<std::string::String, TextureItem>, ui: &mut Ui, scene_id: u64)
 {
+    ui.horizontal(|ui|
+    {
+        ui.label("🔍");
+        ui.add(egui::TextEdit::singleline(&mut editor_state.texture_filter).desired_width(80.0));
+
+        ui.selectable_value(&mut editor_state.texture_sorting, ListSorting::Id, "id").on_hover_text("sort by id");
+        ui.selectable_value(&mut editor_state.texture_sorting, ListSorting::Name, "name").on_hover_text("sort by name");
+    });
+
+    // hash map order is random -> sort by id or name
+    let filter = editor_state.texture_filter.trim().to_lowercase();
+
+    let mut items: Vec<(u64, String)> = textures.values().map(|texture| { let texture = texture.read().unwrap(); (texture.id, texture.as_ref().name.clone()) }).collect();
+    items.retain(|(_, name)| filter.is_empty() || name.to_lowercase().contains(&filter));
+
+    match editor_state.texture_sorting
+    {
+        ListSorting::Id => items.sort_by_key(|(texture_id, _)| *texture_id),
+        ListSorting::Name => items.sort_by(|a, b| a.1.to_lowercase().cmp(&b.1.to_lowercase()).then(a.0.cmp(&b.0))),
+    }
+
     ui.with_layout(egui::Layout::top_down_justified(egui::Align::LEFT), |ui|
     {
-        for (_texture_hash, texture) in textures
+        for (texture_id, name) in items
         {
-            let texture = texture.read().unwrap();
-            let headline_name = format!("⚫ {}: {}", texture.id, texture.as_ref().name);
+            let headline_name = format!("⚫ {}: {}", texture_id, name);
 
-            let id = format!("texture_{}", texture.id);
+            let id = format!("texture_{}", texture_id);
 
             let heading = RichText::new(headline_name).strong();
 
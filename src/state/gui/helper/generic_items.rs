@@ -1,5 +1,10 @@
 use egui::{Ui, Color32, RichText, Align2, Id};
 
+use crate::state::scene::node::LAYER_EDITOR;
+
+// number of generic render layers shown in the ui (the editor layer is the last bit)
+const UI_LAYERS: u32 = 8;
+
 pub fn collapse<R>(ui: &mut Ui, id: String, open: bool, header: impl FnOnce(&mut Ui) -> R, body: impl FnOnce(&mut Ui) -> R)
 {
     let bg_color = Color32::from_white_alpha(3);
@@ -107,4 +112,36 @@ pub fn enable_drag(ui: &mut Ui, response: &egui::Response, id: Id)
         }
     }
 }
-*/
\ No newline at end of file
+*/
+
+// checkboxes for the render layers of a node, instance or camera - returns true if the mask has changed
+pub fn layer_mask(ui: &mut Ui, mask: &mut u32) -> bool
+{
+    let mut changed = false;
+
+    ui.horizontal_wrapped(|ui|
+    {
+        ui.label("layers: ");
+
+        for layer in 0..UI_LAYERS
+        {
+            let bit = 1 << layer;
+            let mut enabled = *mask & bit != 0;
+
+            if ui.checkbox(&mut enabled, format!("{}", layer)).changed()
+            {
+                *mask ^= bit;
+                changed = true;
+            }
+        }
+
+        let mut editor = *mask & LAYER_EDITOR != 0;
+        if ui.checkbox(&mut editor, "editor").on_hover_text("editor overlays (grid)").changed()
+        {
+            *mask ^= LAYER_EDITOR;
+            changed = true;
+        }
+    });
+
+    changed
+}
@@ -6,7 +6,7 @@ use parry3d::query::Ray;
 
 use crate::{helper::{math::approx_equal, change_tracker::ChangeTracker}, state::helper::render_item::{RenderItemOption}, input::input_manager::InputManager};
 
-use super::{node::NodeItem, camera_controller::{camera_controller::CameraControllerBox, fly_controller::FlyController, target_rotation_controller::TargetRotationController}};
+use super::{node::{NodeItem, LAYER_ALL}, camera_controller::{camera_controller::CameraControllerBox, fly_controller::FlyController, target_rotation_controller::TargetRotationController}};
 
 const DEFAULT_CAM_POS: Point3::<f32> = Point3::<f32>::new(0.0, 0.0, 0.0);
 const DEFAULT_CAM_UP: Vector3::<f32> = Vector3::<f32>::new(0.0, 1.0, 0.0);
@@ -20,6 +20,11 @@ pub const DEFAULT_FOVY: f32 = 90.0f32;
 const DEFAULT_CLIPPING_NEAR: f32 = 0.1;
 const DEFAULT_CLIPPING_FAR: f32 = 1000.0;
 
+const CLIPPING_NEAR_MIN: f32 = 0.0001;
+const CLIPPING_RATIO_WARNING: f32 = 100000.0; // far / near - depth precision is getting too low (z-fighting)
+const CLIPPING_FIT_MARGIN: f32 = 0.1;
+const CLIPPING_FIT_MAX_RATIO: f32 = 10000.0;
+
 /*
 pub const OPENGL_TO_WGPU_MATRIX: nalgebra::Matrix4<f32> = nalgebra::Matrix4::new
 (
@@ -68,6 +73,8 @@ pub struct CameraData
 
     pub projection_inverse: Matrix4<f32>,
     pub view_inverse: Matrix4<f32>,
+
+    pub layer_mask: u32, // render layers visible to this camera (see Node::layer_mask)
 }
 
 pub struct Camera
@@ -122,6 +129,8 @@ impl Camera
 
                 projection_inverse: Matrix4::<f32>::identity(),
                 view_inverse: Matrix4::<f32>::identity(),
+
+                layer_mask: LAYER_ALL,
             }),
 
             controller: None,
@@ -218,6 +227,47 @@ impl Camera
         data.view_inverse = data.view.try_inverse().unwrap();
     }
 
+    // near/far clipping based on the distance of the bounding box corners along the view direction
+    pub fn fit_clipping(&mut self, min: Point3<f32>, max: Point3<f32>)
+    {
+        let data = self.data.get_mut();
+        let dir = data.dir.normalize();
+
+        let mut near = f32::MAX;
+        let mut far = f32::MIN;
+
+        for i in 0..8
+        {
+            let corner = Point3::<f32>::new
+            (
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            );
+
+            let distance = (corner - data.eye_pos).dot(&dir);
+
+            near = near.min(distance);
+            far = far.max(distance);
+        }
+
+        // everything is behind the camera
+        if far <= 0.0
+        {
+            return;
+        }
+
+        far *= 1.0 + CLIPPING_FIT_MARGIN;
+
+        // camera inside of the bounding box -> limit the ratio
+        near = (near * (1.0 - CLIPPING_FIT_MARGIN)).max(far / CLIPPING_FIT_MAX_RATIO).max(CLIPPING_NEAR_MIN);
+
+        data.clipping_near = near;
+        data.clipping_far = far.max(near + 0.001);
+
+        self.init_matrices();
+    }
+
     pub fn add_controller_fly(&mut self, collision: bool, mouse_sensitivity: Vector2::<f32>, move_speed: f32, move_speed_shift: f32)
     {
         self.controller = Some(Box::new(FlyController::new(collision, mouse_sensitivity, move_speed, move_speed_shift)));
@@ -300,8 +350,9 @@ impl Camera
     {
         let data = self.get_data();
 
+        // the point is bottom left based (like the mouse input) - the viewport is top left based (like in rendering)
         let x0 = data.viewport_x * data.resolution_width as f32;
-        let y0 = data.viewport_y * data.resolution_height as f32;
+        let y0 = (1.0 - data.viewport_y - data.viewport_height) * data.resolution_height as f32;
 
         let width = data.viewport_width * data.resolution_width as f32;
         let height = data.viewport_height * data.resolution_height as f32;
@@ -320,12 +371,38 @@ impl Camera
         false
     }
 
+    pub fn get_viewport_coordinates_from_point(&self, point: &Point3<f32>, width: u32, height: u32) -> Option<Point2<f32>>
+    {
+        let data = self.get_data();
+
+        let pv = data.projection.to_homogeneous() * data.view;
+        let point_clip = pv * point.to_homogeneous();
+
+        // point is behind the camera
+        if point_clip.w <= 0.0
+        {
+            return None;
+        }
+
+        let ndc = point_clip.xyz() / point_clip.w;
+
+        // bottom left based (see is_point_in_viewport)
+        let x0 = data.viewport_x * width as f32;
+        let y0 = (1.0 - data.viewport_y - data.viewport_height) * height as f32;
+
+        let w = data.viewport_width * width as f32;
+        let h = data.viewport_height * height as f32;
+
+        Some(Point2::<f32>::new(x0 + (ndc.x * 0.5 + 0.5) * w, y0 + (ndc.y * 0.5 + 0.5) * h))
+    }
+
     pub fn get_ray_from_viewport_coordinates(&self, point: &Point2<f32>, width: u32, height: u32) -> Ray
     {
         let data = self.get_data();
 
-        let x_f = point.x as f32;
-        let y_f = point.y as f32;
+        // relative to the viewport (the point is bottom left based - see is_point_in_viewport)
+        let x_f = point.x as f32 - data.viewport_x * width as f32;
+        let y_f = point.y as f32 - (1.0 - data.viewport_y - data.viewport_height) * height as f32;
 
         let w = data.viewport_width as f32 * width as f32;
         let h = data.viewport_height as f32 * height as f32;
@@ -432,8 +509,18 @@ impl Camera
         });
 
         changed = ui.add(egui::Slider::new(&mut fovy, 0.001..=180.0).suffix(" °").text("Field of view (fov)")).changed() || changed;
-        changed = ui.add(egui::Slider::new(&mut clipping_near, 0.001..=1000.0).text("Near clipping plane")).changed() || changed;
-        changed = ui.add(egui::Slider::new(&mut clipping_far, 1.0..=100000.0).text("Far clipping plane")).changed() || changed;
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Clipping:");
+            changed = ui.add(egui::DragValue::new(&mut clipping_near).clamp_range(CLIPPING_NEAR_MIN..=f32::MAX).speed(0.01).prefix("near: ")).changed() || changed;
+            changed = ui.add(egui::DragValue::new(&mut clipping_far).clamp_range(CLIPPING_NEAR_MIN..=f32::MAX).speed(1.0).prefix("far: ")).changed() || changed;
+        });
+
+        if clipping_far / clipping_near > CLIPPING_RATIO_WARNING
+        {
+            ui.label(RichText::new(format!("⚠ far/near ratio {:.0}: z-fighting risk", clipping_far / clipping_near)).color(Color32::YELLOW));
+        }
 
         if changed
         {
@@ -450,12 +537,12 @@ impl Camera
             data.up = up;
             data.dir = dir;
 
-            data.clipping_near = clipping_near;
+            data.clipping_near = clipping_near.max(CLIPPING_NEAR_MIN);
             data.clipping_far = clipping_far;
 
             if data.clipping_near >= data.clipping_far
             {
-                data.clipping_near = data.clipping_far - 0.001
+                data.clipping_far = data.clipping_near + 0.001
             }
 
             self.init_matrices();
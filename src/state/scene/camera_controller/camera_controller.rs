@@ -1,6 +1,6 @@
 use std::any::Any;
 
-use crate::{state::scene::node::NodeItem, input::input_manager::InputManager};
+use crate::{state::scene::node::NodeItem, input::{input_manager::InputManager, mouse::MouseSmoothing}};
 
 pub type CameraControllerBox = Box<dyn CameraController + Send + Sync>;
 
@@ -21,6 +21,9 @@ pub struct CameraControllerBase
     pub is_enabled: bool,
     pub name: String,
     pub icon: String,
+
+    pub mouse_smoothing: f32, // 0 = off
+    pub mouse_smoothing_filter: MouseSmoothing,
 }
 
 impl CameraControllerBase
@@ -31,7 +34,10 @@ impl CameraControllerBase
         {
             name,
             icon,
-            is_enabled: true
+            is_enabled: true,
+
+            mouse_smoothing: 0.0,
+            mouse_smoothing_filter: MouseSmoothing::new(),
         }
     }
 }
@@ -1,16 +1,29 @@
 use std::f32::consts::PI;
 
-use nalgebra::{Vector2, Vector3, Isometry, Isometry3};
-use parry3d::{shape::Ball, query};
+use nalgebra::{Vector2, Vector3, Isometry3, Point3};
+use parry3d::{shape::{Capsule, Triangle}, query::{self, Ray}, bounding_volume::BoundingVolume};
 
-use crate::{camera_controller_impl_default, state::scene::{node::NodeItem, scene::Scene, camera::CameraData, components::mesh::Mesh}, input::{input_manager::InputManager, keyboard::{Key, Modifier}}, helper::{change_tracker::ChangeTracker, math::{approx_zero_vec2, self, approx_zero}}, component_downcast};
+use crate::{interface::main_interface::REFERENCE_UPDATE_FRAMES, camera_controller_impl_default, state::scene::{node::NodeItem, scene::Scene, camera::CameraData, components::mesh::Mesh, events::SceneEvent}, input::{input_manager::InputManager, keyboard::{Key, Modifier}, input_point::PointState}, helper::{change_tracker::ChangeTracker, math::{approx_zero_vec2, self, approx_zero, transform_bounding_sphere}}, component_downcast};
 
 use super::camera_controller::{CameraController, CameraControllerBase};
 
 const ANGLE_OFFSET_UP: f32 = 0.01;
 const ANGLE_OFFSET_DOWN: f32 = 0.1;
 
-const DEFAULT_SPHERE_RADIUS: f32 = 2.0;
+const DEFAULT_CAPSULE_RADIUS: f32 = 0.3;
+const DEFAULT_CAPSULE_HEIGHT: f32 = 1.8;
+
+// the capsule starts a bit above the feet to be able to walk up small steps
+const STEP_OFFSET: f32 = 0.25;
+const COLLISION_ITERATIONS: usize = 3;
+
+const DEFAULT_JUMP_HEIGHT: f32 = 1.0;
+const DEFAULT_EYE_HEIGHT: f32 = 1.8;
+
+// cap vertical movement per frame (to prevent falling through thin floors on low frame rates)
+const MAX_VERTICAL_MOVEMENT: f32 = 0.5;
+
+const TOUCH_MOVE_FACTOR: f32 = 0.01; // pinch/pan distance (pixels) -> movement (scaled by the movement speed)
 
 pub struct FlyController
 {
@@ -22,7 +35,17 @@ pub struct FlyController
     move_speed_shift: f32,
     mouse_sensitivity: Vector2::<f32>,
 
-    sphere_shape: Ball
+    pub walk: bool, // gravity is taken from the scene
+    pub jump_height: f32,
+    pub eye_height: f32,
+
+    vertical_velocity: f32,
+    on_ground: bool,
+
+    touching: Vec<(u64, u64)>, // (node id, instance id) - to emit collision events only on the first contact
+
+    pub capsule_radius: f32,
+    pub capsule_height: f32,
 }
 
 impl FlyController
@@ -39,8 +62,184 @@ impl FlyController
             move_speed_shift,
             mouse_sensitivity,
 
-            sphere_shape: Ball::new(DEFAULT_SPHERE_RADIUS)
+            walk: false,
+            jump_height: DEFAULT_JUMP_HEIGHT,
+            eye_height: DEFAULT_EYE_HEIGHT,
+
+            vertical_velocity: 0.0,
+            on_ground: false,
+
+            touching: vec![],
+
+            capsule_radius: DEFAULT_CAPSULE_RADIUS,
+            capsule_height: DEFAULT_CAPSULE_HEIGHT,
+        }
+    }
+
+    pub fn get_capsule(&self, eye_pos: &Point3<f32>, up: &Vector3<f32>) -> Capsule
+    {
+        // the upper sphere is centered at the eye position
+        let bottom_offset = (self.capsule_height - self.capsule_radius - STEP_OFFSET).max(0.0);
+        let bottom = eye_pos - up * bottom_offset;
+
+        Capsule::new(bottom, *eye_pos, self.capsule_radius)
+    }
+
+    // returns the touched instances (node id, instance id)
+    fn resolve_collisions(&self, scene: &Scene, cam_data: &mut ChangeTracker<CameraData>) -> Vec<(u64, u64)>
+    {
+        let mut touched = vec![];
+
+        let start_pos = cam_data.get_ref().eye_pos;
+        let mut pos = start_pos;
+
+        let identity = Isometry3::<f32>::identity();
+        let nodes = Scene::list_all_child_nodes_with_mesh(&scene.nodes);
+        let up = scene.get_up_axis();
+
+        for _ in 0..COLLISION_ITERATIONS
+        {
+            let capsule = self.get_capsule(&pos, &up);
+            let capsule_aabb = capsule.local_aabb();
+
+            let mut pushed = false;
+
+            for node_arc in &nodes
+            {
+                let node = node_arc.read().unwrap();
+
+                if !node.is_visible()
+                {
+                    continue;
+                }
+
+                let mesh = node.find_component::<Mesh>();
+                if mesh.is_none()
+                {
+                    continue;
+                }
+
+                let mesh = mesh.unwrap();
+                component_downcast!(mesh, Mesh);
+
+                if !mesh.get_base().is_enabled
+                {
+                    continue;
+                }
+
+                let mesh_data = mesh.get_data();
+
+                for instance in node.instances.get_ref()
+                {
+                    let instance = instance.read().unwrap();
+
+                    // helpers like the grid are not pickable and should not collide
+                    if !instance.pickable || !instance.get_data().collision || approx_zero(instance.get_alpha())
+                    {
+                        continue;
+                    }
+
+                    let transform = instance.get_transform();
+
+                    // broad phase: bounding sphere vs capsule segment
+                    let (center, radius) = transform_bounding_sphere(&mesh.bounding_sphere(), &transform);
+
+                    let segment = capsule.segment.b - capsule.segment.a;
+                    let t = ((center - capsule.segment.a).dot(&segment) / segment.dot(&segment).max(std::f32::EPSILON)).clamp(0.0, 1.0);
+                    let closest = capsule.segment.a + segment * t;
+
+                    if nalgebra::distance(&closest, &center) > radius + capsule.radius
+                    {
+                        continue;
+                    }
+
+                    // narrow phase: capsule vs triangles
+                    for face in &mesh_data.indices
+                    {
+                        let triangle = Triangle::new
+                        (
+                            transform.transform_point(&mesh_data.vertices[face[0] as usize]),
+                            transform.transform_point(&mesh_data.vertices[face[1] as usize]),
+                            transform.transform_point(&mesh_data.vertices[face[2] as usize]),
+                        );
+
+                        if !triangle.local_aabb().intersects(&capsule_aabb)
+                        {
+                            continue;
+                        }
+
+                        if let Ok(Some(contact)) = query::contact(&identity, &capsule, &identity, &triangle, 0.0)
+                        {
+                            // penetration -> push out along the contact normal (which results in sliding along the surface)
+                            if contact.dist < 0.0
+                            {
+                                pos += contact.normal1.into_inner() * contact.dist;
+                                pushed = true;
+
+                                if !touched.contains(&(node.id, instance.id))
+                                {
+                                    touched.push((node.id, instance.id));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if !pushed
+            {
+                break;
+            }
+        }
+
+        if pos != start_pos
+        {
+            cam_data.get_mut().eye_pos = pos;
+        }
+
+        touched
+    }
+
+    fn apply_gravity(&mut self, scene: &Scene, cam_data: &mut ChangeTracker<CameraData>, frame_scale: f32) -> bool
+    {
+        let dt = frame_scale / REFERENCE_UPDATE_FRAMES;
+        let eye_pos = cam_data.get_ref().eye_pos;
+
+        // vertical means along the up axis of the scene
+        let up = scene.get_up_axis();
+        let gravity = scene.get_data().gravity.dot(&-up);
+
+        // ground check (ray downwards from the eye position)
+        let ray = Ray::new(eye_pos, -up);
+        let ground_dist = scene.pick(&ray, false, false).map(|hit| hit.0);
+
+        self.vertical_velocity -= gravity * dt;
+
+        let mut vertical_movement = (self.vertical_velocity * dt).clamp(-MAX_VERTICAL_MOVEMENT, MAX_VERTICAL_MOVEMENT);
+
+        self.on_ground = false;
+        if let Some(ground_dist) = ground_dist
+        {
+            // distance between feet and ground
+            let feet_dist = ground_dist - self.eye_height;
+
+            // land on the ground (or step up)
+            if vertical_movement <= 0.0 && feet_dist + vertical_movement <= 0.0
+            {
+                vertical_movement = -feet_dist;
+                self.vertical_velocity = 0.0;
+                self.on_ground = true;
+            }
+        }
+
+        if approx_zero(vertical_movement)
+        {
+            return false;
         }
+
+        cam_data.get_mut().eye_pos += up * vertical_movement;
+
+        true
     }
 }
 
@@ -53,44 +252,57 @@ impl CameraController for FlyController
         let mut change = false;
         let mut last_eye_pos = None;
 
-        if
-        (
-            input_manager.mouse.is_any_button_holding() && *input_manager.mouse.visible.get_ref()
-        )
-        ||
-            !*input_manager.mouse.visible.get_ref()
+        // pointer lock while looking around (after the cursor started to move - so clicks are not affected)
+        let mouse = &mut input_manager.mouse;
+        let look = mouse.is_any_button_holding() && *mouse.visible.get_ref();
+        let lock = look && (*mouse.locked.get_ref() || mouse.point.state == PointState::Move);
+
+        if lock != *mouse.locked.get_ref()
         {
-            let velocity = input_manager.mouse.point.velocity;
-            if approx_zero_vec2(&velocity) == false
-            {
-                let cam_data = cam_data.get_mut();
+            mouse.locked.set(lock);
+        }
 
-                let dir: Vector3::<f32> = cam_data.dir.normalize();
+        let mut velocity = input_manager.touch.drag_velocity();
+        if look || !*input_manager.mouse.visible.get_ref()
+        {
+            velocity += input_manager.mouse.point.velocity;
+        }
 
-                let delta_x = velocity.x * self.mouse_sensitivity.x;
-                let delta_y = velocity.y * self.mouse_sensitivity.y;
+        // applied every frame - so the remaining smoothed movement is continued after releasing
+        let smoothing = self.base.mouse_smoothing;
+        let velocity = self.base.mouse_smoothing_filter.apply(velocity, smoothing, frame_scale);
 
-                let (mut yaw, mut pitch) = math::yaw_pitch_from_direction(dir);
+        if approx_zero_vec2(&velocity) == false
+        {
+            let cam_data = cam_data.get_mut();
 
-                pitch += delta_y;
-                yaw -= delta_x;
+            // yaw/pitch are calculated in y-up space
+            let up_rotation = scene.get_up_rotation();
+            let dir: Vector3::<f32> = up_rotation.inverse() * cam_data.dir.normalize();
 
-                // check that you can not look up/down to 90°
-                if pitch > (PI/2.0) - ANGLE_OFFSET_UP
-                {
-                    pitch = (PI/2.0) - ANGLE_OFFSET_UP;
-                }
-                else if pitch < (-PI/2.0) + ANGLE_OFFSET_DOWN
-                {
-                    pitch = (-PI / 2.0) + ANGLE_OFFSET_DOWN;
-                }
+            let delta_x = velocity.x * self.mouse_sensitivity.x;
+            let delta_y = velocity.y * self.mouse_sensitivity.y;
 
-                let dir = math::yaw_pitch_to_direction(yaw, pitch);
+            let (mut yaw, mut pitch) = math::yaw_pitch_from_direction(dir);
 
-                cam_data.dir = dir;
+            pitch += delta_y;
+            yaw -= delta_x;
 
-                change = true;
+            // check that you can not look up/down to 90°
+            if pitch > (PI/2.0) - ANGLE_OFFSET_UP
+            {
+                pitch = (PI/2.0) - ANGLE_OFFSET_UP;
             }
+            else if pitch < (-PI/2.0) + ANGLE_OFFSET_DOWN
+            {
+                pitch = (-PI / 2.0) + ANGLE_OFFSET_DOWN;
+            }
+
+            let dir = math::yaw_pitch_to_direction(yaw, pitch);
+
+            cam_data.dir = up_rotation * dir;
+
+            change = true;
         }
 
         if input_manager.keyboard.is_holding_by_keys([Key::W, Key::A, Key::S, Key::D, Key::Space, Key::C].to_vec()) || input_manager.keyboard.is_holding_modifier(Modifier::Ctrl)
@@ -98,8 +310,20 @@ impl CameraController for FlyController
             let cam_data = cam_data.get_mut();
             last_eye_pos = Some(cam_data.eye_pos.clone());
 
-            let dir = cam_data.dir.normalize();
+            let mut dir = cam_data.dir.normalize();
             let up = cam_data.up.normalize();
+
+            // walking -> move on the horizontal plane only
+            if self.walk
+            {
+                let scene_up = scene.get_up_axis();
+                let horizontal = dir - scene_up * dir.dot(&scene_up);
+                if !approx_zero(horizontal.magnitude())
+                {
+                    dir = horizontal.normalize();
+                }
+            }
+
             let right = up.cross(&dir);
 
             let mut vec = Vector3::<f32>::zeros();
@@ -128,12 +352,12 @@ impl CameraController for FlyController
             {
                 vec += right * sensitivity;
             }
-            if input_manager.keyboard.is_holding(Key::Space)
+            if input_manager.keyboard.is_holding(Key::Space) && !self.walk
             {
                 vec += up * sensitivity;
             }
             //if input_manager.keyboard.is_holding(Key::C) || input_manager.keyboard.is_holding_modifier(Modifier::Ctrl)
-            if input_manager.keyboard.is_holding(Key::C)
+            if input_manager.keyboard.is_holding(Key::C) && !self.walk
             {
                 vec -= up * sensitivity;
             }
@@ -143,48 +367,62 @@ impl CameraController for FlyController
             change = true;
         }
 
-        // collision check
+        // touch: pinch -> forward/backward, two finger drag -> sideways/up/down
+        let pinch_delta = input_manager.touch.pinch_delta;
+        let pan_velocity = input_manager.touch.pan_velocity;
 
-        /*
-        if change
+        if !approx_zero(pinch_delta) || !approx_zero_vec2(&pan_velocity)
         {
-            let nodes = Scene::list_all_child_nodes_with_mesh(&scene.nodes);
-
-            for node_arc in &nodes
-            {
-                let node = node_arc.read().unwrap();
+            let cam_data = cam_data.get_mut();
 
-                for instance in node.instances.get_ref()
-                {
-                    let instance = instance.borrow();
-                    let instance = instance.get_ref();
+            let dir = cam_data.dir.normalize();
+            let up = cam_data.up.normalize();
+            let right = up.cross(&dir);
 
-                    let alpha = instance.get_alpha();
+            let factor = self.move_speed * TOUCH_MOVE_FACTOR;
 
-                    if approx_zero(alpha)
-                    {
-                        continue;
-                    }
+            let mut vec = dir * pinch_delta * factor;
+            vec += right * pan_velocity.x * factor;
 
-                    let transform = instance.get_transform();
+            if !self.walk
+            {
+                vec -= up * pan_velocity.y * factor;
+            }
 
-                    let mesh = node.find_component::<Mesh>().unwrap();
-                    component_downcast!(mesh, Mesh);
+            cam_data.eye_pos += vec;
 
-                    let mesh_data = mesh.get_data();
+            change = true;
+        }
 
-                    Isometry3::from_parts(translation, rotation)
+        // jump
+        if self.walk && self.on_ground && input_manager.keyboard.is_pressed(Key::Space)
+        {
+            let gravity = scene.get_data().gravity.dot(&-scene.get_up_axis()).max(0.0);
+            self.vertical_velocity = (2.0 * gravity * self.jump_height).sqrt();
+            self.on_ground = false;
+        }
 
-                    //mesh_data.mesh.coll
+        // gravity and ground collision
+        if self.walk
+        {
+            change = self.apply_gravity(scene, cam_data, frame_scale) || change;
+        }
 
-                    //parry3d::
-                    //query::contact(pos1, g1, pos2, g2, prediction)
-                    //mesh_data.mesh.
+        // collision check
+        if self.collision && change
+        {
+            let touched = self.resolve_collisions(scene, cam_data);
 
+            for (node_id, instance_id) in &touched
+            {
+                if !self.touching.contains(&(*node_id, *instance_id))
+                {
+                    scene.push_event(SceneEvent::Collision { node_id: *node_id, instance_id: *instance_id });
                 }
             }
+
+            self.touching = touched;
         }
-         */
 
         change
     }
@@ -206,5 +444,32 @@ impl CameraController for FlyController
             ui.add(egui::DragValue::new(&mut self.move_speed).speed(0.1).prefix("normal: "));
             ui.add(egui::DragValue::new(&mut self.move_speed_shift).speed(0.1).prefix("shift: "));
         });
+
+        ui.add_enabled_ui(self.collision, |ui|
+        {
+            ui.horizontal(|ui|
+            {
+                ui.label("Capsule: ");
+                ui.add(egui::DragValue::new(&mut self.capsule_radius).speed(0.01).clamp_range(0.01..=100.0).prefix("radius: "));
+                ui.add(egui::DragValue::new(&mut self.capsule_height).speed(0.01).clamp_range(0.01..=100.0).prefix("height: "));
+            });
+        });
+
+        ui.checkbox(&mut self.walk, "walk (gravity)");
+
+        ui.add_enabled_ui(self.walk, |ui|
+        {
+            ui.horizontal(|ui|
+            {
+                ui.label("Jump height: ");
+                ui.add(egui::DragValue::new(&mut self.jump_height).speed(0.1).clamp_range(0.0..=100.0));
+            });
+
+            ui.horizontal(|ui|
+            {
+                ui.label("Eye height: ");
+                ui.add(egui::DragValue::new(&mut self.eye_height).speed(0.1).clamp_range(0.0..=100.0));
+            });
+        });
     }
 }
\ No newline at end of file
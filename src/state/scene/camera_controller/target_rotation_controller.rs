@@ -9,6 +9,7 @@ use super::camera_controller::{CameraController, CameraControllerBase};
 const DEFAULT_TARGET_POS: Point3::<f32> = Point3::new(0.0, 0.0, 0.0);
 const ANGLE_OFFSET: f32 = 0.01;
 const DEFAULT_AUTO_ROTATE_TIMEOUT: u64 = 2000;
+const TOUCH_PINCH_WHEEL_FACTOR: f32 = 0.05; // pinch distance (pixels) -> mouse wheel steps
 
 pub struct TargetRotationControllerData
 {
@@ -70,12 +71,28 @@ impl CameraController for TargetRotationController
 {
     camera_controller_impl_default!();
 
-    fn update(&mut self, node: Option<NodeItem>, _scene: &mut Scene, input_manager: &mut InputManager, cam_data: &mut ChangeTracker<CameraData>, frame_scale: f32) -> bool
+    fn update(&mut self, node: Option<NodeItem>, scene: &mut Scene, input_manager: &mut InputManager, cam_data: &mut ChangeTracker<CameraData>, frame_scale: f32) -> bool
     {
         let mut change = false;
 
         let velocity = &input_manager.mouse.point.velocity;
 
+        // touch: two finger drag -> offset, single finger drag -> rotation
+        let mut offset_velocity = input_manager.touch.pan_velocity;
+        if input_manager.mouse.is_holding(MouseButton::Right)
+        {
+            offset_velocity += velocity;
+        }
+
+        let mut rotation_velocity = input_manager.touch.drag_velocity();
+        if input_manager.mouse.is_holding(MouseButton::Left)
+        {
+            rotation_velocity += velocity;
+        }
+
+        let smoothing = self.base.mouse_smoothing;
+        let rotation_velocity = self.base.mouse_smoothing_filter.apply(rotation_velocity, smoothing, frame_scale);
+
         let mut update_needed = false;
         if let Some(node) = &node
         {
@@ -83,10 +100,10 @@ impl CameraController for TargetRotationController
         }
 
         // offset
-        if input_manager.mouse.is_holding(MouseButton::Right) && !approx_zero_vec2(velocity)
+        if !approx_zero_vec2(&offset_velocity)
         {
-            let delta_x = velocity.x * self.mouse_sensitivity.x;
-            let delta_y = velocity.y * self.mouse_sensitivity.y;
+            let delta_x = offset_velocity.x * self.mouse_sensitivity.x;
+            let delta_y = offset_velocity.y * self.mouse_sensitivity.y;
 
             let offset_movement = Vector3::<f32>::new(delta_x, delta_y, 0.0);
 
@@ -104,10 +121,10 @@ impl CameraController for TargetRotationController
         }
 
         // rotation
-        if input_manager.mouse.is_holding(MouseButton::Left) && !approx_zero_vec2(velocity)
+        if !approx_zero_vec2(&rotation_velocity)
         {
-            let delta_x = velocity.x * self.mouse_sensitivity.x;
-            let delta_y = velocity.y * self.mouse_sensitivity.y;
+            let delta_x = rotation_velocity.x * self.mouse_sensitivity.x;
+            let delta_y = rotation_velocity.y * self.mouse_sensitivity.y;
 
             let data = self.data.get_mut();
             data.alpha -= delta_x;
@@ -129,7 +146,7 @@ impl CameraController for TargetRotationController
         }
 
         // auto rotate
-        if !input_manager.mouse.is_any_button_holding() && self.last_manual_move + self.auto_rotate_timeout < get_millis()
+        if !input_manager.mouse.is_any_button_holding() && !input_manager.touch.is_active() && self.last_manual_move + self.auto_rotate_timeout < get_millis()
         {
             if let Some(auto_rotate) = self.auto_rotate
             {
@@ -141,10 +158,11 @@ impl CameraController for TargetRotationController
         }
 
         // distance
-        if !math::approx_zero(input_manager.mouse.wheel_delta_y)
+        let wheel_delta = input_manager.mouse.wheel_delta_y + input_manager.touch.pinch_delta * TOUCH_PINCH_WHEEL_FACTOR;
+        if !math::approx_zero(wheel_delta)
         {
             let data = self.data.get_mut();
-            data.radius += self.mouse_wheel_sensitivity * -input_manager.mouse.wheel_delta_y;
+            data.radius += self.mouse_wheel_sensitivity * -wheel_delta;
 
             update_needed = true;
         }
@@ -167,7 +185,8 @@ impl CameraController for TargetRotationController
 
             let cam_data = cam_data.get_mut();
 
-            let dir = math::yaw_pitch_to_direction(data.alpha, data.beta).normalize();
+            // alpha/beta are defined in y-up space
+            let dir = scene.get_up_rotation() * math::yaw_pitch_to_direction(data.alpha, data.beta).normalize();
 
             cam_data.dir = -dir;
             let dir = dir * data.radius;
@@ -1,6 +1,8 @@
 use std::any::Any;
 
-use crate::{helper::change_tracker::ChangeTracker, component_impl_default, state::scene::node::{NodeItem, InstanceItemArc}, component_impl_no_update};
+use strum::IntoEnumIterator;
+
+use crate::{helper::{change_tracker::ChangeTracker, easing::Easing}, component_impl_default, state::scene::node::{NodeItem, InstanceItemArc}, input::input_manager::InputManager, interface::main_interface::REFERENCE_UPDATE_FRAMES};
 
 use super::component::{ComponentBase, Component};
 
@@ -11,10 +13,25 @@ pub struct AlphaData
     pub alpha: f32,
 }
 
+struct AlphaFade
+{
+    from: f32,
+    to: f32,
+
+    duration: f32, // seconds
+    time: f32,
+}
+
 pub struct Alpha
 {
     base: ComponentBase,
-    data: ChangeTracker<AlphaData>
+    data: ChangeTracker<AlphaData>,
+
+    fade: Option<AlphaFade>,
+
+    pub fade_target: f32,
+    pub fade_duration: f32,
+    pub fade_easing: Easing,
 }
 
 impl Alpha
@@ -30,7 +47,13 @@ impl Alpha
         let mut alpha = Alpha
         {
             base: ComponentBase::new(id, name.to_string(), "Alpha".to_string(), "🌖".to_string()),
-            data: ChangeTracker::new(data)
+            data: ChangeTracker::new(data),
+
+            fade: None,
+
+            fade_target: 0.0,
+            fade_duration: 1.0,
+            fade_easing: Easing::EaseInOut,
         };
 
         alpha
@@ -60,12 +83,55 @@ impl Alpha
     {
         self.data.get_ref().alpha
     }
+
+    // animates the alpha value (see fade_easing) - a new fade starts from the current alpha value
+    pub fn fade_to(&mut self, target: f32, seconds: f32)
+    {
+        let target = target.clamp(0.0, 1.0);
+
+        if seconds <= 0.0
+        {
+            self.fade = None;
+            self.data.get_mut().alpha = target;
+            return;
+        }
+
+        self.fade = Some(AlphaFade
+        {
+            from: self.get_alpha(),
+            to: target,
+            duration: seconds,
+            time: 0.0
+        });
+    }
+
+    pub fn is_fading(&self) -> bool
+    {
+        self.fade.is_some()
+    }
+
+    fn update_fade(&mut self, frame_scale: f32)
+    {
+        if let Some(fade) = &mut self.fade
+        {
+            fade.time += frame_scale / REFERENCE_UPDATE_FRAMES;
+
+            let progress = (fade.time / fade.duration).min(1.0);
+            let alpha = (fade.from + (fade.to - fade.from) * self.fade_easing.apply(progress)).clamp(0.0, 1.0);
+
+            self.data.get_mut().alpha = alpha;
+
+            if progress >= 1.0
+            {
+                self.fade = None;
+            }
+        }
+    }
 }
 
 impl Component for Alpha
 {
     component_impl_default!();
-    component_impl_no_update!();
 
     fn instantiable(&self) -> bool
     {
@@ -83,6 +149,16 @@ impl Component for Alpha
         }
     }
 
+    fn update(&mut self, _node: NodeItem, _input_manager: &mut InputManager, frame_scale: f32)
+    {
+        self.update_fade(frame_scale);
+    }
+
+    fn update_instance(&mut self, _node: NodeItem, _instance: &InstanceItemArc, _input_manager: &mut InputManager, frame_scale: f32)
+    {
+        self.update_fade(frame_scale);
+    }
+
     fn ui(&mut self, ui: &mut egui::Ui)
     {
         let mut changed = false;
@@ -102,9 +178,62 @@ impl Component for Alpha
 
         if changed
         {
+            // manual changes are stopping the fade
+            self.fade = None;
+
             let data = self.get_data_mut();
             data.get_mut().alpha = alpha;
             data.get_mut().alpha_inheritance = alpha_inheritance;
         }
+
+        // fading
+        ui.separator();
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Fade:");
+            ui.add(egui::Slider::new(&mut self.fade_target, 0.0..=1.0).text("target"));
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Duration:");
+            ui.add(egui::DragValue::new(&mut self.fade_duration).speed(0.1).clamp_range(0.0..=3600.0).suffix("s"));
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Easing:");
+            egui::ComboBox::from_id_source(format!("alpha_easing_{}", self.base.id)).selected_text(self.fade_easing.to_string()).show_ui(ui, |ui|
+            {
+                for easing in Easing::iter()
+                {
+                    ui.selectable_value(&mut self.fade_easing, easing, easing.to_string());
+                }
+            });
+        });
+
+        ui.horizontal(|ui|
+        {
+            if ui.button("fade in").clicked()
+            {
+                self.fade_to(1.0, self.fade_duration);
+            }
+
+            if ui.button("fade out").clicked()
+            {
+                self.fade_to(0.0, self.fade_duration);
+            }
+
+            if ui.button("fade to target").clicked()
+            {
+                self.fade_to(self.fade_target, self.fade_duration);
+            }
+
+            if self.is_fading() && ui.button("stop").clicked()
+            {
+                self.fade = None;
+            }
+        });
     }
 }
\ No newline at end of file
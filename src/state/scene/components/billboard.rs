@@ -0,0 +1,178 @@
+use nalgebra::{Point3, Vector3, Rotation3, Matrix3};
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, Display};
+
+use crate::{helper::{change_tracker::ChangeTracker, math::approx_equal_vec}, component_impl_default, component_impl_no_update, component_impl_set_enabled, state::scene::node::NodeItem, component_downcast_mut};
+
+use super::{component::{ComponentBase, Component}, transformation::Transformation};
+
+const INFO_STRING: &str = "Rotates the node (+z axis) towards the active camera.\nThe rotation of the Transform Component is overwritten (position and scale are kept).\nIf there is no Transform Component: Nothing is happening.";
+
+#[derive(EnumIter, Display, Debug, PartialEq, Clone, Copy)]
+pub enum BillboardMode
+{
+    Full, // faces the camera completely (sprites, labels)
+    #[strum(to_string = "Y-Axis")]
+    YAxis, // only rotates around the y axis (trees, grass)
+}
+
+pub struct BillboardData
+{
+    pub mode: BillboardMode,
+}
+
+pub struct Billboard
+{
+    base: ComponentBase,
+    data: ChangeTracker<BillboardData>,
+}
+
+impl Billboard
+{
+    pub fn new(id: u64, name: &str, mode: BillboardMode) -> Billboard
+    {
+        let data = BillboardData
+        {
+            mode
+        };
+
+        let mut billboard = Billboard
+        {
+            base: ComponentBase::new(id, name.to_string(), "Billboard".to_string(), "🪧".to_string()),
+            data: ChangeTracker::new(data),
+        };
+
+        billboard.base.info = Some(INFO_STRING.to_string());
+
+        billboard
+    }
+
+    pub fn get_data(&self) -> &BillboardData
+    {
+        &self.data.get_ref()
+    }
+
+    pub fn get_data_tracker(&self) -> &ChangeTracker<BillboardData>
+    {
+        &self.data
+    }
+
+    pub fn get_data_mut(&mut self) -> &mut ChangeTracker<BillboardData>
+    {
+        &mut self.data
+    }
+
+    // called by the scene after the camera update
+    pub fn apply(&self, node: NodeItem, camera_pos: &Point3<f32>)
+    {
+        let node = node.read().unwrap();
+
+        let transform = node.find_component::<Transformation>();
+        if transform.is_none()
+        {
+            return;
+        }
+        let transform = transform.unwrap();
+
+        let parent_trans = node.parent.as_ref().map(|parent| parent.read().unwrap().get_full_transform());
+
+        let world_pos = node.get_world_position();
+        let mut dir = camera_pos - world_pos;
+
+        if self.get_data().mode == BillboardMode::YAxis
+        {
+            dir.y = 0.0;
+        }
+
+        // camera is at the same position (or directly above in y-axis mode)
+        if dir.magnitude_squared() < std::f32::EPSILON
+        {
+            return;
+        }
+
+        let up = if dir.normalize().y.abs() > 0.999 { Vector3::<f32>::z() } else { Vector3::<f32>::y() };
+        let mut rotation = Rotation3::<f32>::face_towards(&dir, &up);
+
+        component_downcast_mut!(transform, Transformation);
+
+        // the rotation is needed in the local space of the parent
+        if let Some(parent_trans) = parent_trans
+        {
+            if transform.has_parent_inheritance()
+            {
+                let parent_rotation = Matrix3::<f32>::from_columns
+                (&[
+                    parent_trans.fixed_view::<3, 1>(0, 0).normalize(),
+                    parent_trans.fixed_view::<3, 1>(0, 1).normalize(),
+                    parent_trans.fixed_view::<3, 1>(0, 2).normalize(),
+                ]);
+
+                rotation = Rotation3::<f32>::from_matrix(&parent_rotation).inverse() * rotation;
+            }
+        }
+
+        let (roll, pitch, yaw) = rotation.euler_angles();
+        let euler = Vector3::<f32>::new(roll, pitch, yaw);
+
+        // prevent useless updates
+        if approx_equal_vec(&transform.get_data().rotation, &euler)
+        {
+            return;
+        }
+
+        if transform.get_data().transform_vectors
+        {
+            transform.get_data_mut().get_mut().rotation = euler;
+            transform.calc_transform();
+        }
+        else
+        {
+            let current = transform.get_transform().clone();
+            let scale = Vector3::<f32>::new(current.column(0).xyz().magnitude(), current.column(1).xyz().magnitude(), current.column(2).xyz().magnitude());
+
+            let mut trans = rotation.to_homogeneous() * nalgebra::Matrix4::new_nonuniform_scaling(&scale);
+            trans.fixed_view_mut::<3, 1>(0, 3).copy_from(&current.column(3).xyz());
+
+            transform.get_data_mut().get_mut().rotation = euler;
+            transform.set_transform(trans);
+        }
+    }
+}
+
+impl Component for Billboard
+{
+    component_impl_default!();
+    component_impl_no_update!();
+    component_impl_set_enabled!();
+
+    fn instantiable(&self) -> bool
+    {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui)
+    {
+        let mut mode = self.get_data().mode;
+        let mut changed = false;
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Mode: ");
+            egui::ComboBox::from_id_source("billboard_mode").selected_text(mode.to_string()).show_ui(ui, |ui|
+            {
+                ui.style_mut().wrap = Some(false);
+                ui.set_min_width(40.0);
+
+                for mode_item in BillboardMode::iter()
+                {
+                    changed = ui.selectable_value(&mut mode, mode_item, mode_item.to_string()).changed() || changed;
+                }
+            });
+        });
+
+        if changed
+        {
+            self.get_data_mut().get_mut().mode = mode;
+        }
+    }
+}
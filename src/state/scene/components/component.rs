@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::{RwLock, Arc};
 use std::any::Any;
 
@@ -49,6 +50,8 @@ pub struct ComponentBase
     pub icon: String,
     pub info: Option<String>,
 
+    pub extras: HashMap<String, String>, // custom properties of the loaded file (glTF extras)
+
     pub render_item: RenderItemOption
 }
 
@@ -64,7 +67,8 @@ impl ComponentBase
             icon,
             is_enabled: true,
             render_item: None,
-            info: None
+            info: None,
+            extras: HashMap::new()
         }
     }
 }
@@ -243,6 +247,26 @@ pub fn remove_component_by_id(components: &mut Vec<ComponentItem>, id: u64) -> b
     false
 }
 
+pub fn replace_component_by_id(components: &mut Vec<ComponentItem>, id: u64, component: &ComponentItem) -> bool
+{
+    let index = components.iter().position
+    (
+        |c|
+        {
+            let component = c.read().unwrap();
+            component.id() == id
+        }
+    );
+
+    if let Some(index) = index
+    {
+        components[index] = component.clone();
+        return true;
+    }
+
+    false
+}
+
 // ******************** macros ********************
 
 #[macro_export]
@@ -0,0 +1,111 @@
+use nalgebra::Vector3;
+
+use crate::{helper::change_tracker::ChangeTracker, component_impl_default, component_impl_no_update, component_impl_set_enabled};
+
+use super::component::{ComponentBase, Component};
+
+const INFO_STRING: &str = "Text label above the node (camera facing).\nSize is the height of one text line in world units.\nOnly ASCII characters are supported.";
+
+pub struct LabelData
+{
+    pub text: String,
+    pub size: f32,
+    pub color: Vector3<f32>,
+    pub offset: f32, // vertical offset above the node (or the top of its bounding box)
+    pub always_on_top: bool,
+}
+
+pub struct Label
+{
+    base: ComponentBase,
+    data: ChangeTracker<LabelData>,
+}
+
+impl Label
+{
+    pub fn new(id: u64, name: &str, text: &str) -> Label
+    {
+        let data = LabelData
+        {
+            text: text.to_string(),
+            size: 0.25,
+            color: Vector3::<f32>::new(1.0, 1.0, 1.0),
+            offset: 0.1,
+            always_on_top: false,
+        };
+
+        let mut label = Label
+        {
+            base: ComponentBase::new(id, name.to_string(), "Label".to_string(), "🏷".to_string()),
+            data: ChangeTracker::new(data),
+        };
+
+        label.base.info = Some(INFO_STRING.to_string());
+
+        label
+    }
+
+    pub fn get_data(&self) -> &LabelData
+    {
+        &self.data.get_ref()
+    }
+
+    pub fn get_data_tracker(&self) -> &ChangeTracker<LabelData>
+    {
+        &self.data
+    }
+
+    pub fn get_data_mut(&mut self) -> &mut ChangeTracker<LabelData>
+    {
+        &mut self.data
+    }
+}
+
+impl Component for Label
+{
+    component_impl_default!();
+    component_impl_no_update!();
+    component_impl_set_enabled!();
+
+    fn instantiable(&self) -> bool
+    {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui)
+    {
+        let data = self.get_data();
+
+        let mut text = data.text.clone();
+        let mut size = data.size;
+        let mut color = [data.color.x, data.color.y, data.color.z];
+        let mut offset = data.offset;
+        let mut always_on_top = data.always_on_top;
+
+        let mut changed = false;
+
+        ui.label("Text:");
+        changed = ui.text_edit_multiline(&mut text).changed() || changed;
+
+        changed = ui.add(egui::Slider::new(&mut size, 0.01..=10.0).logarithmic(true).text("size")).changed() || changed;
+        changed = ui.add(egui::Slider::new(&mut offset, 0.0..=10.0).text("offset")).changed() || changed;
+
+        ui.horizontal(|ui|
+        {
+            ui.label("color:");
+            changed = ui.color_edit_button_rgb(&mut color).changed() || changed;
+        });
+
+        changed = ui.checkbox(&mut always_on_top, "always on top").changed() || changed;
+
+        if changed
+        {
+            let data = self.get_data_mut().get_mut();
+            data.text = text;
+            data.size = size;
+            data.color = Vector3::<f32>::new(color[0], color[1], color[2]);
+            data.offset = offset;
+            data.always_on_top = always_on_top;
+        }
+    }
+}
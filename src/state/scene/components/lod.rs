@@ -0,0 +1,207 @@
+use std::sync::{Arc, RwLock};
+
+use egui::{RichText, Color32};
+
+use crate::{helper::change_tracker::ChangeTracker, component_impl_default, component_impl_no_update, component_impl_set_enabled, state::scene::node::NodeItem, component_downcast};
+
+use super::{component::{ComponentBase, Component, ComponentItem}, mesh::Mesh};
+
+const INFO_STRING: &str = "Switches the rendered mesh based on the distance to the rendering camera.\nThe original mesh is used until the first LOD distance is reached.";
+
+const DEFAULT_GRID_RESOLUTION: u32 = 32;
+const DEFAULT_DISTANCE_STEP: f32 = 10.0;
+
+pub struct LodLevel
+{
+    pub distance: f32,
+    pub mesh: ComponentItem,
+}
+
+pub struct LodData
+{
+    pub levels: Vec<LodLevel>,
+}
+
+pub struct Lod
+{
+    base: ComponentBase,
+    data: ChangeTracker<LodData>,
+
+    pub grid_resolution: u32,
+    generate: bool,
+}
+
+impl Lod
+{
+    pub fn new(id: u64, name: &str) -> Lod
+    {
+        let data = LodData
+        {
+            levels: vec![]
+        };
+
+        let mut lod = Lod
+        {
+            base: ComponentBase::new(id, name.to_string(), "LOD".to_string(), "🔻".to_string()),
+            data: ChangeTracker::new(data),
+
+            grid_resolution: DEFAULT_GRID_RESOLUTION,
+            generate: false,
+        };
+
+        lod.base.info = Some(INFO_STRING.to_string());
+
+        lod
+    }
+
+    pub fn get_data(&self) -> &LodData
+    {
+        &self.data.get_ref()
+    }
+
+    pub fn get_data_tracker(&self) -> &ChangeTracker<LodData>
+    {
+        &self.data
+    }
+
+    pub fn get_data_mut(&mut self) -> &mut ChangeTracker<LodData>
+    {
+        &mut self.data
+    }
+
+    pub fn add_level(&mut self, distance: f32, mesh: Mesh)
+    {
+        let data = self.data.get_mut();
+        data.levels.push(LodLevel { distance, mesh: Arc::new(RwLock::new(Box::new(mesh))) });
+        data.levels.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+    }
+
+    // index of the level to use (None: the original mesh)
+    pub fn get_level_for_distance(&self, distance: f32) -> Option<usize>
+    {
+        let mut level_index = None;
+
+        // levels are sorted by distance -> use the last one in range
+        for (i, level) in self.get_data().levels.iter().enumerate()
+        {
+            if distance >= level.distance
+            {
+                level_index = Some(i);
+            }
+        }
+
+        level_index
+    }
+
+    pub fn generate_requested(&self) -> bool
+    {
+        self.generate
+    }
+
+    // the mesh id is needed from the scene (see Scene::update_lods)
+    pub fn generate_level(&mut self, node: NodeItem, mesh_id: u64)
+    {
+        self.generate = false;
+
+        let node = node.read().unwrap();
+        let mesh = node.find_component::<Mesh>();
+
+        if mesh.is_none()
+        {
+            println!("can not generate LOD for {}: no mesh found", node.name);
+            return;
+        }
+
+        let mesh = mesh.unwrap();
+        component_downcast!(mesh, Mesh);
+
+        // every further level halves the grid resolution
+        let levels = self.get_data().levels.len() as u32;
+        let grid_resolution = (self.grid_resolution >> levels).max(1);
+        let distance = (levels + 1) as f32 * DEFAULT_DISTANCE_STEP;
+
+        let name = format!("LOD {}", levels + 1);
+        if let Some(simplified) = mesh.create_simplified(mesh_id, name.as_str(), grid_resolution)
+        {
+            println!("LOD {} generated: {} -> {} vertices", levels + 1, mesh.get_data().vertices.len(), simplified.get_data().vertices.len());
+            self.add_level(distance, simplified);
+        }
+        else
+        {
+            println!("can not generate LOD for {}: mesh collapsed completely", node.name);
+        }
+    }
+}
+
+impl Component for Lod
+{
+    component_impl_default!();
+    component_impl_no_update!();
+    component_impl_set_enabled!();
+
+    fn instantiable(&self) -> bool
+    {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui)
+    {
+        let mut changed = false;
+        let mut remove_level = None;
+
+        let mut distances: Vec<f32>;
+        {
+            let data = self.get_data();
+            distances = data.levels.iter().map(|level| level.distance).collect();
+
+            for (i, level) in data.levels.iter().enumerate()
+            {
+                let vertices;
+                {
+                    let mesh = level.mesh.clone();
+                    component_downcast!(mesh, Mesh);
+                    vertices = mesh.get_data().vertices.len();
+                }
+
+                ui.horizontal(|ui|
+                {
+                    ui.label(format!("LOD {}: ", i + 1));
+                    changed = ui.add(egui::DragValue::new(&mut distances[i]).speed(0.1).clamp_range(0.0..=std::f32::MAX).prefix("distance: ")).changed() || changed;
+                    ui.label(format!("({} vertices)", vertices));
+
+                    if ui.button(RichText::new("🗑").color(Color32::LIGHT_RED)).on_hover_text("remove level").clicked()
+                    {
+                        remove_level = Some(i);
+                    }
+                });
+            }
+        }
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Grid resolution: ");
+            ui.add(egui::DragValue::new(&mut self.grid_resolution).speed(1).clamp_range(1..=1024));
+        });
+
+        if ui.button("Generate LOD").clicked()
+        {
+            self.generate = true;
+        }
+
+        if changed
+        {
+            let data = self.get_data_mut().get_mut();
+            for (i, level) in data.levels.iter_mut().enumerate()
+            {
+                level.distance = distances[i];
+            }
+
+            data.levels.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+        }
+
+        if let Some(remove_level) = remove_level
+        {
+            self.get_data_mut().get_mut().levels.remove(remove_level);
+        }
+    }
+}
@@ -3,10 +3,11 @@ use std::sync::{RwLock, Arc};
 use std::any::Any;
 
 use nalgebra::{Vector3, Vector4};
+use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter};
 
 use crate::helper::change_tracker::ChangeTracker;
-use crate::helper::math::approx_equal;
+use crate::helper::math::{approx_equal, approx_equal_vec};
 use crate::{component_impl_default, component_impl_no_update, component_impl_set_enabled};
 use crate::state::scene::node::NodeItem;
 use crate::{state::scene::texture::{TextureItem, Texture}, helper};
@@ -37,10 +38,14 @@ pub enum TextureType
     Custom0,
     Custom1,
     Custom2,
-    Custom3
+    Custom3,
+
+    Clearcoat,
+    ClearcoatRoughness,
+    ClearcoatNormal
 }
 
-pub const ALL_TEXTURE_TYPES: [TextureType; 14] =
+pub const ALL_TEXTURE_TYPES: [TextureType; 17] =
 [
     TextureType::AmbientEmissive,
     TextureType::Base,
@@ -56,14 +61,86 @@ pub const ALL_TEXTURE_TYPES: [TextureType; 14] =
     TextureType::Custom0,
     TextureType::Custom1,
     TextureType::Custom2,
-    TextureType::Custom3
+    TextureType::Custom3,
+
+    TextureType::Clearcoat,
+    TextureType::ClearcoatRoughness,
+    TextureType::ClearcoatNormal
 ];
 
+impl TextureType
+{
+    // single channel textures can be packed into one texture (like occlusion/roughness/metallic) - the used channel is selectable
+    pub fn is_single_channel(&self) -> bool
+    {
+        match self
+        {
+            TextureType::Roughness | TextureType::AmbientOcclusion | TextureType::Reflectivity | TextureType::Clearcoat | TextureType::ClearcoatRoughness => true,
+            _ => false
+        }
+    }
+
+    // gltf convention: occlusion (r), roughness (g), metallic (b)
+    pub fn default_channel(&self) -> TextureChannel
+    {
+        match self
+        {
+            TextureType::Roughness | TextureType::ClearcoatRoughness => TextureChannel::G,
+            TextureType::Reflectivity => TextureChannel::B,
+            _ => TextureChannel::R
+        }
+    }
+
+    // colors are srgb encoded - data (normals, roughness, metallic, occlusion, ...) is linear
+    pub fn color_space(&self) -> TextureColorSpace
+    {
+        match self
+        {
+            TextureType::Base | TextureType::AmbientEmissive | TextureType::Specular | TextureType::Environment => TextureColorSpace::Srgb,
+            _ => TextureColorSpace::Linear
+        }
+    }
+}
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TextureColorSpace
+{
+    Srgb,
+    Linear
+}
+
+// depth comparison of the color and depth pass
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Display, EnumIter)]
+pub enum DepthCompare
+{
+    Less,
+    #[strum(to_string = "Less Equal")]
+    LessEqual,
+    Equal,
+    #[strum(to_string = "Greater Equal")]
+    GreaterEqual,
+    Greater,
+    #[strum(to_string = "Not Equal")]
+    NotEqual,
+    Always,
+    Never,
+}
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum TextureChannel
+{
+    R,
+    G,
+    B,
+    A
+}
+
 #[derive(Clone)]
 pub struct TextureState
 {
     pub item: TextureItem,
-    pub enabled: bool
+    pub enabled: bool,
+    pub channel: TextureChannel // only used for single channel texture types
 }
 
 impl TextureState
@@ -73,7 +150,18 @@ impl TextureState
         TextureState
         {
             item,
-            enabled: true
+            enabled: true,
+            channel: TextureChannel::R
+        }
+    }
+
+    pub fn new_with_channel(item: TextureItem, channel: TextureChannel) -> TextureState
+    {
+        TextureState
+        {
+            item,
+            enabled: true,
+            channel
         }
     }
 
@@ -107,6 +195,10 @@ pub struct MaterialData
     pub texture_custom2: Option<TextureState>,
     pub texture_custom3: Option<TextureState>,
 
+    pub texture_clearcoat: Option<TextureState>,
+    pub texture_clearcoat_roughness: Option<TextureState>,
+    pub texture_clearcoat_normal: Option<TextureState>,
+
     pub alpha: f32,
     pub shininess: f32,
     pub reflectivity: f32,
@@ -123,10 +215,20 @@ pub struct MaterialData
 
     pub roughness: f32, //degree in rad (max PI/2)
 
+    pub clearcoat: f32, // second specular layer (car paint, lacquer)
+    pub clearcoat_roughness: f32,
+
+    pub transmission: f32, // light passing through the surface (glass, water) - the opaque scene behind is refracted
+
     pub smooth_shading: bool,
 
     pub reflection_only: bool,
-    pub backface_cullig: bool
+    pub backface_cullig: bool,
+
+    pub depth_write: bool, // disable for overlays/decals (not occluding other objects)
+    pub depth_test: DepthCompare,
+    pub depth_bias: i32, // polygon offset against z-fighting of coplanar geometry (negative = towards the camera)
+    pub depth_bias_slope_scale: f32,
 }
 
 pub struct Material
@@ -163,6 +265,10 @@ impl Material
             texture_custom2: None,
             texture_custom3: None,
 
+            texture_clearcoat: None,
+            texture_clearcoat_roughness: None,
+            texture_clearcoat_normal: None,
+
             alpha: 1.0,
             shininess: 150.0,
             reflectivity: 0.0,
@@ -177,12 +283,22 @@ impl Material
 
             roughness: 0.0,
 
+            clearcoat: 0.0,
+            clearcoat_roughness: 0.0,
+
+            transmission: 0.0,
+
             monte_carlo: true,
 
             smooth_shading: true,
 
             reflection_only: false,
             backface_cullig: true,
+
+            depth_write: true,
+            depth_test: DepthCompare::Less,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
         };
 
         Material
@@ -197,6 +313,11 @@ impl Material
         &self.data.get_ref()
     }
 
+    pub fn get_data_tracker(&self) -> &ChangeTracker<MaterialData>
+    {
+        &self.data
+    }
+
     pub fn get_data_mut(&mut self) -> &mut ChangeTracker<MaterialData>
     {
         &mut self.data
@@ -260,12 +381,22 @@ impl Material
 
         if !helper::math::approx_equal(default_material_data.roughness, new_mat_data.roughness) { data.roughness = new_mat_data.roughness; }
 
+        if !helper::math::approx_equal(default_material_data.clearcoat, new_mat_data.clearcoat) { data.clearcoat = new_mat_data.clearcoat; }
+        if !helper::math::approx_equal(default_material_data.clearcoat_roughness, new_mat_data.clearcoat_roughness) { data.clearcoat_roughness = new_mat_data.clearcoat_roughness; }
+
+        if !helper::math::approx_equal(default_material_data.transmission, new_mat_data.transmission) { data.transmission = new_mat_data.transmission; }
+
         if default_material_data.monte_carlo != new_mat_data.monte_carlo { data.monte_carlo = new_mat_data.monte_carlo; }
 
         if default_material_data.smooth_shading != new_mat_data.smooth_shading { data.smooth_shading = new_mat_data.smooth_shading; }
 
         if default_material_data.reflection_only != new_mat_data.reflection_only { data.reflection_only = new_mat_data.reflection_only; }
         if default_material_data.backface_cullig != new_mat_data.backface_cullig { data.backface_cullig = new_mat_data.backface_cullig; }
+
+        if default_material_data.depth_write != new_mat_data.depth_write { data.depth_write = new_mat_data.depth_write; }
+        if default_material_data.depth_test != new_mat_data.depth_test { data.depth_test = new_mat_data.depth_test; }
+        if default_material_data.depth_bias != new_mat_data.depth_bias { data.depth_bias = new_mat_data.depth_bias; }
+        if !helper::math::approx_equal(default_material_data.depth_bias_slope_scale, new_mat_data.depth_bias_slope_scale) { data.depth_bias_slope_scale = new_mat_data.depth_bias_slope_scale; }
     }
 
     pub fn apply_diff(&mut self, new_mat: &Material)
@@ -313,6 +444,10 @@ impl Material
         compare_and_apply_texture_diff!(data.texture_custom1, default_material_data.texture_custom1.as_ref(), new_mat_data.texture_custom1.clone());
         compare_and_apply_texture_diff!(data.texture_custom2, default_material_data.texture_custom2.as_ref(), new_mat_data.texture_custom2.clone());
         compare_and_apply_texture_diff!(data.texture_custom3, default_material_data.texture_custom3.as_ref(), new_mat_data.texture_custom3.clone());
+
+        compare_and_apply_texture_diff!(data.texture_clearcoat, default_material_data.texture_clearcoat.as_ref(), new_mat_data.texture_clearcoat.clone());
+        compare_and_apply_texture_diff!(data.texture_clearcoat_roughness, default_material_data.texture_clearcoat_roughness.as_ref(), new_mat_data.texture_clearcoat_roughness.clone());
+        compare_and_apply_texture_diff!(data.texture_clearcoat_normal, default_material_data.texture_clearcoat_normal.as_ref(), new_mat_data.texture_clearcoat_normal.clone());
     }
 
     pub fn print(&self)
@@ -338,6 +473,10 @@ impl Material
         println!("texture_custom2: {:?}", data.texture_custom2.is_some());
         println!("texture_custom3: {:?}", data.texture_custom3.is_some());
 
+        println!("texture_clearcoat: {:?}", data.texture_clearcoat.is_some());
+        println!("texture_clearcoat_roughness: {:?}", data.texture_clearcoat_roughness.is_some());
+        println!("texture_clearcoat_normal: {:?}", data.texture_clearcoat_normal.is_some());
+
         println!("alpha: {:?}", data.alpha);
         println!("shininess: {:?}", data.shininess);
         println!("reflectivity: {:?}", data.reflectivity);
@@ -351,12 +490,22 @@ impl Material
 
         println!("roughness: {:?}", data.roughness);
 
+        println!("clearcoat: {:?}", data.clearcoat);
+        println!("clearcoat_roughness: {:?}", data.clearcoat_roughness);
+
+        println!("transmission: {:?}", data.transmission);
+
         println!("monte_carlo: {:?}", data.monte_carlo);
 
         println!("smooth_shading: {:?}", data.smooth_shading);
 
         println!("reflection_only: {:?}", data.reflection_only);
         println!("backface_cullig: {:?}", data.backface_cullig);
+
+        println!("depth_write: {:?}", data.depth_write);
+        println!("depth_test: {:?}", data.depth_test);
+        println!("depth_bias: {:?}", data.depth_bias);
+        println!("depth_bias_slope_scale: {:?}", data.depth_bias_slope_scale);
     }
 
     pub fn remove_texture(&mut self, tex_type: TextureType)
@@ -380,6 +529,10 @@ impl Material
             TextureType::Custom1 => { data.texture_custom1 = None; },
             TextureType::Custom2 => { data.texture_custom2 = None; },
             TextureType::Custom3 => { data.texture_custom3 = None; },
+
+            TextureType::Clearcoat => { data.texture_clearcoat = None; },
+            TextureType::ClearcoatRoughness => { data.texture_clearcoat_roughness = None; },
+            TextureType::ClearcoatNormal => { data.texture_clearcoat_normal = None; },
         }
     }
 
@@ -404,6 +557,15 @@ impl Material
             TextureType::Custom1 => { data.texture_custom1 = Some(TextureState::new(tex.clone())); },
             TextureType::Custom2 => { data.texture_custom2 = Some(TextureState::new(tex.clone())); },
             TextureType::Custom3 => { data.texture_custom3 = Some(TextureState::new(tex.clone())); },
+
+            TextureType::Clearcoat => { data.texture_clearcoat = Some(TextureState::new(tex.clone())); },
+            TextureType::ClearcoatRoughness => { data.texture_clearcoat_roughness = Some(TextureState::new(tex.clone())); },
+            TextureType::ClearcoatNormal => { data.texture_clearcoat_normal = Some(TextureState::new(tex.clone())); },
+        }
+
+        if let Some(tex_state) = self.get_texture_by_type_mut(tex_type)
+        {
+            tex_state.channel = tex_type.default_channel();
         }
     }
 
@@ -417,6 +579,16 @@ impl Material
         self.get_texture_by_type_mut(tex_type).unwrap().enabled = state;
     }
 
+    pub fn set_texture_channel(&mut self, tex_type: TextureType, channel: TextureChannel)
+    {
+        if !self.has_texture(tex_type)
+        {
+            return;
+        }
+
+        self.get_texture_by_type_mut(tex_type).unwrap().channel = channel;
+    }
+
     pub fn has_texture_id(&self, texture_id: u64) -> bool
     {
         for texture_type in ALL_TEXTURE_TYPES
@@ -433,6 +605,81 @@ impl Material
         false
     }
 
+    // replaces all usages of the texture (enabled state and channel are kept)
+    pub fn replace_texture_by_id(&mut self, id: u64, texture: &TextureItem) -> bool
+    {
+        let mut replaced = false;
+        for texture_type in ALL_TEXTURE_TYPES
+        {
+            if let Some(state) = self.get_texture_by_type(texture_type)
+            {
+                if state.get().read().unwrap().id == id
+                {
+                    self.get_texture_by_type_mut(texture_type).unwrap().item = texture.clone();
+                    replaced = true;
+                }
+            }
+        }
+
+        replaced
+    }
+
+    // same values and the same textures (name and id are not compared)
+    pub fn is_identical(&self, other: &Material) -> bool
+    {
+        let a = self.get_data();
+        let b = other.get_data();
+
+        let same_values =
+            approx_equal_vec(&a.ambient_color, &b.ambient_color)
+            && approx_equal_vec(&a.base_color, &b.base_color)
+            && approx_equal_vec(&a.specular_color, &b.specular_color)
+            && approx_equal_vec(&a.highlight_color, &b.highlight_color)
+            && approx_equal(a.alpha, b.alpha)
+            && approx_equal(a.shininess, b.shininess)
+            && approx_equal(a.reflectivity, b.reflectivity)
+            && approx_equal(a.refraction_index, b.refraction_index)
+            && approx_equal(a.normal_map_strength, b.normal_map_strength)
+            && a.unlit_shading == b.unlit_shading
+            && a.cast_shadow == b.cast_shadow
+            && a.receive_shadow == b.receive_shadow
+            && approx_equal(a.shadow_softness, b.shadow_softness)
+            && a.monte_carlo == b.monte_carlo
+            && approx_equal(a.roughness, b.roughness)
+            && approx_equal(a.clearcoat, b.clearcoat)
+            && approx_equal(a.clearcoat_roughness, b.clearcoat_roughness)
+            && approx_equal(a.transmission, b.transmission)
+            && a.smooth_shading == b.smooth_shading
+            && a.reflection_only == b.reflection_only
+            && a.backface_cullig == b.backface_cullig
+            && a.depth_write == b.depth_write
+            && a.depth_test == b.depth_test
+            && a.depth_bias == b.depth_bias
+            && approx_equal(a.depth_bias_slope_scale, b.depth_bias_slope_scale);
+
+        if !same_values
+        {
+            return false;
+        }
+
+        for texture_type in ALL_TEXTURE_TYPES
+        {
+            let same_texture = match (self.get_texture_by_type(texture_type), other.get_texture_by_type(texture_type))
+            {
+                (None, None) => true,
+                (Some(a), Some(b)) => Arc::ptr_eq(&a.item, &b.item) && a.enabled == b.enabled && a.channel == b.channel,
+                _ => false,
+            };
+
+            if !same_texture
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
     pub fn has_any_texture(&self) -> bool
     {
         for texture_type in ALL_TEXTURE_TYPES
@@ -473,6 +720,11 @@ impl Material
         false
     }
 
+    pub fn has_transmission(&self) -> bool
+    {
+        self.get_data().transmission > 0.001
+    }
+
     pub fn get_texture_by_type(&self, tex_type: TextureType) -> Option<TextureState>
     {
         let tex;
@@ -496,6 +748,10 @@ impl Material
             TextureType::Custom1 => { tex = data.texture_custom1.clone() },
             TextureType::Custom2 => { tex = data.texture_custom2.clone() },
             TextureType::Custom3 => { tex = data.texture_custom3.clone() },
+
+            TextureType::Clearcoat => { tex = data.texture_clearcoat.clone() },
+            TextureType::ClearcoatRoughness => { tex = data.texture_clearcoat_roughness.clone() },
+            TextureType::ClearcoatNormal => { tex = data.texture_clearcoat_normal.clone() },
         }
 
         tex
@@ -524,6 +780,10 @@ impl Material
             TextureType::Custom1 => { if let Some(tex_state) = data.texture_custom1.as_mut() { tex = Some(tex_state) } else { tex = None; } },
             TextureType::Custom2 => { if let Some(tex_state) = data.texture_custom2.as_mut() { tex = Some(tex_state) } else { tex = None; } },
             TextureType::Custom3 => { if let Some(tex_state) = data.texture_custom3.as_mut() { tex = Some(tex_state) } else { tex = None; } },
+
+            TextureType::Clearcoat => { if let Some(tex_state) = data.texture_clearcoat.as_mut() { tex = Some(tex_state) } else { tex = None; } },
+            TextureType::ClearcoatRoughness => { if let Some(tex_state) = data.texture_clearcoat_roughness.as_mut() { tex = Some(tex_state) } else { tex = None; } },
+            TextureType::ClearcoatNormal => { if let Some(tex_state) = data.texture_clearcoat_normal.as_mut() { tex = Some(tex_state) } else { tex = None; } },
         }
 
         tex
@@ -666,10 +926,17 @@ impl Component for Material
 
         let mut shadow_softness;
         let mut roughness;
+        let mut clearcoat;
+        let mut clearcoat_roughness;
+        let mut transmission;
         let mut monte_carlo;
         let mut smooth_shading;
         let mut reflection_only;
         let mut backface_cullig;
+        let mut depth_write;
+        let mut depth_test;
+        let mut depth_bias;
+        let mut depth_bias_slope_scale;
 
         let mut ambient_color;
         let mut base_color;
@@ -691,10 +958,17 @@ impl Component for Material
 
             shadow_softness = data.shadow_softness;
             roughness = data.roughness;
+            clearcoat = data.clearcoat;
+            clearcoat_roughness = data.clearcoat_roughness;
+            transmission = data.transmission;
             monte_carlo = data.monte_carlo;
             smooth_shading = data.smooth_shading;
             reflection_only = data.reflection_only;
             backface_cullig = data.backface_cullig;
+            depth_write = data.depth_write;
+            depth_test = data.depth_test;
+            depth_bias = data.depth_bias;
+            depth_bias_slope_scale = data.depth_bias_slope_scale;
 
             let r = (data.ambient_color.x * 255.0) as u8;
             let g = (data.ambient_color.y * 255.0) as u8;
@@ -731,10 +1005,33 @@ impl Component for Material
 
         apply_settings = ui.add(egui::Slider::new(&mut shadow_softness, 0.0..=100.0).text("shadow softness")).changed() || apply_settings;
         apply_settings = ui.add(egui::Slider::new(&mut roughness, 0.0..=5.0).text("roughness")).changed() || apply_settings;
+        apply_settings = ui.add(egui::Slider::new(&mut clearcoat, 0.0..=1.0).text("clearcoat")).changed() || apply_settings;
+        apply_settings = ui.add(egui::Slider::new(&mut clearcoat_roughness, 0.0..=1.0).text("clearcoat roughness")).changed() || apply_settings;
+        apply_settings = ui.add(egui::Slider::new(&mut transmission, 0.0..=1.0).text("transmission")).changed() || apply_settings;
         apply_settings = ui.checkbox(&mut monte_carlo, "monte carlo").changed() || apply_settings;
         apply_settings = ui.checkbox(&mut smooth_shading, "smooth shading").changed() || apply_settings;
         apply_settings = ui.checkbox(&mut reflection_only, "reflection only").changed() || apply_settings;
-        apply_settings = ui.checkbox(&mut backface_cullig, "backface cullig").changed() || apply_settings;
+        apply_settings = ui.checkbox(&mut backface_cullig, "backface culling (single sided)").changed() || apply_settings;
+        apply_settings = ui.checkbox(&mut depth_write, "depth write").changed() || apply_settings;
+
+        ui.horizontal(|ui|
+        {
+            ui.label("depth test:");
+            egui::ComboBox::from_id_source("material_depth_test").selected_text(depth_test.to_string()).show_ui(ui, |ui|
+            {
+                ui.style_mut().wrap = Some(false);
+                ui.set_min_width(40.0);
+
+                for depth_compare in DepthCompare::iter()
+                {
+                    apply_settings = ui.selectable_value(&mut depth_test, depth_compare, depth_compare.to_string()).changed() || apply_settings;
+                }
+            });
+        });
+
+        // every distinct bias combination is a separate pipeline -> coarse steps
+        apply_settings = ui.add(egui::Slider::new(&mut depth_bias, -100..=100).text("depth bias")).changed() || apply_settings;
+        apply_settings = ui.add(egui::Slider::new(&mut depth_bias_slope_scale, -10.0..=10.0).step_by(0.5).text("depth bias slope scale")).changed() || apply_settings;
 
         ui.horizontal(|ui|
         {
@@ -777,10 +1074,17 @@ impl Component for Material
 
             data.shadow_softness = shadow_softness;
             data.roughness = roughness;
+            data.clearcoat = clearcoat;
+            data.clearcoat_roughness = clearcoat_roughness;
+            data.transmission = transmission;
             data.monte_carlo = monte_carlo;
             data.smooth_shading = smooth_shading;
             data.reflection_only = reflection_only;
             data.backface_cullig = backface_cullig;
+            data.depth_write = depth_write;
+            data.depth_test = depth_test;
+            data.depth_bias = depth_bias;
+            data.depth_bias_slope_scale = depth_bias_slope_scale;
 
             let r = ((ambient_color.r() as f32) / 255.0).clamp(0.0, 1.0);
             let g = ((ambient_color.g() as f32) / 255.0).clamp(0.0, 1.0);
@@ -1,3 +1,5 @@
+use std::collections::{HashMap, HashSet};
+
 use nalgebra::{Point2, Point3, Isometry3, Vector3, Matrix4};
 use parry3d::{shape::{TriMesh, FeatureId}, bounding_volume::Aabb, query::{Ray, RayCast}};
 
@@ -5,6 +7,36 @@ use crate::{component_impl_default, helper::change_tracker::ChangeTracker, state
 
 use super::component::{Component, ComponentBase};
 
+const DEFAULT_WELD_POSITION_EPSILON: f32 = 0.0001;
+const DEFAULT_WELD_NORMAL_EPSILON: f32 = 0.01;
+const DEGENERATE_AREA_EPSILON: f32 = 1e-12;
+
+// attributes which are available on a mesh
+struct AttributeState
+{
+    normals: bool,
+    uvs_1: bool,
+    uvs_2: bool,
+    uvs_3: bool,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct MeshValidation
+{
+    pub degenerate_triangles: usize, // zero area or the same vertex used twice
+    pub duplicate_triangles: usize, // same vertices and winding as an other triangle
+    pub invalid_vertices: usize, // NaN or inf
+    pub out_of_range_indices: usize,
+}
+
+impl MeshValidation
+{
+    pub fn is_valid(&self) -> bool
+    {
+        self.degenerate_triangles == 0 && self.duplicate_triangles == 0 && self.invalid_vertices == 0 && self.out_of_range_indices == 0
+    }
+}
+
 pub struct MeshData
 {
     pub mesh: TriMesh,
@@ -22,6 +54,7 @@ pub struct MeshData
 
     pub flip_normals: bool,
     pub b_box: Aabb,
+    pub b_sphere: (Point3<f32>, f32),
 }
 
 impl MeshData
@@ -46,6 +79,7 @@ impl MeshData
         self.mesh = TriMesh::new(triangle.to_vec(), [indices].to_vec());
 
         self.b_box = Aabb::new_invalid();
+        self.b_sphere = (Point3::<f32>::origin(), 0.0);
     }
 }
 
@@ -53,6 +87,11 @@ pub struct Mesh
 {
     base: ComponentBase,
     data: ChangeTracker<MeshData>,
+
+    pub weld_position_epsilon: f32,
+    pub weld_normal_epsilon: f32,
+    weld_info: Option<(usize, usize)>,
+    validation: Option<MeshValidation>,
 }
 
 impl Mesh
@@ -75,12 +114,18 @@ impl Mesh
 
             flip_normals: false,
             b_box: Aabb::new_invalid(),
+            b_sphere: (Point3::<f32>::origin(), 0.0),
         };
 
         let mut mesh = Mesh
         {
             base: ComponentBase::new(id, name.to_string(), "Mesh".to_string(), "◼".to_string()),
-            data: ChangeTracker::new(mesh_data)
+            data: ChangeTracker::new(mesh_data),
+
+            weld_position_epsilon: DEFAULT_WELD_POSITION_EPSILON,
+            weld_normal_epsilon: DEFAULT_WELD_NORMAL_EPSILON,
+            weld_info: None,
+            validation: None,
         };
 
         mesh.calc_bbox();
@@ -100,10 +145,220 @@ impl Mesh
             Point2::new(0.0, 1.0),
         ];
 
+        let normal = (x1 - x0).cross(&(x2 - x0)).normalize();
+        let normals = vec![normal];
+
         let indices = vec![[0u32, 1, 2], [0, 2, 3]];
         let uv_indices = vec![[0u32, 1, 2], [0, 2, 3]];
+        let normals_indices = vec![[0u32, 0, 0], [0, 0, 0]];
+
+        let mut mesh = Mesh::new_with_data(id, name, points, indices, uvs, uv_indices, normals, normals_indices);
+
+        mesh.calc_bbox();
+
+        mesh
+    }
+
+    pub fn new_box(id: u64, name: &str, size: Vector3<f32>) -> Mesh
+    {
+        let half = size / 2.0;
+
+        // normal, u axis, v axis (u x v = normal -> ccw)
+        let faces =
+        [
+            (Vector3::<f32>::x(), -Vector3::<f32>::z(), Vector3::<f32>::y()),
+            (-Vector3::<f32>::x(), Vector3::<f32>::z(), Vector3::<f32>::y()),
+            (Vector3::<f32>::y(), Vector3::<f32>::x(), -Vector3::<f32>::z()),
+            (-Vector3::<f32>::y(), Vector3::<f32>::x(), Vector3::<f32>::z()),
+            (Vector3::<f32>::z(), Vector3::<f32>::x(), Vector3::<f32>::y()),
+            (-Vector3::<f32>::z(), -Vector3::<f32>::x(), Vector3::<f32>::y()),
+        ];
+
+        let mut points = vec![];
+        let mut normals = vec![];
+        let mut uvs = vec![];
+        let mut indices = vec![];
+
+        for (normal, u_axis, v_axis) in faces
+        {
+            let center = normal.component_mul(&half);
+            let u = u_axis.component_mul(&half);
+            let v = v_axis.component_mul(&half);
+
+            let start = points.len() as u32;
+
+            points.push(Point3::<f32>::from(center - u - v));
+            points.push(Point3::<f32>::from(center + u - v));
+            points.push(Point3::<f32>::from(center + u + v));
+            points.push(Point3::<f32>::from(center - u + v));
+
+            uvs.push(Point2::<f32>::new(0.0, 0.0));
+            uvs.push(Point2::<f32>::new(1.0, 0.0));
+            uvs.push(Point2::<f32>::new(1.0, 1.0));
+            uvs.push(Point2::<f32>::new(0.0, 1.0));
+
+            for _ in 0..4
+            {
+                normals.push(normal);
+            }
+
+            indices.push([start, start + 1, start + 2]);
+            indices.push([start, start + 2, start + 3]);
+        }
+
+        let mut mesh = Mesh::new_with_data(id, name, points, indices.clone(), uvs, indices.clone(), normals, indices);
+
+        mesh.calc_bbox();
+
+        mesh
+    }
+
+    pub fn new_cylinder(id: u64, name: &str, radius: f32, height: f32, segments: u32) -> Mesh
+    {
+        Self::new_cylinder_or_cone(id, name, radius, radius, height, segments)
+    }
+
+    pub fn new_cone(id: u64, name: &str, radius: f32, height: f32, segments: u32) -> Mesh
+    {
+        Self::new_cylinder_or_cone(id, name, radius, 0.0, height, segments)
+    }
+
+    // y-axis aligned and centered at the origin
+    fn new_cylinder_or_cone(id: u64, name: &str, radius_bottom: f32, radius_top: f32, height: f32, segments: u32) -> Mesh
+    {
+        let segments = segments.max(3);
+        let half_height = height / 2.0;
+
+        let mut points = vec![];
+        let mut normals = vec![];
+        let mut uvs = vec![];
+        let mut indices = vec![];
+
+        // side (the normal is tilted based on the radius difference)
+        let slope = (radius_bottom - radius_top) / height.max(std::f32::EPSILON);
+
+        for segment in 0..=segments
+        {
+            let u = segment as f32 / segments as f32;
+            let phi = u * std::f32::consts::PI * 2.0;
+            let (sin, cos) = phi.sin_cos();
+
+            let normal = Vector3::<f32>::new(cos, slope, sin).normalize();
+
+            points.push(Point3::<f32>::new(cos * radius_top, half_height, sin * radius_top));
+            normals.push(normal);
+            uvs.push(Point2::<f32>::new(u, 0.0));
+
+            points.push(Point3::<f32>::new(cos * radius_bottom, -half_height, sin * radius_bottom));
+            normals.push(normal);
+            uvs.push(Point2::<f32>::new(u, 1.0));
+        }
+
+        for segment in 0..segments
+        {
+            let top = segment * 2;
+            let bottom = top + 1;
+
+            // the top triangle is collapsed for cones
+            if radius_top > 0.0
+            {
+                indices.push([top, top + 2, bottom]);
+            }
+
+            if radius_bottom > 0.0
+            {
+                indices.push([top + 2, bottom + 2, bottom]);
+            }
+        }
+
+        // caps
+        let caps = [(radius_top, half_height, Vector3::<f32>::y()), (radius_bottom, -half_height, -Vector3::<f32>::y())];
+
+        for (radius, y, normal) in caps
+        {
+            if radius <= 0.0
+            {
+                continue;
+            }
+
+            let center = points.len() as u32;
+            points.push(Point3::<f32>::new(0.0, y, 0.0));
+            normals.push(normal);
+            uvs.push(Point2::<f32>::new(0.5, 0.5));
+
+            for segment in 0..=segments
+            {
+                let phi = segment as f32 / segments as f32 * std::f32::consts::PI * 2.0;
+                let (sin, cos) = phi.sin_cos();
+
+                points.push(Point3::<f32>::new(cos * radius, y, sin * radius));
+                normals.push(normal);
+                uvs.push(Point2::<f32>::new(0.5 + cos * 0.5, 0.5 + sin * 0.5));
+            }
+
+            for segment in 0..segments
+            {
+                let current = center + 1 + segment;
+
+                if normal.y > 0.0
+                {
+                    indices.push([center, current + 1, current]);
+                }
+                else
+                {
+                    indices.push([center, current, current + 1]);
+                }
+            }
+        }
+
+        let mut mesh = Mesh::new_with_data(id, name, points, indices.clone(), uvs, indices.clone(), normals, indices);
+
+        mesh.calc_bbox();
+
+        mesh
+    }
+
+    pub fn new_sphere(id: u64, name: &str, radius: f32, segments: u32, rings: u32) -> Mesh
+    {
+        let segments = segments.max(3);
+        let rings = rings.max(2);
 
-        let mut mesh = Mesh::new_with_data(id, name, points, indices, uvs, uv_indices, vec![], vec![]);
+        let mut points = vec![];
+        let mut normals = vec![];
+        let mut uvs = vec![];
+        let mut indices = vec![];
+
+        for ring in 0..=rings
+        {
+            let v = ring as f32 / rings as f32;
+            let theta = v * std::f32::consts::PI;
+
+            for segment in 0..=segments
+            {
+                let u = segment as f32 / segments as f32;
+                let phi = u * std::f32::consts::PI * 2.0;
+
+                let normal = Vector3::<f32>::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+
+                points.push(Point3::<f32>::from(normal * radius));
+                normals.push(normal);
+                uvs.push(Point2::<f32>::new(u, v));
+            }
+        }
+
+        for ring in 0..rings
+        {
+            for segment in 0..segments
+            {
+                let a = ring * (segments + 1) + segment;
+                let b = a + segments + 1;
+
+                indices.push([a, a + 1, b]);
+                indices.push([a + 1, b + 1, b]);
+            }
+        }
+
+        let mut mesh = Mesh::new_with_data(id, name, points, indices.clone(), uvs, indices.clone(), normals, indices);
 
         mesh.calc_bbox();
 
@@ -134,6 +389,59 @@ impl Mesh
         let trans = Isometry3::<f32>::identity();
         let mut data = self.data.get_mut();
         data.b_box = data.mesh.aabb(&trans);
+        data.b_sphere = Self::calc_bounding_sphere(&data.vertices);
+    }
+
+    fn calc_bounding_sphere(vertices: &Vec<Point3<f32>>) -> (Point3<f32>, f32)
+    {
+        // Ritter's bounding sphere (approximation)
+        if vertices.len() == 0
+        {
+            return (Point3::<f32>::origin(), 0.0);
+        }
+
+        let farthest_from = |point: &Point3<f32>| -> Point3<f32>
+        {
+            let mut farthest = *point;
+            let mut max_dist = 0.0;
+
+            for vertex in vertices
+            {
+                let dist = nalgebra::distance_squared(point, vertex);
+                if dist > max_dist
+                {
+                    max_dist = dist;
+                    farthest = *vertex;
+                }
+            }
+
+            farthest
+        };
+
+        let p1 = farthest_from(&vertices[0]);
+        let p2 = farthest_from(&p1);
+
+        let mut center = nalgebra::center(&p1, &p2);
+        let mut radius = nalgebra::distance(&p1, &p2) / 2.0;
+
+        // grow the sphere for all points outside
+        for vertex in vertices
+        {
+            let dist = nalgebra::distance(vertex, &center);
+            if dist > radius
+            {
+                let new_radius = (radius + dist) / 2.0;
+                center = center + (vertex - center) * ((new_radius - radius) / dist);
+                radius = new_radius;
+            }
+        }
+
+        (center, radius)
+    }
+
+    pub fn bounding_sphere(&self) -> (Point3<f32>, f32)
+    {
+        self.get_data().b_sphere
     }
 
     pub fn intersect_b_box(&self, ray_inverse: &Ray, solid: bool) -> Option<f32>
@@ -206,22 +514,56 @@ impl Mesh
     }
 
     pub fn merge(&mut self, mesh_data: &MeshData)
+    {
+        self.append(mesh_data, &Matrix4::<f32>::identity());
+        self.rebuild();
+    }
+
+    // appends the (transformed) mesh data without updating the collision mesh - rebuild() needs to be called afterwards
+    // (to merge many meshes at once)
+    pub fn append(&mut self, mesh_data: &MeshData, transform: &Matrix4<f32>)
     {
         let data = self.data.get_mut();
 
+        // attributes which are only available on one of the meshes are filled up (for the other one)
+        let required = AttributeState
+        {
+            normals: !data.normals.is_empty() || !mesh_data.normals.is_empty(),
+            uvs_1: !data.uvs_1.is_empty() || !mesh_data.uvs_1.is_empty(),
+            uvs_2: !data.uvs_2.is_empty() || !mesh_data.uvs_2.is_empty(),
+            uvs_3: !data.uvs_3.is_empty() || !mesh_data.uvs_3.is_empty(),
+        };
+
+        Self::fill_attributes(data, &required, 0, 0, 0, 0);
+
         let vertices_offset = data.vertices.len() as u32;
+        let faces_offset = data.indices.len();
         let normals_offset = data.normals.len() as u32;
         let uv_offset = data.uvs_1.len() as u32;
 
+        let identity = *transform == Matrix4::<f32>::identity();
+        let normal_matrix = transform.fixed_view::<3, 3>(0, 0).try_inverse().unwrap_or_default().transpose();
+
+        // mirroring transformations are flipping the winding order
+        let mirrored = transform.fixed_view::<3, 3>(0, 0).determinant() < 0.0;
+        let face = |i: &[u32; 3], offset: u32| -> [u32; 3]
+        {
+            if mirrored { [i[0] + offset, i[2] + offset, i[1] + offset] } else { [i[0] + offset, i[1] + offset, i[2] + offset] }
+        };
+
         // vertices and indices
-        data.vertices.extend(&mesh_data.vertices);
+        if identity
+        {
+            data.vertices.extend(&mesh_data.vertices);
+        }
+        else
+        {
+            data.vertices.extend(mesh_data.vertices.iter().map(|vertex| transform.transform_point(vertex)));
+        }
 
         for i in &mesh_data.indices
         {
-            let i0 = i[0] + vertices_offset;
-            let i1 = i[1] + vertices_offset;
-            let i2 = i[2] + vertices_offset;
-            data.indices.push([i0, i1, i2]);
+            data.indices.push(face(i, vertices_offset));
         }
 
         // uvs and uv indices (1)
@@ -231,24 +573,113 @@ impl Mesh
 
         for i in &mesh_data.uv_indices
         {
-            let i0 = i[0] + uv_offset;
-            let i1 = i[1] + uv_offset;
-            let i2 = i[2] + uv_offset;
-            data.uv_indices.push([i0, i1, i2]);
+            data.uv_indices.push(face(i, uv_offset));
         }
 
         // normals
-        data.normals.extend(&mesh_data.normals);
+        if identity
+        {
+            data.normals.extend(&mesh_data.normals);
+        }
+        else
+        {
+            data.normals.extend(mesh_data.normals.iter().map(|normal|
+            {
+                let transformed = normal_matrix * normal;
+                if transformed.magnitude_squared() > 0.0 { transformed.normalize() } else { *normal }
+            }));
+        }
 
         for i in &mesh_data.normals_indices
         {
-            let i0 = i[0] + normals_offset;
-            let i1 = i[1] + normals_offset;
-            let i2 = i[2] + normals_offset;
-            data.normals_indices.push([i0, i1, i2]);
+            data.normals_indices.push(face(i, normals_offset));
         }
 
-        data.mesh = TriMesh::new(data.vertices.clone(), data.indices.clone());
+        Self::fill_attributes(data, &required, vertices_offset as usize, faces_offset, normals_offset as usize, uv_offset as usize);
+    }
+
+    // fills missing attributes of the mesh part starting at the given offsets - to keep them aligned with the vertices
+    // normals are calculated based on the faces and uvs are zero
+    // missing attribute indices are using the vertex indices
+    fn fill_attributes(data: &mut MeshData, required: &AttributeState, vertices_offset: usize, faces_offset: usize, normals_offset: usize, uv_offset: usize)
+    {
+        let vertices = data.vertices.len() - vertices_offset;
+
+        // corner index -> attribute index (for attributes which are indexed like the vertices)
+        let attribute_face = |face: &[u32; 3], attribute_offset: usize| -> [u32; 3]
+        {
+            let offset = attribute_offset as i64 - vertices_offset as i64;
+            [(face[0] as i64 + offset) as u32, (face[1] as i64 + offset) as u32, (face[2] as i64 + offset) as u32]
+        };
+
+        // ********** normals **********
+        if required.normals && data.normals.len() == normals_offset
+        {
+            data.normals.resize(normals_offset + vertices, Vector3::<f32>::zeros());
+
+            for face in &data.indices[faces_offset..]
+            {
+                let (a, b, c) = (data.vertices[face[0] as usize], data.vertices[face[1] as usize], data.vertices[face[2] as usize]);
+                let normal = (b - a).cross(&(c - a));
+
+                for index in attribute_face(face, normals_offset)
+                {
+                    data.normals[index as usize] += normal;
+                }
+            }
+
+            for normal in &mut data.normals[normals_offset..]
+            {
+                if normal.magnitude_squared() > 0.0
+                {
+                    *normal = normal.normalize();
+                }
+            }
+        }
+
+        if required.normals && data.normals_indices.len() < data.indices.len()
+        {
+            let start = data.normals_indices.len().max(faces_offset);
+            for face in &data.indices[start..]
+            {
+                data.normals_indices.push(attribute_face(face, normals_offset));
+            }
+        }
+
+        // ********** uvs **********
+        if required.uvs_1 && data.uvs_1.len() == uv_offset
+        {
+            data.uvs_1.resize(uv_offset + vertices, Point2::<f32>::origin());
+        }
+
+        if required.uvs_1 && data.uv_indices.len() < data.indices.len()
+        {
+            let start = data.uv_indices.len().max(faces_offset);
+            for face in &data.indices[start..]
+            {
+                data.uv_indices.push(attribute_face(face, uv_offset));
+            }
+        }
+
+        // additional uv channels are using the uv indices as well
+        if required.uvs_2 && data.uvs_2.len() < data.uvs_1.len()
+        {
+            data.uvs_2.resize(data.uvs_1.len(), Point2::<f32>::origin());
+        }
+
+        if required.uvs_3 && data.uvs_3.len() < data.uvs_1.len()
+        {
+            data.uvs_3.resize(data.uvs_1.len(), Point2::<f32>::origin());
+        }
+    }
+
+    // updates the collision mesh and the bounding volumes (after changing the vertices/indices)
+    pub fn rebuild(&mut self)
+    {
+        {
+            let data = self.data.get_mut();
+            data.mesh = TriMesh::new(data.vertices.clone(), data.indices.clone());
+        }
 
         self.calc_bbox();
     }
@@ -347,63 +778,790 @@ impl Mesh
         self.calc_bbox();
     }
 
-    pub fn get_normal(&self, hit: Point3<f32>, face_id: u32, tran_inverse: &Matrix4<f32>) -> Vector3<f32>
+    pub fn create_simplified(&self, id: u64, name: &str, grid_resolution: u32) -> Option<Mesh>
     {
-        let data = self.data.get_ref();
+        // vertex clustering: all vertices in the same grid cell are merged into one vertex
+        let data = self.get_data();
 
-        // https://stackoverflow.com/questions/23980748/triangle-texture-mapping-with-barycentric-coordinates
-        // https://answers.unity.com/questions/383804/calculate-uv-coordinates-of-3d-point-on-plane-of-m.html
+        let grid_resolution = grid_resolution.max(1) as f32;
+        let extents = data.b_box.extents();
 
-        //transform hit to local coords
-        let hit_pos_local = tran_inverse * hit.to_homogeneous();
-        let hit_pos_local = Point3::<f32>::from_homogeneous(hit_pos_local).unwrap();
+        let cell_size = Vector3::<f32>::new
+        (
+            (extents.x / grid_resolution).max(std::f32::EPSILON),
+            (extents.y / grid_resolution).max(std::f32::EPSILON),
+            (extents.z / grid_resolution).max(std::f32::EPSILON),
+        );
 
-        let f_id = (face_id % data.mesh.indices().len() as u32) as usize;
+        let has_normals = Self::has_attribute(data, data.normals.len(), &data.normals_indices);
+        let has_uvs = Self::has_attribute(data, data.uvs_1.len(), &data.uv_indices);
+        let has_uvs_2 = has_uvs && data.uvs_2.len() == data.uvs_1.len();
+        let has_uvs_3 = has_uvs && data.uvs_3.len() == data.uvs_1.len();
 
-        let face = data.mesh.indices()[f_id];
-        let normal_face = data.normals_indices[f_id];
+        let mut cells: HashMap<(i32, i32, i32), u32> = HashMap::new();
+        let mut vertex_map: Vec<u32> = Vec::with_capacity(data.vertices.len());
 
-        let i0 = face[0] as usize;
-        let i1 = face[1] as usize;
-        let i2 = face[2] as usize;
+        let mut vertices: Vec<Point3<f32>> = vec![];
+        let mut counts: Vec<f32> = vec![];
 
-        let i_normal_0 = normal_face[0] as usize;
-        let i_normal_1 = normal_face[1] as usize;
-        let i_normal_2 = normal_face[2] as usize;
+        for vertex in &data.vertices
+        {
+            let cell =
+            (
+                ((vertex.x - data.b_box.mins.x) / cell_size.x).floor() as i32,
+                ((vertex.y - data.b_box.mins.y) / cell_size.y).floor() as i32,
+                ((vertex.z - data.b_box.mins.z) / cell_size.z).floor() as i32,
+            );
+
+            let index = *cells.entry(cell).or_insert_with(||
+            {
+                vertices.push(Point3::<f32>::origin());
+                counts.push(0.0);
 
-        let a = data.mesh.vertices()[i0].to_homogeneous();
-        let b = data.mesh.vertices()[i1].to_homogeneous();
-        let c = data.mesh.vertices()[i2].to_homogeneous();
+                (vertices.len() - 1) as u32
+            });
 
-        let a_t = data.normals[i_normal_0];
-        let b_t = data.normals[i_normal_1];
-        let c_t = data.normals[i_normal_2];
+            vertices[index as usize].coords += vertex.coords;
+            counts[index as usize] += 1.0;
 
-        let a = Point3::<f32>::from_homogeneous(a).unwrap();
-        let b = Point3::<f32>::from_homogeneous(b).unwrap();
-        let c = Point3::<f32>::from_homogeneous(c).unwrap();
+            vertex_map.push(index);
+        }
 
-        let f1 = a - hit_pos_local;
-        let f2 = b - hit_pos_local;
-        let f3 = c - hit_pos_local;
+        for i in 0..vertices.len()
+        {
+            vertices[i].coords /= counts[i];
+        }
 
-        let a = (a-b).cross(&(a-c)).magnitude();
-        let a1 = f2.cross(&f3).magnitude() / a;
-        let a2 = f3.cross(&f1).magnitude() / a;
-        let a3 = f1.cross(&f2).magnitude() / a;
+        // normals and uvs are accumulated per face corner (they can have their own index arrays)
+        let mut normals: Vec<Vector3<f32>> = vec![Vector3::<f32>::zeros(); vertices.len()];
+        let mut uvs_1: Vec<Point2<f32>> = vec![Point2::<f32>::origin(); vertices.len()];
+        let mut uvs_2: Vec<Point2<f32>> = vec![Point2::<f32>::origin(); vertices.len()];
+        let mut uvs_3: Vec<Point2<f32>> = vec![Point2::<f32>::origin(); vertices.len()];
+        let mut uv_counts: Vec<f32> = vec![0.0; vertices.len()];
 
-        let part_1 = a_t * a1;
-        let part_2 = b_t * a2;
-        let part_3 = c_t * a3;
+        for (face_index, face) in data.indices.iter().enumerate()
+        {
+            for corner in 0..3
+            {
+                let index = vertex_map[face[corner] as usize] as usize;
 
-        let normal = Point3::<f32>::new
-        (
-            part_1.x + part_2.x + part_3.x,
-            part_1.y + part_2.y + part_3.y,
-            part_1.z + part_2.z + part_3.z,
-        );
+                if has_normals
+                {
+                    let normal_index = Self::corner_attribute_index(data, &data.normals_indices, face_index, corner);
+                    if let Some(normal) = data.normals.get(normal_index)
+                    {
+                        normals[index] += normal;
+                    }
+                }
 
-        Vector3::<f32>::new(normal.x, normal.y, normal.z)
+                if has_uvs
+                {
+                    let uv_index = Self::corner_attribute_index(data, &data.uv_indices, face_index, corner);
+                    if uv_index >= data.uvs_1.len()
+                    {
+                        continue;
+                    }
+
+                    uvs_1[index].coords += data.uvs_1[uv_index].coords;
+
+                    if has_uvs_2
+                    {
+                        uvs_2[index].coords += data.uvs_2[uv_index].coords;
+                    }
+
+                    if has_uvs_3
+                    {
+                        uvs_3[index].coords += data.uvs_3[uv_index].coords;
+                    }
+
+                    uv_counts[index] += 1.0;
+                }
+            }
+        }
+
+        for i in 0..vertices.len()
+        {
+            if normals[i].magnitude() > std::f32::EPSILON
+            {
+                normals[i] = normals[i].normalize();
+            }
+
+            if uv_counts[i] > 0.0
+            {
+                uvs_1[i].coords /= uv_counts[i];
+                uvs_2[i].coords /= uv_counts[i];
+                uvs_3[i].coords /= uv_counts[i];
+            }
+        }
+
+        // remap indices and remove collapsed triangles
+        let mut indices: Vec<[u32; 3]> = vec![];
+        for face in &data.indices
+        {
+            let i0 = vertex_map[face[0] as usize];
+            let i1 = vertex_map[face[1] as usize];
+            let i2 = vertex_map[face[2] as usize];
+
+            if i0 == i1 || i1 == i2 || i0 == i2
+            {
+                continue;
+            }
+
+            indices.push([i0, i1, i2]);
+        }
+
+        // everything collapsed
+        if indices.len() == 0
+        {
+            return None;
+        }
+
+        // the simplified attributes are stored per vertex
+        let mut normals_indices = vec![];
+        if has_normals
+        {
+            normals_indices = indices.clone();
+        }
+        else
+        {
+            normals.clear();
+        }
+
+        let mut uv_indices = vec![];
+        if has_uvs
+        {
+            uv_indices = indices.clone();
+        }
+        else
+        {
+            uvs_1.clear();
+        }
+
+        let mut mesh = Mesh::new_with_data(id, name, vertices, indices, uvs_1, uv_indices, normals, normals_indices);
+        {
+            let mesh_data = mesh.get_data_mut().get_mut();
+            mesh_data.flip_normals = data.flip_normals;
+
+            if has_uvs_2
+            {
+                mesh_data.uvs_2 = uvs_2;
+            }
+
+            if has_uvs_3
+            {
+                mesh_data.uvs_3 = uvs_3;
+            }
+        }
+
+        Some(mesh)
+    }
+
+    // the attribute (normals, uvs) has an own index array or one entry per vertex
+    fn has_attribute(data: &MeshData, attributes: usize, attribute_indices: &Vec<[u32; 3]>) -> bool
+    {
+        attributes > 0 && (attribute_indices.len() == data.indices.len() || attributes == data.vertices.len())
+    }
+
+    // attribute index of a face corner (see has_attribute)
+    fn corner_attribute_index(data: &MeshData, attribute_indices: &Vec<[u32; 3]>, face_index: usize, corner: usize) -> usize
+    {
+        if attribute_indices.len() == data.indices.len()
+        {
+            attribute_indices[face_index][corner] as usize
+        }
+        else
+        {
+            data.indices[face_index][corner] as usize
+        }
+    }
+
+    // faces grouped by connected parts (faces sharing a vertex index are connected)
+    pub fn get_connected_components(&self) -> Vec<Vec<usize>>
+    {
+        let data = self.get_data();
+
+        // union find over the vertex indices
+        let mut parents: Vec<usize> = (0..data.vertices.len()).collect();
+
+        fn find(parents: &mut Vec<usize>, mut index: usize) -> usize
+        {
+            while parents[index] != index
+            {
+                parents[index] = parents[parents[index]];
+                index = parents[index];
+            }
+
+            index
+        }
+
+        for face in &data.indices
+        {
+            if face.iter().any(|index| *index as usize >= parents.len())
+            {
+                continue;
+            }
+
+            let root = find(&mut parents, face[0] as usize);
+
+            for index in &face[1..]
+            {
+                let other = find(&mut parents, *index as usize);
+                parents[other] = root;
+            }
+        }
+
+        // the order of the parts is based on the first face
+        let mut components: Vec<Vec<usize>> = vec![];
+        let mut component_indices: HashMap<usize, usize> = HashMap::new();
+
+        for (face_index, face) in data.indices.iter().enumerate()
+        {
+            if face.iter().any(|index| *index as usize >= parents.len())
+            {
+                continue;
+            }
+
+            let root = find(&mut parents, face[0] as usize);
+
+            let component_index = *component_indices.entry(root).or_insert_with(||
+            {
+                components.push(vec![]);
+                components.len() - 1
+            });
+
+            components[component_index].push(face_index);
+        }
+
+        components
+    }
+
+    // new mesh containing the given faces (only the used vertices, normals and uvs are kept)
+    // normals and uvs are remapped by their own index arrays (like append)
+    pub fn extract_faces(&self, id: u64, name: &str, faces: &Vec<usize>) -> Mesh
+    {
+        let data = self.get_data();
+
+        let has_normals = Self::has_attribute(data, data.normals.len(), &data.normals_indices);
+        let has_uvs = Self::has_attribute(data, data.uvs_1.len(), &data.uv_indices);
+        let has_uvs_2 = has_uvs && data.uvs_2.len() == data.uvs_1.len();
+        let has_uvs_3 = has_uvs && data.uvs_3.len() == data.uvs_1.len();
+
+        let mut vertex_map: HashMap<usize, u32> = HashMap::new();
+        let mut normal_map: HashMap<usize, u32> = HashMap::new();
+        let mut uv_map: HashMap<usize, u32> = HashMap::new();
+
+        let mut vertices = vec![];
+        let mut normals = vec![];
+        let mut uvs_1 = vec![];
+        let mut uvs_2 = vec![];
+        let mut uvs_3 = vec![];
+
+        let mut indices = vec![];
+        let mut normals_indices = vec![];
+        let mut uv_indices = vec![];
+
+        for face_index in faces
+        {
+            let face_index = *face_index;
+
+            let mut face = [0; 3];
+            let mut normal_face = [0; 3];
+            let mut uv_face = [0; 3];
+
+            for corner in 0..3
+            {
+                let index = data.indices[face_index][corner] as usize;
+                face[corner] = *vertex_map.entry(index).or_insert_with(||
+                {
+                    vertices.push(data.vertices[index]);
+                    (vertices.len() - 1) as u32
+                });
+
+                if has_normals
+                {
+                    let normal_index = Self::corner_attribute_index(data, &data.normals_indices, face_index, corner);
+                    normal_face[corner] = *normal_map.entry(normal_index).or_insert_with(||
+                    {
+                        normals.push(data.normals.get(normal_index).cloned().unwrap_or(Vector3::<f32>::zeros()));
+                        (normals.len() - 1) as u32
+                    });
+                }
+
+                if has_uvs
+                {
+                    let uv_index = Self::corner_attribute_index(data, &data.uv_indices, face_index, corner);
+                    uv_face[corner] = *uv_map.entry(uv_index).or_insert_with(||
+                    {
+                        uvs_1.push(data.uvs_1.get(uv_index).cloned().unwrap_or(Point2::<f32>::origin()));
+
+                        if has_uvs_2
+                        {
+                            uvs_2.push(data.uvs_2.get(uv_index).cloned().unwrap_or(Point2::<f32>::origin()));
+                        }
+
+                        if has_uvs_3
+                        {
+                            uvs_3.push(data.uvs_3.get(uv_index).cloned().unwrap_or(Point2::<f32>::origin()));
+                        }
+
+                        (uvs_1.len() - 1) as u32
+                    });
+                }
+            }
+
+            indices.push(face);
+
+            if has_normals
+            {
+                normals_indices.push(normal_face);
+            }
+
+            if has_uvs
+            {
+                uv_indices.push(uv_face);
+            }
+        }
+
+        let mut mesh = Mesh::new_with_data(id, name, vertices, indices, uvs_1, uv_indices, normals, normals_indices);
+
+        {
+            let mesh_data = mesh.get_data_mut().get_mut();
+            mesh_data.uvs_2 = uvs_2;
+            mesh_data.uvs_3 = uvs_3;
+            mesh_data.flip_normals = data.flip_normals;
+        }
+
+        mesh
+    }
+
+    pub fn weld_vertices(&mut self, position_epsilon: f32, normal_epsilon: f32) -> (usize, usize)
+    {
+        let vertices_before = self.get_data().vertices.len();
+
+        let vertices;
+        let indices;
+        let normals;
+        let uvs_1;
+        let uvs_2;
+        let uvs_3;
+        {
+            let data = self.get_data();
+
+            // welding is only possible if normals and uvs are indexed like the vertices
+            if (data.normals.len() > 0 && data.normals_indices != data.indices) || (data.uvs_1.len() > 0 && data.uv_indices != data.indices)
+            {
+                println!("can not weld vertices of {}: normals or uvs are not indexed per vertex", self.base.name);
+                return (vertices_before, vertices_before);
+            }
+
+            let position_epsilon = position_epsilon.max(std::f32::EPSILON);
+
+            let has_normals = data.normals.len() == vertices_before;
+            let has_uvs_1 = data.uvs_1.len() == vertices_before;
+            let has_uvs_2 = data.uvs_2.len() == vertices_before;
+            let has_uvs_3 = data.uvs_3.len() == vertices_before;
+
+            let get_cell = |vertex: &Point3<f32>| -> (i64, i64, i64)
+            {
+                (
+                    (vertex.x / position_epsilon).floor() as i64,
+                    (vertex.y / position_epsilon).floor() as i64,
+                    (vertex.z / position_epsilon).floor() as i64,
+                )
+            };
+
+            // spatial hash of the kept vertices
+            let mut cells: HashMap<(i64, i64, i64), Vec<u32>> = HashMap::new();
+            let mut kept: Vec<usize> = vec![];
+            let mut vertex_map: Vec<u32> = Vec::with_capacity(vertices_before);
+
+            for (i, vertex) in data.vertices.iter().enumerate()
+            {
+                let cell = get_cell(vertex);
+                let mut found = None;
+
+                // check neighbor cells as well - vertices within epsilon can be located in the next cell
+                'search: for x in -1..=1
+                {
+                    for y in -1..=1
+                    {
+                        for z in -1..=1
+                        {
+                            if let Some(candidates) = cells.get(&(cell.0 + x, cell.1 + y, cell.2 + z))
+                            {
+                                for candidate in candidates
+                                {
+                                    let c = kept[*candidate as usize];
+
+                                    if nalgebra::distance(&data.vertices[c], vertex) > position_epsilon
+                                    {
+                                        continue;
+                                    }
+
+                                    if has_normals && (data.normals[c] - data.normals[i]).magnitude() > normal_epsilon
+                                    {
+                                        continue;
+                                    }
+
+                                    if has_uvs_1 && nalgebra::distance(&data.uvs_1[c], &data.uvs_1[i]) > position_epsilon
+                                    {
+                                        continue;
+                                    }
+
+                                    found = Some(*candidate);
+                                    break 'search;
+                                }
+                            }
+                        }
+                    }
+                }
+
+                let index = match found
+                {
+                    Some(index) => index,
+                    None =>
+                    {
+                        kept.push(i);
+                        let index = (kept.len() - 1) as u32;
+                        cells.entry(cell).or_insert_with(Vec::new).push(index);
+
+                        index
+                    }
+                };
+
+                vertex_map.push(index);
+            }
+
+            // nothing to weld
+            if kept.len() == vertices_before
+            {
+                return (vertices_before, vertices_before);
+            }
+
+            vertices = kept.iter().map(|i| data.vertices[*i]).collect::<Vec<Point3<f32>>>();
+            normals = if has_normals { kept.iter().map(|i| data.normals[*i]).collect::<Vec<Vector3<f32>>>() } else { data.normals.clone() };
+            uvs_1 = if has_uvs_1 { kept.iter().map(|i| data.uvs_1[*i]).collect::<Vec<Point2<f32>>>() } else { data.uvs_1.clone() };
+            uvs_2 = if has_uvs_2 { kept.iter().map(|i| data.uvs_2[*i]).collect::<Vec<Point2<f32>>>() } else { data.uvs_2.clone() };
+            uvs_3 = if has_uvs_3 { kept.iter().map(|i| data.uvs_3[*i]).collect::<Vec<Point2<f32>>>() } else { data.uvs_3.clone() };
+
+            // remap indices and remove collapsed triangles
+            let mut remapped: Vec<[u32; 3]> = vec![];
+            for face in &data.indices
+            {
+                let i0 = vertex_map[face[0] as usize];
+                let i1 = vertex_map[face[1] as usize];
+                let i2 = vertex_map[face[2] as usize];
+
+                if i0 == i1 || i1 == i2 || i0 == i2
+                {
+                    continue;
+                }
+
+                remapped.push([i0, i1, i2]);
+            }
+
+            indices = remapped;
+        }
+
+        {
+            let data = self.get_data_mut().get_mut();
+
+            if data.normals.len() > 0
+            {
+                data.normals_indices = indices.clone();
+            }
+
+            if data.uvs_1.len() > 0
+            {
+                data.uv_indices = indices.clone();
+            }
+
+            data.vertices = vertices;
+            data.indices = indices;
+            data.normals = normals;
+            data.uvs_1 = uvs_1;
+            data.uvs_2 = uvs_2;
+            data.uvs_3 = uvs_3;
+
+            data.mesh = TriMesh::new(data.vertices.clone(), data.indices.clone());
+        }
+
+        self.calc_bbox();
+
+        (vertices_before, self.get_data().vertices.len())
+    }
+
+    fn is_degenerate(vertices: &Vec<Point3<f32>>, face: &[u32; 3]) -> bool
+    {
+        if face[0] == face[1] || face[1] == face[2] || face[0] == face[2]
+        {
+            return true;
+        }
+
+        let a = &vertices[face[0] as usize];
+        let b = &vertices[face[1] as usize];
+        let c = &vertices[face[2] as usize];
+
+        let area = (b - a).cross(&(c - a)).magnitude_squared();
+
+        !area.is_finite() || area < DEGENERATE_AREA_EPSILON
+    }
+
+    // rotates the face so that the smallest index comes first - the winding is kept (double sided faces are not duplicates)
+    fn canonical_face(face: &[u32; 3]) -> [u32; 3]
+    {
+        let mut canonical = *face;
+
+        if face[1] < face[0] && face[1] <= face[2]
+        {
+            canonical.rotate_left(1);
+        }
+        else if face[2] < face[0] && face[2] < face[1]
+        {
+            canonical.rotate_left(2);
+        }
+
+        canonical
+    }
+
+    pub fn validate(&mut self) -> MeshValidation
+    {
+        let mut validation = MeshValidation::default();
+
+        {
+            let data = self.get_data();
+            let vertices_amount = data.vertices.len() as u32;
+
+            validation.invalid_vertices = data.vertices.iter().filter(|vertex| !vertex.coords.iter().all(|value| value.is_finite())).count();
+
+            let mut faces: HashSet<[u32; 3]> = HashSet::with_capacity(data.indices.len());
+
+            for face in &data.indices
+            {
+                let out_of_range = face.iter().filter(|index| **index >= vertices_amount).count();
+                validation.out_of_range_indices += out_of_range;
+
+                if out_of_range > 0
+                {
+                    continue;
+                }
+
+                if Self::is_degenerate(&data.vertices, face)
+                {
+                    validation.degenerate_triangles += 1;
+                }
+                else if !faces.insert(Self::canonical_face(face))
+                {
+                    validation.duplicate_triangles += 1;
+                }
+            }
+        }
+
+        self.validation = Some(validation);
+
+        validation
+    }
+
+    // result of the last validation
+    pub fn get_validation(&self) -> Option<MeshValidation>
+    {
+        self.validation
+    }
+
+    // clamps out of range indices, resets invalid vertices and removes degenerate and duplicate triangles (returns the amount of removed triangles)
+    pub fn clean(&mut self) -> usize
+    {
+        let triangles_before = self.get_data().indices.len();
+
+        {
+            let data = self.get_data_mut().get_mut();
+
+            if data.vertices.is_empty()
+            {
+                data.clear();
+                return triangles_before;
+            }
+
+            let max_vertex = data.vertices.len() as u32 - 1;
+            let max_normal = (data.normals.len() as u32).max(1) - 1;
+            let max_uv = (data.uvs_1.len() as u32).max(1) - 1;
+
+            // normals and uvs can have their own index per face
+            let has_normals_indices = data.normals_indices.len() == triangles_before;
+            let has_uv_indices = data.uv_indices.len() == triangles_before;
+
+            // triangles with invalid vertices are degenerate as well
+            let invalid_vertices: Vec<bool> = data.vertices.iter().map(|vertex| !vertex.coords.iter().all(|value| value.is_finite())).collect();
+
+            let mut faces: HashSet<[u32; 3]> = HashSet::with_capacity(triangles_before);
+
+            let mut indices: Vec<[u32; 3]> = vec![];
+            let mut normals_indices: Vec<[u32; 3]> = vec![];
+            let mut uv_indices: Vec<[u32; 3]> = vec![];
+
+            for (i, face) in data.indices.iter().enumerate()
+            {
+                let face = face.map(|index| index.min(max_vertex));
+
+                if face.iter().any(|index| invalid_vertices[*index as usize]) || Self::is_degenerate(&data.vertices, &face)
+                {
+                    continue;
+                }
+
+                if !faces.insert(Self::canonical_face(&face))
+                {
+                    continue;
+                }
+
+                indices.push(face);
+
+                if has_normals_indices
+                {
+                    normals_indices.push(data.normals_indices[i].map(|index| index.min(max_normal)));
+                }
+
+                if has_uv_indices
+                {
+                    uv_indices.push(data.uv_indices[i].map(|index| index.min(max_uv)));
+                }
+            }
+
+            if indices.is_empty()
+            {
+                data.clear();
+                return triangles_before;
+            }
+
+            for (vertex, invalid) in data.vertices.iter_mut().zip(invalid_vertices)
+            {
+                if invalid
+                {
+                    *vertex = Point3::<f32>::origin();
+                }
+            }
+
+            if has_normals_indices
+            {
+                data.normals_indices = normals_indices;
+            }
+
+            if has_uv_indices
+            {
+                data.uv_indices = uv_indices;
+            }
+
+            data.indices = indices;
+
+            data.mesh = TriMesh::new(data.vertices.clone(), data.indices.clone());
+        }
+
+        self.calc_bbox();
+
+        triangles_before - self.get_data().indices.len()
+    }
+
+    // transforms the vertices and normals (mirroring transformations are flipping the winding order to keep the faces front facing)
+    pub fn apply_transform(&mut self, transform: &Matrix4<f32>)
+    {
+        let normal_matrix = transform.fixed_view::<3, 3>(0, 0).try_inverse().unwrap_or_default().transpose();
+        let mirrored = transform.fixed_view::<3, 3>(0, 0).determinant() < 0.0;
+
+        {
+            let data = self.get_data_mut().get_mut();
+
+            for vertex in &mut data.vertices
+            {
+                *vertex = transform.transform_point(vertex);
+            }
+
+            for normal in &mut data.normals
+            {
+                let transformed = normal_matrix * *normal;
+
+                if transformed.magnitude_squared() > 0.0
+                {
+                    *normal = transformed.normalize();
+                }
+            }
+
+            if mirrored
+            {
+                for face in data.indices.iter_mut().chain(data.normals_indices.iter_mut()).chain(data.uv_indices.iter_mut())
+                {
+                    face.swap(1, 2);
+                }
+            }
+
+            data.mesh = TriMesh::new(data.vertices.clone(), data.indices.clone());
+        }
+
+        self.calc_bbox();
+    }
+
+    pub fn get_normal(&self, hit: Point3<f32>, face_id: u32, tran_inverse: &Matrix4<f32>) -> Vector3<f32>
+    {
+        let data = self.data.get_ref();
+
+        // https://stackoverflow.com/questions/23980748/triangle-texture-mapping-with-barycentric-coordinates
+        // https://answers.unity.com/questions/383804/calculate-uv-coordinates-of-3d-point-on-plane-of-m.html
+
+        //transform hit to local coords
+        let hit_pos_local = tran_inverse * hit.to_homogeneous();
+        let hit_pos_local = Point3::<f32>::from_homogeneous(hit_pos_local).unwrap();
+
+        let f_id = (face_id % data.mesh.indices().len() as u32) as usize;
+
+        let face = data.mesh.indices()[f_id];
+        let normal_face = data.normals_indices[f_id];
+
+        let i0 = face[0] as usize;
+        let i1 = face[1] as usize;
+        let i2 = face[2] as usize;
+
+        let i_normal_0 = normal_face[0] as usize;
+        let i_normal_1 = normal_face[1] as usize;
+        let i_normal_2 = normal_face[2] as usize;
+
+        let a = data.mesh.vertices()[i0].to_homogeneous();
+        let b = data.mesh.vertices()[i1].to_homogeneous();
+        let c = data.mesh.vertices()[i2].to_homogeneous();
+
+        let a_t = data.normals[i_normal_0];
+        let b_t = data.normals[i_normal_1];
+        let c_t = data.normals[i_normal_2];
+
+        let a = Point3::<f32>::from_homogeneous(a).unwrap();
+        let b = Point3::<f32>::from_homogeneous(b).unwrap();
+        let c = Point3::<f32>::from_homogeneous(c).unwrap();
+
+        let f1 = a - hit_pos_local;
+        let f2 = b - hit_pos_local;
+        let f3 = c - hit_pos_local;
+
+        let a = (a-b).cross(&(a-c)).magnitude();
+
+        // degenerate triangle: no barycentric coordinates possible
+        if !(a > 0.0) || !a.is_finite()
+        {
+            return (a_t + b_t + c_t) / 3.0;
+        }
+
+        let a1 = f2.cross(&f3).magnitude() / a;
+        let a2 = f3.cross(&f1).magnitude() / a;
+        let a3 = f1.cross(&f2).magnitude() / a;
+
+        let part_1 = a_t * a1;
+        let part_2 = b_t * a2;
+        let part_3 = c_t * a3;
+
+        let normal = Point3::<f32>::new
+        (
+            part_1.x + part_2.x + part_3.x,
+            part_1.y + part_2.y + part_3.y,
+            part_1.z + part_2.z + part_3.z,
+        );
+
+        Vector3::<f32>::new(normal.x, normal.y, normal.z)
     }
 }
 
@@ -420,6 +1578,100 @@ impl Component for Mesh
 
     fn ui(&mut self, ui: &mut egui::Ui)
     {
+        {
+            let data = self.get_data();
+            ui.label(format!("vertices: {}", data.vertices.len()));
+            ui.label(format!("indices: {}", data.indices.len()));
+        }
+
+        ui.separator();
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Position epsilon: ");
+            ui.add(egui::DragValue::new(&mut self.weld_position_epsilon).speed(0.0001).max_decimals(6).clamp_range(0.0..=std::f32::MAX));
+        });
 
+        ui.horizontal(|ui|
+        {
+            ui.label("Normal epsilon: ");
+            ui.add(egui::DragValue::new(&mut self.weld_normal_epsilon).speed(0.001).max_decimals(6).clamp_range(0.0..=2.0));
+        });
+
+        if ui.button("Weld vertices").clicked()
+        {
+            self.weld_info = Some(self.weld_vertices(self.weld_position_epsilon, self.weld_normal_epsilon));
+        }
+
+        if let Some((before, after)) = self.weld_info
+        {
+            ui.label(format!("welded: {} -> {} vertices", before, after));
+        }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra::{Point3, Vector3, Matrix4};
+
+    use super::Mesh;
+
+    // ccw triangle in the xy plane without uvs and normals
+    fn create_triangle(id: u64) -> Mesh
+    {
+        let vertices = vec![Point3::<f32>::new(0.0, 0.0, 0.0), Point3::<f32>::new(1.0, 0.0, 0.0), Point3::<f32>::new(0.0, 1.0, 0.0)];
+
+        Mesh::new_with_data(id, "triangle", vertices, vec![[0, 1, 2]], vec![], vec![], vec![], vec![])
+    }
+
+    fn assert_aligned(mesh: &Mesh)
+    {
+        let data = mesh.get_data();
+
+        assert_eq!(data.normals.len(), data.vertices.len());
+        assert_eq!(data.uvs_1.len(), data.vertices.len());
+        assert_eq!(data.normals_indices.len(), data.indices.len());
+        assert_eq!(data.uv_indices.len(), data.indices.len());
+    }
+
+    #[test]
+    fn append_fills_missing_attributes()
+    {
+        let mut mesh = Mesh::new_box(0, "box", Vector3::<f32>::new(2.0, 2.0, 2.0));
+        let box_vertices = mesh.get_data().vertices.len();
+        let box_normals = mesh.get_data().normals.clone();
+
+        mesh.append(create_triangle(1).get_data(), &Matrix4::<f32>::identity());
+        assert_aligned(&mesh);
+
+        let data = mesh.get_data();
+        assert_eq!(data.vertices.len(), box_vertices + 3);
+        assert_eq!(data.normals[..box_vertices], box_normals[..]);
+
+        // face normal of the triangle
+        for normal in &data.normals[box_vertices..]
+        {
+            assert!((normal - Vector3::<f32>::z()).magnitude() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn append_fills_missing_attributes_of_the_target()
+    {
+        let mut mesh = create_triangle(0);
+        let mesh_box = Mesh::new_box(1, "box", Vector3::<f32>::new(2.0, 2.0, 2.0));
+
+        mesh.append(mesh_box.get_data(), &Matrix4::<f32>::identity());
+        assert_aligned(&mesh);
+
+        let data = mesh.get_data();
+        assert_eq!(data.vertices.len(), mesh_box.get_data().vertices.len() + 3);
+        assert_eq!(data.normals[3..], mesh_box.get_data().normals[..]);
+
+        for normal in &data.normals[..3]
+        {
+            assert!((normal - Vector3::<f32>::z()).magnitude() < 0.0001);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{component_impl_default, component_impl_no_update, component_impl_set_enabled, input::input_manager::InputManager, state::scene::{node::NodeItem, scene::Scene}};
+
+use super::component::{ComponentBase, Component};
+
+const INFO_STRING: &str = "Custom logic which is executed on each update (defined in code).";
+
+// node, scene, input manager, frame scale
+// the script component itself is locked while running (do not access it via the node)
+pub type ScriptFn = Box<dyn FnMut(NodeItem, &mut Scene, &mut InputManager, f32) + Send + Sync>;
+
+pub struct Script
+{
+    base: ComponentBase,
+    script: ScriptFn,
+}
+
+impl Script
+{
+    pub fn new(id: u64, name: &str, script: impl FnMut(NodeItem, &mut Scene, &mut InputManager, f32) + Send + Sync + 'static) -> Script
+    {
+        let mut component = Script
+        {
+            base: ComponentBase::new(id, name.to_string(), "Script".to_string(), "📜".to_string()),
+            script: Box::new(script),
+        };
+
+        component.base.info = Some(INFO_STRING.to_string());
+
+        component
+    }
+
+    // called by the scene after the node update (see Scene::update_scripts)
+    pub fn run(&mut self, node: NodeItem, scene: &mut Scene, input_manager: &mut InputManager, frame_scale: f32)
+    {
+        (self.script)(node, scene, input_manager, frame_scale);
+    }
+}
+
+impl Component for Script
+{
+    component_impl_default!();
+    component_impl_no_update!();
+    component_impl_set_enabled!();
+
+    fn instantiable(&self) -> bool
+    {
+        false
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui)
+    {
+        ui.label("defined in code");
+    }
+}
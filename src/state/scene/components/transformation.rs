@@ -15,6 +15,11 @@ pub struct TransformationData
     pub rotation: Vector3<f32>,
     pub scale: Vector3<f32>,
 
+    // per axis locks (for editing)
+    pub position_lock: [bool; 3],
+    pub rotation_lock: [bool; 3],
+    pub scale_lock: [bool; 3],
+
     trans: Matrix4<f32>,
     tran_inverse: Matrix4<f32>
 }
@@ -22,7 +27,11 @@ pub struct TransformationData
 pub struct Transformation
 {
     base: ComponentBase,
-    data: ChangeTracker<TransformationData>
+    data: ChangeTracker<TransformationData>,
+
+    // ui: show/edit world space values (based on the parent world transformation)
+    pub world_mode: bool,
+    parent_trans: Matrix4<f32>,
 }
 
 impl Transformation
@@ -38,6 +47,10 @@ impl Transformation
             rotation,
             scale,
 
+            position_lock: [false; 3],
+            rotation_lock: [false; 3],
+            scale_lock: [false; 3],
+
             trans: Matrix4::<f32>::identity(),
             tran_inverse: Matrix4::<f32>::identity()
         };
@@ -45,7 +58,10 @@ impl Transformation
         let mut transform = Transformation
         {
             base: ComponentBase::new(id, name.to_string(), "Transformation".to_string(), "📌".to_string()),
-            data: ChangeTracker::new(data)
+            data: ChangeTracker::new(data),
+
+            world_mode: false,
+            parent_trans: Matrix4::<f32>::identity(),
         };
         transform.calc_transform();
 
@@ -63,6 +79,10 @@ impl Transformation
             rotation: Vector3::<f32>::zeros(),
             scale: Vector3::<f32>::new(1.0, 1.0, 1.0),
 
+            position_lock: [false; 3],
+            rotation_lock: [false; 3],
+            scale_lock: [false; 3],
+
             trans: trans,
             tran_inverse: Matrix4::<f32>::identity()
         };
@@ -70,7 +90,10 @@ impl Transformation
         let mut transform = Transformation
         {
             base: ComponentBase::new(id, name.to_string(), "Transformation".to_string(), "📌".to_string()),
-            data: ChangeTracker::new(data)
+            data: ChangeTracker::new(data),
+
+            world_mode: false,
+            parent_trans: Matrix4::<f32>::identity(),
         };
         transform.calc_transform();
 
@@ -88,6 +111,10 @@ impl Transformation
             rotation: Vector3::<f32>::new(0.0, 0.0, 0.0),
             scale: Vector3::<f32>::new(1.0, 1.0, 1.0),
 
+            position_lock: [false; 3],
+            rotation_lock: [false; 3],
+            scale_lock: [false; 3],
+
             trans: Matrix4::<f32>::identity(),
             tran_inverse: Matrix4::<f32>::identity()
         };
@@ -95,7 +122,10 @@ impl Transformation
         let mut transform = Transformation
         {
             base: ComponentBase::new(id, name.to_string(), "Transformation".to_string(), "📌".to_string()),
-            data: ChangeTracker::new(data)
+            data: ChangeTracker::new(data),
+
+            world_mode: false,
+            parent_trans: Matrix4::<f32>::identity(),
         };
         transform.calc_transform();
 
@@ -117,6 +147,24 @@ impl Transformation
         &mut self.data
     }
 
+    // parent world transformation - used to display world space values
+    pub fn set_parent_transform(&mut self, parent_trans: Matrix4<f32>)
+    {
+        self.parent_trans = parent_trans;
+    }
+
+    pub fn reset(&mut self)
+    {
+        let data = self.data.get_mut();
+
+        data.position = Vector3::<f32>::zeros();
+        data.rotation = Vector3::<f32>::zeros();
+        data.scale = Vector3::<f32>::new(1.0, 1.0, 1.0);
+        data.trans = Matrix4::<f32>::identity();
+
+        self.calc_transform();
+    }
+
     pub fn has_parent_inheritance(&self) -> bool
     {
         self.data.get_ref().parent_inheritance
@@ -151,6 +199,49 @@ impl Transformation
         data.tran_inverse = data.trans.try_inverse().unwrap();
     }
 
+    // sets the local transformation (vectors are updated if they are used)
+    pub fn set_transform(&mut self, trans: Matrix4<f32>)
+    {
+        let data = self.data.get_mut();
+
+        if data.transform_vectors
+        {
+            (data.position, data.rotation, data.scale) = math::decompose_transform(&trans);
+        }
+        else
+        {
+            data.trans = trans;
+        }
+
+        self.calc_transform();
+    }
+
+    // sets the local transformation without losing shearing
+    // vectors are only used if they can represent the matrix - otherwise only the matrix is used
+    pub fn set_transform_matrix(&mut self, trans: Matrix4<f32>)
+    {
+        let (position, rotation, scale) = math::decompose_transform(&trans);
+        let composed = math::compose_transform(&position, &rotation, &scale);
+
+        let representable = composed.iter().zip(trans.iter()).all(|(a, b)| (a - b).abs() <= 0.0001 * b.abs().max(1.0));
+
+        let data = self.data.get_mut();
+
+        if data.transform_vectors && representable
+        {
+            data.position = position;
+            data.rotation = rotation;
+            data.scale = scale;
+        }
+        else
+        {
+            data.transform_vectors = false;
+            data.trans = trans;
+        }
+
+        self.calc_transform();
+    }
+
     pub fn get_transform(&self) -> &Matrix4::<f32>
     {
         &self.data.get_ref().trans
@@ -362,55 +453,131 @@ impl Component for Transformation
         let mut scale;
         let mut inheritance;
 
+        let mut position_lock;
+        let mut rotation_lock;
+        let mut scale_lock;
+
+        let mut world_mode = self.world_mode;
+        let mut reset = false;
+
+        // world transformation of this component
+        let parent_trans = if self.has_parent_inheritance() { self.parent_trans } else { Matrix4::<f32>::identity() };
+
         {
             let data = self.get_data();
 
-            pos = data.position;
-            rot = data.rotation;
-            scale = data.scale;
+            if self.world_mode
+            {
+                (pos, rot, scale) = math::decompose_transform(&(parent_trans * data.trans));
+            }
+            else
+            {
+                pos = data.position;
+                rot = data.rotation;
+                scale = data.scale;
+            }
+
             inheritance = data.parent_inheritance;
 
+            position_lock = data.position_lock;
+            rotation_lock = data.rotation_lock;
+            scale_lock = data.scale_lock;
+
             ui.vertical(|ui|
             {
                 changed = ui.checkbox(&mut inheritance, "parent transformation inheritance").changed() || changed;
 
                 ui.horizontal(|ui|
                 {
-                    ui.label("Position: ");
-                    changed = ui.add(egui::DragValue::new(&mut pos.x).speed(0.1).prefix("x: ")).changed() || changed;
-                    changed = ui.add(egui::DragValue::new(&mut pos.y).speed(0.1).prefix("y: ")).changed() || changed;
-                    changed = ui.add(egui::DragValue::new(&mut pos.z).speed(0.1).prefix("z: ")).changed() || changed;
-                });
-                ui.horizontal(|ui|
-                {
-                    ui.label("Rotation: ");
-                    changed = ui.add(egui::DragValue::new(&mut rot.x).speed(0.1).prefix("x: ")).changed() || changed;
-                    changed = ui.add(egui::DragValue::new(&mut rot.y).speed(0.1).prefix("y: ")).changed() || changed;
-                    changed = ui.add(egui::DragValue::new(&mut rot.z).speed(0.1).prefix("z: ")).changed() || changed;
-                });
-                ui.horizontal(|ui|
-                {
-                    ui.label("Scale: ");
-                    changed = ui.add(egui::DragValue::new(&mut scale.x).speed(0.1).prefix("x: ")).changed() || changed;
-                    changed = ui.add(egui::DragValue::new(&mut scale.y).speed(0.1).prefix("y: ")).changed() || changed;
-                    changed = ui.add(egui::DragValue::new(&mut scale.z).speed(0.1).prefix("z: ")).changed() || changed;
-
-                    // scale = 0 is not supported / working -> otherwise a inverse transform can not be created
-                    if scale.x == 0.0 { scale.x = 0.00000001; }
-                    if scale.y == 0.0 { scale.y = 0.00000001; }
-                    if scale.z == 0.0 { scale.z = 0.00000001; }
+                    ui.selectable_value(&mut world_mode, false, "local");
+                    ui.selectable_value(&mut world_mode, true, "world");
+
+                    if ui.button("reset to identity").clicked()
+                    {
+                        reset = true;
+                    }
                 });
+
+                changed = vector_ui(ui, "Position: ", &mut pos, &mut position_lock) || changed;
+                changed = vector_ui(ui, "Rotation: ", &mut rot, &mut rotation_lock) || changed;
+                changed = vector_ui(ui, "Scale: ", &mut scale, &mut scale_lock) || changed;
+
+                // scale = 0 is not supported / working -> otherwise a inverse transform can not be created
+                if scale.x == 0.0 { scale.x = 0.00000001; }
+                if scale.y == 0.0 { scale.y = 0.00000001; }
+                if scale.z == 0.0 { scale.z = 0.00000001; }
             });
         }
 
-        if changed
+        self.world_mode = world_mode;
+
+        if reset
+        {
+            self.reset();
+        }
+        else if changed
         {
+            // convert world space values back to local space
+            if self.world_mode
+            {
+                let world_trans = math::compose_transform(&pos, &rot, &scale);
+                let local_trans = parent_trans.try_inverse().unwrap_or(Matrix4::<f32>::identity()) * world_trans;
+
+                (pos, rot, scale) = math::decompose_transform(&local_trans);
+
+                let data = self.get_data_mut().get_mut();
+                if !data.transform_vectors
+                {
+                    data.trans = local_trans;
+                }
+            }
+
             let data = self.get_data_mut();
             data.get_mut().position = pos;
             data.get_mut().rotation = rot;
             data.get_mut().scale = scale;
             data.get_mut().parent_inheritance = inheritance;
+            data.get_mut().position_lock = position_lock;
+            data.get_mut().rotation_lock = rotation_lock;
+            data.get_mut().scale_lock = scale_lock;
             self.calc_transform();
         }
     }
-}
\ No newline at end of file
+}
+
+// removes the locked axes of a transformation delta
+pub fn apply_lock(vec: Vector3<f32>, lock: &[bool; 3]) -> Vector3<f32>
+{
+    let mut vec = vec;
+
+    for i in 0..3
+    {
+        if lock[i]
+        {
+            vec[i] = 0.0;
+        }
+    }
+
+    vec
+}
+
+fn vector_ui(ui: &mut egui::Ui, label: &str, value: &mut Vector3<f32>, lock: &mut [bool; 3]) -> bool
+{
+    let mut changed = false;
+
+    ui.horizontal(|ui|
+    {
+        ui.label(label);
+
+        for (i, prefix) in ["x: ", "y: ", "z: "].iter().enumerate()
+        {
+            // locked axes are not editable
+            changed = ui.add_enabled(!lock[i], egui::DragValue::new(&mut value[i]).speed(0.1).prefix(*prefix)).changed() || changed;
+
+            let icon = if lock[i] { "🔒" } else { "🔓" };
+            changed = ui.toggle_value(&mut lock[i], icon).on_hover_text("lock axis").changed() || changed;
+        }
+    });
+
+    changed
+}
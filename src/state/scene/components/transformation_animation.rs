@@ -1,17 +1,32 @@
 use egui::Color32;
 use nalgebra::Vector3;
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, Display};
 
-use crate::{helper::{change_tracker::ChangeTracker, self}, component_impl_default, state::{scene::{node::{NodeItem, InstanceItemArc}, instance::InstanceItem}}, component_downcast, component_downcast_mut, input::{input_manager::InputManager, keyboard::{Key, get_keys_as_string_vec}}};
+use crate::{helper::{change_tracker::ChangeTracker, easing::Easing, self}, interface::main_interface::REFERENCE_UPDATE_FRAMES, component_impl_default, state::{scene::{node::{NodeItem, InstanceItemArc}, instance::InstanceItem}}, component_downcast, component_downcast_mut, input::{input_manager::InputManager, keyboard::{Key, get_keys_as_string_vec}}};
 
 use super::{component::{ComponentBase, Component, ComponentItem}, transformation::Transformation};
 
-const INFO_STRING: &str = "The changes are applies on the Transform Component.\nThey are multiplied by frame_scale for each frame.\nIf there is no Transform Component: Nothing is happening.";
+const INFO_STRING: &str = "The changes are applies on the Transform Component.\nConstant: They are multiplied by frame_scale for each frame.\nLoop/Ping Pong: They are the offset reached at the end of each cycle (multiplied by the amplitude).\nIf there is no Transform Component: Nothing is happening.";
+
+#[derive(EnumIter, Display, Debug, PartialEq, Clone, Copy)]
+pub enum AnimationMode
+{
+    Constant, // per frame deltas
+    Loop, // 0 -> 1, jumps back to 0
+    PingPong, // 0 -> 1 -> 0
+}
 
 pub struct TransformationAnimationData
 {
     pub translation: Vector3<f32>,
     pub rotation: Vector3<f32>,
     pub scale: Vector3<f32>,
+
+    pub mode: AnimationMode,
+    pub easing: Easing,
+    pub frequency: f32, // cycles per second
+    pub amplitude: f32,
 }
 
 pub struct TransformationAnimation
@@ -20,6 +35,9 @@ pub struct TransformationAnimation
     data: ChangeTracker<TransformationAnimationData>,
 
     pub keyboard_key: Option<usize>,
+
+    time: f32,
+    last_value: f32,
 }
 
 impl TransformationAnimation
@@ -30,14 +48,22 @@ impl TransformationAnimation
         {
             translation,
             rotation,
-            scale
+            scale,
+
+            mode: AnimationMode::Constant,
+            easing: Easing::Linear,
+            frequency: 1.0,
+            amplitude: 1.0,
         };
 
         let mut transform_animation = TransformationAnimation
         {
             base: ComponentBase::new(id, name.to_string(), "Transform. Animation".to_string(), "🏃".to_string()),
             data: ChangeTracker::new(data),
-            keyboard_key: None
+            keyboard_key: None,
+
+            time: 0.0,
+            last_value: 0.0,
         };
 
         transform_animation.base.info = Some(INFO_STRING.to_string());
@@ -51,14 +77,22 @@ impl TransformationAnimation
         {
             translation: Vector3::<f32>::zeros(),
             rotation: Vector3::<f32>::zeros(),
-            scale: Vector3::<f32>::zeros()
+            scale: Vector3::<f32>::zeros(),
+
+            mode: AnimationMode::Constant,
+            easing: Easing::Linear,
+            frequency: 1.0,
+            amplitude: 1.0,
         };
 
         let mut transform_animation = TransformationAnimation
         {
             base: ComponentBase::new(id, name.to_string(), "Transform. Animation".to_string(), "🏃".to_string()),
             data: ChangeTracker::new(data),
-            keyboard_key: None
+            keyboard_key: None,
+
+            time: 0.0,
+            last_value: 0.0,
         };
 
         transform_animation.base.info = Some(INFO_STRING.to_string());
@@ -81,6 +115,48 @@ impl TransformationAnimation
         &mut self.data
     }
 
+    // position on the animation curve (0..1 - easings may overshoot)
+    fn curve_value(&self) -> f32
+    {
+        let data = self.get_data();
+        let phase = (self.time * data.frequency).fract();
+
+        match data.mode
+        {
+            AnimationMode::Constant => 0.0,
+            AnimationMode::Loop => data.easing.apply(phase),
+            AnimationMode::PingPong =>
+            {
+                let phase = phase * 2.0;
+
+                if phase <= 1.0 { data.easing.apply(phase) } else { data.easing.apply(2.0 - phase) }
+            },
+        }
+    }
+
+    // factor applied to the data vectors for this frame
+    fn frame_factor(&mut self, frame_scale: f32) -> f32
+    {
+        if self.get_data().mode == AnimationMode::Constant
+        {
+            return frame_scale;
+        }
+
+        self.time += frame_scale / REFERENCE_UPDATE_FRAMES;
+
+        let value = self.curve_value();
+        let factor = (value - self.last_value) * self.get_data().amplitude;
+        self.last_value = value;
+
+        factor
+    }
+
+    pub fn reset_time(&mut self)
+    {
+        self.time = 0.0;
+        self.last_value = 0.0;
+    }
+
     fn _update(&mut self, transform_component: Option<ComponentItem>, input_manager: &mut InputManager, frame_scale: f32)
     {
         if let Some(keyboard_key) = self.keyboard_key
@@ -91,6 +167,8 @@ impl TransformationAnimation
             }
         }
 
+        let factor = self.frame_factor(frame_scale);
+
         if let Some(transform_component) = transform_component
         {
             component_downcast_mut!(transform_component, Transformation);
@@ -102,17 +180,17 @@ impl TransformationAnimation
 
             if !helper::math::approx_zero(data.translation.x) || !helper::math::approx_zero(data.translation.y) || !helper::math::approx_zero(data.translation.z)
             {
-                translation = Some(Vector3::<f32>::new(data.translation.x * frame_scale, data.translation.y * frame_scale, data.translation.z * frame_scale));
+                translation = Some(data.translation * factor);
             }
 
             if !helper::math::approx_zero(data.rotation.x) || !helper::math::approx_zero(data.rotation.y) || !helper::math::approx_zero(data.rotation.z)
             {
-                rotation = Some(Vector3::<f32>::new(data.rotation.x * frame_scale, data.rotation.y * frame_scale, data.rotation.z * frame_scale));
+                rotation = Some(data.rotation * factor);
             }
 
             if !helper::math::approx_zero(data.scale.x) || !helper::math::approx_zero(data.scale.y) || !helper::math::approx_zero(data.scale.z)
             {
-                scale = Some(Vector3::<f32>::new(data.scale.x * frame_scale, data.scale.y * frame_scale, data.scale.z * frame_scale));
+                scale = Some(data.scale * factor);
             }
 
             transform_component.apply_transformation(translation, None, rotation);
@@ -164,12 +242,20 @@ impl Component for TransformationAnimation
         let mut trans;
         let mut rot;
         let mut scale;
+        let mut mode;
+        let mut easing;
+        let mut frequency;
+        let mut amplitude;
         {
             let data = self.get_data();
 
             trans = data.translation;
             rot = data.rotation;
             scale = data.scale;
+            mode = data.mode;
+            easing = data.easing;
+            frequency = data.frequency;
+            amplitude = data.amplitude;
 
             //info_box(ui, "The changes are applies on the Transform Component (multiplied by frame_scale for each frame). If there is no Transform Component. Nothing is happening.");
 
@@ -196,6 +282,51 @@ impl Component for TransformationAnimation
                     changed = ui.add(egui::DragValue::new(&mut scale.y).speed(0.1).prefix("y: ")).changed() || changed;
                     changed = ui.add(egui::DragValue::new(&mut scale.z).speed(0.1).prefix("z: ")).changed() || changed;
                 });
+
+                ui.horizontal(|ui|
+                {
+                    ui.label("Mode: ");
+                    egui::ComboBox::from_id_source("animation_mode").selected_text(mode.to_string()).show_ui(ui, |ui|
+                    {
+                        ui.style_mut().wrap = Some(false);
+                        ui.set_min_width(40.0);
+
+                        for mode_item in AnimationMode::iter()
+                        {
+                            changed = ui.selectable_value(&mut mode, mode_item, mode_item.to_string()).changed() || changed;
+                        }
+                    });
+                });
+
+                if mode != AnimationMode::Constant
+                {
+                    ui.horizontal(|ui|
+                    {
+                        ui.label("Easing: ");
+                        egui::ComboBox::from_id_source("animation_easing").selected_text(easing.to_string()).show_ui(ui, |ui|
+                        {
+                            ui.style_mut().wrap = Some(false);
+                            ui.set_min_width(40.0);
+
+                            for easing_item in Easing::iter()
+                            {
+                                changed = ui.selectable_value(&mut easing, easing_item, easing_item.to_string()).changed() || changed;
+                            }
+                        });
+                    });
+
+                    ui.horizontal(|ui|
+                    {
+                        ui.label("Frequency: ");
+                        changed = ui.add(egui::DragValue::new(&mut frequency).speed(0.01).clamp_range(0.0..=100.0).suffix(" Hz")).changed() || changed;
+                    });
+
+                    ui.horizontal(|ui|
+                    {
+                        ui.label("Amplitude: ");
+                        changed = ui.add(egui::DragValue::new(&mut amplitude).speed(0.1)).changed() || changed;
+                    });
+                }
             });
         }
 
@@ -249,9 +380,20 @@ impl Component for TransformationAnimation
         {
             let data = self.get_data_mut();
             let data = data.get_mut();
+            let mode_changed = data.mode != mode;
+
             data.translation = trans;
             data.rotation = rot;
             data.scale = scale;
+            data.mode = mode;
+            data.easing = easing;
+            data.frequency = frequency;
+            data.amplitude = amplitude;
+
+            if mode_changed
+            {
+                self.reset_time();
+            }
         }
     }
 }
\ No newline at end of file
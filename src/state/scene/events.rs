@@ -0,0 +1,27 @@
+use nalgebra::Point3;
+
+use crate::input::mouse::MouseButton;
+
+// events emitted by the scene - they are collected until they are drained (by the app each frame)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SceneEvent
+{
+    // an instance was clicked/tapped (a tap is handled like a left click) - see State::update_picks
+    Picked
+    {
+        node_id: u64,
+        instance_id: u64,
+        point: Point3<f32>,
+        button: MouseButton,
+    },
+
+    // a camera (with collision enabled) started to touch an instance
+    Collision
+    {
+        node_id: u64,
+        instance_id: u64,
+    },
+}
+
+// prevent endless growth if nobody is draining the events
+pub const MAX_SCENE_EVENTS: usize = 1000;
@@ -4,7 +4,7 @@ use nalgebra::{Matrix3, Matrix4, Vector3};
 
 use crate::{component_downcast, component_downcast_mut, input::input_manager::InputManager, helper::change_tracker::ChangeTracker};
 
-use super::{node::{NodeItem, Node, InstanceItemArc}, components::{transformation::{Transformation}, alpha::Alpha, component::{ComponentItem, find_component, Component, find_components, remove_component_by_type, remove_component_by_id, find_component_by_id}}};
+use super::{node::{NodeItem, Node, InstanceItemArc, LAYER_ALL}, components::{transformation::{Transformation}, alpha::Alpha, component::{ComponentItem, find_component, Component, find_components, remove_component_by_type, remove_component_by_id, replace_component_by_id, find_component_by_id}}};
 
 pub type InstanceItem = Box<Instance>;
 
@@ -22,6 +22,7 @@ pub struct InstanceData
     pub visible: bool,
     pub highlight: bool,
     pub collision: bool,
+    pub layer_mask: u32, // combined with the layers of the node
 }
 
 
@@ -64,7 +65,8 @@ impl Instance
 
                 visible: true,
                 highlight: false,
-                collision: true
+                collision: true,
+                layer_mask: LAYER_ALL,
             })
         };
 
@@ -94,7 +96,8 @@ impl Instance
 
                 visible: true,
                 highlight: false,
-                collision: true
+                collision: true,
+                layer_mask: LAYER_ALL,
             })
         };
 
@@ -159,6 +162,17 @@ impl Instance
         }
     }
 
+    pub fn replace_component_by_id(&mut self, id: u64, component: &ComponentItem) -> bool
+    {
+        if replace_component_by_id(&mut self.components, id, component)
+        {
+            self.force_update = true;
+            return true;
+        }
+
+        false
+    }
+
     pub fn update(instance: &InstanceItemArc, input_manager: &mut InputManager, frame_scale: f32) -> bool
     {
         let node;
@@ -305,6 +319,12 @@ impl Instance
         {
             component_downcast!(transform_component, Transformation);
 
+            // a disabled transformation is handled like no transformation (identity)
+            if !transform_component.get_base().is_enabled
+            {
+                return node_trans;
+            }
+
             let instance_trans = transform_component.get_transform();
 
             if transform_component.has_parent_inheritance()
@@ -332,6 +352,11 @@ impl Instance
         {
             component_downcast!(alpha_component, Alpha);
 
+            if !alpha_component.get_base().is_enabled
+            {
+                return node_alpha;
+            }
+
             if alpha_component.has_alpha_inheritance()
             {
                 alpha_component.get_alpha() * node_alpha
@@ -421,4 +446,97 @@ impl Instance
             panic!("trnasform component not found");
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::{Arc, RwLock};
+
+    use nalgebra::Vector3;
+
+    use crate::state::scene::{scene::{Scene, test_utils::{create_scene, add_box, update}}, node::{NodeItem, InstanceItemArc}, components::{transformation::Transformation, alpha::Alpha, component::Component}};
+
+    fn create_instance() -> (Scene, NodeItem, InstanceItemArc)
+    {
+        let mut scene = create_scene(0);
+        let node = add_box(&mut scene, "box");
+        let instance = node.read().unwrap().instances.get_ref()[0].clone();
+
+        (scene, node, instance)
+    }
+
+    fn translation_x(instance: &InstanceItemArc) -> f32
+    {
+        instance.read().unwrap().get_transform()[(0, 3)]
+    }
+
+    #[test]
+    fn disabled_instance_transformation_is_identity()
+    {
+        let (mut scene, _node, instance) = create_instance();
+
+        let transform = Transformation::new(scene.id_manager.get_next_component_id(), "Transform", Vector3::<f32>::new(5.0, 0.0, 0.0), Vector3::<f32>::zeros(), Vector3::<f32>::new(1.0, 1.0, 1.0));
+        let transform: ComponentItem = Arc::new(RwLock::new(Box::new(transform)));
+
+        {
+            let mut instance = instance.write().unwrap();
+            instance.add_component(transform.clone());
+            instance.set_force_update();
+        }
+
+        update(&mut scene);
+        assert_eq!(translation_x(&instance), 5.0);
+
+        transform.write().unwrap().set_enabled(false);
+        update(&mut scene);
+        assert_eq!(translation_x(&instance), 0.0);
+
+        transform.write().unwrap().set_enabled(true);
+        update(&mut scene);
+        assert_eq!(translation_x(&instance), 5.0);
+    }
+
+    #[test]
+    fn disabled_node_transformation_is_identity()
+    {
+        let (mut scene, node, instance) = create_instance();
+
+        let transform = Transformation::new(scene.id_manager.get_next_component_id(), "Transform", Vector3::<f32>::new(5.0, 0.0, 0.0), Vector3::<f32>::zeros(), Vector3::<f32>::new(1.0, 1.0, 1.0));
+        let transform: ComponentItem = Arc::new(RwLock::new(Box::new(transform)));
+        node.write().unwrap().add_component(transform.clone());
+
+        update(&mut scene);
+        assert_eq!(translation_x(&instance), 5.0);
+
+        transform.write().unwrap().set_enabled(false);
+        update(&mut scene);
+        assert_eq!(translation_x(&instance), 0.0);
+    }
+
+    #[test]
+    fn disabled_alpha_is_opaque()
+    {
+        let (mut scene, _node, instance) = create_instance();
+
+        let alpha = Alpha::new(scene.id_manager.get_next_component_id(), "Alpha", 0.5);
+        let alpha: ComponentItem = Arc::new(RwLock::new(Box::new(alpha)));
+
+        {
+            let mut instance = instance.write().unwrap();
+            instance.add_component(alpha.clone());
+            instance.set_force_update();
+        }
+
+        update(&mut scene);
+        assert_eq!(instance.read().unwrap().get_alpha(), 0.5);
+
+        alpha.write().unwrap().set_enabled(false);
+        update(&mut scene);
+        assert_eq!(instance.read().unwrap().get_alpha(), 1.0);
+
+        alpha.write().unwrap().set_enabled(true);
+        update(&mut scene);
+        assert_eq!(instance.read().unwrap().get_alpha(), 0.5);
+    }
+}
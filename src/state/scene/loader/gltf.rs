@@ -1,14 +1,39 @@
 
 use std::{path::Path, ffi::OsStr, sync::{Arc, RwLock, Weak}, cell::RefCell, collections::HashMap, mem::swap};
 
-use gltf::{Gltf, texture};
+use gltf::{Gltf, texture, mesh::Mode};
 
 use base64::{engine::general_purpose::STANDARD, Engine};
 use nalgebra::{Vector3, Matrix4, Point3, Point2, UnitQuaternion, Quaternion, Rotation3};
 
-use crate::{state::scene::{scene::Scene, components::{material::{Material, MaterialItem, TextureState, TextureType}, mesh::Mesh, transformation::Transformation, component::Component}, texture::{Texture, TextureItem, TextureAddressMode, TextureFilterMode}, light::Light, camera::Camera, node::{NodeItem, Node}, utilities::scene_utils::{load_texture_byte_or_reuse, execute_on_scene_mut_and_wait, insert_texture_or_reuse, get_new_tex_id, get_new_component_id, get_new_light_id, get_new_camera_id, get_new_node_id, get_new_instance_id}}, resources::resources::load_binary, helper::{change_tracker::ChangeTracker, math::{approx_zero_vec3, approx_one_vec3}, file::get_stem, concurrency::execution_queue::ExecutionQueueItem}, rendering::{scene, light}};
+use crate::{state::scene::{scene::Scene, components::{material::{Material, MaterialItem, TextureState, TextureType}, mesh::Mesh, transformation::Transformation, component::Component}, texture::{Texture, TextureItem, TextureAddressMode, TextureFilterMode}, light::Light, camera::Camera, node::{NodeItem, Node}, loader::progress::{self, LoadingProgressItem}, utilities::scene_utils::{load_texture_byte_or_reuse, execute_on_scene_mut_and_wait, execute_on_scene_mut, insert_texture_or_reuse, get_new_tex_id, get_new_component_id, get_new_light_id, get_new_camera_id, ReservedIds}}, resources::resources::load_binary, helper::{change_tracker::ChangeTracker, math::{approx_zero_vec3, approx_one_vec3}, file::get_stem, concurrency::execution_queue::ExecutionQueueItem}, rendering::{scene, light}};
 
-pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_root_node: bool, reuse_materials: bool, object_only: bool, create_mipmaps: bool) -> anyhow::Result<Vec<u64>>
+// index and name of all scenes defined in the file (the default scene is marked)
+pub fn get_scenes(path: &str) -> anyhow::Result<Vec<(usize, String, bool)>>
+{
+    let gltf_content = load_binary(path)?;
+    let gltf = Gltf::from_slice(gltf_content.as_slice())?;
+
+    let default_scene = gltf.default_scene().map(|scene| scene.index());
+
+    Ok(gltf.scenes().map(|scene|
+    {
+        let name = scene.name().map(|name| name.to_string()).unwrap_or(format!("Scene {}", scene.index()));
+        (scene.index(), name, Some(scene.index()) == default_scene)
+    }).collect())
+}
+
+// up axis hint of the asset (see get_up_axis_hint)
+pub fn get_up_axis(path: &str) -> anyhow::Result<Option<Vector3<f32>>>
+{
+    let gltf_content = load_binary(path)?;
+    let gltf = Gltf::from_slice(gltf_content.as_slice())?;
+
+    Ok(get_up_axis_hint(&gltf))
+}
+
+// scenes: indices of the scenes to load (None: default scene or the first one if there is no default)
+pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_root_node: bool, reuse_materials: bool, object_only: bool, create_mipmaps: bool, scenes: Option<Vec<usize>>, progress: Option<LoadingProgressItem>) -> anyhow::Result<Vec<u64>>
 {
     let gltf_content = load_binary(path)?;
 
@@ -19,25 +44,35 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
 
     // ********** buffers **********
     let mut buffers: Vec<gltf::buffer::Data> = vec![];
+    progress::set_stage(&progress, "buffers", gltf.buffers().count());
 
     for buffer in gltf.buffers()
     {
+        progress::check_canceled(&progress)?;
+
         let data = load_buffer(path, &mut blob, &buffer);
         buffers.push(gltf::buffer::Data(data));
+
+        progress::step(&progress);
     }
 
     // ********** textures **********
     dbg!("loading textures...");
     let mut loaded_textures = vec![];
+    progress::set_stage(&progress, "textures", gltf.textures().count());
 
     for gltf_texture in gltf.textures()
     {
+        progress::check_canceled(&progress)?;
+
         let (bytes, extension) = load_texture(path, &gltf_texture, &buffers);
 
         let tex = load_texture_byte_or_reuse(scene_id, main_queue.clone(), &bytes, gltf_texture.name().unwrap_or("unknown"), extension);
         apply_texture_filtering_settings(tex.clone(), &gltf_texture, create_mipmaps);
 
         loaded_textures.push((tex, gltf_texture.index()));
+
+        progress::step(&progress);
     }
 
     // because metallic and roughness are combined -> and we will use it seperatly -> the initial loaded texture should be removed again
@@ -48,8 +83,12 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
     dbg!("loading materials...");
     let resource_name = get_stem(path);
     let mut loaded_materials: HashMap<usize, MaterialItem> = HashMap::new();
+    progress::set_stage(&progress, "materials", gltf.materials().count());
+
     for gltf_material in gltf.materials()
     {
+        progress::check_canceled(&progress)?;
+
         let gltf_material_index = gltf_material.index().unwrap();
 
         let material: Arc<RwLock<Option<MaterialItem>>> = Arc::new(RwLock::new(None));
@@ -83,39 +122,65 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
             }
 
             let material_arc_clone = material_arc.clone();
-            execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+            execute_on_scene_mut(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
             {
                 scene.add_material(id, &material_arc_clone);
             }));
 
             loaded_materials.insert(gltf_material_index, material_arc);
         }
+
+        progress::step(&progress);
     }
 
     // ********** scene items **********
     dbg!("loading scene items...");
-    let mut root_node = None;
-    if create_root_node
+    let mut ids = ReservedIds::new(main_queue.clone(), scene_id);
+
+    let gltf_scenes: Vec<gltf::Scene> = match scenes
     {
-        let node_id = get_new_node_id(main_queue.clone(), scene_id);
-        loaded_ids.push(node_id);
+        Some(scenes) => gltf.scenes().filter(|gltf_scene| scenes.contains(&gltf_scene.index())).collect(),
+        None => gltf.default_scene().or(gltf.scenes().next()).into_iter().collect(),
+    };
 
-        let node = Node::new(node_id, resource_name.as_str());
-        node.write().unwrap().root_node = true;
-        root_node = Some(node.clone());
-    }
+    // each scene gets its own root node if there are multiple
+    let multiple_scenes = gltf_scenes.len() > 1;
 
     dbg!("------");
     dbg!(path);
     dbg!(create_root_node);
 
     dbg!("reading nodes...");
+    let mut root_nodes = vec![];
     let mut scene_nodes = vec![];
-    for gltf_scene in gltf.scenes()
+    progress::set_stage(&progress, "nodes", gltf.nodes().count());
+
+    for gltf_scene in gltf_scenes
     {
+        let mut root_node = None;
+        if create_root_node || multiple_scenes
+        {
+            let node_id = ids.node_id();
+            loaded_ids.push(node_id);
+
+            let name = match (multiple_scenes, gltf_scene.name())
+            {
+                (false, _) => resource_name.clone(),
+                (true, Some(scene_name)) => format!("{} - {}", resource_name, scene_name),
+                (true, None) => format!("{} - Scene {}", resource_name, gltf_scene.index()),
+            };
+
+            let node = Node::new(node_id, name.as_str());
+            node.write().unwrap().root_node = true;
+            root_node = Some(node.clone());
+            root_nodes.push(node);
+        }
+
         for node in gltf_scene.nodes()
         {
-            let nodes = read_node(&node, &buffers, object_only, &loaded_materials, scene_id, main_queue.clone(), root_node.clone(), &Matrix4::<f32>::identity(), 1);
+            progress::check_canceled(&progress)?;
+
+            let nodes = read_node(&node, &buffers, object_only, &loaded_materials, scene_id, main_queue.clone(), root_node.clone(), &Matrix4::<f32>::identity(), 1, &mut ids, &progress);
             scene_nodes.extend(nodes.clone());
 
             let all_nodes = Scene::list_all_child_nodes(&nodes);
@@ -129,19 +194,19 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
     }
 
     // ********** add to scene **********
+    progress::check_canceled(&progress)?;
+
     dbg!("adding nodes to scene...");
-    if let Some(root_node) = root_node
+    // all nodes at once (one main thread call)
+    if root_nodes.len() > 0 || scene_nodes.len() > 0
     {
         execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
         {
-            scene.add_node(root_node.clone());
-        }));
-    }
+            for root_node in &root_nodes
+            {
+                scene.add_node(root_node.clone());
+            }
 
-    if scene_nodes.len() > 0
-    {
-        execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
-        {
             for scene_node in &scene_nodes
             {
                 scene.add_node(scene_node.clone());
@@ -149,6 +214,18 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
         }));
     }
 
+    // up axis hint of the asset (object imports are keeping the scene settings)
+    if !object_only
+    {
+        if let Some(up_axis) = get_up_axis_hint(&gltf)
+        {
+            execute_on_scene_mut(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+            {
+                scene.set_up_axis(up_axis);
+            }));
+        }
+    }
+
     // cleanup
     dbg!("cleanup...");
     execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
@@ -162,8 +239,10 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
     Ok(loaded_ids)
 }
 
-fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only: bool, loaded_materials: &HashMap<usize, MaterialItem>, scene_id: u64, main_queue: ExecutionQueueItem, parent: Option<NodeItem>, parent_transform: &Matrix4<f32>, level: usize) -> Vec<Arc<RwLock<Box<Node>>>>
+fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only: bool, loaded_materials: &HashMap<usize, MaterialItem>, scene_id: u64, main_queue: ExecutionQueueItem, parent: Option<NodeItem>, parent_transform: &Matrix4<f32>, level: usize, ids: &mut ReservedIds, progress: &Option<LoadingProgressItem>) -> Vec<Arc<RwLock<Box<Node>>>>
 {
+    progress::step(progress);
+
     //https://github.com/flomonster/easy-gltf/blob/de8654c1d3f069132dbf1bf3b50b1868f6cf1f84/src/scene/mod.rs#L69
 
     //let mut loaded_ids: Vec<u64> = vec![];
@@ -200,7 +279,7 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
                     println!("load light {}", name.as_str());
                     let name = Arc::new(name);
 
-                    execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+                    execute_on_scene_mut(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
                     {
                         let light = Light::new_directional(light_id, (*name).clone(), pos, dir, color, intensity);
                         scene.lights.get_mut().push(RefCell::new(ChangeTracker::new(Box::new(light))));
@@ -212,7 +291,7 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
                     println!("load light {}", name.as_str());
                     let name = Arc::new(name);
 
-                    execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+                    execute_on_scene_mut(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
                     {
                         let light = Light::new_point(light_id, (*name).clone(), pos, color, intensity);
                         scene.lights.get_mut().push(RefCell::new(ChangeTracker::new(Box::new(light))));
@@ -224,7 +303,7 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
                     println!("load light {}", name.as_str());
                     let name = Arc::new(name);
 
-                    execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+                    execute_on_scene_mut(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
                     {
                         let light = Light::new_spot(light_id, (*name).clone(), pos, dir, color, outer_cone_angle, intensity);
                         scene.lights.get_mut().push(RefCell::new(ChangeTracker::new(Box::new(light))));
@@ -263,7 +342,7 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
                     let znear = pers.znear();
                     let zfar = pers.zfar();
 
-                    execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+                    execute_on_scene_mut(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
                     {
                         let mut cam = Camera::new(cam_id, (*name).clone());
                         let cam_data = cam.get_data_mut().get_mut();
@@ -293,6 +372,14 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
 
             println!("load mesh {}", name.as_str());
 
+            // only triangle based primitives are supported
+            let mode = primitive.mode();
+            if !is_triangle_mode(mode)
+            {
+                println!("warning: skipping primitive {} of mesh {}: unsupported primitive mode {:?}", primitive_id, name.as_str(), mode);
+                continue;
+            }
+
             let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
 
             let material_index = primitive.material().index();
@@ -361,19 +448,21 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
             }
 
             // indices
-            let gltf_indices: Option<Vec<u32>> = reader.read_indices().map(|indices| indices.into_u32().collect());
+            let mut gltf_indices: Option<Vec<u32>> = reader.read_indices().map(|indices| indices.into_u32().collect());
+
+            // non indexed geometry -> use vertices in order
+            if gltf_indices.is_none()
+            {
+                gltf_indices = Some((0..verts.len() as u32).collect());
+            }
 
             if let Some(gltf_indices) = gltf_indices
             {
-                for vtx in 0..gltf_indices.len() / 3
+                for face in triangulate_indices(mode, &gltf_indices)
                 {
-                    let i0 = gltf_indices[3 * vtx];
-                    let i1 = gltf_indices[3 * vtx + 1];
-                    let i2 = gltf_indices[3 * vtx + 2];
-
-                    indices.push([i0, i1, i2]);
-                    uv_indices.push([i0, i1, i2]);
-                    normals_indices.push([i0, i1, i2]);
+                    indices.push(face);
+                    uv_indices.push(face);
+                    normals_indices.push(face);
                 }
             }
 
@@ -382,12 +471,13 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
                 continue;
             }
 
-            let component_id = get_new_component_id(main_queue.clone(), scene_id);
+            let component_id = ids.component_id();
             let mut item = Mesh::new_with_data(component_id, "Mesh", verts, indices, uvs1, uv_indices, normals, normals_indices);
+            item.get_base_mut().extras = read_extras(mesh.extras());
             item.get_data_mut().get_mut().uvs_2 = uvs2;
             item.get_data_mut().get_mut().uvs_3 = uvs3;
 
-            let id = get_new_node_id(main_queue.clone(), scene_id);
+            let id = ids.node_id();
             //loaded_ids.push(id);
 
             if primitives_amount > 1
@@ -395,9 +485,17 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
                 name = format!("{} primitive_{}", name, primitive_id);
             }
 
+            let source_name = node.name().map(|name| name.to_string());
+            let source_index = node.index();
+            let node_extras = node.extras();
+
             let node_arc = Node::new(id, name.as_str());
             {
                 let mut node = node_arc.write().unwrap();
+                node.source_name = source_name;
+                node.source_index = Some(source_index);
+                node.extras = read_extras(node_extras);
+
                 node.add_component(Arc::new(RwLock::new(Box::new(item))));
 
                 // add material
@@ -420,12 +518,12 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
                 // transformation
                 if !approx_zero_vec3(&translate) || !approx_zero_vec3(&rotation) || !approx_one_vec3(&scale)
                 {
-                    let component_id = get_new_component_id(main_queue.clone(), scene_id);
+                    let component_id = ids.component_id();
                     node.add_component(Arc::new(RwLock::new(Box::new(Transformation::new(component_id, "Transform", translate, rotation, scale)))));
                 }
 
                 // add default instance
-                let instance_id = get_new_instance_id(main_queue.clone(), scene_id);
+                let instance_id = ids.instance_id();
                 node.create_default_instance(node_arc.clone(), instance_id);
 
                 // parent
@@ -453,19 +551,33 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
     // if there is nothing set -> its just a transform node
     if node.camera().is_none() && node.mesh().is_none() && node.light().is_none()
     {
-        // only if the node has children -> otherwise ignore it
-        if node.children().len() > 0
+        let marker = get_marker_kind(node);
+
+        // only if the node has children or is a marker -> otherwise ignore it
+        if node.children().len() > 0 || marker.is_some()
         {
             let name = node.name().unwrap_or("transform node");
             println!("load empty {}", name);
 
-            let node_id = get_new_node_id(main_queue.clone(), scene_id);
+            let node_id = ids.node_id();
             let scene_node = Node::new(node_id, name);
+            {
+                let mut scene_node = scene_node.write().unwrap();
+                scene_node.source_name = node.name().map(|name| name.to_string());
+                scene_node.source_index = Some(node.index());
+                scene_node.extras = read_extras(node.extras());
+
+                if let Some(marker) = marker
+                {
+                    scene_node.add_tag(marker.as_str());
+                    scene_node.marker = Some(marker);
+                }
+            }
 
             // add transformation
             if !approx_zero_vec3(&translate) || !approx_zero_vec3(&rotation) || !approx_one_vec3(&scale)
             {
-                let component_id = get_new_component_id(main_queue.clone(), scene_id);
+                let component_id = ids.component_id();
                 scene_node.write().unwrap().add_component(Arc::new(RwLock::new(Box::new(Transformation::new(component_id, "Transform", translate, rotation, scale)))));
             }
 
@@ -485,13 +597,140 @@ fn read_node(node: &gltf::Node, buffers: &Vec<gltf::buffer::Data>, object_only:
     // ********** children **********
     for child in node.children()
     {
-        let loaded_nodes = read_node(&child, &buffers, object_only, loaded_materials, scene_id, main_queue.clone(), parent_node.clone(), &world_transform, level + 1);
+        let loaded_nodes = read_node(&child, &buffers, object_only, loaded_materials, scene_id, main_queue.clone(), parent_node.clone(), &world_transform, level + 1, ids, progress);
         scene_nodes.extend(loaded_nodes);
     }
 
     scene_nodes
 }
 
+// gltf is y-up by definition - so only an explicit hint in the asset extras is used ("up_axis" or "upAxis": "Z" / "-Z" / "Y" ...)
+fn get_up_axis_hint(gltf: &Gltf) -> Option<Vector3<f32>>
+{
+    let extras = read_extras(&gltf.as_json().asset.extras);
+    let hint = extras.get("up_axis").or(extras.get("upAxis"))?;
+
+    match hint.trim().to_uppercase().as_str()
+    {
+        "X" | "+X" => Some(Vector3::<f32>::x()),
+        "Y" | "+Y" => Some(Vector3::<f32>::y()),
+        "Z" | "+Z" => Some(Vector3::<f32>::z()),
+        "-X" => Some(-Vector3::<f32>::x()),
+        "-Y" => Some(-Vector3::<f32>::y()),
+        "-Z" => Some(-Vector3::<f32>::z()),
+        _ =>
+        {
+            println!("unknown up axis hint: {}", hint);
+            None
+        }
+    }
+}
+
+// empty nodes (like empties in blender) are used as markers (spawn points, triggers, ...)
+// the kind is defined by the "marker" extra or by the name of an empty node without children ("Spawn.001" -> "spawn")
+fn get_marker_kind(node: &gltf::Node) -> Option<String>
+{
+    if let Some(kind) = read_extras(node.extras()).get("marker")
+    {
+        return Some(kind.to_lowercase());
+    }
+
+    if node.children().len() > 0
+    {
+        return None;
+    }
+
+    let name = node.name().unwrap_or("").to_lowercase();
+    let kind: String = name.chars().take_while(|c| c.is_alphabetic()).collect();
+
+    if kind.is_empty()
+    {
+        return Some("marker".to_string());
+    }
+
+    Some(kind)
+}
+
+// custom properties (top level keys of the extras object) - values which are not a string are stored as json
+pub fn read_extras(extras: &gltf::json::Extras) -> HashMap<String, String>
+{
+    let mut res = HashMap::new();
+
+    if extras.is_none()
+    {
+        return res;
+    }
+
+    let extras = extras.as_ref().unwrap();
+
+    match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(extras.get())
+    {
+        Ok(map) =>
+        {
+            for (key, value) in map
+            {
+                let value = match value
+                {
+                    serde_json::Value::String(string) => string,
+                    value => value.to_string(),
+                };
+
+                res.insert(key, value);
+            }
+        },
+        Err(err) => println!("can not parse extras {}: {}", extras.get(), err)
+    }
+
+    res
+}
+
+pub fn is_triangle_mode(mode: Mode) -> bool
+{
+    mode == Mode::Triangles || mode == Mode::TriangleStrip || mode == Mode::TriangleFan
+}
+
+// points and lines are resulting in no faces
+pub fn triangulate_indices(mode: Mode, indices: &Vec<u32>) -> Vec<[u32; 3]>
+{
+    let mut faces: Vec<[u32; 3]> = vec![];
+
+    match mode
+    {
+        Mode::TriangleStrip =>
+        {
+            // every second triangle is flipped to keep the winding order
+            for i in 0..indices.len().saturating_sub(2)
+            {
+                if i % 2 == 0
+                {
+                    faces.push([indices[i], indices[i + 1], indices[i + 2]]);
+                }
+                else
+                {
+                    faces.push([indices[i + 1], indices[i], indices[i + 2]]);
+                }
+            }
+        },
+        Mode::TriangleFan =>
+        {
+            for i in 1..indices.len().saturating_sub(1)
+            {
+                faces.push([indices[0], indices[i], indices[i + 1]]);
+            }
+        },
+        Mode::Triangles =>
+        {
+            for i in 0..indices.len() / 3
+            {
+                faces.push([indices[3 * i], indices[3 * i + 1], indices[3 * i + 2]]);
+            }
+        },
+        _ => {}
+    }
+
+    faces
+}
+
 pub fn transform_to_matrix(transform: gltf::scene::Transform) -> Matrix4<f32>
 {
     let tr = transform.matrix();
@@ -650,6 +889,7 @@ pub fn load_material(gltf_material: &gltf::Material<'_>, scene_id: u64, main_que
     let component_id: u64 = get_new_component_id(main_queue.clone(), scene_id);
 
     let mut material = Material::new(component_id, gltf_material.name().unwrap_or("unknown"));
+    material.get_base_mut().extras = read_extras(gltf_material.extras());
     let material_name = material.get_base().name.clone();
     let data = material.get_data_mut().get_mut();
 
@@ -726,7 +966,7 @@ pub fn load_material(gltf_material: &gltf::Material<'_>, scene_id: u64, main_que
             tex_arc.write().unwrap().data.get_mut().mipmapping = create_mipmaps;
 
             set_texture_name(tex_arc.clone(), material_name.clone(), resource_name.clone(), TextureType::Reflectivity);
-            data.texture_reflectivity = Some(TextureState::new(tex_arc));
+            data.texture_reflectivity = Some(TextureState::new_with_channel(tex_arc, TextureType::Reflectivity.default_channel()));
 
             // add texture to clearable textures
             clear_textures.push(texture.clone());
@@ -755,7 +995,7 @@ pub fn load_material(gltf_material: &gltf::Material<'_>, scene_id: u64, main_que
             tex_arc.write().unwrap().data.get_mut().mipmapping = create_mipmaps;
 
             set_texture_name(tex_arc.clone(), material_name.clone(), resource_name.clone(), TextureType::Roughness);
-            data.texture_roughness = Some(TextureState::new(tex_arc));
+            data.texture_roughness = Some(TextureState::new_with_channel(tex_arc, TextureType::Roughness.default_channel()));
 
             // add texture to clearable textures
             clear_textures.push(texture.clone());
@@ -796,7 +1036,7 @@ pub fn load_material(gltf_material: &gltf::Material<'_>, scene_id: u64, main_que
             tex_arc.write().unwrap().data.get_mut().mipmapping = create_mipmaps;
 
             set_texture_name(tex_arc.clone(), material_name.clone(), resource_name.clone(), TextureType::AmbientOcclusion);
-            data.texture_ambient_occlusion = Some(TextureState::new(tex_arc));
+            data.texture_ambient_occlusion = Some(TextureState::new_with_channel(tex_arc, TextureType::AmbientOcclusion.default_channel()));
 
             // add texture to clearable textures
             clear_textures.push(texture.clone());
@@ -815,6 +1055,47 @@ pub fn load_material(gltf_material: &gltf::Material<'_>, scene_id: u64, main_que
     // unlit
     data.unlit_shading = gltf_material.unlit();
 
+    // clearcoat (not supported by the gltf crate -> read from the extension json)
+    // https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_clearcoat/README.md
+    if let Some(clearcoat) = gltf_material.extension_value("KHR_materials_clearcoat")
+    {
+        data.clearcoat = clearcoat.get("clearcoatFactor").and_then(|value| value.as_f64()).unwrap_or(0.0) as f32;
+        data.clearcoat_roughness = clearcoat.get("clearcoatRoughnessFactor").and_then(|value| value.as_f64()).unwrap_or(0.0) as f32;
+
+        let get_texture = |key: &str| -> Option<TextureItem>
+        {
+            let index = clearcoat.get(key)?.get("index")?.as_u64()? as usize;
+            let tex_index = loaded_textures.iter().position(|t| t.1 == index)?;
+
+            Some(loaded_textures.get(tex_index).unwrap().0.clone())
+        };
+
+        if let Some(texture) = get_texture("clearcoatTexture")
+        {
+            set_texture_name(texture.clone(), material_name.clone(), resource_name.clone(), TextureType::Clearcoat);
+            data.texture_clearcoat = Some(TextureState::new_with_channel(texture, TextureType::Clearcoat.default_channel()));
+        }
+
+        if let Some(texture) = get_texture("clearcoatRoughnessTexture")
+        {
+            set_texture_name(texture.clone(), material_name.clone(), resource_name.clone(), TextureType::ClearcoatRoughness);
+            data.texture_clearcoat_roughness = Some(TextureState::new_with_channel(texture, TextureType::ClearcoatRoughness.default_channel()));
+        }
+
+        if let Some(texture) = get_texture("clearcoatNormalTexture")
+        {
+            set_texture_name(texture.clone(), material_name.clone(), resource_name.clone(), TextureType::ClearcoatNormal);
+            data.texture_clearcoat_normal = Some(TextureState::new(texture));
+        }
+    }
+
+    // transmission
+    // https://github.com/KhronosGroup/glTF/blob/main/extensions/2.0/Khronos/KHR_materials_transmission/README.md
+    if let Some(transmission) = gltf_material.extension_value("KHR_materials_transmission")
+    {
+        data.transmission = transmission.get("transmissionFactor").and_then(|value| value.as_f64()).unwrap_or(0.0) as f32;
+    }
+
     material
 }
 
@@ -924,4 +1205,63 @@ pub fn load_texture(gltf_path: &str, texture: &gltf::Texture<'_>, buffers: &Vec<
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use gltf::mesh::Mode;
+
+    use super::{triangulate_indices, is_triangle_mode};
+
+    #[test]
+    fn triangle_strip()
+    {
+        let faces = triangulate_indices(Mode::TriangleStrip, &vec![0, 1, 2, 3, 4, 5]);
+
+        // every second triangle is flipped to keep the winding order
+        assert_eq!(faces, vec![[0, 1, 2], [2, 1, 3], [2, 3, 4], [4, 3, 5]]);
+    }
+
+    #[test]
+    fn triangle_fan()
+    {
+        let faces = triangulate_indices(Mode::TriangleFan, &vec![0, 1, 2, 3, 4]);
+
+        assert_eq!(faces, vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+    }
+
+    #[test]
+    fn triangle_list()
+    {
+        // incomplete triangles at the end are ignored
+        let faces = triangulate_indices(Mode::Triangles, &vec![0, 1, 2, 2, 1, 3, 4]);
+
+        assert_eq!(faces, vec![[0, 1, 2], [2, 1, 3]]);
+    }
+
+    #[test]
+    fn not_enough_indices()
+    {
+        assert!(triangulate_indices(Mode::TriangleStrip, &vec![0, 1]).is_empty());
+        assert!(triangulate_indices(Mode::TriangleFan, &vec![0, 1]).is_empty());
+        assert!(triangulate_indices(Mode::TriangleStrip, &vec![]).is_empty());
+    }
+
+    #[test]
+    fn points_and_lines_are_skipped()
+    {
+        let indices = vec![0, 1, 2, 3, 4, 5];
+
+        for mode in [Mode::Points, Mode::Lines, Mode::LineLoop, Mode::LineStrip]
+        {
+            assert!(!is_triangle_mode(mode));
+            assert!(triangulate_indices(mode, &indices).is_empty());
+        }
+
+        for mode in [Mode::Triangles, Mode::TriangleStrip, Mode::TriangleFan]
+        {
+            assert!(is_triangle_mode(mode));
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::sync::{Arc, RwLock};
+
+pub type LoadingProgressItem = Arc<RwLock<LoadingProgress>>;
+
+// shared between the loader (thread) and the ui - the loader checks the cancel flag between the items/stages
+pub struct LoadingProgress
+{
+    pub stage: String,
+    pub current: usize,
+    pub total: usize,
+
+    canceled: bool,
+}
+
+impl LoadingProgress
+{
+    pub fn new() -> LoadingProgress
+    {
+        LoadingProgress
+        {
+            stage: String::new(),
+            current: 0,
+            total: 0,
+
+            canceled: false,
+        }
+    }
+
+    pub fn new_item() -> LoadingProgressItem
+    {
+        Arc::new(RwLock::new(LoadingProgress::new()))
+    }
+
+    pub fn reset(&mut self)
+    {
+        *self = LoadingProgress::new();
+    }
+
+    pub fn set_stage(&mut self, stage: &str, total: usize)
+    {
+        self.stage = stage.to_string();
+        self.current = 0;
+        self.total = total;
+    }
+
+    pub fn step(&mut self)
+    {
+        self.current = (self.current + 1).min(self.total);
+    }
+
+    // progress of the current stage (0..1)
+    pub fn percentage(&self) -> f32
+    {
+        if self.total == 0
+        {
+            return 0.0;
+        }
+
+        self.current as f32 / self.total as f32
+    }
+
+    pub fn is_active(&self) -> bool
+    {
+        !self.stage.is_empty()
+    }
+
+    pub fn cancel(&mut self)
+    {
+        self.canceled = true;
+    }
+
+    pub fn is_canceled(&self) -> bool
+    {
+        self.canceled
+    }
+}
+
+// ********** helper for the loaders (progress is optional) **********
+
+pub fn set_stage(progress: &Option<LoadingProgressItem>, stage: &str, total: usize)
+{
+    if let Some(progress) = progress
+    {
+        progress.write().unwrap().set_stage(stage, total);
+    }
+}
+
+pub fn step(progress: &Option<LoadingProgressItem>)
+{
+    if let Some(progress) = progress
+    {
+        progress.write().unwrap().step();
+    }
+}
+
+pub fn check_canceled(progress: &Option<LoadingProgressItem>) -> anyhow::Result<()>
+{
+    if let Some(progress) = progress
+    {
+        if progress.read().unwrap().is_canceled()
+        {
+            return Err(anyhow::anyhow!("loading canceled"));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn is_canceled(progress: &Option<LoadingProgressItem>) -> bool
+{
+    progress.as_ref().map(|progress| progress.read().unwrap().is_canceled()).unwrap_or(false)
+}
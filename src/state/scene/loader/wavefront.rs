@@ -1,8 +1,8 @@
-use std::{io::{Cursor, BufReader}, sync::{RwLock, Arc}, path::Path};
+use std::{io::{Cursor, BufReader}, sync::{RwLock, Arc}, path::Path, collections::HashMap};
 
 use nalgebra::{Point3, Point2, Vector3};
 
-use crate::{resources::resources::load_string, state::scene::{components::{mesh::Mesh, material::{Material, TextureType, MaterialItem}, component::Component}, scene::Scene, node::Node, utilities::scene_utils::{get_new_component_id, load_texture_or_reuse, get_new_instance_id, get_new_node_id, execute_on_scene_mut_and_wait}}, helper::{self, concurrency::execution_queue::ExecutionQueueItem, file::get_stem}, new_component};
+use crate::{resources::resources::load_string, state::scene::{components::{mesh::Mesh, material::{Material, TextureType, MaterialItem}, component::Component}, scene::Scene, node::{Node, NodeItem}, loader::progress::{self, LoadingProgressItem}, utilities::scene_utils::{load_texture_or_reuse, execute_on_scene_mut_and_wait, execute_on_scene_mut, ReservedIds}}, helper::{self, concurrency::execution_queue::ExecutionQueueItem, file::get_stem}, new_component};
 
 pub fn get_texture_path(tex_path: &String, mtl_path: &str) -> String
 {
@@ -20,17 +20,148 @@ pub fn get_texture_path(tex_path: &String, mtl_path: &str) -> String
     tex_path
 }
 
-pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_root_node: bool,reuse_materials: bool, _object_only: bool, create_mipmaps: bool) -> anyhow::Result<Vec<u64>>
+fn remove_invalid_faces(indices: &mut Vec<[u32; 3]>, uv_indices: &mut Vec<[u32; 3]>, normals_indices: &mut Vec<[u32; 3]>, vertices_amount: usize, uvs_amount: usize, normals_amount: usize) -> usize
 {
-    let mut loaded_ids: Vec<u64> = vec![];
+    let is_valid = |face: &[u32; 3], amount: usize| -> bool
+    {
+        face.iter().all(|i| (*i as usize) < amount)
+    };
 
-    let resource_name = get_stem(path);
+    let has_uv_indices = uv_indices.len() == indices.len();
+    let has_normals_indices = normals_indices.len() == indices.len();
+
+    let mut valid_indices = vec![];
+    let mut valid_uv_indices = vec![];
+    let mut valid_normals_indices = vec![];
+
+    for (i, face) in indices.iter().enumerate()
+    {
+        let mut valid = is_valid(face, vertices_amount);
+
+        // uvs/normals are using the vertex indices if there are no separate indices
+        if has_uv_indices
+        {
+            valid = valid && is_valid(&uv_indices[i], uvs_amount);
+        }
+        else if uvs_amount > 0
+        {
+            valid = valid && is_valid(face, uvs_amount);
+        }
+
+        if has_normals_indices
+        {
+            valid = valid && is_valid(&normals_indices[i], normals_amount);
+        }
+        else if normals_amount > 0
+        {
+            valid = valid && is_valid(face, normals_amount);
+        }
+
+        if valid
+        {
+            valid_indices.push(*face);
+
+            if has_uv_indices
+            {
+                valid_uv_indices.push(uv_indices[i]);
+            }
+
+            if has_normals_indices
+            {
+                valid_normals_indices.push(normals_indices[i]);
+            }
+        }
+    }
+
+    let removed = indices.len() - valid_indices.len();
+
+    *indices = valid_indices;
+
+    if has_uv_indices
+    {
+        *uv_indices = valid_uv_indices;
+    }
+
+    if has_normals_indices
+    {
+        *normals_indices = valid_normals_indices;
+    }
+
+    removed
+}
+
+// tobj fails to load the whole file if one face is using an invalid index -> these faces are removed before parsing
+// relative (negative) indices are resolved against the amount of elements defined before the face (like tobj does)
+fn remove_invalid_face_lines(obj_text: &str) -> (String, usize)
+{
+    let mut positions = 0;
+    let mut uvs = 0;
+    let mut normals = 0;
+
+    // uv and normal indices are ignored by tobj if there are no uvs/normals at all
+    let is_valid = |index: Option<&str>, amount: usize, required: bool| -> bool
+    {
+        let index = index.unwrap_or("");
+
+        if index.is_empty() || (!required && amount == 0)
+        {
+            return !required;
+        }
+
+        match index.parse::<i64>()
+        {
+            Ok(i) => i != 0 && i.unsigned_abs() as usize <= amount,
+            Err(_) => false,
+        }
+    };
+
+    let mut removed = 0;
+    let mut lines = vec![];
+
+    for line in obj_text.lines()
+    {
+        let mut words = line.split_whitespace();
+
+        match words.next()
+        {
+            Some("v") => positions += 1,
+            Some("vt") => uvs += 1,
+            Some("vn") => normals += 1,
+            Some("f") =>
+            {
+                let valid = words.all(|vertex|
+                {
+                    let mut parts = vertex.split('/');
+                    is_valid(parts.next(), positions, true) && is_valid(parts.next(), uvs, false) && is_valid(parts.next(), normals, false)
+                });
+
+                if !valid
+                {
+                    removed += 1;
+                    continue;
+                }
+            },
+            _ => {}
+        }
+
+        lines.push(line);
+    }
+
+    (lines.join("\n"), removed)
+}
+
+fn parse_obj<ML>(obj_text: &str, name: &str, material_loader: ML) -> tobj::LoadResult where ML: Fn(&Path) -> tobj::MTLLoadResult
+{
+    let (obj_text, removed_faces) = remove_invalid_face_lines(obj_text);
+    if removed_faces > 0
+    {
+        println!("warning: {} faces of {} removed because of invalid indices", removed_faces, name);
+    }
 
-    let obj_text = load_string(path)?;
     let obj_cursor = Cursor::new(obj_text);
     let mut obj_reader = BufReader::new(obj_cursor);
 
-    let (models, materials) = tobj::load_obj_buf
+    tobj::load_obj_buf
     (
         &mut obj_reader,
         &tobj::LoadOptions
@@ -39,6 +170,26 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
             single_index: true,
             ..Default::default()
         },
+        material_loader
+    )
+}
+
+pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_root_node: bool,reuse_materials: bool, _object_only: bool, create_mipmaps: bool, progress: Option<LoadingProgressItem>) -> anyhow::Result<Vec<u64>>
+{
+    let mut loaded_ids: Vec<u64> = vec![];
+
+    let resource_name = get_stem(path);
+
+    // textures are relative to the mtl file (which could be located somewhere else)
+    let mtl_path = Arc::new(RwLock::new(path.to_string()));
+    let mtl_path_clone = mtl_path.clone();
+
+    let obj_text = load_string(path)?;
+
+    let (models, materials) = parse_obj
+    (
+        &obj_text,
+        path,
         move |p|
         {
             let mut file_path = p.to_str().unwrap().to_string();
@@ -47,18 +198,37 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                 file_path = helper::file::get_dirname(path) + "/" + &file_path;
             }
 
+            *mtl_path_clone.write().unwrap() = file_path.clone();
+
             let mat_text = load_string(&file_path).unwrap();
             tobj::load_mtl_buf(&mut BufReader::new(Cursor::new(mat_text)))
         },
     )?;
 
     let wavefront_materials = materials.unwrap();
+    let mtl_path = mtl_path.read().unwrap().clone();
     let mut scene_nodes = vec![];
 
     let mut double_check_materials: Vec<(usize, MaterialItem)> = vec![];
 
+    // objects with multiple materials (usemtl) are split into multiple models with the same name
+    let mut models_per_name: HashMap<String, usize> = HashMap::new();
+    for m in &models
+    {
+        *models_per_name.entry(m.name.clone()).or_insert(0) += 1;
+    }
+
+    let mut group_nodes: HashMap<String, NodeItem> = HashMap::new();
+
+    let mut ids = ReservedIds::new(main_queue.clone(), scene_id);
+
+    progress::set_stage(&progress, "meshes", models.len());
+
     for (_i, m) in models.iter().enumerate()
     {
+        progress::check_canceled(&progress)?;
+        progress::step(&progress);
+
         let mesh = &m.mesh;
 
         if mesh.texcoord_indices.len() > 0 && mesh.indices.len() != mesh.texcoord_indices.len()
@@ -135,6 +305,13 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
             normals_indices.push([i0, i1, i2]);
         }
 
+        // negative (relative) indices are already resolved by tobj -> but invalid indices should not end up in the mesh
+        let removed_faces = remove_invalid_faces(&mut indices, &mut uv_indices, &mut normals_indices, verts.len(), uvs.len(), normals.len());
+        if removed_faces > 0
+        {
+            println!("warning: {} faces of {} removed because of invalid indices", removed_faces, m.name.as_str());
+        }
+
         if verts.len() > 0
         {
             //let material_arc;
@@ -160,7 +337,7 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                 else
                 {
                     //let component_id = scene.id_manager.get_next_component_id();
-                    let component_id = get_new_component_id(main_queue.clone(), scene_id);
+                    let component_id = ids.component_id();
                     material_arc = new_component!(Material::new(component_id, ""));
 
                     let mut material_guard = material_arc.write().unwrap();
@@ -177,7 +354,11 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
 
                     if mat.shininess.is_some()
                     {
-                        material_data.shininess = mat.shininess.unwrap();
+                        let shininess = mat.shininess.unwrap();
+                        material_data.shininess = shininess;
+
+                        // approximate roughness based on the (phong) specular exponent
+                        material_data.roughness = (2.0 / (shininess.max(0.0) + 2.0)).sqrt().min(1.0);
                     }
 
                     if mat.ambient.is_some()
@@ -208,8 +389,11 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                         material_data.alpha = mat.dissolve.unwrap();
                     }
 
-
-                    material_data.ambient_color = material_data.base_color * 0.01;
+                    // use a darker version of the base color if there is no ambient color set
+                    if mat.ambient.is_none()
+                    {
+                        material_data.ambient_color = material_data.base_color * 0.01;
+                    }
 
                     if let Some(illumination) = mat.illumination_model
                     {
@@ -224,7 +408,7 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                     {
                         println!("loading diffuse texture {}", mat.diffuse_texture.clone().unwrap());
                         let diffuse_texture = mat.diffuse_texture.clone().unwrap();
-                        let tex_path = get_texture_path(&diffuse_texture, path);
+                        let tex_path = get_texture_path(&diffuse_texture, mtl_path.as_str());
                         let tex = load_texture_or_reuse(scene_id, main_queue.clone(), tex_path.as_str(), None)?;
                         {
                             let mut tex = tex.write().unwrap();
@@ -239,7 +423,7 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                     {
                         println!("loading normal texture {}", mat.normal_texture.clone().unwrap());
                         let normal_texture = mat.normal_texture.clone().unwrap();
-                        let tex_path = get_texture_path(&normal_texture, path);
+                        let tex_path = get_texture_path(&normal_texture, mtl_path.as_str());
                         let tex = load_texture_or_reuse(scene_id, main_queue.clone(), tex_path.as_str(), None)?;
                         {
                             let mut tex = tex.write().unwrap();
@@ -254,7 +438,7 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                     {
                         println!("loading ambient texture {}", mat.ambient_texture.clone().unwrap());
                         let ambient_texture = mat.ambient_texture.clone().unwrap();
-                        let tex_path = get_texture_path(&ambient_texture, path);
+                        let tex_path = get_texture_path(&ambient_texture, mtl_path.as_str());
                         let tex = load_texture_or_reuse(scene_id, main_queue.clone(), tex_path.as_str(), None)?;
                         {
                             let mut tex = tex.write().unwrap();
@@ -269,7 +453,7 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                     {
                         println!("loading specular texture {}", mat.specular_texture.clone().unwrap());
                         let specular_texture = mat.specular_texture.clone().unwrap();
-                        let tex_path: String = get_texture_path(&specular_texture, path);
+                        let tex_path: String = get_texture_path(&specular_texture, mtl_path.as_str());
                         let tex = load_texture_or_reuse(scene_id, main_queue.clone(), tex_path.as_str(), None)?;
                         {
                             let mut tex = tex.write().unwrap();
@@ -284,7 +468,7 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                     {
                         println!("loading dissolve texture {}", mat.dissolve_texture.clone().unwrap());
                         let dissolve_texture = mat.dissolve_texture.clone().unwrap();
-                        let tex_path = get_texture_path(&dissolve_texture, path);
+                        let tex_path = get_texture_path(&dissolve_texture, mtl_path.as_str());
                         let tex = load_texture_or_reuse(scene_id, main_queue.clone(), tex_path.as_str(), None)?;
                         {
                             let mut tex = tex.write().unwrap();
@@ -299,7 +483,7 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                     {
                         println!("loading shininess texture {}", mat.shininess_texture.clone().unwrap());
                         let shininess_texture = mat.shininess_texture.clone().unwrap();
-                        let tex_path = get_texture_path(&shininess_texture, path);
+                        let tex_path = get_texture_path(&shininess_texture, mtl_path.as_str());
                         let tex = load_texture_or_reuse(scene_id, main_queue.clone(), tex_path.as_str(), None)?;
                         {
                             let mut tex = tex.write().unwrap();
@@ -310,7 +494,7 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                     }
 
                     let material_arc_clone = material_arc.clone();
-                    execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+                    execute_on_scene_mut(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
                     {
                         scene.add_material(component_id, &material_arc_clone);
                     }));
@@ -319,7 +503,7 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
             }
             else
             {
-                let material_id = get_new_component_id(main_queue.clone(), scene_id);
+                let material_id = ids.component_id();
                 material_arc = Arc::new(RwLock::new(Box::new(Material::new(material_id, ""))));
             }
 
@@ -333,13 +517,25 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
                 normals_indices = indices.clone();
             }
 
-            let component_id = get_new_component_id(main_queue.clone(), scene_id);
+            let component_id = ids.component_id();
             let item = Mesh::new_with_data(component_id, "mesh", verts, indices, uvs, uv_indices, normals, normals_indices);
 
-            let id = get_new_node_id(main_queue.clone(), scene_id);
+            let is_grouped = *models_per_name.get(&m.name).unwrap_or(&0) > 1;
+
+            // name sub meshes by material
+            let mut node_name = m.name.clone();
+            if is_grouped
+            {
+                if let Some(wavefront_mat_id) = mesh.material_id
+                {
+                    node_name = format!("{} {}", m.name, wavefront_materials[wavefront_mat_id].name);
+                }
+            }
+
+            let id = ids.node_id();
             loaded_ids.push(id);
 
-            let node_arc = Node::new(id, m.name.as_str());
+            let node_arc = Node::new(id, node_name.as_str());
             {
                 let mut node = node_arc.write().unwrap();
                 node.add_component(Arc::new(RwLock::new(Box::new(item))));
@@ -349,44 +545,227 @@ pub fn load(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_ro
 
                 // add default instance
                 //let node = scene.nodes.get_mut(0).unwrap();
-                let instance_id = get_new_instance_id(main_queue.clone(), scene_id);
+                let instance_id = ids.instance_id();
                 node.create_default_instance(node_arc.clone(), instance_id);
             }
 
-            scene_nodes.push(node_arc)
+            if is_grouped
+            {
+                // all sub meshes of an object are placed under one parent node
+                let group_node = group_nodes.entry(m.name.clone()).or_insert_with(||
+                {
+                    let group_id = ids.node_id();
+                    loaded_ids.push(group_id);
+
+                    let group_node = Node::new(group_id, m.name.as_str());
+                    scene_nodes.push(group_node.clone());
+
+                    group_node
+                });
+
+                Node::add_node(group_node.clone(), node_arc);
+            }
+            else
+            {
+                scene_nodes.push(node_arc)
+            }
         }
     }
 
     // ********** add to scene **********
+    progress::check_canceled(&progress)?;
+
     if create_root_node
     {
-        let node_id = get_new_node_id(main_queue.clone(), scene_id);
+        let node_id = ids.node_id();
         loaded_ids.push(node_id);
 
         let root_node = Node::new(node_id, resource_name.as_str());
         root_node.write().unwrap().root_node = true;
 
-        let root_node_clone = root_node.clone();
-        execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
-        {
-            scene.add_node(root_node_clone.clone());
-        }));
-
         for scene_node in &scene_nodes
         {
             Node::add_node(root_node.clone(), scene_node.clone());
         }
+
+        scene_nodes = vec![root_node];
     }
-    else
+
+    // all nodes at once (one main thread call)
+    execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
     {
-        execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+        for scene_node in &scene_nodes
         {
-            for scene_node in &scene_nodes
-            {
-                scene.add_node(scene_node.clone());
-            }
-        }));
-    }
+            scene.add_node(scene_node.clone());
+        }
+    }));
 
     Ok(loaded_ids)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{thread, time::Duration};
+
+    use crate::{component_downcast, helper::concurrency::execution_queue::ExecutionQueue, state::{state::State, scene::{scene::Scene, components::{material::Material, component::Component}}}};
+
+    use super::{load, parse_obj, remove_invalid_faces};
+
+    // positions of all faces of the first model
+    fn load_faces(obj_text: &str) -> Vec<[[f32; 3]; 3]>
+    {
+        let (models, _materials) = parse_obj(obj_text, "test", |_| Err(tobj::LoadError::OpenFileFailed)).unwrap();
+        let mesh = &models[0].mesh;
+
+        let position = |i: u32| -> [f32; 3]
+        {
+            let i = i as usize * 3;
+            [mesh.positions[i], mesh.positions[i + 1], mesh.positions[i + 2]]
+        };
+
+        mesh.indices.chunks(3).map(|face| [position(face[0]), position(face[1]), position(face[2])]).collect()
+    }
+
+    #[test]
+    fn relative_indices()
+    {
+        let obj_text = "
+            v 0 0 0
+            v 1 0 0
+            v 0 1 0
+            f -3 -2 -1
+            v 1 1 0
+            f -3 -1 -2
+            f 1 2 -1
+        ";
+
+        let faces = load_faces(obj_text);
+
+        assert_eq!(faces, vec!
+        [
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            [[1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]],
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]],
+        ]);
+    }
+
+    #[test]
+    fn out_of_range_indices()
+    {
+        // index 0, an index after the last vertex, a relative index before the first vertex and an invalid normal index
+        let obj_text = "
+            v 0 0 0
+            v 1 0 0
+            v 0 1 0
+            vn 0 0 1
+            f 1//1 2//1 3//1
+            f 0 1 2
+            f 1 2 4
+            f -4 -2 -1
+            f 1//1 2//1 3//2
+            v 1 1 0
+            f 2//1 4//1 3//1
+        ";
+
+        let faces = load_faces(obj_text);
+
+        assert_eq!(faces, vec!
+        [
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            [[1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]],
+        ]);
+    }
+
+    #[test]
+    fn invalid_faces_after_parsing()
+    {
+        let mut indices = vec![[0, 1, 2], [0, 1, 3], [1, 2, 0]];
+        let mut uv_indices = vec![[0, 1, 2], [0, 1, 2], [5, 1, 0]];
+        let mut normals_indices = vec![];
+
+        let removed = remove_invalid_faces(&mut indices, &mut uv_indices, &mut normals_indices, 3, 3, 0);
+
+        assert_eq!(removed, 2);
+        assert_eq!(indices, vec![[0, 1, 2]]);
+        assert_eq!(uv_indices, vec![[0, 1, 2]]);
+        assert!(normals_indices.is_empty());
+    }
+
+    #[test]
+    fn usemtl_groups_sub_meshes()
+    {
+        let dir = std::env::temp_dir().join(format!("rustl_wavefront_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let obj_text = "
+            mtllib test.mtl
+            o quad
+            v 0 0 0
+            v 1 0 0
+            v 0 1 0
+            v 1 1 0
+            usemtl red
+            f 1 2 3
+            usemtl blue
+            f 2 4 3
+        ";
+
+        let mtl_text = "
+            newmtl red
+            Kd 1 0 0
+            newmtl blue
+            Kd 0 0 1
+        ";
+
+        std::fs::write(dir.join("test.obj"), obj_text).unwrap();
+        std::fs::write(dir.join("test.mtl"), mtl_text).unwrap();
+
+        let mut state = State::new();
+        state.scenes.push(Box::new(Scene::new(1, "test")));
+
+        // the loader is waiting for the main thread (ids, adding the nodes) -> process the queue while loading
+        let main_queue = state.main_thread_execution_queue.clone();
+        let path = dir.join("test.obj").to_str().unwrap().to_string();
+        let loader = thread::spawn(move || load(path.as_str(), 1, main_queue, false, false, false, false, None).is_ok());
+
+        while !loader.is_finished()
+        {
+            ExecutionQueue::run_all(state.main_thread_execution_queue.clone(), &mut state);
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        ExecutionQueue::run_all(state.main_thread_execution_queue.clone(), &mut state);
+        assert!(loader.join().unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // one parent node with one child per material
+        let scene = &state.scenes[0];
+        assert_eq!(scene.nodes.len(), 1);
+
+        let parent = scene.nodes[0].read().unwrap();
+        assert_eq!(parent.name, "quad");
+        assert!(parent.get_mesh().is_none());
+        assert_eq!(parent.nodes.len(), 2);
+
+        let mut materials = vec![];
+        for child in &parent.nodes
+        {
+            let child = child.read().unwrap();
+            assert!(child.get_mesh().is_some());
+
+            let material = child.find_component::<Material>().unwrap();
+            component_downcast!(material, Material);
+
+            materials.push((child.name.clone(), material.get_base().name.clone(), material.get_base().id));
+        }
+
+        materials.sort();
+        assert_eq!(materials[0].0, "quad blue");
+        assert_eq!(materials[0].1, "blue");
+        assert_eq!(materials[1].0, "quad red");
+        assert_eq!(materials[1].1, "red");
+        assert_ne!(materials[0].2, materials[1].2);
+    }
+}
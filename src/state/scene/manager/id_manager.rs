@@ -1,6 +1,10 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// texture ids are unique across all scenes (textures can be shared between scenes - see TextureCache)
+static TEXTURE_ID: AtomicU64 = AtomicU64::new(0);
+
 pub struct IdManager
 {
-    texture_id: u64,
     node_id: u64,
     instance_id: u64,
     camera_id: u64,
@@ -14,7 +18,6 @@ impl IdManager
     {
         Self
         {
-            texture_id: 0,
             node_id: 0,
             instance_id: 0,
             camera_id: 0,
@@ -25,9 +28,7 @@ impl IdManager
 
     pub fn get_next_texture_id(&mut self) -> u64
     {
-        self.texture_id = self.texture_id + 1;
-
-        self.texture_id
+        TEXTURE_ID.fetch_add(1, Ordering::Relaxed) + 1
     }
 
     pub fn get_next_node_id(&mut self) -> u64
@@ -65,4 +66,29 @@ impl IdManager
         self.component_id
     }
 
+    // reserve a block of ids (returns the first one) - used by the loaders to avoid a main thread round trip per id
+    pub fn reserve_node_ids(&mut self, amount: u64) -> u64
+    {
+        let first = self.node_id + 1;
+        self.node_id += amount;
+
+        first
+    }
+
+    pub fn reserve_instance_ids(&mut self, amount: u64) -> u64
+    {
+        let first = self.instance_id + 1;
+        self.instance_id += amount;
+
+        first
+    }
+
+    pub fn reserve_component_ids(&mut self, amount: u64) -> u64
+    {
+        let first = self.component_id + 1;
+        self.component_id += amount;
+
+        first
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,49 @@
+use std::{collections::HashMap, sync::{RwLock, Weak, Arc}};
+
+use crate::state::scene::texture::{Texture, TextureItem};
+
+// process wide texture cache by content hash - to reuse decoded and uploaded textures across scenes
+// only weak references are stored: textures are freed when they are removed from all scenes
+pub struct TextureCache
+{
+    textures: HashMap<String, Weak<RwLock<Box<Texture>>>>,
+
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl TextureCache
+{
+    pub fn new() -> TextureCache
+    {
+        TextureCache
+        {
+            textures: HashMap::new(),
+
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn get(&mut self, hash: &str) -> Option<TextureItem>
+    {
+        let texture = self.textures.get(hash).and_then(|texture| texture.upgrade());
+
+        if texture.is_none()
+        {
+            self.textures.remove(hash);
+        }
+
+        texture
+    }
+
+    pub fn insert(&mut self, hash: &str, texture: &TextureItem)
+    {
+        self.textures.insert(hash.to_string(), Arc::downgrade(texture));
+    }
+
+    pub fn count(&self) -> usize
+    {
+        self.textures.values().filter(|texture| texture.strong_count() > 0).count()
+    }
+}
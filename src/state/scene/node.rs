@@ -1,26 +1,44 @@
-use std::{sync::{Arc, RwLock}, cell::RefCell};
+use std::{sync::{Arc, RwLock}, cell::RefCell, collections::{HashSet, HashMap}};
 use bvh::aabb::Bounded;
 use bvh::bounding_hierarchy::BHShape;
 use nalgebra::{Matrix4, Point3};
 
-use crate::{state::helper::render_item::RenderItemOption, helper::change_tracker::ChangeTracker, component_downcast, component_downcast_mut, input::input_manager::InputManager};
+use crate::{state::helper::render_item::RenderItemOption, helper::{change_tracker::ChangeTracker, math::{transform_bounding_sphere, merge_bounding_spheres, approx_equal}}, component_downcast, component_downcast_mut, input::input_manager::InputManager};
 
-use super::{components::{component::{ComponentItem, Component, find_component, find_components, remove_component_by_type, remove_component_by_id, find_component_by_id}, mesh::Mesh, transformation::Transformation, alpha::Alpha}, instance::{InstanceItem, Instance}};
+use super::{manager::id_manager::IdManager, scene::Scene, components::{component::{ComponentItem, Component, find_component, find_components, remove_component_by_type, remove_component_by_id, replace_component_by_id, find_component_by_id}, mesh::Mesh, transformation::Transformation, alpha::Alpha}, instance::{InstanceItem, Instance}};
 
 pub type NodeItem = Arc<RwLock<Box<Node>>>;
 pub type InstanceItemArc = Arc<RwLock<InstanceItem>>;
 
 const UPDATE_ALL_INSTANCES_THRESHOLD: u32 = 10; // if more than 10 instances got an update -> update all instances at once to save performance
 
+// render layers: a node/instance is only rendered by cameras sharing at least one layer (see CameraData::layer_mask)
+pub const LAYER_DEFAULT: u32 = 1 << 0;
+pub const LAYER_EDITOR: u32 = 1 << 31; // editor overlays like the grid (hide it from gameplay cameras)
+pub const LAYER_ALL: u32 = u32::MAX;
+
 pub struct Node
 {
     pub id: u64,
     pub name: String,
     pub visible: bool,
     pub root_node: bool,
+    pub layer_mask: u32,
+
+    // name and index of the node in the loaded file (the name above can be changed in the editor and may not be unique)
+    pub source_name: Option<String>,
+    pub source_index: Option<usize>,
+    pub extras: HashMap<String, String>, // custom properties of the loaded file (glTF extras)
+    pub marker: Option<String>, // kind of the marker (empty nodes used as spawn points, triggers, ...)
+
+    pub tags: HashSet<String>,
 
     pub render_children_first: bool,
-    pub alpha_index: u64, // this can be used to influence the sorting (for rendering)
+    pub alpha_index: u64, // manual draw order of transparent nodes: higher indices are drawn later (applied before the distance sorting)
+
+    // static nodes (and their instances/components) are only updated if they are marked as dirty (see mark_dirty)
+    pub is_static: bool,
+    static_dirty: bool,
 
     pub parent: Option<NodeItem>,
 
@@ -47,10 +65,21 @@ impl Node
             name: name.to_string(),
             visible: true,
             root_node: false,
+            layer_mask: LAYER_DEFAULT,
+
+            source_name: None,
+            source_index: None,
+            extras: HashMap::new(),
+            marker: None,
+
+            tags: HashSet::new(),
 
             render_children_first: false,
             alpha_index: 0,
 
+            is_static: false,
+            static_dirty: true, // initial update to compute the world matrices
+
             components: vec![],
 
             parent: None,
@@ -65,6 +94,48 @@ impl Node
         Arc::new(RwLock::new(Box::new(node)))
     }
 
+    pub fn add_tag(&mut self, tag: &str)
+    {
+        let tag = tag.trim();
+
+        if !tag.is_empty()
+        {
+            self.tags.insert(tag.to_string());
+        }
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) -> bool
+    {
+        self.tags.remove(tag)
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool
+    {
+        self.tags.contains(tag)
+    }
+
+    // case insensitive match of the name or one of the tags (filter needs to be lowercase)
+    pub fn matches_filter(&self, filter: &str) -> bool
+    {
+        if self.name.to_lowercase().contains(filter)
+        {
+            return true;
+        }
+
+        self.tags.iter().any(|tag| tag.to_lowercase().contains(filter))
+    }
+
+    // checks the node itself and all descendants
+    pub fn matches_filter_recursive(&self, filter: &str) -> bool
+    {
+        if self.matches_filter(filter)
+        {
+            return true;
+        }
+
+        self.nodes.iter().any(|node| node.read().unwrap().matches_filter_recursive(filter))
+    }
+
     pub fn add_node(node: NodeItem, child_node: NodeItem)
     {
         {
@@ -81,6 +152,44 @@ impl Node
     pub fn add_component(&mut self, component: ComponentItem)
     {
         self.components.push(component);
+        self.static_dirty = true;
+    }
+
+    // update a static node once (in the next update)
+    pub fn mark_dirty(&mut self)
+    {
+        self.static_dirty = true;
+    }
+
+    // transformation, alpha or the instances list has changed (not consumed yet)
+    pub fn has_changed_data(&self) -> bool
+    {
+        if self.instances.changed()
+        {
+            return true;
+        }
+
+        let trans_component = self.find_component::<Transformation>();
+        if let Some(trans_component) = trans_component
+        {
+            component_downcast!(trans_component, Transformation);
+            if trans_component.get_data_tracker().changed()
+            {
+                return true;
+            }
+        }
+
+        let alpha_component = self.find_component::<Alpha>();
+        if let Some(alpha_component) = alpha_component
+        {
+            component_downcast!(alpha_component, Alpha);
+            if alpha_component.get_data_tracker().changed()
+            {
+                return true;
+            }
+        }
+
+        false
     }
 
     pub fn find_component<T>(&self) -> Option<ComponentItem> where T: 'static
@@ -114,6 +223,17 @@ impl Node
         }
     }
 
+    pub fn replace_component_by_id(&mut self, id: u64, component: &ComponentItem) -> bool
+    {
+        if replace_component_by_id(&mut self.components, id, component)
+        {
+            self.force_instances_update();
+            return true;
+        }
+
+        false
+    }
+
     pub fn get_mesh(&self) -> Option<ComponentItem>
     {
         self.find_component::<Mesh>()
@@ -217,6 +337,31 @@ impl Node
         None
     }
 
+    pub fn get_world_bounding_sphere(&self, instance_id: u64) -> Option<(Point3<f32>, f32)>
+    {
+        let instance = self.find_instance_by_id(instance_id)?;
+        let transform = instance.read().unwrap().calculate_transform();
+
+        let mut sphere: Option<(Point3<f32>, f32)> = None;
+
+        for mesh in &self.get_meshes()
+        {
+            component_downcast!(mesh, Mesh);
+            let mesh_sphere = transform_bounding_sphere(&mesh.bounding_sphere(), &transform);
+
+            if let Some(current) = sphere
+            {
+                sphere = Some(merge_bounding_spheres(&current, &mesh_sphere));
+            }
+            else
+            {
+                sphere = Some(mesh_sphere);
+            }
+        }
+
+        sphere
+    }
+
     pub fn has_changed_instance_data(&self) -> bool
     {
         for instance in self.instances.get_ref()
@@ -276,6 +421,13 @@ impl Node
         }
     }
 
+    pub fn get_world_position(&self) -> Point3<f32>
+    {
+        let transform = self.get_full_transform();
+
+        Point3::<f32>::new(transform[(0, 3)], transform[(1, 3)], transform[(2, 3)])
+    }
+
     pub fn get_alpha(&self) -> (f32, bool)
     {
         let alpha_component = self.find_component::<Alpha>();
@@ -319,8 +471,41 @@ impl Node
         }
     }
 
+    // effective layers: the layers of all parent nodes are applied to the subtree
+    pub fn get_layer_mask(&self) -> u32
+    {
+        if let Some(parent_node) = &self.parent
+        {
+            return self.layer_mask & parent_node.read().unwrap().get_layer_mask();
+        }
+
+        self.layer_mask
+    }
+
+    // effective visibility: hidden parent nodes are hiding the whole subtree
+    pub fn is_visible(&self) -> bool
+    {
+        if !self.visible
+        {
+            return false;
+        }
+
+        if let Some(parent_node) = &self.parent
+        {
+            return parent_node.read().unwrap().is_visible();
+        }
+
+        true
+    }
+
     pub fn is_empty(&self) -> bool
     {
+        // markers are used without any mesh
+        if self.marker.is_some()
+        {
+            return false;
+        }
+
         let has_meshes = self.get_mesh().is_some();
 
         if has_meshes
@@ -361,6 +546,28 @@ impl Node
 
     pub fn update(node: NodeItem, input_manager: &mut InputManager, frame_scale: f32)
     {
+        // ***** static nodes *****
+        // skipped until marked as dirty - changing the transformation/alpha of a static node marks it as dirty for one update
+        let skip_update;
+        let was_dirty;
+        {
+            let mut node = node.write().unwrap();
+            was_dirty = node.static_dirty;
+            skip_update = node.is_static && !was_dirty && !node.has_changed_data();
+            node.static_dirty = false;
+        }
+
+        if skip_update
+        {
+            let node_read = node.read().unwrap();
+            for child_node in &node_read.nodes
+            {
+                Self::update(child_node.clone(), input_manager, frame_scale);
+            }
+
+            return;
+        }
+
         // ***** copy all components *****
         let all_components;
         {
@@ -437,8 +644,16 @@ impl Node
 
         // ***** update childs *****
         let node_read = node.read().unwrap();
+        let changed = was_dirty || node_read.has_changed_data();
+
         for child_node in &node_read.nodes
         {
+            // static children need to follow the changed (or dirty) parent
+            if changed
+            {
+                child_node.write().unwrap().mark_dirty();
+            }
+
             Self::update(child_node.clone(), input_manager, frame_scale);
         }
     }
@@ -529,8 +744,116 @@ impl Node
         true
     }
 
+    // bakes the world transformation into the mesh vertices and resets the node transformation to identity (without parent inheritance)
+    // the local transformations of the children are adjusted to keep their world transformation
+    pub fn bake_transform(node: NodeItem, id_manager: &mut IdManager) -> bool
+    {
+        let world_transform;
+        let children;
+        {
+            let node = node.read().unwrap();
+
+            if node.get_meshes().is_empty()
+            {
+                println!("can not bake transformation -> node has no mesh");
+                return false;
+            }
+
+            // instance transformations are applied after the node transformation -> they can not be baked
+            for instance in node.instances.get_ref()
+            {
+                let instance = instance.read().unwrap();
+                let transform = instance.find_component::<Transformation>();
+
+                if let Some(transform) = transform
+                {
+                    component_downcast!(transform, Transformation);
+
+                    let is_identity = transform.get_transform().iter().zip(Matrix4::<f32>::identity().iter()).all(|(a, b)| approx_equal(*a, *b));
+
+                    if transform.get_base().is_enabled && (!is_identity || !transform.has_parent_inheritance())
+                    {
+                        println!("can not bake transformation -> instances are transformed");
+                        return false;
+                    }
+                }
+            }
+
+            world_transform = node.get_full_transform();
+            children = node.nodes.clone();
+        }
+
+        {
+            let mut node = node.write().unwrap();
+
+            for mesh in node.get_meshes()
+            {
+                component_downcast_mut!(mesh, Mesh);
+                mesh.apply_transform(&world_transform);
+            }
+
+            let transform = node.find_component::<Transformation>();
+            let transform = match transform
+            {
+                Some(transform) => transform,
+                None =>
+                {
+                    let transform: ComponentItem = Arc::new(RwLock::new(Box::new(Transformation::identity(id_manager.get_next_component_id(), "Transform"))));
+                    node.add_component(transform.clone());
+                    transform
+                }
+            };
+
+            {
+                component_downcast_mut!(transform, Transformation);
+                transform.get_base_mut().is_enabled = true;
+                transform.get_data_mut().get_mut().parent_inheritance = false;
+                transform.reset();
+            }
+
+            node.force_instances_update();
+        }
+
+        // children: the former world transformation of the node is moved into their local transformation
+        // (set as matrix: a non uniform scaled node shears rotated children)
+        for child in &children
+        {
+            let mut child = child.write().unwrap();
+            let (local_transform, parent_inheritance) = child.get_transform();
+
+            if !parent_inheritance
+            {
+                continue;
+            }
+
+            let transform = child.find_component::<Transformation>();
+            if let Some(transform) = transform
+            {
+                component_downcast_mut!(transform, Transformation);
+                transform.get_base_mut().is_enabled = true;
+                transform.set_transform_matrix(world_transform * local_transform);
+            }
+            else
+            {
+                let mut transform = Transformation::identity(id_manager.get_next_component_id(), "Transform");
+                transform.set_transform_matrix(world_transform * local_transform);
+
+                child.add_component(Arc::new(RwLock::new(Box::new(transform))));
+            }
+        }
+
+        for child in Scene::list_all_child_nodes(&children)
+        {
+            child.write().unwrap().force_instances_update();
+        }
+
+        true
+    }
+
     pub fn force_instances_update(&mut self)
     {
+        self.static_dirty = true;
+
         for instance in self.instances.get_ref()
         {
             let mut instance = instance.write().unwrap();
@@ -676,4 +999,141 @@ impl BHShape for Node
     {
         self.b_box_node_index
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::{Arc, RwLock};
+
+    use nalgebra::{Point3, Vector3};
+
+    use crate::{component_downcast, helper::math::approx_equal, state::scene::{scene::{Scene, test_utils::{create_scene as create_empty_scene, create_box, update}}, components::{mesh::Mesh, transformation::Transformation}}};
+
+    use super::{Node, NodeItem};
+
+    // parent node (added to the scene) with a 2x2x2 box child node
+    fn create_scene() -> (Scene, NodeItem, NodeItem)
+    {
+        let mut scene = create_empty_scene(0);
+
+        let parent = Node::new(scene.id_manager.get_next_node_id(), "parent");
+        let child = create_box(&mut scene, "child");
+
+        Node::add_node(parent.clone(), child.clone());
+        scene.add_node(parent.clone());
+
+        update(&mut scene);
+
+        (scene, parent, child)
+    }
+
+    #[test]
+    fn hidden_parent_hides_child()
+    {
+        let (_scene, parent, child) = create_scene();
+
+        assert!(child.read().unwrap().is_visible());
+
+        parent.write().unwrap().visible = false;
+
+        assert!(!child.read().unwrap().is_visible());
+        assert!(child.read().unwrap().visible);
+    }
+
+    #[test]
+    fn raycast_skips_child_of_hidden_parent()
+    {
+        let (scene, parent, child) = create_scene();
+        let origin = Point3::<f32>::new(0.0, 0.0, 10.0);
+
+        let hit = scene.raycast(origin, -Vector3::<f32>::z(), 100.0, |_, _| true);
+        assert!(hit.is_some());
+        assert_eq!(hit.unwrap().3.read().unwrap().id, child.read().unwrap().id);
+
+        parent.write().unwrap().visible = false;
+
+        let hit = scene.raycast(origin, -Vector3::<f32>::z(), 100.0, |_, _| true);
+        assert!(hit.is_none());
+    }
+
+    fn add_transformation(scene: &mut Scene, node: &NodeItem, position: Vector3<f32>, rotation: Vector3<f32>, scale: Vector3<f32>)
+    {
+        let transform = Transformation::new(scene.id_manager.get_next_component_id(), "Transform", position, rotation, scale);
+        node.write().unwrap().add_component(Arc::new(RwLock::new(Box::new(transform))));
+    }
+
+    // mesh vertices in world space
+    fn world_vertices(node: &NodeItem) -> Vec<Point3<f32>>
+    {
+        let node = node.read().unwrap();
+        let transform = node.get_full_transform();
+
+        let mesh = node.find_component::<Mesh>().unwrap();
+        component_downcast!(mesh, Mesh);
+
+        let vertices = mesh.get_data().vertices.iter().map(|vertex| transform.transform_point(vertex)).collect();
+
+        vertices
+    }
+
+    fn mesh_indices(node: &NodeItem) -> Vec<[u32; 3]>
+    {
+        let mesh = node.read().unwrap().find_component::<Mesh>().unwrap();
+        component_downcast!(mesh, Mesh);
+
+        let indices = mesh.get_data().indices.clone();
+
+        indices
+    }
+
+    fn assert_same_points(a: &Vec<Point3<f32>>, b: &Vec<Point3<f32>>)
+    {
+        assert_eq!(a.len(), b.len());
+
+        for (a, b) in a.iter().zip(b)
+        {
+            assert!((a - b).magnitude() < 0.0001, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn bake_transform_keeps_world_positions()
+    {
+        let (mut scene, parent, child) = create_scene();
+
+        // the parent gets a mesh and a mirrored transformation
+        {
+            let mesh = Mesh::new_box(scene.id_manager.get_next_component_id(), "box", Vector3::<f32>::new(1.0, 2.0, 3.0));
+            parent.write().unwrap().add_component(Arc::new(RwLock::new(Box::new(mesh))));
+        }
+
+        add_transformation(&mut scene, &parent, Vector3::<f32>::new(1.0, 2.0, 3.0), Vector3::<f32>::new(0.3, 0.5, 0.0), Vector3::<f32>::new(-1.0, 2.0, 1.0));
+        add_transformation(&mut scene, &child, Vector3::<f32>::new(0.0, 3.0, 0.0), Vector3::<f32>::new(0.0, 0.0, 0.2), Vector3::<f32>::new(0.5, 0.5, 0.5));
+
+        update(&mut scene);
+
+        let parent_before = world_vertices(&parent);
+        let child_before = world_vertices(&child);
+        let parent_indices_before = mesh_indices(&parent);
+
+        assert!(Node::bake_transform(parent.clone(), &mut scene.id_manager));
+        update(&mut scene);
+
+        // the node transformation is identity now
+        let transform = parent.read().unwrap().get_full_transform();
+        assert!(transform.iter().zip(nalgebra::Matrix4::<f32>::identity().iter()).all(|(a, b)| approx_equal(*a, *b)));
+
+        assert_same_points(&parent_before, &world_vertices(&parent));
+        assert_same_points(&child_before, &world_vertices(&child));
+
+        // mirrored: the winding is flipped to keep the faces front facing
+        let parent_indices = mesh_indices(&parent);
+        assert_eq!(parent_indices_before.len(), parent_indices.len());
+
+        for (before, after) in parent_indices_before.iter().zip(parent_indices.iter())
+        {
+            assert_eq!([before[0], before[2], before[1]], *after);
+        }
+    }
+}
@@ -1,23 +1,36 @@
 use std::{collections::HashMap, sync::{RwLock, Arc}, cell::RefCell, mem::swap};
 
 use anyhow::Ok;
-use nalgebra::Vector3;
+use nalgebra::{Vector3, Matrix4, Rotation3, Isometry3};
 use nalgebra::Point3;
-use parry3d::query::Ray;
+use parry3d::{query::{self, Ray}, shape::Triangle, bounding_volume::{Aabb, BoundingVolume}};
 
-use crate::{resources::resources, helper::{self, change_tracker::ChangeTracker, math::{approx_zero, self}}, state::{helper::render_item::RenderItemOption, scene::components::component::Component}, input::input_manager::InputManager, component_downcast, component_downcast_mut};
+use crate::{resources::resources, helper::{self, change_tracker::ChangeTracker, math::{approx_zero, self}}, state::{helper::render_item::RenderItemOption, scene::components::component::{Component, ComponentItem}}, input::input_manager::InputManager, component_downcast, component_downcast_mut};
 
-use super::{manager::id_manager::IdManager, node::{NodeItem, Node}, camera::{CameraItem, Camera}, loader::wavefront, loader::gltf, texture::{TextureItem, Texture}, components::{material::{MaterialItem, Material, TextureType, TextureState}, mesh::Mesh}, light::{LightItem, Light}};
+use super::{manager::id_manager::IdManager, node::{NodeItem, Node}, camera::{CameraItem, Camera}, loader::wavefront, loader::gltf, texture::{TextureItem, Texture}, components::{material::{MaterialItem, Material, TextureType, TextureState}, mesh::Mesh, transformation::Transformation, billboard::Billboard, script::Script, lod::Lod}, light::{LightItem, Light}, events::{SceneEvent, MAX_SCENE_EVENTS}};
 
 pub type SceneItem = Box<Scene>;
 
+pub const DEFAULT_UP_AXIS: Vector3<f32> = Vector3::<f32>::new(0.0, 1.0, 0.0);
+pub const DEFAULT_GRAVITY: f32 = 9.81;
+
+// distance, position, normal, node, instance id, face id
+pub type ScenePickRes = (f32, Point3<f32>, Option<Vector3<f32>>, NodeItem, u64, Option<u32>);
+
 
 pub struct SceneData
 {
     pub max_lights: u32,
     pub environment_texture: Option<TextureState>,
+    pub environment_path: Option<String>, // source of the environment texture (to reload it)
+    pub environment_intensity: f32,
+    pub environment_irradiance: Option<TextureItem>, // image based lighting (precomputed from the environment texture)
+    pub environment_brdf_lut: Option<TextureItem>,
     pub gamma: Option<f32>,
-    pub exposure: Option<f32>
+    pub exposure: Option<f32>,
+
+    pub up_axis: Vector3<f32>, // world up direction (y-up by default - z-up for some imports)
+    pub gravity: Vector3<f32>, // acceleration in world units per second² (used by the walk mode of the fly controller)
 }
 
 pub struct Scene
@@ -36,6 +49,8 @@ pub struct Scene
     pub textures: HashMap<String, TextureItem>,
     pub materials: HashMap<u64, MaterialItem>,
 
+    events: Vec<SceneEvent>,
+
     pub render_item: RenderItemOption,
     pub lights_render_item: RenderItemOption,
 }
@@ -56,8 +71,15 @@ impl Scene
             {
                 max_lights: 10,
                 environment_texture: None,
+                environment_path: None,
+                environment_intensity: 1.0,
+                environment_irradiance: None,
+                environment_brdf_lut: None,
                 gamma: None,
                 exposure: None,
+
+                up_axis: DEFAULT_UP_AXIS,
+                gravity: DEFAULT_UP_AXIS * -DEFAULT_GRAVITY,
             }),
 
             nodes: vec![],
@@ -66,6 +88,8 @@ impl Scene
             textures: HashMap::new(),
             materials: HashMap::new(),
 
+            events: vec![],
+
             render_item: None,
             lights_render_item: None,
         }
@@ -81,6 +105,62 @@ impl Scene
         &mut self.data
     }
 
+    pub fn push_event(&mut self, event: SceneEvent)
+    {
+        if self.events.len() >= MAX_SCENE_EVENTS
+        {
+            self.events.remove(0);
+        }
+
+        self.events.push(event);
+    }
+
+    // returns all events since the last call
+    pub fn drain_events(&mut self) -> Vec<SceneEvent>
+    {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn get_up_axis(&self) -> Vector3<f32>
+    {
+        let up_axis = self.get_data().up_axis;
+
+        if approx_zero(up_axis.magnitude())
+        {
+            return DEFAULT_UP_AXIS;
+        }
+
+        up_axis.normalize()
+    }
+
+    // rotation from the y-up space into the up space of the scene
+    pub fn get_up_rotation(&self) -> Rotation3<f32>
+    {
+        let up_axis = self.get_up_axis();
+
+        Rotation3::<f32>::rotation_between(&DEFAULT_UP_AXIS, &up_axis).unwrap_or_else(|| Rotation3::<f32>::from_axis_angle(&Vector3::x_axis(), std::f32::consts::PI))
+    }
+
+    // gravity is kept pointing downwards (with the same strength) and the camera up vectors are adjusted
+    pub fn set_up_axis(&mut self, up_axis: Vector3<f32>)
+    {
+        if approx_zero(up_axis.magnitude())
+        {
+            return;
+        }
+
+        let up_axis = up_axis.normalize();
+
+        let data = self.get_data_mut().get_mut();
+        data.gravity = up_axis * -data.gravity.magnitude();
+        data.up_axis = up_axis;
+
+        for cam in &mut self.cameras
+        {
+            cam.get_data_mut().get_mut().up = up_axis;
+        }
+    }
+
     /*
     pub fn load(&mut self, path: &str, create_mipmaps: bool) -> anyhow::Result<Vec<u64>>
     {
@@ -117,14 +197,40 @@ impl Scene
     }
      */
 
-    pub fn update(&mut self, input_manager: &mut InputManager, frame_scale: f32)
+    pub fn update_nodes(&mut self, input_manager: &mut InputManager, frame_scale: f32)
     {
-        // update nodes
         for node in &self.nodes
         {
             Node::update(node.clone(), input_manager, frame_scale);
         }
 
+        self.update_scripts(input_manager, frame_scale);
+    }
+
+    // scripts have access to the whole scene - so they are executed after the node update
+    pub fn update_scripts(&mut self, input_manager: &mut InputManager, frame_scale: f32)
+    {
+        for node in Scene::list_all_child_nodes(&self.nodes)
+        {
+            let scripts = node.read().unwrap().find_components::<Script>();
+
+            for script in scripts
+            {
+                let mut script = script.write().unwrap();
+
+                if !script.is_enabled()
+                {
+                    continue;
+                }
+
+                let script = script.as_any_mut().downcast_mut::<Script>().unwrap();
+                script.run(node.clone(), self, input_manager, frame_scale);
+            }
+        }
+    }
+
+    pub fn update_cameras(&mut self, input_manager: &mut InputManager, frame_scale: f32)
+    {
         let mut cameras = vec![];
         swap(&mut self.cameras, &mut cameras);
         for cam in &mut cameras
@@ -135,6 +241,52 @@ impl Scene
         swap(&mut cameras, &mut self.cameras);
     }
 
+    // billboards are facing the first active camera (after the camera movement)
+    pub fn update_billboards(&mut self)
+    {
+        let camera = self.cameras.iter().find(|camera| camera.enabled);
+        if camera.is_none()
+        {
+            return;
+        }
+
+        let camera_pos = camera.unwrap().get_data().eye_pos;
+
+        for node in Self::list_all_child_nodes(&self.nodes)
+        {
+            let billboard = node.read().unwrap().find_component::<Billboard>();
+
+            if let Some(billboard) = billboard
+            {
+                component_downcast!(billboard, Billboard);
+
+                if billboard.get_base().is_enabled
+                {
+                    billboard.apply(node.clone(), &camera_pos);
+                }
+            }
+        }
+    }
+
+    // LOD levels requested in the component ui (the meshes need ids of the scene)
+    pub fn update_lods(&mut self)
+    {
+        for node in Self::list_all_child_nodes(&self.nodes)
+        {
+            let lod = node.read().unwrap().find_component::<Lod>();
+
+            if let Some(lod) = lod
+            {
+                component_downcast_mut!(lod, Lod);
+
+                if lod.generate_requested()
+                {
+                    lod.generate_level(node.clone(), self.id_manager.get_next_component_id());
+                }
+            }
+        }
+    }
+
     pub fn print(&self)
     {
         println!(" - (SCENE) id={} name={} nodes={} cameras={} lights={} materials={} textures={}", self.id, self.name, self.nodes.len(), self.cameras.len(), self.lights.get_ref().len(), self.materials.len(), self.textures.len());
@@ -165,11 +317,236 @@ impl Scene
         self.nodes.push(node);
     }
 
+    // creates a node with a default instance (the default material is used for rendering)
+    pub fn add_mesh_node(&mut self, name: &str, mesh: Mesh) -> NodeItem
+    {
+        let node_arc = Node::new(self.id_manager.get_next_node_id(), name);
+
+        {
+            let mut node = node_arc.write().unwrap();
+            node.add_component(Arc::new(RwLock::new(Box::new(mesh))));
+
+            let instance_id = self.id_manager.get_next_instance_id();
+            node.create_default_instance(node_arc.clone(), instance_id);
+        }
+
+        self.add_node(node_arc.clone());
+
+        node_arc
+    }
+
+    // combines the meshes of the node and all descendants into world space meshes of a new node (the source node is hidden)
+    // meshes are grouped by material (one child node per material) - or merged into one mesh if a material is given
+    pub fn merge_meshes(&mut self, node: NodeItem, material: Option<MaterialItem>) -> Option<NodeItem>
+    {
+        let mut nodes = vec![node.clone()];
+        nodes.extend(Scene::list_all_child_nodes(&node.read().unwrap().nodes));
+
+        // material id (None: default material), material, merged mesh
+        let mut groups: Vec<(Option<u64>, Option<MaterialItem>, Mesh)> = vec![];
+
+        for node in &nodes
+        {
+            let node = node.read().unwrap();
+
+            if !node.is_visible()
+            {
+                continue;
+            }
+
+            let node_material = if material.is_some() { material.clone() } else { node.find_component::<Material>() };
+            let material_id = node_material.as_ref().map(|material| material.read().unwrap().id());
+
+            for mesh in node.get_meshes()
+            {
+                component_downcast!(mesh, Mesh);
+
+                if !mesh.get_base().is_enabled
+                {
+                    continue;
+                }
+
+                let group_index = match groups.iter().position(|(id, _, _)| *id == material_id)
+                {
+                    Some(index) => index,
+                    None =>
+                    {
+                        groups.push((material_id, node_material.clone(), Mesh::empty(self.id_manager.get_next_component_id(), "mesh")));
+                        groups.len() - 1
+                    }
+                };
+
+                // each instance is a copy of the mesh in world space
+                for instance in node.instances.get_ref()
+                {
+                    let instance = instance.read().unwrap();
+
+                    if !instance.get_data().visible
+                    {
+                        continue;
+                    }
+
+                    groups[group_index].2.append(mesh.get_data(), &instance.get_transform());
+                }
+            }
+        }
+
+        groups.retain(|(_, _, mesh)| !mesh.get_data().indices.is_empty());
+
+        for (_, _, mesh) in &mut groups
+        {
+            mesh.rebuild();
+        }
+
+        if groups.is_empty()
+        {
+            println!("nothing to merge -> no visible meshes");
+            return None;
+        }
+
+        let name = format!("{} merged", node.read().unwrap().name);
+        let merged_node = Node::new(self.id_manager.get_next_node_id(), name.as_str());
+
+        let single_group = groups.len() == 1;
+
+        for (_, material, mesh) in groups
+        {
+            let target_node = if single_group
+            {
+                merged_node.clone()
+            }
+            else
+            {
+                let material_name = material.as_ref().map_or("default".to_string(), |material| material.read().unwrap().get_base().name.clone());
+                let child_node = Node::new(self.id_manager.get_next_node_id(), format!("{} ({})", name, material_name).as_str());
+                Node::add_node(merged_node.clone(), child_node.clone());
+
+                child_node
+            };
+
+            let mut target = target_node.write().unwrap();
+            target.add_component(Arc::new(RwLock::new(Box::new(mesh))));
+
+            if let Some(material) = material
+            {
+                target.add_component(material);
+            }
+
+            target.create_default_instance(target_node.clone(), self.id_manager.get_next_instance_id());
+        }
+
+        node.write().unwrap().visible = false;
+        self.add_node(merged_node.clone());
+
+        Some(merged_node)
+    }
+
+    // splits the mesh of the node into its connected parts - one new node per part (next to the source node which is hidden)
+    // the transformation and the material are taken over
+    pub fn split_connected_components(&mut self, node: NodeItem) -> Vec<NodeItem>
+    {
+        let mut parts = vec![];
+
+        let name;
+        let parent;
+        let material;
+        let transform;
+        {
+            let node = node.read().unwrap();
+            name = node.name.clone();
+            parent = node.parent.clone();
+            material = node.find_component::<Material>();
+            transform = node.get_transform();
+
+            let mesh = node.find_component::<Mesh>();
+            if mesh.is_none()
+            {
+                println!("can not split node -> node has no mesh");
+                return parts;
+            }
+
+            let mesh = mesh.unwrap();
+            component_downcast!(mesh, Mesh);
+
+            let components = mesh.get_connected_components();
+            if components.len() <= 1
+            {
+                println!("nothing to split -> mesh has only one connected part");
+                return parts;
+            }
+
+            for (i, faces) in components.iter().enumerate()
+            {
+                let part_mesh = mesh.extract_faces(self.id_manager.get_next_component_id(), "mesh", faces);
+                parts.push((format!("{} part {}", name, i), part_mesh));
+            }
+        }
+
+        let mut nodes = vec![];
+
+        for (part_name, part_mesh) in parts
+        {
+            let part_node = Node::new(self.id_manager.get_next_node_id(), part_name.as_str());
+
+            {
+                let mut part = part_node.write().unwrap();
+                part.add_component(Arc::new(RwLock::new(Box::new(part_mesh))));
+
+                let mut part_transform = Transformation::identity(self.id_manager.get_next_component_id(), "Transform");
+                part_transform.get_data_mut().get_mut().parent_inheritance = transform.1;
+                part_transform.set_transform(transform.0);
+                part.add_component(Arc::new(RwLock::new(Box::new(part_transform))));
+
+                if let Some(material) = &material
+                {
+                    part.add_component(material.clone());
+                }
+
+                part.create_default_instance(part_node.clone(), self.id_manager.get_next_instance_id());
+            }
+
+            if let Some(parent) = &parent
+            {
+                Node::add_node(parent.clone(), part_node.clone());
+            }
+            else
+            {
+                self.add_node(part_node.clone());
+            }
+
+            nodes.push(part_node);
+        }
+
+        node.write().unwrap().visible = false;
+
+        nodes
+    }
+
     pub fn clear_nodes(&mut self)
     {
         self.nodes.clear();
     }
 
+    // world space min/max of all nodes
+    pub fn get_bounding_info(&self) -> Option<(Point3<f32>, Point3<f32>)>
+    {
+        let mut bounding_info: Option<(Point3<f32>, Point3<f32>)> = None;
+
+        for node in &self.nodes
+        {
+            if let Some((node_min, node_max)) = node.read().unwrap().get_bounding_info(true)
+            {
+                bounding_info = match bounding_info
+                {
+                    Some((min, max)) => Some((min.inf(&node_min), max.sup(&node_max))),
+                    None => Some((node_min, node_max)),
+                };
+            }
+        }
+
+        bounding_info
+    }
+
     pub async fn load_texture_or_reuse_async(&mut self, path: &str, extension: Option<String>) -> anyhow::Result<TextureItem>
     {
         let image_bytes = resources::load_binary_async(path).await?;
@@ -319,7 +696,8 @@ impl Scene
         let node = node.read().unwrap();
         let mut material = node.find_component::<Material>();
 
-        if material.is_none()
+        // disabled materials are handled like no material (same as in rendering)
+        if material.is_none() || !material.as_ref().unwrap().read().unwrap().is_enabled()
         {
             material = self.get_default_material();
         }
@@ -391,70 +769,203 @@ impl Scene
         self.textures.len() != len
     }
 
-    pub fn get_camera_by_id(&self, id: u64) -> Option<&CameraItem>
+    // materials with identical settings and textures are replaced by the one with the lowest id
+    pub fn merge_duplicate_materials(&mut self) -> usize
     {
-        self.cameras.iter().find(|cam|{ cam.id == id })
-    }
+        let mut ids: Vec<u64> = self.materials.keys().cloned().collect();
+        ids.sort();
 
-    pub fn get_camera_by_id_mut(&mut self, id: u64) -> Option<&mut CameraItem>
-    {
-        self.cameras.iter_mut().find(|cam|{ cam.id == id })
-    }
+        let mut unique: Vec<MaterialItem> = vec![];
+        let mut duplicates: Vec<(u64, MaterialItem)> = vec![]; // duplicate id, material to use instead
 
-    pub fn delete_camera_by_id(&mut self, id: u64) -> bool
-    {
-        let len = self.cameras.len();
-        self.cameras.retain(|camera|
+        for id in ids
         {
-            camera.id != id
-        });
+            let material_arc = self.materials.get(&id).unwrap().clone();
 
-        self.cameras.len() != len
-    }
+            if material_arc.read().unwrap().get_base().name == "default"
+            {
+                continue;
+            }
 
-    pub fn add_camera(&mut self, name: &str) -> &CameraItem
-    {
-        let cam = Camera::new(self.id_manager.get_next_camera_id(), name.to_string());
-        self.cameras.push(Box::new(cam));
+            let existing = unique.iter().find(|unique_material|
+            {
+                let material = material_arc.clone();
+                let unique_material = (*unique_material).clone();
 
-        self.cameras.last().unwrap()
-    }
+                component_downcast!(material, Material);
+                component_downcast!(unique_material, Material);
 
-    //pub fn get_active_camera() -> Option<&'static CameraItem>
-    pub fn get_active_camera(&self) -> Option<&CameraItem>
-    {
-        for camera in &self.cameras
-        {
-            if camera.enabled
+                material.is_identical(unique_material)
+            });
+
+            if let Some(existing) = existing
             {
-                return Some(camera);
+                duplicates.push((id, existing.clone()));
+            }
+            else
+            {
+                unique.push(material_arc);
             }
         }
-        None
-    }
 
-    pub fn get_light_by_id(&self, id: u64) -> Option<&RefCell<ChangeTracker<Box<Light>>>>
-    {
-        let lights = self.lights.get_ref();
-        lights.iter().find(|light|{ light.borrow().get_ref().id == id })
-    }
+        if duplicates.is_empty()
+        {
+            return 0;
+        }
 
-    pub fn delete_light_by_id(&mut self, id: u64) -> bool
-    {
-        // only mark as changed if there was a change
-        let lights = self.lights.get_unmarked_mut();
+        // use the remaining material in all nodes and instances
+        let all_nodes = Self::list_all_child_nodes(&self.nodes);
 
-        let len = lights.len();
-        lights.retain(|light|
+        for node in all_nodes
         {
-            light.borrow().get_ref().id != id
-        });
+            let mut node = node.write().unwrap();
 
-        if lights.len() != len
-        {
-            // only mark as changed if there was a change
-            self.lights.force_change();
-            return true;
+            for (id, material) in &duplicates
+            {
+                node.replace_component_by_id(*id, material);
+
+                for instance in node.instances.get_ref()
+                {
+                    if instance.read().unwrap().find_component_by_id(*id).is_some()
+                    {
+                        instance.write().unwrap().replace_component_by_id(*id, material);
+                    }
+                }
+            }
+        }
+
+        for (id, _) in &duplicates
+        {
+            self.materials.remove(id);
+        }
+
+        duplicates.len()
+    }
+
+    // textures with the same content and sampling settings are replaced by the one with the lowest id
+    pub fn merge_duplicate_textures(&mut self) -> usize
+    {
+        let mut textures: Vec<(String, TextureItem)> = self.textures.iter().map(|(key, texture)| (key.clone(), texture.clone())).collect();
+        textures.sort_by_key(|(_, texture)| texture.read().unwrap().id);
+
+        let mut unique: HashMap<String, TextureItem> = HashMap::new(); // content hash, texture
+        let mut duplicates: Vec<(String, u64, TextureItem)> = vec![]; // duplicate key, duplicate id, texture to use instead
+
+        for (key, texture) in textures
+        {
+            let (id, content_hash) =
+            {
+                let texture = texture.read().unwrap();
+                (texture.id, texture.content_hash())
+            };
+
+            if let Some(existing) = unique.get(&content_hash)
+            {
+                duplicates.push((key, id, existing.clone()));
+            }
+            else
+            {
+                unique.insert(content_hash, texture);
+            }
+        }
+
+        if duplicates.is_empty()
+        {
+            return 0;
+        }
+
+        for material in self.materials.values()
+        {
+            let material = material.clone();
+            component_downcast_mut!(material, Material);
+
+            for (_, id, texture) in &duplicates
+            {
+                material.replace_texture_by_id(*id, texture);
+            }
+        }
+
+        let env_texture_id = self.get_data().environment_texture.as_ref().map(|env_texture| env_texture.item.read().unwrap().id);
+        if let Some(env_texture_id) = env_texture_id
+        {
+            if let Some((_, _, texture)) = duplicates.iter().find(|(_, id, _)| *id == env_texture_id)
+            {
+                self.get_data_mut().get_mut().environment_texture.as_mut().unwrap().item = texture.clone();
+            }
+        }
+
+        for (key, _, _) in &duplicates
+        {
+            self.textures.remove(key);
+        }
+
+        duplicates.len()
+    }
+
+    pub fn get_camera_by_id(&self, id: u64) -> Option<&CameraItem>
+    {
+        self.cameras.iter().find(|cam|{ cam.id == id })
+    }
+
+    pub fn get_camera_by_id_mut(&mut self, id: u64) -> Option<&mut CameraItem>
+    {
+        self.cameras.iter_mut().find(|cam|{ cam.id == id })
+    }
+
+    pub fn delete_camera_by_id(&mut self, id: u64) -> bool
+    {
+        let len = self.cameras.len();
+        self.cameras.retain(|camera|
+        {
+            camera.id != id
+        });
+
+        self.cameras.len() != len
+    }
+
+    pub fn add_camera(&mut self, name: &str) -> &CameraItem
+    {
+        let cam = Camera::new(self.id_manager.get_next_camera_id(), name.to_string());
+        self.cameras.push(Box::new(cam));
+
+        self.cameras.last().unwrap()
+    }
+
+    //pub fn get_active_camera() -> Option<&'static CameraItem>
+    pub fn get_active_camera(&self) -> Option<&CameraItem>
+    {
+        for camera in &self.cameras
+        {
+            if camera.enabled
+            {
+                return Some(camera);
+            }
+        }
+        None
+    }
+
+    pub fn get_light_by_id(&self, id: u64) -> Option<&RefCell<ChangeTracker<Box<Light>>>>
+    {
+        let lights = self.lights.get_ref();
+        lights.iter().find(|light|{ light.borrow().get_ref().id == id })
+    }
+
+    pub fn delete_light_by_id(&mut self, id: u64) -> bool
+    {
+        // only mark as changed if there was a change
+        let lights = self.lights.get_unmarked_mut();
+
+        let len = lights.len();
+        lights.retain(|light|
+        {
+            light.borrow().get_ref().id != id
+        });
+
+        if lights.len() != len
+        {
+            // only mark as changed if there was a change
+            self.lights.force_change();
+            return true;
         }
 
         false
@@ -562,11 +1073,150 @@ impl Scene
         Self::_find_node_by_id(&self.nodes, id)
     }
 
+    // returns the first match (depth first) - names are not unique (see find_nodes_by_name)
     pub fn find_node_by_name(&self, name: &str) -> Option<NodeItem>
     {
         Self::_find_node_by_name(&self.nodes, name.to_string())
     }
 
+    // all nodes with the name or the original name of the loaded file
+    pub fn find_nodes_by_name(&self, name: &str) -> Vec<NodeItem>
+    {
+        let all_nodes = Self::list_all_child_nodes(&self.nodes);
+
+        all_nodes.into_iter().filter(|node|
+        {
+            let node = node.read().unwrap();
+            node.name == name || node.source_name.as_deref() == Some(name)
+        }).collect()
+    }
+
+    pub fn find_nodes_by_tag(&self, tag: &str) -> Vec<NodeItem>
+    {
+        let all_nodes = Self::list_all_child_nodes(&self.nodes);
+
+        all_nodes.into_iter().filter(|node| node.read().unwrap().has_tag(tag)).collect()
+    }
+
+    // marker nodes of the kind (empty kind: all markers) - use get_world_position for spawn positions
+    pub fn find_markers(&self, kind: &str) -> Vec<NodeItem>
+    {
+        let all_nodes = Self::list_all_child_nodes(&self.nodes);
+
+        all_nodes.into_iter().filter(|node|
+        {
+            let node = node.read().unwrap();
+            node.marker.as_ref().map_or(false, |marker| kind.is_empty() || marker == kind)
+        }).collect()
+    }
+
+    // moves a node to another parent (None = scene root) while keeping its world transformation
+    // index: position in the new parent's node list (None = append)
+    pub fn reparent_node(&mut self, node_id: u64, parent_id: Option<u64>, index: Option<usize>) -> bool
+    {
+        let node_arc = self.find_node_by_id(node_id);
+        if node_arc.is_none()
+        {
+            return false;
+        }
+        let node_arc = node_arc.unwrap();
+
+        let mut new_parent = None;
+        if let Some(parent_id) = parent_id
+        {
+            // the node can not be moved into itself or one of its descendants
+            if parent_id == node_id || Self::_find_node_by_id(&node_arc.read().unwrap().nodes, parent_id).is_some()
+            {
+                return false;
+            }
+
+            new_parent = self.find_node_by_id(parent_id);
+
+            if new_parent.is_none()
+            {
+                return false;
+            }
+        }
+
+        let world_trans = node_arc.read().unwrap().get_full_transform();
+        let old_parent = node_arc.read().unwrap().parent.clone();
+
+        // ********** detach **********
+        let old_index;
+        if let Some(old_parent) = &old_parent
+        {
+            let mut old_parent = old_parent.write().unwrap();
+            old_index = old_parent.nodes.iter().position(|node| node.read().unwrap().id == node_id);
+            old_parent.nodes.retain(|node| node.read().unwrap().id != node_id);
+        }
+        else
+        {
+            old_index = self.nodes.iter().position(|node| node.read().unwrap().id == node_id);
+            self.nodes.retain(|node| node.read().unwrap().id != node_id);
+        }
+
+        // ********** attach **********
+        let old_parent_id = old_parent.as_ref().map(|parent| parent.read().unwrap().id);
+        let mut index = index;
+
+        // the index is based on the list before the node was removed
+        if let (Some(new_index), Some(old_index)) = (index, old_index)
+        {
+            if old_parent_id == parent_id && old_index < new_index
+            {
+                index = Some(new_index - 1);
+            }
+        }
+
+        if let Some(new_parent) = &new_parent
+        {
+            let mut new_parent = new_parent.write().unwrap();
+            let index = index.unwrap_or(new_parent.nodes.len()).min(new_parent.nodes.len());
+            new_parent.nodes.insert(index, node_arc.clone());
+        }
+        else
+        {
+            let index = index.unwrap_or(self.nodes.len()).min(self.nodes.len());
+            self.nodes.insert(index, node_arc.clone());
+        }
+
+        node_arc.write().unwrap().parent = new_parent.clone();
+
+        // ********** keep world transformation **********
+        let parent_trans = match &new_parent
+        {
+            Some(new_parent) => new_parent.read().unwrap().get_full_transform(),
+            None => Matrix4::<f32>::identity(),
+        };
+
+        let local_trans = parent_trans.try_inverse().unwrap_or(Matrix4::<f32>::identity()) * world_trans;
+
+        let transform_component = node_arc.read().unwrap().find_component::<Transformation>();
+        if let Some(transform_component) = transform_component
+        {
+            component_downcast_mut!(transform_component, Transformation);
+
+            if transform_component.has_parent_inheritance()
+            {
+                transform_component.set_transform_matrix(local_trans);
+            }
+            else
+            {
+                // world transformation does not change - but the instances needs to be updated
+                transform_component.get_data_mut().force_change();
+            }
+        }
+        else
+        {
+            let mut transform_component = Transformation::identity(self.id_manager.get_next_component_id(), "Transform");
+            transform_component.set_transform_matrix(local_trans);
+
+            node_arc.write().unwrap().add_component(Arc::new(RwLock::new(Box::new(transform_component))));
+        }
+
+        true
+    }
+
     pub fn delete_node_by_id(&mut self, id: u64) -> bool
     {
         // check camera targets and remove
@@ -640,7 +1290,7 @@ impl Scene
             let node = node_arc.read().unwrap();
 
             // early "return" check
-            if !node.visible
+            if !node.is_visible()
             {
                 continue;
             }
@@ -777,6 +1427,280 @@ impl Scene
         best_hit
     }
 
+    pub fn raycast(&self, origin: Point3<f32>, dir: Vector3<f32>, max_distance: f32, predicate: impl Fn(&NodeItem, u64) -> bool) -> Option<ScenePickRes>
+    {
+        self.raycast_all(origin, dir, max_distance, predicate).into_iter().next()
+    }
+
+    // returns the nearest hit of each mesh instance (sorted by distance)
+    // the predicate is called with the node (read locked) and the instance id
+    pub fn raycast_all(&self, origin: Point3<f32>, dir: Vector3<f32>, max_distance: f32, predicate: impl Fn(&NodeItem, u64) -> bool) -> Vec<ScenePickRes>
+    {
+        let mut hits: Vec<ScenePickRes> = vec![];
+
+        if approx_zero(dir.magnitude())
+        {
+            return hits;
+        }
+
+        let ray = Ray::new(origin, dir.normalize());
+        let nodes = Scene::list_all_child_nodes_with_mesh(&self.nodes);
+
+        for node_arc in &nodes
+        {
+            let node = node_arc.read().unwrap();
+
+            if !node.is_visible()
+            {
+                continue;
+            }
+
+            let mesh = node.find_component::<Mesh>();
+
+            if mesh.is_none()
+            {
+                continue;
+            }
+
+            let mesh = mesh.unwrap();
+            component_downcast!(mesh, Mesh);
+
+            if !mesh.get_base().is_enabled
+            {
+                continue;
+            }
+
+            let material = self.get_material_or_default(node_arc.clone());
+            let material = material.unwrap();
+            component_downcast!(material, Material);
+            let material_data = material.get_data();
+
+            let solid = !material_data.backface_cullig;
+
+            for instance in node.instances.get_ref()
+            {
+                let instance = instance.read().unwrap();
+
+                if !instance.pickable || approx_zero(instance.get_alpha())
+                {
+                    continue;
+                }
+
+                if !predicate(node_arc, instance.id)
+                {
+                    continue;
+                }
+
+                let transform = instance.get_transform();
+                let transform_inverse = transform.try_inverse();
+
+                // zero scaled instances can not be hit
+                if transform_inverse.is_none()
+                {
+                    continue;
+                }
+
+                let transform_inverse = transform_inverse.unwrap();
+                let ray_inverse = math::inverse_ray(&ray, &transform_inverse);
+
+                // bounding box check first
+                let b_box_dist = mesh.intersect_b_box(&ray_inverse, true);
+                if b_box_dist.is_none() || b_box_dist.unwrap() > max_distance
+                {
+                    continue;
+                }
+
+                let intersection = mesh.intersect(&ray, &ray_inverse, &transform, &transform_inverse, solid, material_data.smooth_shading);
+
+                if let Some(intersection) = intersection
+                {
+                    if intersection.0 <= max_distance
+                    {
+                        let pos = ray.origin + (ray.dir * intersection.0);
+                        hits.push((intersection.0, pos, Some(intersection.1), node_arc.clone(), instance.id, Some(intersection.2)));
+                    }
+                }
+            }
+        }
+
+        hits.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        hits
+    }
+
+    // world bounding boxes of all collidable mesh instances of the node (and its children): (aabb, transform, mesh)
+    fn get_collision_items(node: &NodeItem, recursive: bool) -> Vec<(Aabb, Matrix4<f32>, ComponentItem)>
+    {
+        let mut items = vec![];
+
+        let mut nodes = vec![node.clone()];
+        if recursive
+        {
+            nodes.extend(Scene::list_all_child_nodes(&node.read().unwrap().nodes));
+        }
+
+        for node in nodes
+        {
+            let node = node.read().unwrap();
+
+            if !node.is_visible()
+            {
+                continue;
+            }
+
+            for mesh_item in node.get_meshes()
+            {
+                let b_box;
+                {
+                    component_downcast!(mesh_item, Mesh);
+
+                    if !mesh_item.get_base().is_enabled
+                    {
+                        continue;
+                    }
+
+                    b_box = mesh_item.get_data().b_box;
+                }
+
+                for instance in node.instances.get_ref()
+                {
+                    let instance = instance.read().unwrap();
+
+                    if !instance.get_data().collision || approx_zero(instance.get_alpha())
+                    {
+                        continue;
+                    }
+
+                    let transform = instance.get_transform();
+                    items.push((math::transform_aabb(&b_box, &transform), transform, mesh_item.clone()));
+                }
+            }
+        }
+
+        items
+    }
+
+    // world space triangles of the mesh which are touching the area
+    fn get_triangles_in_aabb(mesh: &ComponentItem, transform: &Matrix4<f32>, area: &Aabb) -> Vec<Triangle>
+    {
+        let mut triangles = vec![];
+
+        component_downcast!(mesh, Mesh);
+        let mesh_data = mesh.get_data();
+
+        for face in &mesh_data.indices
+        {
+            let triangle = Triangle::new
+            (
+                transform.transform_point(&mesh_data.vertices[face[0] as usize]),
+                transform.transform_point(&mesh_data.vertices[face[1] as usize]),
+                transform.transform_point(&mesh_data.vertices[face[2] as usize]),
+            );
+
+            if triangle.local_aabb().intersects(area)
+            {
+                triangles.push(triangle);
+            }
+        }
+
+        triangles
+    }
+
+    // overlap test based on the world bounding boxes of the collidable instances (instance collision flag)
+    // with triangle_test: the mesh surfaces must intersect (a mesh completely inside of the other one is not detected)
+    pub fn nodes_overlap(a: &NodeItem, b: &NodeItem, triangle_test: bool) -> bool
+    {
+        let items_a = Self::get_collision_items(a, true);
+        let items_b = Self::get_collision_items(b, true);
+
+        Self::collision_items_overlap(&items_a, &items_b, triangle_test)
+    }
+
+    fn collision_items_overlap(items_a: &Vec<(Aabb, Matrix4<f32>, ComponentItem)>, items_b: &Vec<(Aabb, Matrix4<f32>, ComponentItem)>, triangle_test: bool) -> bool
+    {
+        let identity = Isometry3::<f32>::identity();
+
+        for (aabb_a, transform_a, mesh_a) in items_a
+        {
+            for (aabb_b, transform_b, mesh_b) in items_b
+            {
+                if !aabb_a.intersects(aabb_b)
+                {
+                    continue;
+                }
+
+                if !triangle_test
+                {
+                    return true;
+                }
+
+                // only triangles in the overlapping area are relevant
+                let area = aabb_a.intersection(aabb_b).unwrap_or(*aabb_a);
+
+                let triangles_a = Self::get_triangles_in_aabb(mesh_a, transform_a, &area);
+                if triangles_a.is_empty()
+                {
+                    continue;
+                }
+
+                let triangles_b = Self::get_triangles_in_aabb(mesh_b, transform_b, &area);
+
+                for triangle_a in &triangles_a
+                {
+                    let triangle_a_aabb = triangle_a.local_aabb();
+
+                    for triangle_b in &triangles_b
+                    {
+                        if !triangle_a_aabb.intersects(&triangle_b.local_aabb())
+                        {
+                            continue;
+                        }
+
+                        if query::intersection_test(&identity, triangle_a, &identity, triangle_b).unwrap_or(false)
+                        {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    // all nodes (with a mesh) overlapping with the node - the node itself and its children are excluded
+    pub fn overlapping(&self, node: &NodeItem, triangle_test: bool) -> Vec<NodeItem>
+    {
+        let mut res = vec![];
+
+        let items = Self::get_collision_items(node, true);
+        if items.is_empty()
+        {
+            return res;
+        }
+
+        let mut excluded = vec![node.read().unwrap().id];
+        excluded.extend(Scene::list_all_child_nodes(&node.read().unwrap().nodes).iter().map(|child| child.read().unwrap().id));
+
+        for other in Scene::list_all_child_nodes_with_mesh(&self.nodes)
+        {
+            if excluded.contains(&other.read().unwrap().id)
+            {
+                continue;
+            }
+
+            // children are checked separately
+            let other_items = Self::get_collision_items(&other, false);
+
+            if Self::collision_items_overlap(&items, &other_items, triangle_test)
+            {
+                res.push(other);
+            }
+        }
+
+        res
+    }
+
     pub fn ui(&mut self, ui: &mut egui::Ui)
     {
         ui.horizontal(|ui|
@@ -790,6 +1714,8 @@ impl Scene
         let mut max_lights = self.get_data().max_lights;
         let mut gamma = if let Some(gamma_val) = self.get_data().gamma { gamma_val } else { 0.0 };
         let mut exposure = if let Some(exposure_val) = self.get_data().exposure { exposure_val } else { 0.0 };
+        let mut up_axis = self.get_up_axis();
+        let mut gravity = self.get_data().gravity;
 
         ui.horizontal(|ui|
         {
@@ -842,5 +1768,237 @@ impl Scene
                 }
             }
         });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Up axis:");
+
+            let mut changed = false;
+            changed = ui.selectable_value(&mut up_axis, Vector3::<f32>::y(), "Y").changed() || changed;
+            changed = ui.selectable_value(&mut up_axis, Vector3::<f32>::z(), "Z").changed() || changed;
+
+            if changed
+            {
+                self.set_up_axis(up_axis);
+            }
+        });
+
+        ui.horizontal(|ui|
+        {
+            ui.label("Gravity:");
+
+            let mut changed = false;
+            changed = ui.add(egui::DragValue::new(&mut gravity.x).speed(0.1).prefix("x: ")).changed() || changed;
+            changed = ui.add(egui::DragValue::new(&mut gravity.y).speed(0.1).prefix("y: ")).changed() || changed;
+            changed = ui.add(egui::DragValue::new(&mut gravity.z).speed(0.1).prefix("z: ")).changed() || changed;
+
+            if changed
+            {
+                self.get_data_mut().get_mut().gravity = gravity;
+            }
+        });
+    }
+}
+
+// shared test fixtures
+#[cfg(test)]
+pub mod test_utils
+{
+    use std::sync::{Arc, RwLock};
+
+    use nalgebra::Vector3;
+
+    use crate::{input::input_manager::InputManager, state::scene::{node::{Node, NodeItem}, components::mesh::Mesh}};
+
+    use super::Scene;
+
+    // scene with the default material (needed for raycasts)
+    pub fn create_scene(id: u64) -> Scene
+    {
+        let mut scene = Scene::new(id, "test");
+        scene.add_default_material();
+
+        scene
+    }
+
+    // node with a 2x2x2 box around its origin (not added to the scene)
+    pub fn create_box(scene: &mut Scene, name: &str) -> NodeItem
+    {
+        let mesh = Mesh::new_box(scene.id_manager.get_next_component_id(), "box", Vector3::<f32>::new(2.0, 2.0, 2.0));
+        let node_arc = Node::new(scene.id_manager.get_next_node_id(), name);
+
+        {
+            let mut node = node_arc.write().unwrap();
+            node.add_component(Arc::new(RwLock::new(Box::new(mesh))));
+
+            let instance_id = scene.id_manager.get_next_instance_id();
+            node.create_default_instance(node_arc.clone(), instance_id);
+        }
+
+        node_arc
+    }
+
+    pub fn add_box(scene: &mut Scene, name: &str) -> NodeItem
+    {
+        let node = create_box(scene, name);
+        scene.add_node(node.clone());
+
+        node
+    }
+
+    // computes the world matrices
+    pub fn update(scene: &mut Scene)
+    {
+        scene.update_nodes(&mut InputManager::new(), 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::{Arc, RwLock};
+
+    use nalgebra::{Point3, Vector3, Matrix4};
+
+    use crate::{helper::math::approx_equal, state::scene::{node::{Node, NodeItem}, components::{material::{Material, MaterialItem}, component::Component, transformation::Transformation}}};
+
+    use super::{Scene, test_utils::{create_scene as create_empty_scene, add_box, update}};
+
+    fn set_world_matrix(node: &NodeItem, world_matrix: Matrix4<f32>)
+    {
+        for instance in node.read().unwrap().instances.get_ref()
+        {
+            instance.write().unwrap().get_data_mut().get_mut().computed.world_matrix = world_matrix;
+        }
+    }
+
+    fn create_scene() -> (Scene, NodeItem, NodeItem)
+    {
+        let mut scene = create_empty_scene(0);
+
+        let near = add_box(&mut scene, "near");
+        let far = add_box(&mut scene, "far");
+
+        update(&mut scene);
+
+        set_world_matrix(&far, Matrix4::new_translation(&Vector3::<f32>::new(0.0, 0.0, -5.0)));
+
+        (scene, near, far)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn raycast_hits_box_at_distance()
+    {
+        let (scene, near, _far) = create_scene();
+
+        let hit = scene.raycast(Point3::<f32>::new(0.0, 0.0, 10.0), -Vector3::<f32>::z(), 100.0, |_, _| true).unwrap();
+
+        assert!(approx_equal(hit.0, 9.0));
+        assert!(approx_equal(hit.1.z, 1.0));
+        assert_eq!(hit.3.read().unwrap().id, near.read().unwrap().id);
+    }
+
+    #[test]
+    fn raycast_all_is_sorted_and_limited()
+    {
+        let (scene, near, far) = create_scene();
+        let origin = Point3::<f32>::new(0.0, 0.0, 10.0);
+
+        let hits = scene.raycast_all(origin, -Vector3::<f32>::z(), 100.0, |_, _| true);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].3.read().unwrap().id, near.read().unwrap().id);
+        assert_eq!(hits[1].3.read().unwrap().id, far.read().unwrap().id);
+        assert!(approx_equal(hits[1].0, 14.0));
+
+        // the far box is out of range
+        let hits = scene.raycast_all(origin, -Vector3::<f32>::z(), 10.0, |_, _| true);
+        assert_eq!(hits.len(), 1);
+
+        // the near box is filtered out
+        let near_id = near.read().unwrap().id;
+        let hit = scene.raycast(origin, -Vector3::<f32>::z(), 100.0, |node, _| node.read().unwrap().id != near_id).unwrap();
+        assert_eq!(hit.3.read().unwrap().id, far.read().unwrap().id);
+    }
+
+    #[test]
+    fn raycast_skips_zero_scaled_instances()
+    {
+        let (scene, near, far) = create_scene();
+
+        set_world_matrix(&near, Matrix4::new_scaling(0.0));
+
+        let hit = scene.raycast(Point3::<f32>::new(0.0, 0.0, 10.0), -Vector3::<f32>::z(), 100.0, |_, _| true).unwrap();
+        assert_eq!(hit.3.read().unwrap().id, far.read().unwrap().id);
+    }
+
+    #[test]
+    fn disabled_material_falls_back_to_default()
+    {
+        let mut scene = create_empty_scene(0);
+        let node = add_box(&mut scene, "box");
+
+        let material: MaterialItem = Arc::new(RwLock::new(Box::new(Material::new(scene.id_manager.get_next_component_id(), "red"))));
+        node.write().unwrap().add_component(material.clone());
+
+        let material_id = material.read().unwrap().id();
+        let default_id = scene.get_default_material().unwrap().read().unwrap().id();
+
+        assert_eq!(scene.get_material_or_default(node.clone()).unwrap().read().unwrap().id(), material_id);
+
+        material.write().unwrap().set_enabled(false);
+        assert_eq!(scene.get_material_or_default(node.clone()).unwrap().read().unwrap().id(), default_id);
+
+        material.write().unwrap().set_enabled(true);
+        assert_eq!(scene.get_material_or_default(node.clone()).unwrap().read().unwrap().id(), material_id);
+    }
+
+    fn add_transformation(scene: &mut Scene, node: &NodeItem, position: Vector3<f32>, rotation: Vector3<f32>, scale: Vector3<f32>)
+    {
+        let transform = Transformation::new(scene.id_manager.get_next_component_id(), "Transform", position, rotation, scale);
+        node.write().unwrap().add_component(Arc::new(RwLock::new(Box::new(transform))));
+    }
+
+    fn assert_same_matrix(a: &Matrix4<f32>, b: &Matrix4<f32>)
+    {
+        for (a, b) in a.iter().zip(b.iter())
+        {
+            assert!((a - b).abs() < 0.0001, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn reparent_node_keeps_world_positions()
+    {
+        let mut scene = create_empty_scene(0);
+
+        // non uniform scaled parent: the rotated child is sheared in parent space
+        let parent = Node::new(scene.id_manager.get_next_node_id(), "parent");
+        add_transformation(&mut scene, &parent, Vector3::<f32>::new(1.0, 2.0, 3.0), Vector3::<f32>::new(0.0, 0.0, 0.4), Vector3::<f32>::new(1.0, 3.0, 1.0));
+        scene.add_node(parent.clone());
+
+        let child = add_box(&mut scene, "child");
+        add_transformation(&mut scene, &child, Vector3::<f32>::new(0.0, 1.0, 0.0), Vector3::<f32>::new(0.3, 0.0, 0.5), Vector3::<f32>::new(1.0, 1.0, 1.0));
+
+        update(&mut scene);
+
+        let parent_id = parent.read().unwrap().id;
+        let child_id = child.read().unwrap().id;
+        let world = child.read().unwrap().get_full_transform();
+
+        // into the parent
+        assert!(scene.reparent_node(child_id, Some(parent_id), None));
+        update(&mut scene);
+
+        assert_eq!(child.read().unwrap().parent.as_ref().unwrap().read().unwrap().id, parent_id);
+        assert_same_matrix(&world, &child.read().unwrap().get_full_transform());
+        assert_same_matrix(&world, &child.read().unwrap().instances.get_ref()[0].read().unwrap().get_transform());
+
+        // back to the root
+        assert!(scene.reparent_node(child_id, None, None));
+        update(&mut scene);
+
+        assert!(child.read().unwrap().parent.is_none());
+        assert_same_matrix(&world, &child.read().unwrap().get_full_transform());
+        assert_same_matrix(&world, &child.read().unwrap().instances.get_ref()[0].read().unwrap().get_transform());
+    }
+}
@@ -1,9 +1,9 @@
 use std::{sync::{RwLock, Arc}, fmt::format};
 
-use image::{DynamicImage, GenericImageView, Pixel, ImageFormat, Rgba, ImageBuffer, imageops, RgbaImage, GrayImage};
+use image::{DynamicImage, GenericImageView, Pixel, ImageFormat, Rgba, ImageBuffer, imageops, RgbaImage, GrayImage, Rgba32FImage};
 use nalgebra::Vector4;
 
-use crate::{helper::{self, change_tracker::ChangeTracker}, state::helper::render_item::RenderItemOption};
+use crate::{helper::{self, change_tracker::ChangeTracker}, state::{helper::render_item::RenderItemOption, scene::components::material::TextureColorSpace}};
 
 pub type TextureItem = Arc<RwLock<Box<Texture>>>;
 
@@ -47,6 +47,9 @@ pub struct TextureData
     pub mipmapping: bool,
 
     pub mipmap_sampling_type: MipmapSamplingFilterType,
+    pub custom_mipmaps: Vec<DynamicImage>, // precomputed mipmaps (prefiltered env maps) - used instead of generated ones
+
+    pub cubemap: bool, // the six faces are stacked vertically in the image (+x, -x, +y, -y, +z, -z)
 
     pub has_transparency: bool, // if there is a pixel with a alpha value < 1.0
 
@@ -56,6 +59,7 @@ pub struct TextureData
     pub mag_filter: TextureFilterMode,
     pub min_filter: TextureFilterMode,
     pub mipmap_filter: TextureFilterMode,
+    pub anisotropy: Option<u8>, // overrides the global setting (only applied if all filters are linear)
 }
 
 pub struct Texture
@@ -65,6 +69,7 @@ pub struct Texture
     pub hash: String, // this is mainly used for initial loading and to check if there is a texture already loaded (in dynamic textires - this may does not get updates)
 
     pub data: ChangeTracker<TextureData>,
+    pub color_space: TextureColorSpace, // based on the material slots using the texture (see State::update_texture_color_spaces)
 
     pub egui_preview: Option<egui::TextureHandle>,
     pub render_item: RenderItemOption
@@ -85,6 +90,9 @@ impl Texture
             mipmapping: false,
 
             mipmap_sampling_type: MipmapSamplingFilterType::Triangle,
+            custom_mipmaps: vec![],
+
+            cubemap: false,
 
             has_transparency: false,
 
@@ -93,7 +101,8 @@ impl Texture
             address_mode_w: TextureAddressMode::ClampToEdge,
             mag_filter: TextureFilterMode::Linear,
             min_filter: TextureFilterMode::Nearest,
-            mipmap_filter: TextureFilterMode::Nearest
+            mipmap_filter: TextureFilterMode::Nearest,
+            anisotropy: None,
         };
 
         Texture
@@ -103,6 +112,7 @@ impl Texture
             hash: "".to_string(),
 
             data: ChangeTracker::new(data),
+            color_space: TextureColorSpace::Srgb,
 
             egui_preview: None,
             render_item: None
@@ -130,7 +140,17 @@ impl Texture
 
         let has_transparency = rgba.enumerate_pixels().find(|pixel| { pixel.2[3] < 255 }).is_some();
 
-        let image = image::DynamicImage::ImageRgba8(rgba);
+        // hdr images (.hdr/.exr) are keeping their float values
+        let is_hdr = match image
+        {
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => true,
+            _ => false
+        };
+
+        let ldr_image = image::DynamicImage::ImageRgba8(rgba);
+        let preview = Self::create_preview(&ldr_image);
+
+        let image = if is_hdr { image::DynamicImage::ImageRgba32F(image.to_rgba32f()) } else { ldr_image };
 
         let data: TextureData = TextureData
         {
@@ -140,10 +160,13 @@ impl Texture
             mipmapping: false,
 
             mipmap_sampling_type: MipmapSamplingFilterType::Triangle,
+            custom_mipmaps: vec![],
+
+            cubemap: false,
 
             has_transparency: has_transparency,
 
-            preview: Self::create_preview(&image),
+            preview: preview,
             image: image,
 
             address_mode_u: TextureAddressMode::ClampToEdge,
@@ -151,7 +174,8 @@ impl Texture
             address_mode_w: TextureAddressMode::ClampToEdge,
             mag_filter: TextureFilterMode::Linear,
             min_filter: TextureFilterMode::Linear,
-            mipmap_filter: TextureFilterMode::Linear
+            mipmap_filter: TextureFilterMode::Linear,
+            anisotropy: None,
         };
 
         Texture
@@ -161,6 +185,54 @@ impl Texture
             hash,
 
             data: ChangeTracker::new(data),
+            color_space: TextureColorSpace::Srgb,
+
+            egui_preview: None,
+            render_item: None
+        }
+    }
+
+    pub fn new_from_image(id: u64, name: &str, image: DynamicImage) -> Texture
+    {
+        let hash = helper::crypto::get_hash_from_byte_vec(&image.as_bytes().to_vec());
+
+        // previews are always 8 bit
+        let preview = Self::create_preview(&image::DynamicImage::ImageRgba8(image.to_rgba8()));
+
+        let data: TextureData = TextureData
+        {
+            width: image.width() as u64,
+            height: image.height() as u64,
+
+            has_transparency: false,
+
+            mipmapping: false,
+
+            mipmap_sampling_type: MipmapSamplingFilterType::Triangle,
+            custom_mipmaps: vec![],
+
+            cubemap: false,
+
+            preview: preview,
+            image: image,
+
+            address_mode_u: TextureAddressMode::ClampToEdge,
+            address_mode_v: TextureAddressMode::ClampToEdge,
+            address_mode_w: TextureAddressMode::ClampToEdge,
+            mag_filter: TextureFilterMode::Linear,
+            min_filter: TextureFilterMode::Linear,
+            mipmap_filter: TextureFilterMode::Linear,
+            anisotropy: None,
+        };
+
+        Texture
+        {
+            id,
+            name: name.to_string(),
+            hash,
+
+            data: ChangeTracker::new(data),
+            color_space: TextureColorSpace::Srgb,
 
             egui_preview: None,
             render_item: None
@@ -198,6 +270,9 @@ impl Texture
             mipmapping: false,
 
             mipmap_sampling_type: MipmapSamplingFilterType::Triangle,
+            custom_mipmaps: vec![],
+
+            cubemap: false,
 
             preview: Self::create_preview(&image),
             image: image,
@@ -207,7 +282,8 @@ impl Texture
             address_mode_w: TextureAddressMode::ClampToEdge,
             mag_filter: TextureFilterMode::Linear,
             min_filter: TextureFilterMode::Linear,
-            mipmap_filter: TextureFilterMode::Linear
+            mipmap_filter: TextureFilterMode::Linear,
+            anisotropy: None,
         };
 
         Texture
@@ -217,12 +293,133 @@ impl Texture
             hash,
 
             data: ChangeTracker::new(data),
+            color_space: TextureColorSpace::Srgb,
 
             egui_preview: None,
             render_item: None
         }
     }
 
+    // faces: +x, -x, +y, -y, +z, -z (all faces are resized to the size of the first one)
+    pub fn new_cubemap(id: u64, name: &str, faces: Vec<DynamicImage>) -> Texture
+    {
+        let size = faces.first().map(|face| face.width()).unwrap_or(1);
+        let hdr = faces.iter().any(|face| match face { DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => true, _ => false });
+
+        let image = Self::stack_cubemap_faces(&faces, size, hdr);
+
+        let mut texture = Self::new_from_image(id, name, image);
+
+        {
+            let data = texture.get_data_mut().get_mut();
+            data.cubemap = true;
+            data.has_transparency = false;
+        }
+
+        texture
+    }
+
+    fn stack_cubemap_faces(faces: &Vec<DynamicImage>, size: u32, hdr: bool) -> DynamicImage
+    {
+        let faces: Vec<DynamicImage> = faces.iter().take(6).map(|face|
+        {
+            if face.width() != size || face.height() != size
+            {
+                face.resize_exact(size, size, imageops::FilterType::Triangle)
+            }
+            else
+            {
+                face.clone()
+            }
+        }).collect();
+
+        // typed buffers - DynamicImage itself would convert the pixels to 8 bit
+        if hdr
+        {
+            let mut image = Rgba32FImage::new(size, size * 6);
+            for (i, face) in faces.iter().enumerate()
+            {
+                imageops::replace(&mut image, &face.to_rgba32f(), 0, (i as u32 * size) as i64);
+            }
+
+            DynamicImage::ImageRgba32F(image)
+        }
+        else
+        {
+            let mut image = RgbaImage::new(size, size * 6);
+            for (i, face) in faces.iter().enumerate()
+            {
+                imageops::replace(&mut image, &face.to_rgba8(), 0, (i as u32 * size) as i64);
+            }
+
+            DynamicImage::ImageRgba8(image)
+        }
+    }
+
+    // splits cross (horizontal 4:3 or vertical 3:4) and strip (6:1 or 1:6) layouts into the six cubemap faces
+    pub fn cubemap_faces_from_layout(image: &DynamicImage) -> Option<Vec<DynamicImage>>
+    {
+        let width = image.width();
+        let height = image.height();
+
+        // face positions in face units
+        let positions: [(u32, u32); 6];
+        let size;
+        let mut rotate_back_face = false;
+
+        if width * 3 == height * 4
+        {
+            //     +y
+            // -x  +z  +x  -z
+            //     -y
+            size = width / 4;
+            positions = [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)];
+        }
+        else if width * 4 == height * 3
+        {
+            //     +y
+            // -x  +z  +x
+            //     -y
+            //     -z (upside down)
+            size = width / 3;
+            positions = [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (1, 3)];
+            rotate_back_face = true;
+        }
+        else if width == height * 6
+        {
+            size = height;
+            positions = [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0)];
+        }
+        else if width * 6 == height
+        {
+            size = width;
+            positions = [(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (0, 5)];
+        }
+        else
+        {
+            return None;
+        }
+
+        if size == 0
+        {
+            return None;
+        }
+
+        let mut faces: Vec<DynamicImage> = positions.iter().map(|(x, y)| image.crop_imm(x * size, y * size, size, size)).collect();
+
+        if rotate_back_face
+        {
+            faces[5] = faces[5].rotate180();
+        }
+
+        Some(faces)
+    }
+
+    pub fn is_cubemap(&self) -> bool
+    {
+        self.get_data().cubemap
+    }
+
     pub fn create_mipmap_levels(&self) -> Vec<DynamicImage>
     {
         let filter_method;
@@ -235,8 +432,33 @@ impl Texture
             MipmapSamplingFilterType::Lanczos3 => filter_method = imageops::FilterType::Lanczos3,
         }
 
+        if !self.get_data().custom_mipmaps.is_empty()
+        {
+            return self.get_data().custom_mipmaps.clone();
+        }
+
         let mut mipmaps = Vec::new();
 
+        // each face is scaled on its own (no filtering across the face borders)
+        if self.is_cubemap()
+        {
+            let image = &self.get_data().image;
+            let size = image.width();
+
+            let mut faces: Vec<DynamicImage> = (0..6).map(|i| image.crop_imm(0, i * size, size, size)).collect();
+            let mut current_size = size / 2;
+
+            while current_size >= 1 && mipmaps.len() < MAX_MIPMAPS
+            {
+                faces = faces.iter().map(|face| face.resize_exact(current_size, current_size, filter_method)).collect();
+                mipmaps.push(Self::stack_cubemap_faces(&faces, current_size, self.is_hdr()));
+
+                current_size /= 2;
+            }
+
+            return mipmaps;
+        }
+
         let mut current_level = self.get_data().image.clone();
         loop
         {
@@ -258,6 +480,49 @@ impl Texture
         mipmaps
     }
 
+    // scales the image down if the larger side (cubemaps: the face size) exceeds max_size - returns true if the image was changed
+    // textures with precomputed mipmaps are kept as they are
+    pub fn limit_resolution(&mut self, max_size: u32) -> bool
+    {
+        if max_size == 0 || !self.get_data().custom_mipmaps.is_empty()
+        {
+            return false;
+        }
+
+        let filter_method = imageops::FilterType::Triangle;
+        let image;
+
+        if self.is_cubemap()
+        {
+            let size = self.width();
+            if size <= max_size
+            {
+                return false;
+            }
+
+            let source = &self.get_data().image;
+            let faces: Vec<DynamicImage> = (0..6).map(|i| source.crop_imm(0, i * size, size, size).resize_exact(max_size, max_size, filter_method)).collect();
+            image = Self::stack_cubemap_faces(&faces, max_size, self.is_hdr());
+        }
+        else
+        {
+            if self.width().max(self.height()) <= max_size
+            {
+                return false;
+            }
+
+            // keeps the aspect ratio
+            image = self.get_data().image.resize(max_size, max_size, filter_method);
+        }
+
+        let data = self.get_data_mut().get_mut();
+        data.width = image.width() as u64;
+        data.height = image.height() as u64;
+        data.image = image;
+
+        true
+    }
+
     pub fn get_mipmap_levels_amount(&self) -> usize
     {
         if !self.get_data().mipmapping
@@ -347,10 +612,30 @@ impl Texture
         self.get_data().image.color().channel_count() as u32
     }
 
+    pub fn is_hdr(&self) -> bool
+    {
+        match self.get_data().image
+        {
+            DynamicImage::ImageRgba32F(_) => true,
+            _ => false
+        }
+    }
+
+    // bytes per pixel of the gpu texture (hdr textures are uploaded as half floats)
+    pub fn gpu_bytes_per_pixel(&self) -> u32
+    {
+        if self.is_hdr()
+        {
+            return self.channels() * 2;
+        }
+
+        self.channels()
+    }
+
     pub fn memory_usage(&self) -> u64
     {
         // image
-        let mut bytes = self.get_data().width * self.get_data().height * self.channels() as u64;
+        let mut bytes = self.get_data().width * self.get_data().height * self.get_data().image.color().bytes_per_pixel() as u64;
 
         // preview
         bytes += self.get_data().preview.width() as u64 * self.get_data().preview.width() as u64 * 4;
@@ -365,7 +650,7 @@ impl Texture
             return 0;
         }
 
-        let mut bytes = self.get_data().width * self.get_data().height * self.channels() as u64;
+        let mut bytes = self.get_data().width * self.get_data().height * self.gpu_bytes_per_pixel() as u64;
 
         // mipmaps are using around + 1/3 more gpu memory --> https://en.wikipedia.org/wiki/Mipmap
         if self.get_data().mipmapping
@@ -411,6 +696,24 @@ impl Texture
         self.data.get_ref().image.as_bytes()
     }
 
+    // hash of the decoded image and the sampling settings (textures with the same hash can be shared)
+    pub fn content_hash(&self) -> String
+    {
+        let data = self.get_data();
+
+        let mut bytes = format!
+        (
+            "{}x{} {:?} cubemap={} mipmapping={} {:?} custom_mipmaps={} {:?} {:?} {:?} {:?} {:?} {:?};",
+            data.width, data.height, data.image.color(), data.cubemap, data.mipmapping, data.mipmap_sampling_type, data.custom_mipmaps.len(),
+            data.address_mode_u, data.address_mode_v, data.address_mode_w,
+            data.mag_filter, data.min_filter, data.mipmap_filter
+        ).into_bytes();
+
+        bytes.extend_from_slice(self.raw_data());
+
+        helper::crypto::get_hash_from_byte_vec(&bytes)
+    }
+
     pub fn create_egui_preview(&mut self, ctx: &egui::Context)
     {
         if self.egui_preview.is_some()
@@ -456,9 +759,16 @@ impl Texture
 
         let gpu_size = self.gpu_usage() as f32 / 1024.0 / 1024.0;
 
-        let format = if self.channels() == 1 { "Gray" } else { "RGBA" };
+        let format = if self.channels() == 1 { "Gray" } else if self.is_hdr() { "RGBA HDR" } else { "RGBA" };
 
-        ui.label(format!("{}x{}, {}, {} mips, {:.2} MB", data.width, data.height, format, self.get_mipmap_levels_amount(), gpu_size));
+        if data.cubemap
+        {
+            ui.label(format!("cubemap 6x{}x{}, {}, {} mips, {:.2} MB", data.width, data.width, format, self.get_mipmap_levels_amount(), gpu_size));
+        }
+        else
+        {
+            ui.label(format!("{}x{}, {}, {} mips, {:.2} MB", data.width, data.height, format, self.get_mipmap_levels_amount(), gpu_size));
+        }
     }
 
     pub fn ui(&mut self, ui: &mut egui::Ui)
@@ -473,6 +783,7 @@ impl Texture
         let mut mag_filter;
         let mut min_filter;
         let mut mipmap_filter;
+        let mut anisotropy;
 
         {
             let data = self.data.get_ref();
@@ -487,6 +798,7 @@ impl Texture
             mag_filter = data.mag_filter;
             min_filter = data.min_filter;
             mipmap_filter = data.mipmap_filter;
+            anisotropy = data.anisotropy;
         }
 
         let mut apply_settings = false;
@@ -580,6 +892,25 @@ impl Texture
             });
         });
 
+        ui.horizontal(|ui|
+        {
+            ui.label("Anisotropy: ");
+
+            let selected = anisotropy.map_or("default".to_string(), |anisotropy| format!("{}x", anisotropy));
+
+            egui::ComboBox::from_id_source(ui.make_persistent_id("anisotropy")).selected_text(selected).show_ui(ui, |ui|
+            {
+                apply_settings = ui.selectable_value(& mut anisotropy, None, "default").changed() || apply_settings;
+
+                for value in [1, 2, 4, 8, 16]
+                {
+                    apply_settings = ui.selectable_value(& mut anisotropy, Some(value), format!("{}x", value)).changed() || apply_settings;
+                }
+            });
+
+            ui.label("ℹ").on_hover_text("default: global rendering setting\nonly applied if all filters are linear (clamped to the adapter limit)");
+        });
+
         if apply_settings
         {
             let data = self.get_data_mut().get_mut();
@@ -594,6 +925,7 @@ impl Texture
             data.mag_filter = mag_filter;
             data.min_filter = min_filter;
             data.mipmap_filter = mipmap_filter;
+            data.anisotropy = anisotropy;
         }
     }
 
@@ -0,0 +1,312 @@
+use std::f32::consts::PI;
+
+use image::{DynamicImage, Rgba32FImage, Rgba, imageops};
+use nalgebra::{Vector2, Vector3};
+
+// precomputed maps for image based lighting (split sum approximation)
+// https://learnopengl.com/PBR/IBL/Diffuse-irradiance
+// https://learnopengl.com/PBR/IBL/Specular-IBL
+// all maps are equirectangular like the environment map itself (see sphericalCoords in phong.wgsl)
+
+const IRRADIANCE_WIDTH: u32 = 32;
+const IRRADIANCE_SOURCE_WIDTH: u32 = 64;
+const IRRADIANCE_SOURCE_CUBE_SIZE: u32 = 16;
+
+const PREFILTER_MAX_WIDTH: u32 = 64; // larger mipmap levels (low roughness) are used as they are
+
+const BRDF_LUT_SIZE: u32 = 32;
+const BRDF_LUT_SAMPLES: u32 = 128;
+
+// direction and solid angle of each texel
+fn texel_directions(width: u32, height: u32) -> Vec<(Vector3<f32>, f32)>
+{
+    let mut directions = Vec::with_capacity((width * height) as usize);
+
+    let texel_angle = (2.0 * PI / width as f32) * (PI / height as f32);
+
+    for y in 0..height
+    {
+        let latitude = (0.5 - (y as f32 + 0.5) / height as f32) * PI;
+
+        for x in 0..width
+        {
+            let phi = ((x as f32 + 0.5) / width as f32 - 0.5) * 2.0 * PI;
+            let dir = Vector3::<f32>::new(latitude.cos() * phi.cos(), latitude.sin(), latitude.cos() * phi.sin());
+
+            directions.push((dir, texel_angle * latitude.cos()));
+        }
+    }
+
+    directions
+}
+
+// cubemap faces (+x, -x, +y, -y, +z, -z) stacked vertically - same orientation as wgpu cube textures
+fn cubemap_texel_directions(size: u32) -> Vec<(Vector3<f32>, f32)>
+{
+    let mut directions = Vec::with_capacity((size * size * 6) as usize);
+
+    let texel_size = 2.0 / size as f32;
+
+    for face in 0..6
+    {
+        for y in 0..size
+        {
+            let t = (y as f32 + 0.5) * texel_size - 1.0;
+
+            for x in 0..size
+            {
+                let s = (x as f32 + 0.5) * texel_size - 1.0;
+
+                let dir = match face
+                {
+                    0 => Vector3::<f32>::new(1.0, -t, -s),
+                    1 => Vector3::<f32>::new(-1.0, -t, s),
+                    2 => Vector3::<f32>::new(s, 1.0, t),
+                    3 => Vector3::<f32>::new(s, -1.0, -t),
+                    4 => Vector3::<f32>::new(s, -t, 1.0),
+                    _ => Vector3::<f32>::new(-s, -t, -1.0),
+                };
+
+                let solid_angle = texel_size * texel_size / (1.0 + s * s + t * t).powf(1.5);
+
+                directions.push((dir.normalize(), solid_angle));
+            }
+        }
+    }
+
+    directions
+}
+
+fn srgb_to_linear(value: f32) -> f32
+{
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(value: f32) -> f32
+{
+    if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
+
+// ldr images are uploaded as srgb textures - so the convolution needs to be done in linear space
+fn to_linear(image: &DynamicImage, hdr: bool) -> Rgba32FImage
+{
+    let mut linear = image.to_rgba32f();
+
+    if !hdr
+    {
+        for pixel in linear.pixels_mut()
+        {
+            pixel[0] = srgb_to_linear(pixel[0]);
+            pixel[1] = srgb_to_linear(pixel[1]);
+            pixel[2] = srgb_to_linear(pixel[2]);
+        }
+    }
+
+    linear
+}
+
+fn from_linear(mut linear: Rgba32FImage, hdr: bool) -> DynamicImage
+{
+    if hdr
+    {
+        return DynamicImage::ImageRgba32F(linear);
+    }
+
+    for pixel in linear.pixels_mut()
+    {
+        pixel[0] = linear_to_srgb(pixel[0]);
+        pixel[1] = linear_to_srgb(pixel[1]);
+        pixel[2] = linear_to_srgb(pixel[2]);
+    }
+
+    DynamicImage::ImageRgba8(DynamicImage::ImageRgba32F(linear).to_rgba8())
+}
+
+// diffuse: cosine weighted convolution of the environment (already divided by PI - so it can be multiplied with the albedo)
+pub fn create_irradiance_map(image: &DynamicImage, hdr: bool) -> DynamicImage
+{
+    let source = image.resize_exact(IRRADIANCE_SOURCE_WIDTH, IRRADIANCE_SOURCE_WIDTH / 2, imageops::FilterType::Triangle);
+    let source = to_linear(&source, hdr);
+    let source_directions = texel_directions(source.width(), source.height());
+
+    convolve_irradiance(&source, &source_directions)
+}
+
+// the irradiance map itself is still equirectangular (it's small - so the conversion does not matter)
+pub fn create_irradiance_map_from_cubemap(image: &DynamicImage, hdr: bool) -> DynamicImage
+{
+    let size = image.width();
+    let source_size = IRRADIANCE_SOURCE_CUBE_SIZE.min(size);
+
+    let mut faces = vec![];
+    for face in 0..6
+    {
+        faces.push(image.crop_imm(0, face * size, size, size).resize_exact(source_size, source_size, imageops::FilterType::Triangle));
+    }
+
+    let mut source = Rgba32FImage::new(source_size, source_size * 6);
+    for (face, face_image) in faces.iter().enumerate()
+    {
+        imageops::replace(&mut source, &to_linear(face_image, hdr), 0, (face as u32 * source_size) as i64);
+    }
+
+    let source_directions = cubemap_texel_directions(source_size);
+
+    convolve_irradiance(&source, &source_directions)
+}
+
+fn convolve_irradiance(source: &Rgba32FImage, source_directions: &Vec<(Vector3<f32>, f32)>) -> DynamicImage
+{
+    let width = IRRADIANCE_WIDTH;
+    let height = IRRADIANCE_WIDTH / 2;
+
+    let mut irradiance = Rgba32FImage::new(width, height);
+
+    for (i, (normal, _)) in texel_directions(width, height).iter().enumerate()
+    {
+        let mut sum = Vector3::<f32>::zeros();
+
+        for ((dir, solid_angle), pixel) in source_directions.iter().zip(source.pixels())
+        {
+            let cos = normal.dot(dir);
+
+            if cos > 0.0
+            {
+                sum += Vector3::<f32>::new(pixel[0], pixel[1], pixel[2]) * cos * *solid_angle;
+            }
+        }
+
+        sum /= PI;
+
+        irradiance.put_pixel(i as u32 % width, i as u32 / width, Rgba([sum.x, sum.y, sum.z, 1.0]));
+    }
+
+    DynamicImage::ImageRgba32F(irradiance)
+}
+
+// specular: each (small) mipmap level is convolved with a lobe matching the roughness which is used to sample this level
+pub fn prefilter_mipmaps(mipmaps: &mut Vec<DynamicImage>, hdr: bool)
+{
+    let levels = mipmaps.len();
+
+    for (level, mipmap) in mipmaps.iter_mut().enumerate()
+    {
+        if mipmap.width() > PREFILTER_MAX_WIDTH
+        {
+            continue;
+        }
+
+        // mip level = roughness * (mip levels - 1)
+        let roughness = (level + 1) as f32 / levels as f32;
+        let alpha = roughness * roughness;
+        let power = (2.0 / (alpha * alpha) - 2.0).max(1.0);
+
+        let source = to_linear(mipmap, hdr);
+        let directions = texel_directions(source.width(), source.height());
+
+        let mut prefiltered = Rgba32FImage::new(source.width(), source.height());
+
+        for (i, (reflection, _)) in directions.iter().enumerate()
+        {
+            let mut sum = Vector3::<f32>::zeros();
+            let mut weight_sum = 0.0;
+
+            for ((dir, solid_angle), pixel) in directions.iter().zip(source.pixels())
+            {
+                let cos = reflection.dot(dir);
+
+                if cos > 0.0
+                {
+                    let weight = cos.powf(power) * *solid_angle;
+
+                    sum += Vector3::<f32>::new(pixel[0], pixel[1], pixel[2]) * weight;
+                    weight_sum += weight;
+                }
+            }
+
+            if weight_sum > 0.0
+            {
+                sum /= weight_sum;
+            }
+
+            prefiltered.put_pixel(i as u32 % source.width(), i as u32 / source.width(), Rgba([sum.x, sum.y, sum.z, 1.0]));
+        }
+
+        *mipmap = from_linear(prefiltered, hdr);
+    }
+}
+
+// scale (r) and bias (g) to the fresnel reflectance based on n dot v (x) and the roughness (y)
+pub fn create_brdf_lut() -> DynamicImage
+{
+    let mut lut = Rgba32FImage::new(BRDF_LUT_SIZE, BRDF_LUT_SIZE);
+
+    for y in 0..BRDF_LUT_SIZE
+    {
+        let roughness = (y as f32 + 0.5) / BRDF_LUT_SIZE as f32;
+
+        for x in 0..BRDF_LUT_SIZE
+        {
+            let n_dot_v = (x as f32 + 0.5) / BRDF_LUT_SIZE as f32;
+            let (scale, bias) = integrate_brdf(n_dot_v, roughness);
+
+            lut.put_pixel(x, y, Rgba([scale, bias, 0.0, 1.0]));
+        }
+    }
+
+    DynamicImage::ImageRgba32F(lut)
+}
+
+fn hammersley(i: u32, count: u32) -> Vector2<f32>
+{
+    Vector2::<f32>::new(i as f32 / count as f32, i.reverse_bits() as f32 * 2.328_306_4e-10)
+}
+
+// half vector around the normal (+z)
+fn importance_sample_ggx(xi: Vector2<f32>, roughness: f32) -> Vector3<f32>
+{
+    let alpha = roughness * roughness;
+
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = ((1.0 - xi.y) / (1.0 + (alpha * alpha - 1.0) * xi.y)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+    Vector3::<f32>::new(phi.cos() * sin_theta, phi.sin() * sin_theta, cos_theta)
+}
+
+fn geometry_schlick_ggx(n_dot_v: f32, roughness: f32) -> f32
+{
+    let k = (roughness * roughness) / 2.0;
+
+    n_dot_v / (n_dot_v * (1.0 - k) + k)
+}
+
+fn integrate_brdf(n_dot_v: f32, roughness: f32) -> (f32, f32)
+{
+    let view = Vector3::<f32>::new((1.0 - n_dot_v * n_dot_v).sqrt(), 0.0, n_dot_v);
+
+    let mut scale = 0.0;
+    let mut bias = 0.0;
+
+    for i in 0..BRDF_LUT_SAMPLES
+    {
+        let half = importance_sample_ggx(hammersley(i, BRDF_LUT_SAMPLES), roughness);
+        let light = half * 2.0 * view.dot(&half) - view;
+
+        let n_dot_l = light.z.max(0.0);
+        let n_dot_h = half.z.max(0.0);
+        let v_dot_h = view.dot(&half).max(0.0);
+
+        if n_dot_l > 0.0 && n_dot_h > 0.0
+        {
+            let g = geometry_schlick_ggx(n_dot_v, roughness) * geometry_schlick_ggx(n_dot_l, roughness);
+            let g_vis = (g * v_dot_h) / (n_dot_h * n_dot_v);
+            let fresnel = (1.0 - v_dot_h).powi(5);
+
+            scale += (1.0 - fresnel) * g_vis;
+            bias += fresnel * g_vis;
+        }
+    }
+
+    (scale / BRDF_LUT_SAMPLES as f32, bias / BRDF_LUT_SAMPLES as f32)
+}
@@ -1,11 +1,21 @@
-use std::{sync::{RwLock, Arc}, f32::consts::PI, path::Path};
+use std::{sync::{RwLock, Arc}, path::Path, collections::HashSet, ops::Range};
 
 use nalgebra::Vector3;
 
-use crate::{state::scene::{scene::Scene, instance::Instance, components::{transformation::Transformation, material::{Material, TextureType, TextureState}}, texture::{TextureItem, Texture}, loader::wavefront}, component_downcast_mut, helper::{concurrency::{execution_queue::{ExecutionQueue, ExecutionQueueItem, ExecutionQueueResult}}, file::{get_extension, get_stem, self}, self}, resources::{resources::{self, load_binary}}};
+use crate::{state::{state::State, scene::{scene::Scene, manager::id_manager::IdManager, components::material::{Material, TextureType, TextureState}, texture::{TextureItem, Texture, TextureAddressMode}, loader::{wavefront, progress::{self, LoadingProgressItem}}}}, component_downcast_mut, helper::{concurrency::{execution_queue::{ExecutionQueue, ExecutionQueueItem, ExecutionQueueResult}}, file::{get_extension, get_stem, self}, self}, resources::{resources::{self, load_binary}}};
 use crate::state::scene::loader::gltf;
 
-pub fn load_object(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_root_node: bool, reuse_materials: bool, object_only: bool, create_mipmaps: bool) -> anyhow::Result<Vec<u64>>
+use super::ibl;
+
+const ID_BLOCK_SIZE: u64 = 64;
+
+pub fn load_object(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_root_node: bool, reuse_materials: bool, object_only: bool, create_mipmaps: bool, progress: Option<LoadingProgressItem>) -> anyhow::Result<Vec<u64>>
+{
+    load_object_with_scenes(path, scene_id, main_queue, create_root_node, reuse_materials, object_only, create_mipmaps, None, progress)
+}
+
+// scenes: indices of the scenes to load for file formats with multiple scenes (None: default scene)
+pub fn load_object_with_scenes(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, create_root_node: bool, reuse_materials: bool, object_only: bool, create_mipmaps: bool, scenes: Option<Vec<usize>>, progress: Option<LoadingProgressItem>) -> anyhow::Result<Vec<u64>>
 {
     let extension = Path::new(path).extension();
 
@@ -16,16 +26,62 @@ pub fn load_object(path: &str, scene_id: u64, main_queue: ExecutionQueueItem, cr
     }
     let extension = extension.unwrap();
 
+    // existing textures and materials (to clean up a canceled loading)
+    let existing_ids: Arc<RwLock<(HashSet<u64>, HashSet<u64>)>> = Arc::new(RwLock::new((HashSet::new(), HashSet::new())));
+    if progress.is_some()
+    {
+        let existing_ids = existing_ids.clone();
+        execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+        {
+            let mut existing_ids = existing_ids.write().unwrap();
+            existing_ids.0 = scene.textures.values().map(|texture| texture.read().unwrap().id).collect();
+            existing_ids.1 = scene.materials.keys().cloned().collect();
+        }));
+    }
+
+    let result;
     if extension == "obj"
     {
-        return wavefront::load(path, scene_id, main_queue, create_root_node, reuse_materials, object_only, create_mipmaps);
+        result = wavefront::load(path, scene_id, main_queue.clone(), create_root_node, reuse_materials, object_only, create_mipmaps, progress.clone());
     }
     else if extension == "gltf" || extension == "glb"
     {
-        return gltf::load(path, scene_id, main_queue, create_root_node, reuse_materials, object_only, create_mipmaps);
+        result = gltf::load(path, scene_id, main_queue.clone(), create_root_node, reuse_materials, object_only, create_mipmaps, scenes, progress.clone());
+    }
+    else
+    {
+        result = Ok(vec![]);
+    }
+
+    // remove partially loaded textures and materials (nodes are only added at the end)
+    if progress::is_canceled(&progress)
+    {
+        execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+        {
+            let existing_ids = existing_ids.read().unwrap();
+
+            let textures: Vec<u64> = scene.textures.values().map(|texture| texture.read().unwrap().id).filter(|id| !existing_ids.0.contains(id)).collect();
+            let materials: Vec<u64> = scene.materials.keys().cloned().filter(|id| !existing_ids.1.contains(id)).collect();
+
+            for id in textures
+            {
+                scene.delete_texture_by_id(id);
+            }
+
+            for id in materials
+            {
+                scene.delete_material_by_id(id);
+            }
+        }));
     }
 
-    Ok(vec![])
+    result
+}
+
+// heuristic for files without up axis information: z-up models are usually higher (z) than deep (y) and the height is the largest extent
+pub fn is_z_up(extent: &Vector3<f32>) -> bool
+{
+    extent.z > extent.y && extent.z >= extent.x
 }
 
 pub fn load_texture_or_reuse(scene_id: u64, main_queue: ExecutionQueueItem, path: &str, extension: Option<String>) -> anyhow::Result<TextureItem>
@@ -58,18 +114,13 @@ pub fn load_texture_byte_or_reuse(scene_id: u64, main_queue: ExecutionQueueItem,
         // ***** check for reuse *****
         res = main_queue.add(Box::new(move |state|
         {
-            if let Some(scene) = state.find_scene_by_id_mut(scene_id_clone)
+            if let Some(texture) = find_texture_for_reuse(state, scene_id_clone, &hash_clone, &name_clone)
             {
-                if scene.textures.contains_key(&hash_clone)
-                {
-                    println!("reusing texture {}", name_clone);
-
-                    *res_texture_clone.write().unwrap() = Some(scene.textures.get_mut(&hash_clone).unwrap().clone());
-                }
-                else
-                {
-                    *texture_id_clone.write().unwrap() = Some(scene.id_manager.get_next_texture_id());
-                }
+                *res_texture_clone.write().unwrap() = Some(texture);
+            }
+            else if let Some(scene) = state.find_scene_by_id_mut(scene_id_clone)
+            {
+                *texture_id_clone.write().unwrap() = Some(scene.id_manager.get_next_texture_id());
             }
         }))
     }
@@ -94,10 +145,7 @@ pub fn load_texture_byte_or_reuse(scene_id: u64, main_queue: ExecutionQueueItem,
         let mut main_queue = main_queue.write().unwrap();
         res = main_queue.add(Box::new(move |state|
         {
-            if let Some(scene) = state.find_scene_by_id_mut(scene_id_clone)
-            {
-                scene.textures.insert(hash_clone.clone(), arc_clone.clone());
-            }
+            add_texture(state, scene_id_clone, &hash_clone, arc_clone.clone());
         }));
     }
     res.join();
@@ -120,15 +168,7 @@ pub fn insert_texture_or_reuse(scene_id: u64, main_queue: ExecutionQueueItem, te
         let mut main_queue = main_queue.write().unwrap();
         res = main_queue.add(Box::new(move |state|
         {
-            if let Some(scene) = state.find_scene_by_id_mut(scene_id)
-            {
-                if scene.textures.contains_key(&hash_clone)
-                {
-                    println!("reusing texture {}", name_clone);
-
-                    *res_texture_clone.write().unwrap() = Some(scene.textures.get_mut(&hash_clone).unwrap().clone());
-                }
-            }
+            *res_texture_clone.write().unwrap() = find_texture_for_reuse(state, scene_id, &hash_clone, &name_clone);
         }));
     }
     res.join();
@@ -152,10 +192,7 @@ pub fn insert_texture_or_reuse(scene_id: u64, main_queue: ExecutionQueueItem, te
         let mut main_queue = main_queue.write().unwrap();
         res = main_queue.add(Box::new(move |state|
         {
-            if let Some(scene) = state.find_scene_by_id_mut(scene_id_clone)
-            {
-                scene.textures.insert(hash_clone.clone(), arc_clone.clone());
-            }
+            add_texture(state, scene_id_clone, &hash_clone, arc_clone.clone());
         }));
     }
     res.join();
@@ -164,93 +201,62 @@ pub fn insert_texture_or_reuse(scene_id: u64, main_queue: ExecutionQueueItem, te
 
 }
 
-pub fn create_grid(scene_id: u64, main_queue: ExecutionQueueItem, amount: u32, spacing: f32)
+// textures of the scene itself or textures loaded in other scenes (texture cache)
+fn find_texture_for_reuse(state: &mut State, scene_id: u64, hash: &str, name: &str) -> Option<TextureItem>
 {
-    let amount = amount as i32;
-
-    let loaded_ids = load_object("objects/grid/grid.gltf", scene_id, main_queue.clone(), false, true, true, false).unwrap();
-
-    execute_on_scene_mut_and_wait(main_queue.clone(), scene_id, Box::new(move |scene: &mut Scene|
+    let mut texture = None;
+    if let Some(scene) = state.find_scene_by_id(scene_id)
     {
-        if let Some(grid_arc) = scene.find_node_by_name("grid")
-        {
-            {
-                let mut grid = grid_arc.write().unwrap();
-                grid.clear_instances();
-            }
-
-            for i in 0..amount
-            {
-                let pos = i - (amount / 2);
+        texture = scene.textures.get(hash).cloned();
+    }
 
-                // x
-                {
-                    let mut instance = Instance::new
-                    (
-                        scene.id_manager.get_next_instance_id(),
-                        format!("grid_x_{}", pos),
-                        grid_arc.clone()
-                    );
+    if texture.is_none()
+    {
+        texture = state.texture_cache.get(hash);
+    }
 
-                    let mut transformation = Transformation::identity(scene.id_manager.get_next_component_id(), "Transform");
-                    transformation.apply_translation(Vector3::<f32>::new(0.0, 0.0, pos as f32 * spacing));
-                    transformation.apply_scale(Vector3::<f32>::new(amount as f32 * spacing, 1.0, 1.0), true);
+    if texture.is_none()
+    {
+        state.texture_cache.misses += 1;
+        return None;
+    }
 
-                    instance.add_component(Arc::new(RwLock::new(Box::new(transformation))));
+    state.texture_cache.hits += 1;
 
-                    let mut grid = grid_arc.write().unwrap();
-                    grid.add_instance(Box::new(instance));
-                }
-
-                // y
-                {
-                    let mut instance = Instance::new
-                    (
-                        scene.id_manager.get_next_instance_id(),
-                        format!("grid_y_{}", pos),
-                        grid_arc.clone()
-                    );
-
-                    let mut transformation = Transformation::identity(scene.id_manager.get_next_component_id(), "Transform");
-                    transformation.apply_translation(Vector3::<f32>::new(pos as f32 * spacing, 0.0, 0.0));
-                    transformation.apply_rotation(Vector3::<f32>::new(0.0, PI / 2.0, 0.0));
-                    transformation.apply_scale(Vector3::<f32>::new(amount as f32 * spacing, 1.0, 1.0), true);
-
-                    instance.add_component(Arc::new(RwLock::new(Box::new(transformation))));
-
-                    let mut grid = grid_arc.write().unwrap();
-                    grid.add_instance(Box::new(instance));
-                }
-            }
+    if let Some(scene) = state.find_scene_by_id_mut(scene_id)
+    {
+        println!("reusing texture {}", name);
 
-            {
-                let grid = grid_arc.read().unwrap();
+        let texture = texture.clone().unwrap();
+        scene.textures.entry(hash.to_string()).or_insert(texture);
+    }
 
-                if let Some(material) = grid.find_component::<Material>()
-                {
-                    component_downcast_mut!(material, Material);
-                    material.get_data_mut().get_mut().unlit_shading = true;
-                }
-            }
-        }
+    texture
+}
 
-        // merge together
-        for id in &loaded_ids
-        {
-            if let Some(node) = scene.find_node_by_id(*id)
-            {
-                let mut node = node.write().unwrap();
-                node.merge_instances();
+fn add_texture(state: &mut State, scene_id: u64, hash: &str, texture: TextureItem)
+{
+    state.texture_cache.insert(hash, &texture);
 
-                let instance = node.instances.get_mut().first();
-                instance.unwrap().write().unwrap().pickable = false;
-            }
-        }
-    }));
+    if let Some(scene) = state.find_scene_by_id_mut(scene_id)
+    {
+        scene.textures.insert(hash.to_string(), texture);
+    }
 }
 
 pub fn load_texture(path: &str, main_queue: ExecutionQueueItem, texture_type: TextureType, scene_id: u64, material_id: Option<u64>, mipmapping: bool)
 {
+    // scene specific texture
+    if material_id.is_none()
+    {
+        if texture_type == TextureType::Environment
+        {
+            load_environment_texture(path, main_queue, scene_id, mipmapping);
+        }
+
+        return;
+    }
+
     let extension = get_extension(path);
     let name = get_stem(path);
 
@@ -273,20 +279,129 @@ pub fn load_texture(path: &str, main_queue: ExecutionQueueItem, texture_type: Te
                     material.set_texture(tex, texture_type);
                 }
             }
-            // scene specific texture
-            else
-            {
-                if texture_type == TextureType::Environment
-                {
-                    let tex = scene.load_texture_byte_or_reuse(&bytes, name.as_str(), Some(extension.clone()));
-                    tex.write().unwrap().get_data_mut().get_mut().mipmapping = mipmapping;
+        }
+    }));
+}
 
-                    let scene_data = scene.get_data_mut();
-                    let scene_data = scene_data.get_mut();
-                    scene_data.environment_texture = Some(TextureState::new(tex.clone()));
+// the image based lighting maps are precomputed here (in the loading thread) - not on the main thread
+// cross and strip layouts are loaded as cubemap
+pub fn load_environment_texture(path: &str, main_queue: ExecutionQueueItem, scene_id: u64, mipmapping: bool)
+{
+    let extension = get_extension(path);
+    let name = get_stem(path);
 
-                }
-            }
+    let bytes = load_binary(path).unwrap();
+
+    let mut texture = Texture::new(get_new_tex_id(main_queue.clone(), scene_id), name.as_str(), &bytes, Some(extension));
+
+    if let Some(faces) = Texture::cubemap_faces_from_layout(texture.get_dynamic_image())
+    {
+        texture = Texture::new_cubemap(texture.id, name.as_str(), faces);
+    }
+
+    apply_environment_texture(texture, Some(path.to_string()), main_queue, scene_id, mipmapping);
+}
+
+// six images - the face is detected by the file name (px/nx, posx/negx, right/left, ...) otherwise the order is used (+x, -x, +y, -y, +z, -z)
+pub fn load_environment_cubemap(paths: &Vec<String>, main_queue: ExecutionQueueItem, scene_id: u64, mipmapping: bool)
+{
+    if paths.len() != 6
+    {
+        println!("a cubemap needs 6 images ({} given)", paths.len());
+        return;
+    }
+
+    let mut ordered_paths = paths.clone();
+
+    let face_indices: Vec<Option<usize>> = paths.iter().map(|path| cubemap_face_index(get_stem(path).as_str())).collect();
+    let mut unique_indices: Vec<usize> = face_indices.iter().filter_map(|index| *index).collect();
+    unique_indices.sort();
+    unique_indices.dedup();
+
+    if unique_indices.len() == 6
+    {
+        for (path, index) in paths.iter().zip(face_indices.iter())
+        {
+            ordered_paths[index.unwrap()] = path.clone();
+        }
+    }
+
+    let mut faces = vec![];
+    for path in &ordered_paths
+    {
+        let bytes = load_binary(path).unwrap();
+        let face = Texture::new(0, "", &bytes, Some(get_extension(path)));
+
+        faces.push(face.get_dynamic_image().clone());
+    }
+
+    let name = get_stem(ordered_paths.first().unwrap());
+    let texture = Texture::new_cubemap(get_new_tex_id(main_queue.clone(), scene_id), name.as_str(), faces);
+
+    apply_environment_texture(texture, None, main_queue, scene_id, mipmapping);
+}
+
+fn cubemap_face_index(name: &str) -> Option<usize>
+{
+    let name = name.to_lowercase();
+
+    let face_names: [&[&str]; 6] =
+    [
+        &["px", "posx", "right", "+x"],
+        &["nx", "negx", "left", "-x"],
+        &["py", "posy", "top", "up", "+y"],
+        &["ny", "negy", "bottom", "down", "-y"],
+        &["pz", "posz", "front", "+z"],
+        &["nz", "negz", "back", "-z"],
+    ];
+
+    face_names.iter().position(|names| names.iter().any(|face_name| name.ends_with(face_name)))
+}
+
+fn apply_environment_texture(mut texture: Texture, path: Option<String>, main_queue: ExecutionQueueItem, scene_id: u64, mipmapping: bool)
+{
+    let name = texture.name.clone();
+    let hdr = texture.is_hdr();
+    let cubemap = texture.is_cubemap();
+
+    // specular: prefiltered mipmaps (cubemaps are using the regular ones)
+    if mipmapping
+    {
+        let mut mipmaps = texture.create_mipmap_levels();
+
+        if !cubemap
+        {
+            ibl::prefilter_mipmaps(&mut mipmaps, hdr);
+        }
+
+        let data = texture.get_data_mut().get_mut();
+        data.mipmapping = true;
+        data.custom_mipmaps = mipmaps;
+    }
+
+    // diffuse: irradiance map
+    let irradiance_name = format!("{} irradiance", name);
+    let irradiance_image = if cubemap { ibl::create_irradiance_map_from_cubemap(texture.get_dynamic_image(), hdr) } else { ibl::create_irradiance_map(texture.get_dynamic_image(), hdr) };
+    let mut irradiance = Texture::new_from_image(get_new_tex_id(main_queue.clone(), scene_id), irradiance_name.as_str(), irradiance_image);
+    irradiance.get_data_mut().get_mut().address_mode_u = TextureAddressMode::Repeat;
+
+    let brdf_lut = Texture::new_from_image(get_new_tex_id(main_queue.clone(), scene_id), "brdf lut", ibl::create_brdf_lut());
+
+    let texture = insert_texture_or_reuse(scene_id, main_queue.clone(), texture, name.as_str());
+    let irradiance = insert_texture_or_reuse(scene_id, main_queue.clone(), irradiance, irradiance_name.as_str());
+    let brdf_lut = insert_texture_or_reuse(scene_id, main_queue.clone(), brdf_lut, "brdf lut");
+
+    let mut main_queue = main_queue.write().unwrap();
+    main_queue.add(Box::new(move |state|
+    {
+        if let Some(scene) = state.find_scene_by_id_mut(scene_id)
+        {
+            let scene_data = scene.get_data_mut();
+            let scene_data = scene_data.get_mut();
+            scene_data.environment_texture = Some(TextureState::new(texture.clone()));
+            scene_data.environment_path = path.clone();
+            scene_data.environment_irradiance = Some(irradiance.clone());
+            scene_data.environment_brdf_lut = Some(brdf_lut.clone());
         }
     }));
 }
@@ -307,6 +422,80 @@ pub fn execute_on_scene_mut_and_wait(main_queue: ExecutionQueueItem, scene_id: u
     res.join();
 }
 
+// not waiting for the main thread - only if the result is not needed (the queue is processed in order)
+pub fn execute_on_scene_mut(main_queue: ExecutionQueueItem, scene_id: u64, func: Box<dyn Fn(&mut Scene) + Send + Sync>)
+{
+    let mut main_queue = main_queue.write().unwrap();
+    main_queue.add(Box::new(move |state|
+    {
+        if let Some(scene) = state.find_scene_by_id_mut(scene_id)
+        {
+            func(scene);
+        }
+    }));
+}
+
+// ids are reserved in blocks - so loading does not need a main thread round trip (one frame) per node
+pub struct ReservedIds
+{
+    main_queue: ExecutionQueueItem,
+    scene_id: u64,
+
+    nodes: Range<u64>,
+    instances: Range<u64>,
+    components: Range<u64>,
+}
+
+impl ReservedIds
+{
+    pub fn new(main_queue: ExecutionQueueItem, scene_id: u64) -> ReservedIds
+    {
+        ReservedIds
+        {
+            main_queue,
+            scene_id,
+
+            nodes: 0..0,
+            instances: 0..0,
+            components: 0..0,
+        }
+    }
+
+    pub fn node_id(&mut self) -> u64
+    {
+        Self::next_id(&mut self.nodes, self.main_queue.clone(), self.scene_id, IdManager::reserve_node_ids)
+    }
+
+    pub fn instance_id(&mut self) -> u64
+    {
+        Self::next_id(&mut self.instances, self.main_queue.clone(), self.scene_id, IdManager::reserve_instance_ids)
+    }
+
+    pub fn component_id(&mut self) -> u64
+    {
+        Self::next_id(&mut self.components, self.main_queue.clone(), self.scene_id, IdManager::reserve_component_ids)
+    }
+
+    fn next_id(ids: &mut Range<u64>, main_queue: ExecutionQueueItem, scene_id: u64, reserve: fn(&mut IdManager, u64) -> u64) -> u64
+    {
+        if ids.is_empty()
+        {
+            let first: Arc<RwLock<u64>> = Arc::new(RwLock::new(0));
+            let first_clone = first.clone();
+
+            execute_on_scene_mut_and_wait(main_queue, scene_id, Box::new(move |scene: &mut Scene|
+            {
+                *first_clone.write().unwrap() = reserve(&mut scene.id_manager, ID_BLOCK_SIZE);
+            }));
+
+            let first = *first.read().unwrap();
+            *ids = first..first + ID_BLOCK_SIZE;
+        }
+
+        ids.next().unwrap()
+    }
+}
+
 pub fn get_new_tex_id(main_queue: ExecutionQueueItem, scene_id: u64) -> u64
 {
     let id: Arc<RwLock<Option<u64>>> = Arc::new(RwLock::new(None));
@@ -1,15 +1,18 @@
-use std::{cell::RefCell, rc::Rc, sync::{RwLock, Arc}};
+use std::{cell::RefCell, rc::Rc, sync::{RwLock, Arc}, collections::HashSet};
 
 use instant::Instant;
-use nalgebra::Vector3;
+use nalgebra::{Vector3, Vector4, Point2, Point3};
+use strum_macros::{EnumIter, Display};
 
-use crate::{helper::{change_tracker::ChangeTracker, concurrency::{execution_queue::{ExecutionQueue, ExecutionQueueItem}, thread::spawn_thread}}, input::input_manager::InputManager};
+use crate::{interface::main_interface::REFERENCE_UPDATE_FRAMES, helper::{change_tracker::ChangeTracker, concurrency::{execution_queue::{ExecutionQueue, ExecutionQueueItem}, thread::spawn_thread}}, input::{input_manager::InputManager, mouse::MouseButton}, component_downcast};
 
-use super::scene::{scene::SceneItem, components::{component::ComponentItem, material::TextureType}, utilities::scene_utils::load_texture};
+use super::scene::{scene::SceneItem, node::NodeItem, events::SceneEvent, manager::texture_cache::TextureCache, components::{component::ComponentItem, material::{Material, TextureType, TextureColorSpace, ALL_TEXTURE_TYPES}}, utilities::scene_utils::load_texture};
 
 pub type StateItem = Rc<RefCell<State>>;
 
 pub const FPS_CHART_VALUES: usize = 100;
+pub const FIXED_UPDATE_MAX_STEPS: u32 = 10; // per frame - to not fall behind more and more on slow frames
+pub const DEFAULT_ANISOTROPY: u8 = 8;
 
 pub struct AdapterFeatures
 {
@@ -19,19 +22,192 @@ pub struct AdapterFeatures
     pub backend: String,
 
     pub storage_buffer_array_support: bool,
-    pub max_msaa_samples: u32
+    pub timestamp_query_support: bool,
+    pub max_msaa_samples: u32,
+    pub max_anisotropy: u8,
 }
 
 pub struct Rendering
 {
-    pub clear_color: ChangeTracker<Vector3<f32>>,
+    pub clear_color: ChangeTracker<Vector4<f32>>, // alpha < 1.0 for transparent captures/compositing
     pub v_sync: ChangeTracker<bool>,
+    pub target_fps: Option<u32>, // frame rate cap (ignored with vsync)
 
     pub fullscreen: ChangeTracker<bool>,
     pub msaa: ChangeTracker<u32>,
 
     pub distance_sorting: bool,
     pub create_mipmaps: bool,
+    pub max_texture_resolution: Option<u32>, // larger textures are scaled down (None = unlimited)
+    pub anisotropy: u8, // default for all textures (clamped to the adapter limit)
+
+    pub xray_selected: bool,
+    pub debug_channel: DebugChannel,
+
+    pub grid: GridSettings,
+    pub dof: DofSettings,
+    pub outline: OutlineSettings,
+    pub wireframe: WireframeSettings,
+    pub normals: NormalsDebugSettings,
+}
+
+// output of the color pass for material debugging (the order matches DEBUG_CHANNEL_* in phong.wgsl)
+#[derive(EnumIter, Display, Debug, Clone, Copy, PartialEq)]
+pub enum DebugChannel
+{
+    #[strum(to_string = "shaded")]
+    Shaded,
+    #[strum(to_string = "albedo")]
+    Albedo,
+    #[strum(to_string = "world normal")]
+    WorldNormal,
+    #[strum(to_string = "tangent normal")]
+    TangentNormal,
+    #[strum(to_string = "roughness")]
+    Roughness,
+    #[strum(to_string = "metallic (reflectivity)")]
+    Metallic,
+    #[strum(to_string = "uv")]
+    Uv,
+    #[strum(to_string = "overdraw")]
+    Overdraw,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct GridSettings
+{
+    pub enabled: bool,
+
+    pub spacing: f32,
+    pub subdivisions: u32,
+
+    pub color: Vector3<f32>,
+    pub opacity: f32,
+    pub axis_highlight: bool,
+
+    pub fade_distance: f32,
+}
+
+impl GridSettings
+{
+    pub fn new() -> GridSettings
+    {
+        GridSettings
+        {
+            enabled: false,
+
+            spacing: 1.0,
+            subdivisions: 1,
+
+            color: Vector3::<f32>::new(0.5, 0.5, 0.5),
+            opacity: 0.6,
+            axis_highlight: true,
+
+            fade_distance: 100.0,
+        }
+    }
+
+    // spacing of the smallest visible grid cell (used for snapping)
+    pub fn snap_spacing(&self) -> f32
+    {
+        self.spacing / self.subdivisions.max(1) as f32
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct DofSettings
+{
+    pub enabled: bool,
+
+    pub focus_distance: f32,
+    pub f_stop: f32,
+    pub max_blur: f32, // max blur radius in pixels
+}
+
+impl DofSettings
+{
+    pub fn new() -> DofSettings
+    {
+        DofSettings
+        {
+            enabled: false,
+
+            focus_distance: 10.0,
+            f_stop: 2.8,
+            max_blur: 12.0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct OutlineSettings
+{
+    pub enabled: bool,
+
+    pub color: Vector3<f32>,
+    pub thickness: f32, // in pixels
+}
+
+impl OutlineSettings
+{
+    pub fn new() -> OutlineSettings
+    {
+        OutlineSettings
+        {
+            enabled: true,
+
+            color: Vector3::<f32>::new(1.0, 0.6, 0.0),
+            thickness: 3.0,
+        }
+    }
+}
+
+// wireframe on top of the shaded surface of the selected objects
+#[derive(Clone, PartialEq)]
+pub struct WireframeSettings
+{
+    pub enabled: bool,
+
+    pub color: Vector3<f32>,
+    pub opacity: f32,
+}
+
+impl WireframeSettings
+{
+    pub fn new() -> WireframeSettings
+    {
+        WireframeSettings
+        {
+            enabled: false,
+
+            color: Vector3::<f32>::new(0.1, 0.1, 0.1),
+            opacity: 0.8,
+        }
+    }
+}
+
+// debug lines along the normals (and tangents/bitangents) of the selected objects
+#[derive(Clone, PartialEq)]
+pub struct NormalsDebugSettings
+{
+    pub enabled: bool,
+    pub tangents: bool,
+
+    pub length: f32, // in world units
+}
+
+impl NormalsDebugSettings
+{
+    pub fn new() -> NormalsDebugSettings
+    {
+        NormalsDebugSettings
+        {
+            enabled: false,
+            tangents: false,
+
+            length: 0.1,
+        }
+    }
 }
 
 pub struct SupportedFileTypes
@@ -45,16 +221,20 @@ pub struct State
     pub adapter: AdapterFeatures,
     pub rendering: Rendering,
     pub input_manager: InputManager,
+    pub picks: Vec<(u64, SceneEvent)>, // pick events of the current frame (scene id, event) - see update_picks
 
     pub main_thread_execution_queue: ExecutionQueueItem,
 
     pub running: bool,
     pub scenes: Vec<SceneItem>,
+    pub texture_cache: TextureCache,
+    materials_amount: usize, // over all scenes - to detect added/removed materials (see update_texture_color_spaces)
 
     pub registered_components: Vec<(String, fn(u64, &str) -> ComponentItem)>,
     pub supported_file_types: SupportedFileTypes,
 
     pub in_focus: bool,
+    pub pause_on_unfocus: bool, // no scene updates and a throttled frame rate while the window is not focused
 
     pub width: u32,
     pub height: u32,
@@ -63,10 +243,20 @@ pub struct State
     pub save_image: bool,
     pub save_depth_pass_image: bool,
     pub save_depth_buffer_image: bool,
+    pub depth_image_linearized: bool,
+    pub depth_image_normalized: bool, // to the visible depth range
 
     pub save_screenshot: bool,
+    pub screenshot_path: Option<String>,
+    pub screenshot_transparent: bool,
+
+    pub render_image_width: u32,
+    pub render_image_height: u32,
+    pub render_image_supersampling: u32,
+    pub render_image_path: Option<String>,
 
     pub draw_calls: u32,
+    pub rendered_vertices: u32,
     pub fps_timer: Instant,
     pub last_time: u128,
     pub fps: u32,
@@ -77,10 +267,16 @@ pub struct State
     pub frame_update_time: u128,
     pub frame_scale: f32,
 
+    pub fixed_update_rate: Option<u32>, // fixed time step for node/component updates in Hz (None = once per frame)
+    pub fixed_update_accumulator: f32, // in frame scale units
+
     pub frame_time: f32,
 
     pub engine_update_time: f32,
     pub engine_render_time: f32,
+    pub gpu_pass_times: Vec<(String, f32)>,
+    pub gpu_buffer_usage: Vec<(String, u64)>,
+    pub gpu_buffer_uploads: u64,
 
     pub app_update_time: f32,
 
@@ -105,6 +301,9 @@ impl State
         //components.push(("Mesh".to_string(), |id, name| { Arc::new(RwLock::new(Box::new(crate::state::scene::components::mesh::Mesh::new_plane(id, name, x0, x1, x2, x3)))) }));
         components.push(("Transform".to_string(), |id, name| { Arc::new(RwLock::new(Box::new(crate::state::scene::components::transformation::Transformation::identity(id, name)))) }));
         components.push(("Transform Animation".to_string(), |id, name| { Arc::new(RwLock::new(Box::new(crate::state::scene::components::transformation_animation::TransformationAnimation::new_empty(id, name)))) }));
+        components.push(("LOD".to_string(), |id, name| { Arc::new(RwLock::new(Box::new(crate::state::scene::components::lod::Lod::new(id, name)))) }));
+        components.push(("Billboard".to_string(), |id, name| { Arc::new(RwLock::new(Box::new(crate::state::scene::components::billboard::Billboard::new(id, name, crate::state::scene::components::billboard::BillboardMode::Full)))) }));
+        components.push(("Label".to_string(), |id, name| { Arc::new(RwLock::new(Box::new(crate::state::scene::components::label::Label::new(id, name, name)))) }));
 
         Self
         {
@@ -115,37 +314,55 @@ impl State
                 driver_info: String::new(),
                 backend: String::new(),
                 storage_buffer_array_support: false,
-                max_msaa_samples: 1
+                timestamp_query_support: false,
+                max_msaa_samples: 1,
+                max_anisotropy: 1,
             },
 
             rendering: Rendering
             {
-                clear_color: ChangeTracker::new(Vector3::<f32>::new(0.0, 0.0, 0.0)),
+                clear_color: ChangeTracker::new(Vector4::<f32>::new(0.0, 0.0, 0.0, 1.0)),
                 v_sync: ChangeTracker::new(true),
+                target_fps: None,
 
                 fullscreen: ChangeTracker::new(false),
                 msaa: ChangeTracker::new(8),
 
                 distance_sorting: true,
-                create_mipmaps: false
+                create_mipmaps: false,
+                max_texture_resolution: None,
+                anisotropy: DEFAULT_ANISOTROPY,
+
+                xray_selected: false,
+                debug_channel: DebugChannel::Shaded,
+
+                grid: GridSettings::new(),
+                dof: DofSettings::new(),
+                outline: OutlineSettings::new(),
+                wireframe: WireframeSettings::new(),
+                normals: NormalsDebugSettings::new(),
             },
 
             input_manager: InputManager::new(),
+            picks: vec![],
 
             main_thread_execution_queue: Arc::new(RwLock::new(ExecutionQueue::new())),
 
             running: false,
             scenes: vec![],
+            texture_cache: TextureCache::new(),
+            materials_amount: 0,
 
             registered_components: components,
 
             supported_file_types: SupportedFileTypes
             {
                 objects: vec![String::from("obj"), String::from("gltf"), String::from("glb")],
-                textures: vec![String::from("jpg"), String::from("jpeg"), String::from("png")],
+                textures: vec![String::from("jpg"), String::from("jpeg"), String::from("png"), String::from("hdr"), String::from("exr")],
             },
 
             in_focus: true,
+            pause_on_unfocus: true,
 
             width: 0,
             height: 0,
@@ -154,9 +371,19 @@ impl State
             save_image: false,
             save_depth_pass_image: false,
             save_depth_buffer_image: false,
+            depth_image_linearized: true,
+            depth_image_normalized: true,
             save_screenshot: false,
+            screenshot_path: None,
+            screenshot_transparent: false,
+
+            render_image_width: 3840,
+            render_image_height: 2160,
+            render_image_supersampling: 1,
+            render_image_path: None,
 
             draw_calls: 0,
+            rendered_vertices: 0,
             fps_timer: Instant::now(),
             last_time: 0,
             fps: 0,
@@ -167,10 +394,16 @@ impl State
             frame_update_time: 0,
             frame_scale: 0.0,
 
+            fixed_update_rate: None,
+            fixed_update_accumulator: 0.0,
+
             frame_time: 0.0,
 
             engine_update_time: 0.0,
             engine_render_time: 0.0,
+            gpu_pass_times: vec![],
+            gpu_buffer_usage: vec![],
+            gpu_buffer_uploads: 0,
 
             app_update_time: 0.0,
 
@@ -223,12 +456,238 @@ impl State
         None
     }
 
+    // events of all scenes since the last call (scene id, event)
+    pub fn drain_events(&mut self) -> Vec<(u64, SceneEvent)>
+    {
+        let mut events = vec![];
+
+        for scene in &mut self.scenes
+        {
+            let scene_id = scene.id;
+            events.extend(scene.drain_events().into_iter().map(|event| (scene_id, event)));
+        }
+
+        events
+    }
+
+    // nearest hit of all visible scenes and enabled cameras at the viewport position (scene id, hit)
+    pub fn pick(&self, pos: Point2::<f32>) -> Option<(u64, (f32, Point3<f32>, Option<Vector3<f32>>, NodeItem, u64, Option<u32>))>
+    {
+        let mut hit: Option<(f32, Point3<f32>, Option<Vector3<f32>>, NodeItem, u64, Option<u32>)> = None;
+        let mut scene_id: u64 = 0;
+
+        for scene in &self.scenes
+        {
+            // hidden scenes and disabled cameras are not rendered - so they are not pickable
+            if !scene.visible
+            {
+                continue;
+            }
+
+            for camera in &scene.cameras
+            {
+                // check if click is insight
+                if camera.enabled && camera.is_point_in_viewport(&pos)
+                {
+                    let ray = camera.get_ray_from_viewport_coordinates(&pos, self.width, self.height);
+                    let new_hit = scene.pick(&ray, false, false);
+
+                    let mut save_hit = false;
+
+                    if let Some(new_hit) = new_hit.as_ref()
+                    {
+                        if let Some(hit) = hit.as_ref()
+                        {
+                            // check if the new hit is near
+                            if new_hit.0 < hit.0
+                            {
+                                save_hit = true;
+                            }
+                        }
+                        else
+                        {
+                            save_hit = true;
+                        }
+                    }
+
+                    if save_hit
+                    {
+                        hit = new_hit;
+                        scene_id = scene.id;
+                    }
+                }
+            }
+        }
+
+        if let Some(hit) = hit
+        {
+            return Some((scene_id, hit));
+        }
+
+        None
+    }
+
+    // clicked/tapped instances are emitted as pick events (to the scene events and the picks of the current frame)
+    // the clicks are not consumed - this needs to be called after the ui consumed its clicks
+    pub fn update_picks(&mut self)
+    {
+        self.picks.clear();
+
+        // a tap is handled like a left click
+        let tap_pos = self.input_manager.touch.tapped();
+
+        let mut buttons = vec![];
+
+        if self.input_manager.mouse.peek_clicked(MouseButton::Left) || tap_pos.is_some()
+        {
+            buttons.push(MouseButton::Left);
+        }
+
+        if self.input_manager.mouse.peek_clicked(MouseButton::Right)
+        {
+            buttons.push(MouseButton::Right);
+        }
+
+        let pos = tap_pos.or(self.input_manager.mouse.point.pos);
+
+        if buttons.is_empty() || pos.is_none()
+        {
+            return;
+        }
+
+        let hit = self.pick(pos.unwrap());
+
+        if hit.is_none()
+        {
+            return;
+        }
+
+        let (scene_id, (_t, point, _normal, node, instance_id, _face_id)) = hit.unwrap();
+        let node_id = node.read().unwrap().id;
+
+        for button in buttons
+        {
+            let event = SceneEvent::Picked { node_id, instance_id, point, button };
+
+            if let Some(scene) = self.find_scene_by_id_mut(scene_id)
+            {
+                scene.push_event(event.clone());
+            }
+
+            self.picks.push((scene_id, event));
+        }
+    }
+
     pub fn update(&mut self, time_delta: f32)
     {
+        // nodes are updated with a fixed time step (reproducible animations) - cameras are using the input of each frame
+        let mut steps = 1;
+        let mut step_time_delta = time_delta;
+
+        if let Some(fixed_update_rate) = self.fixed_update_rate
+        {
+            step_time_delta = REFERENCE_UPDATE_FRAMES / fixed_update_rate.max(1) as f32;
+
+            self.fixed_update_accumulator += time_delta;
+            steps = (self.fixed_update_accumulator / step_time_delta) as u32;
+
+            if steps > FIXED_UPDATE_MAX_STEPS
+            {
+                steps = FIXED_UPDATE_MAX_STEPS;
+                self.fixed_update_accumulator = 0.0;
+            }
+            else
+            {
+                self.fixed_update_accumulator -= steps as f32 * step_time_delta;
+            }
+        }
+
         // update scenes
         for scene in &mut self.scenes
         {
-            scene.update(&mut self.input_manager, time_delta);
+            for _ in 0..steps
+            {
+                scene.update_nodes(&mut self.input_manager, step_time_delta);
+            }
+
+            scene.update_lods();
+            scene.update_cameras(&mut self.input_manager, time_delta);
+            scene.update_billboards();
+        }
+    }
+
+    // color space of the textures based on the material slots using them - only if materials have changed
+    // this is decided over all scenes: textures can be shared between scenes (see TextureCache)
+    // textures are only linear if they are not used as color anywhere (unused ones are srgb)
+    pub fn update_texture_color_spaces(&mut self)
+    {
+        let mut materials_amount = 0;
+        let mut materials_changed = false;
+
+        for scene in &self.scenes
+        {
+            materials_amount += scene.materials.len();
+
+            for (_, material) in &scene.materials
+            {
+                component_downcast!(material, Material);
+
+                if material.get_data_tracker().changed()
+                {
+                    materials_changed = true;
+                }
+            }
+        }
+
+        if !materials_changed && materials_amount == self.materials_amount
+        {
+            return;
+        }
+
+        self.materials_amount = materials_amount;
+
+        let mut srgb_textures = HashSet::new();
+        let mut linear_textures = HashSet::new();
+
+        for scene in &self.scenes
+        {
+            for (_, material) in &scene.materials
+            {
+                component_downcast!(material, Material);
+
+                for texture_type in ALL_TEXTURE_TYPES
+                {
+                    if let Some(texture) = material.get_texture_by_type(texture_type)
+                    {
+                        let id = texture.get().read().unwrap().id;
+
+                        match texture_type.color_space()
+                        {
+                            TextureColorSpace::Srgb => { srgb_textures.insert(id); },
+                            TextureColorSpace::Linear => { linear_textures.insert(id); },
+                        }
+                    }
+                }
+            }
+        }
+
+        for scene in &self.scenes
+        {
+            for (_, texture) in &scene.textures
+            {
+                let id = texture.read().unwrap().id;
+
+                let mut color_space = TextureColorSpace::Srgb;
+                if linear_textures.contains(&id) && !srgb_textures.contains(&id)
+                {
+                    color_space = TextureColorSpace::Linear;
+                }
+
+                if texture.read().unwrap().color_space != color_space
+                {
+                    texture.write().unwrap().color_space = color_space;
+                }
+            }
         }
     }
 
@@ -242,6 +701,7 @@ impl State
         println!(" - backend: {}", self.adapter.backend);
         println!(" - storage_buffer_array_support: {}", self.adapter.storage_buffer_array_support);
         println!(" - max msaa_samples: {}", self.adapter.max_msaa_samples);
+        println!(" - max anisotropy: {}", self.adapter.max_anisotropy);
 
         println!("");
 
@@ -253,4 +713,162 @@ impl State
             scene.print();
         }
     }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::{Arc, RwLock};
+
+    use nalgebra::{Point2, Point3, Vector3};
+
+    use crate::{input::{input_point::PointState, mouse::MouseButton}, state::scene::{scene::{Scene, test_utils::{create_scene as create_empty_scene, add_box, update}}, components::transformation::Transformation, events::SceneEvent}};
+
+    use super::State;
+
+    const WIDTH: u32 = 200;
+    const HEIGHT: u32 = 100;
+
+    // scene with a 2x2x2 box at the given x position and a camera looking at it (from z = 10) in the given viewport (x, y, width, height)
+    fn create_scene(id: u64, x: f32, viewport: (f32, f32, f32, f32)) -> Scene
+    {
+        let mut scene = create_empty_scene(id);
+        let node = add_box(&mut scene, "box");
+
+        let transform = Transformation::new(scene.id_manager.get_next_component_id(), "Transform", Vector3::<f32>::new(x, 0.0, 0.0), Vector3::<f32>::zeros(), Vector3::<f32>::new(1.0, 1.0, 1.0));
+        node.write().unwrap().add_component(Arc::new(RwLock::new(Box::new(transform))));
+
+        update(&mut scene);
+
+        scene.add_camera("camera");
+        let camera = scene.cameras.last_mut().unwrap();
+        camera.init(viewport.0, viewport.1, viewport.2, viewport.3, WIDTH, HEIGHT);
+        camera.set_cam_position(Point3::<f32>::new(x, 0.0, 10.0), -Vector3::<f32>::z());
+
+        scene
+    }
+
+    // split screen: scene 1 is on the left half and scene 2 on the right half
+    fn create_state() -> State
+    {
+        let mut state = State::new();
+        state.width = WIDTH;
+        state.height = HEIGHT;
+
+        state.scenes.push(Box::new(create_scene(1, 0.0, (0.0, 0.0, 0.5, 1.0))));
+        state.scenes.push(Box::new(create_scene(2, 3.0, (0.5, 0.0, 0.5, 1.0))));
+
+        state
+    }
+
+    // split screen: scene 1 is on the top half and scene 2 on the bottom half (the viewports are top left based)
+    fn create_vertical_state() -> State
+    {
+        let mut state = State::new();
+        state.width = WIDTH;
+        state.height = HEIGHT;
+
+        state.scenes.push(Box::new(create_scene(1, 0.0, (0.0, 0.0, 1.0, 0.5))));
+        state.scenes.push(Box::new(create_scene(2, 3.0, (0.0, 0.5, 1.0, 0.5))));
+
+        state
+    }
+
+    #[test]
+    fn ray_starts_at_the_offset_viewport_camera()
+    {
+        let state = create_state();
+        let camera = &state.scenes[1].cameras[0];
+
+        // center of the right viewport
+        let ray = camera.get_ray_from_viewport_coordinates(&Point2::<f32>::new(149.5, 49.5), WIDTH, HEIGHT);
+
+        assert!((ray.origin.x - 3.0).abs() < 0.001);
+        assert!(ray.origin.y.abs() < 0.001);
+        assert!(ray.origin.z <= 10.0 && ray.origin.z > 8.0);
+        assert!(ray.dir.z < -0.999);
+    }
+
+    #[test]
+    fn pick_returns_the_scene_of_the_viewport()
+    {
+        let state = create_state();
+
+        let (scene_id, hit) = state.pick(Point2::<f32>::new(49.5, 49.5)).unwrap();
+        assert_eq!(scene_id, 1);
+        assert!((hit.1 - Point3::<f32>::new(0.0, 0.0, 1.0)).magnitude() < 0.001);
+
+        let (scene_id, hit) = state.pick(Point2::<f32>::new(149.5, 49.5)).unwrap();
+        assert_eq!(scene_id, 2);
+        assert!((hit.1 - Point3::<f32>::new(3.0, 0.0, 1.0)).magnitude() < 0.001);
+    }
+
+    #[test]
+    fn pick_returns_the_scene_of_the_vertical_viewport()
+    {
+        let state = create_vertical_state();
+
+        // the mouse position is bottom left based: the top viewport is at the upper half
+        let top = Point2::<f32>::new(99.5, 74.5);
+        let bottom = Point2::<f32>::new(99.5, 24.5);
+
+        assert!(state.scenes[0].cameras[0].is_point_in_viewport(&top));
+        assert!(!state.scenes[0].cameras[0].is_point_in_viewport(&bottom));
+        assert!(state.scenes[1].cameras[0].is_point_in_viewport(&bottom));
+
+        let (scene_id, hit) = state.pick(top).unwrap();
+        assert_eq!(scene_id, 1);
+        assert!((hit.1 - Point3::<f32>::new(0.0, 0.0, 1.0)).magnitude() < 0.001);
+
+        let (scene_id, hit) = state.pick(bottom).unwrap();
+        assert_eq!(scene_id, 2);
+        assert!((hit.1 - Point3::<f32>::new(3.0, 0.0, 1.0)).magnitude() < 0.001);
+
+        // center of the bottom viewport
+        let ray = state.scenes[1].cameras[0].get_ray_from_viewport_coordinates(&bottom, WIDTH, HEIGHT);
+        assert!((ray.origin.x - 3.0).abs() < 0.001);
+        assert!(ray.origin.y.abs() < 0.001);
+
+        // and back to the viewport position
+        let pos = state.scenes[1].cameras[0].get_viewport_coordinates_from_point(&Point3::<f32>::new(3.0, 0.0, 0.0), WIDTH, HEIGHT).unwrap();
+        assert!((pos.x - 100.0).abs() < 0.001);
+        assert!((pos.y - 25.0).abs() < 0.001);
+    }
+
+    fn tap(state: &mut State, pos: Point2<f32>)
+    {
+        state.input_manager.touch.set_touch(0, pos, PointState::Down, 0);
+        state.input_manager.touch.set_touch(0, pos, PointState::Up, 0);
+    }
+
+    #[test]
+    fn tap_emits_pick_event()
+    {
+        let mut state = create_state();
+
+        tap(&mut state, Point2::<f32>::new(149.5, 49.5));
+        state.update_picks();
+
+        assert_eq!(state.picks.len(), 1);
+        let (scene_id, event) = state.picks[0].clone();
+        assert_eq!(scene_id, 2);
+
+        match event
+        {
+            SceneEvent::Picked { node_id, button, .. } =>
+            {
+                assert_eq!(node_id, state.scenes[1].nodes[0].read().unwrap().id);
+                assert_eq!(button, MouseButton::Left);
+            },
+            _ => panic!("pick event expected")
+        }
+
+        // the event is also queued for the app
+        assert_eq!(state.drain_events(), vec![(scene_id, state.picks[0].1.clone())]);
+
+        // the picks are only valid for one frame
+        state.input_manager.touch.reset();
+        state.update_picks();
+        assert!(state.picks.is_empty());
+    }
 }
\ No newline at end of file
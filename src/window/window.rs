@@ -69,6 +69,7 @@ fn run(event_loop: winit::event_loop::EventLoop<()>, mut interface: MainInterfac
                 _ => interface.input(event),
             }
         },
+        Event::DeviceEvent { ref event, .. } => interface.device_input(event),
         Event::RedrawRequested(_) =>
         {
             interface.update();